@@ -7,7 +7,9 @@ export const tasks = writable({} as Record<string, TaskDetail>);
 export function monitorTask(taskId: string, callback: (result: TaskDetail) => void) {
 	const interval = setInterval(async () => {
 		const result = await requestTaskDetails(taskId);
-		if (!('error' in result) && (result.state === 'Failed' || result.state === 'Finished')) {
+		if (!('error' in result) && (result.state === 'Failed' ||
+				result.state === 'Finished' ||
+				result.state === 'Cancelled')) {
 			clearInterval(interval);
 			callback(result);
 		}
@@ -6,6 +6,7 @@ export interface AppService {
 	started_at: string;
 	use_tls: boolean;
 	exit_code?: number | null;
+	health?: 'starting' | 'healthy' | 'unhealthy' | null;
 }
 
 export enum AppTtlKeys {
@@ -22,6 +23,7 @@ export type AppTtl =
 	| {
 			Hours?: number;
 			Days?: number;
+			Idle?: number;
 	  }
 	| 'Forever';
 
@@ -29,6 +31,13 @@ export interface ServicePortMapping {
 	service: string;
 	port: number;
 	domains: string[];
+	path?: string;
+	strip_path?: boolean;
+}
+
+export interface BasicAuthUser {
+	username: string;
+	password: string;
 }
 
 export interface AppSettings {
@@ -38,7 +47,7 @@ export interface AppSettings {
 	registry: string;
 	disallow_robots: boolean;
 	app_blueprint: string;
-	basic_auth: [string, string] | null;
+	basic_auth?: BasicAuthUser[];
 	environment: Map<string, string> | null;
 	middlewares: string[];
 }
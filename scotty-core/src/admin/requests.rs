@@ -76,3 +76,14 @@ pub struct GetUserPermissionsRequest {
     /// User identifier to get permissions for
     pub user_id: String,
 }
+
+/// Request to upload a certificate, which apps can use for their domains
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct UploadCertificateRequest {
+    /// Name under which apps refer to the certificate
+    pub name: String,
+    /// PEM encoded certificate, optionally followed by its chain
+    pub certificate: String,
+    /// PEM encoded private key
+    pub key: String,
+}
@@ -84,3 +84,18 @@ pub type CreateScopeResponse = SuccessResponse;
 pub type CreateRoleResponse = SuccessResponse;
 pub type CreateAssignmentResponse = SuccessResponse;
 pub type RemoveAssignmentResponse = SuccessResponse;
+
+/// Information about an uploaded certificate
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema, utoipa::ToResponse)]
+pub struct CertificateInfo {
+    pub name: String,
+    /// Names the certificate is valid for
+    pub domains: Vec<String>,
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Response for listing uploaded certificates
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema, utoipa::ToResponse)]
+pub struct CertificatesListResponse {
+    pub certificates: Vec<CertificateInfo>,
+}
@@ -0,0 +1,35 @@
+use std::net::IpAddr;
+
+/// Check an entry of an IP allowlist, a single address or a CIDR range like
+/// `203.0.113.0/24`
+pub fn validate_source_range(range: &str) -> Result<(), String> {
+    let invalid = || format!("Invalid IP range {range}, use an address or CIDR notation");
+    let (address, prefix) = match range.split_once('/') {
+        Some((address, prefix)) => (address, Some(prefix)),
+        None => (range, None),
+    };
+    let address: IpAddr = address.parse().map_err(|_| invalid())?;
+    if let Some(prefix) = prefix {
+        let max_prefix = if address.is_ipv4() { 32 } else { 128 };
+        match prefix.parse::<u8>() {
+            Ok(prefix) if prefix <= max_prefix => {}
+            _ => return Err(invalid()),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_source_range() {
+        assert!(validate_source_range("203.0.113.7").is_ok());
+        assert!(validate_source_range("203.0.113.0/24").is_ok());
+        assert!(validate_source_range("2001:db8::/32").is_ok());
+        assert!(validate_source_range("203.0.113.0/33").is_err());
+        assert!(validate_source_range("203.0.113.0/").is_err());
+        assert!(validate_source_range("office").is_err());
+    }
+}
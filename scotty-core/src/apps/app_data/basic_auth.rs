@@ -0,0 +1,162 @@
+use serde::{Deserialize, Deserializer, Serialize};
+use utoipa::{ToResponse, ToSchema};
+
+/// A user allowed to access an app via basic auth
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq, ToSchema)]
+pub struct BasicAuthUser {
+    pub username: String,
+    /// Bcrypt hash of the password or a `op://` reference to it. Clear-text
+    /// passwords are only found in settings written by older versions.
+    pub password: String,
+}
+
+impl BasicAuthUser {
+    pub fn new(username: &str, password: &str) -> Self {
+        BasicAuthUser {
+            username: username.to_string(),
+            password: password.to_string(),
+        }
+    }
+
+    /// Whether the password is a 1Password reference, resolved when the load
+    /// balancer config is written
+    pub fn is_reference(&self) -> bool {
+        self.password.starts_with("op://")
+    }
+
+    /// Whether the password is stored as bcrypt hash
+    pub fn is_hashed(&self) -> bool {
+        is_bcrypt_hash(&self.password)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.username.is_empty() || self.username.contains(':') {
+            return Err(format!(
+                "Invalid basic auth user name '{}', it must not be empty or contain ':'",
+                self.username
+            ));
+        }
+        if self.password.is_empty() {
+            return Err(format!("Basic auth user {} has no password", self.username));
+        }
+        Ok(())
+    }
+}
+
+/// Whether `value` looks like a bcrypt hash as written by `htpasswd -B`
+pub fn is_bcrypt_hash(value: &str) -> bool {
+    value.len() == 60
+        && ["$2a$", "$2b$", "$2y$"]
+            .iter()
+            .any(|prefix| value.starts_with(prefix))
+}
+
+/// Basic auth users of an app, also accepting the single `[user, password]`
+/// pair written by older versions
+pub(super) fn deserialize_basic_auth<'de, D>(
+    deserializer: D,
+) -> Result<Vec<BasicAuthUser>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum BasicAuthSetting {
+        Legacy(String, String),
+        Users(Vec<BasicAuthUser>),
+    }
+
+    Ok(
+        match Option::<BasicAuthSetting>::deserialize(deserializer)? {
+            Some(BasicAuthSetting::Legacy(username, password)) => {
+                vec![BasicAuthUser { username, password }]
+            }
+            Some(BasicAuthSetting::Users(users)) => users,
+            None => vec![],
+        },
+    )
+}
+
+/// Request body for adding a basic auth user to an app
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct AddBasicAuthUserRequest {
+    pub username: String,
+    /// Clear-text password, bcrypt hash or `op://` reference. Clear-text
+    /// passwords are hashed before they are stored.
+    pub password: String,
+}
+
+/// A basic auth user of an app, without its password
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct BasicAuthUserInfo {
+    pub username: String,
+    /// The 1Password reference of the password, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password_reference: Option<String>,
+}
+
+impl From<&BasicAuthUser> for BasicAuthUserInfo {
+    fn from(user: &BasicAuthUser) -> Self {
+        BasicAuthUserInfo {
+            username: user.username.clone(),
+            password_reference: user.is_reference().then(|| user.password.clone()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, ToResponse)]
+pub struct BasicAuthUsersResponse {
+    pub users: Vec<BasicAuthUserInfo>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Deserialize)]
+    struct Settings {
+        #[serde(default, deserialize_with = "deserialize_basic_auth")]
+        basic_auth: Vec<BasicAuthUser>,
+    }
+
+    fn parse(yaml: &str) -> Vec<BasicAuthUser> {
+        serde_norway::from_str::<Settings>(yaml).unwrap().basic_auth
+    }
+
+    #[test]
+    fn test_deserialize_basic_auth() {
+        assert_eq!(parse("{}"), vec![]);
+        assert_eq!(parse("basic_auth: null"), vec![]);
+        assert_eq!(
+            parse("basic_auth: [\"user\", \"pass\"]"),
+            vec![BasicAuthUser::new("user", "pass")]
+        );
+        assert_eq!(
+            parse(
+                "basic_auth:\n  - username: alice\n    password: op://vault/item/password\n  - username: bob\n    password: secret\n"
+            ),
+            vec![
+                BasicAuthUser::new("alice", "op://vault/item/password"),
+                BasicAuthUser::new("bob", "secret"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_password_kinds() {
+        let hashed = BasicAuthUser::new(
+            "user",
+            "$2b$12$R9h/cIPz0gi.URNNX3kh2OPST9/PgBkqquzi.Ss7KIUgO2t0jWMUW",
+        );
+        assert!(hashed.is_hashed());
+        assert!(!hashed.is_reference());
+
+        let reference = BasicAuthUser::new("user", "op://token/vault/item");
+        assert!(reference.is_reference());
+        assert!(!reference.is_hashed());
+
+        assert!(!BasicAuthUser::new("user", "secret").is_hashed());
+        assert!(BasicAuthUser::new("us:er", "secret").validate().is_err());
+        assert!(BasicAuthUser::new("user", "").validate().is_err());
+    }
+}
@@ -1,6 +1,6 @@
 use std::fmt::Display;
 
-use bollard_stubs::models::ContainerStateStatusEnum;
+use bollard_stubs::models::{ContainerStateStatusEnum, HealthStatusEnum};
 use chrono::TimeDelta;
 use serde::{Deserialize, Serialize};
 use utoipa::{ToResponse, ToSchema};
@@ -50,6 +50,38 @@ impl From<ContainerStateStatusEnum> for ContainerStatus {
     }
 }
 
+/// Health of a container as reported by its Docker `HEALTHCHECK`
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, ToSchema, ToResponse, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ContainerHealth {
+    Starting,
+    Healthy,
+    Unhealthy,
+}
+
+impl Display for ContainerHealth {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ContainerHealth::Starting => write!(f, "starting"),
+            ContainerHealth::Healthy => write!(f, "healthy"),
+            ContainerHealth::Unhealthy => write!(f, "unhealthy"),
+        }
+    }
+}
+
+impl ContainerHealth {
+    /// Convert the health status reported by Docker, `None` if the container
+    /// has no healthcheck.
+    pub fn from_docker(status: Option<HealthStatusEnum>) -> Option<Self> {
+        match status? {
+            HealthStatusEnum::STARTING => Some(ContainerHealth::Starting),
+            HealthStatusEnum::HEALTHY => Some(ContainerHealth::Healthy),
+            HealthStatusEnum::UNHEALTHY => Some(ContainerHealth::Unhealthy),
+            HealthStatusEnum::EMPTY | HealthStatusEnum::NONE => None,
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, ToSchema, ToResponse)]
 pub struct ContainerState {
     pub status: ContainerStatus,
@@ -65,6 +97,9 @@ pub struct ContainerState {
     /// Used to tell a clean one-shot/init exit (`Exited` + `Some(0)`) apart
     /// from a crash (non-zero exit code).
     pub exit_code: Option<i64>,
+    /// Health reported by the container's healthcheck, `None` if it has none.
+    #[serde(default)]
+    pub health: Option<ContainerHealth>,
 }
 
 impl Default for ContainerState {
@@ -80,6 +115,7 @@ impl Default for ContainerState {
             used_registry: None,
             basic_auth: None,
             exit_code: None,
+            health: None,
         }
     }
 }
@@ -223,6 +259,19 @@ mod tests {
         }
     }
 
+    #[test]
+    fn docker_health_without_healthcheck_is_none() {
+        assert_eq!(ContainerHealth::from_docker(None), None);
+        assert_eq!(
+            ContainerHealth::from_docker(Some(HealthStatusEnum::NONE)),
+            None
+        );
+        assert_eq!(
+            ContainerHealth::from_docker(Some(HealthStatusEnum::UNHEALTHY)),
+            Some(ContainerHealth::Unhealthy)
+        );
+    }
+
     #[test]
     fn terminal_states_are_terminal() {
         for status in [
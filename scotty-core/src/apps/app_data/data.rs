@@ -6,7 +6,9 @@ use utoipa::{ToResponse, ToSchema};
 use crate::notification_types::NotificationReceiver;
 use crate::utils::{format::sanitize_env_var_name, secret::SecretHashMap, slugify::slugify};
 
+use super::basic_auth::BasicAuthUser;
 use super::container::ContainerState;
+use super::drift::LoadBalancerDrift;
 use super::settings::AppSettings;
 use super::status::{get_app_status_from_services, AppStatus};
 use super::ttl::AppTtl;
@@ -20,6 +22,9 @@ pub struct AppData {
     pub services: Vec<ContainerState>,
     pub settings: Option<AppSettings>,
     pub last_checked: Option<chrono::DateTime<chrono::Local>>,
+    /// Drift of the load balancer config, only reported by the app info
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub drift: Option<LoadBalancerDrift>,
 }
 
 impl Default for AppData {
@@ -45,6 +50,7 @@ impl Default for AppData {
             services: Vec::new(),
             settings: None,
             last_checked: None,
+            drift: None,
         }
     }
 }
@@ -78,6 +84,7 @@ impl AppData {
             services,
             settings,
             last_checked: None,
+            drift: None,
         }
     }
 
@@ -113,6 +120,8 @@ impl AppData {
     pub fn add_notifications(&self, service_ids: &[NotificationReceiver]) -> AppData {
         let mut new_settings = self.settings.clone().unwrap_or_default();
         for id in service_ids {
+            // Adding a receiver again updates the commit it reports on
+            new_settings.notify.retain(|x| !x.same_target(id));
             new_settings.notify.insert(id.clone());
         }
         AppData {
@@ -123,7 +132,9 @@ impl AppData {
 
     pub fn remove_notifications(&self, service_ids: &[NotificationReceiver]) -> AppData {
         let mut new_settings = self.settings.clone().unwrap_or_default();
-        new_settings.notify.retain(|x| !service_ids.contains(x));
+        new_settings
+            .notify
+            .retain(|x| !service_ids.iter().any(|id| id.same_target(x)));
         AppData {
             settings: Some(new_settings),
             ..self.clone()
@@ -155,6 +166,19 @@ impl AppData {
                 environment.insert(name, urls[0].to_string());
             }
         }
+
+        if let Some(settings) = &self.settings {
+            for tcp_service in &settings.tcp_services {
+                if let Some(endpoint) = tcp_service.get_endpoint(&settings.domain) {
+                    let name = format!(
+                        "SCOTTY__{}_URL__{}",
+                        tcp_service.protocol.to_string().to_uppercase(),
+                        sanitize_env_var_name(&tcp_service.service)
+                    );
+                    environment.insert(name, endpoint);
+                }
+            }
+        }
         environment
     }
 
@@ -198,6 +222,8 @@ impl AppData {
                         service: service.service.clone(),
                         port: service.port.unwrap(),
                         domains: service.domains.clone(),
+                        path: None,
+                        strip_path: false,
                     });
             }
             if service.basic_auth.is_some() {
@@ -205,7 +231,9 @@ impl AppData {
             }
         }
 
-        new_settings.basic_auth = basic_auth;
+        new_settings.basic_auth = basic_auth
+            .map(|(user, password)| vec![BasicAuthUser::new(&user, &password)])
+            .unwrap_or_default();
 
         let app_data = AppData {
             settings: Some(new_settings),
@@ -0,0 +1,38 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// A difference between the load balancer config scotty generates for an app
+/// and the one in place.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq, ToSchema)]
+pub enum DriftIssue {
+    /// The docker-compose override file of the app is missing
+    OverrideMissing,
+    /// The docker-compose override file differs from the generated one
+    OverrideChanged,
+    /// The load balancer is not connected to the given proxy network of the app
+    NetworkDetached(String),
+}
+
+impl std::fmt::Display for DriftIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DriftIssue::OverrideMissing => write!(f, "docker-compose override is missing"),
+            DriftIssue::OverrideChanged => write!(f, "docker-compose override was changed"),
+            DriftIssue::NetworkDetached(network) => {
+                write!(f, "load balancer is not connected to network {}", network)
+            }
+        }
+    }
+}
+
+/// Drift of the load balancer config of an app, found by the periodic
+/// reconciliation.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq, ToSchema)]
+pub struct LoadBalancerDrift {
+    pub issues: Vec<DriftIssue>,
+    /// When the drift was detected first
+    #[schema(value_type = String, format = DateTime)]
+    pub detected_at: chrono::DateTime<chrono::Local>,
+    /// Whether scotty started a repair of the drift
+    pub repairing: bool,
+}
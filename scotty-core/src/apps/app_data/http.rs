@@ -0,0 +1,99 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Check a custom response header of an app
+pub fn validate_response_header(name: &str, value: &str) -> Result<(), String> {
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(format!(
+            "Invalid header name '{name}', only letters, digits, '-' and '_' are allowed"
+        ));
+    }
+    if value.chars().any(|c| c.is_control()) {
+        return Err(format!(
+            "Invalid value of header {name}, control characters are not allowed"
+        ));
+    }
+    Ok(())
+}
+
+/// Redirect all requests to a domain of an app to another domain, e.g. from
+/// `www.example.com` to `example.com`. Path and query are kept.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq, ToSchema)]
+pub struct RedirectRule {
+    /// Domain to redirect, has to be one of the domains of the app
+    pub from: String,
+    /// Domain to redirect to
+    pub to: String,
+    /// Send a permanent (301) instead of a temporary (302) redirect
+    #[serde(default = "RedirectRule::default_permanent")]
+    pub permanent: bool,
+}
+
+impl RedirectRule {
+    pub fn new(from: &str, to: &str) -> Self {
+        RedirectRule {
+            from: from.to_string(),
+            to: to.to_string(),
+            permanent: true,
+        }
+    }
+
+    fn default_permanent() -> bool {
+        true
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        for domain in [&self.from, &self.to] {
+            if domain.is_empty()
+                || !domain
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '.')
+            {
+                return Err(format!("Invalid domain '{domain}' in redirect"));
+            }
+        }
+        if self.from == self.to {
+            return Err(format!("Redirect from {} to itself", self.from));
+        }
+        Ok(())
+    }
+}
+
+/// Request body for turning the maintenance mode of an app on or off
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SetMaintenanceRequest {
+    pub enabled: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_response_header() {
+        assert!(validate_response_header("Strict-Transport-Security", "max-age=31536000").is_ok());
+        assert!(validate_response_header("Content-Security-Policy", "").is_ok());
+        assert!(validate_response_header("", "value").is_err());
+        assert!(validate_response_header("X Frame", "DENY").is_err());
+        assert!(validate_response_header("X-Frame-Options", "DENY\r\nX-Evil: 1").is_err());
+    }
+
+    #[test]
+    fn test_redirect_rule() {
+        let rule: RedirectRule =
+            serde_norway::from_str("from: www.example.com\nto: example.com\n").unwrap();
+        assert_eq!(rule, RedirectRule::new("www.example.com", "example.com"));
+        assert!(rule.validate().is_ok());
+
+        assert!(RedirectRule::new("example.com", "example.com")
+            .validate()
+            .is_err());
+        assert!(RedirectRule::new("www.example.com", "https://example.com")
+            .validate()
+            .is_err());
+    }
+}
@@ -1,14 +1,28 @@
+mod access;
+mod basic_auth;
 mod container;
 mod data;
+mod drift;
+mod http;
+mod schedule;
 mod service;
 mod settings;
 mod status;
+mod tcp_service;
+mod tls;
 mod ttl;
 
 // Re-export all components
+pub use access::*;
+pub use basic_auth::*;
 pub use container::*;
 pub use data::*;
+pub use drift::*;
+pub use http::*;
+pub use schedule::*;
 pub use service::*;
 pub use settings::*;
 pub use status::*;
+pub use tcp_service::*;
+pub use tls::*;
 pub use ttl::*;
@@ -0,0 +1,310 @@
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Starts and stops an app at fixed times, e.g. only during office hours
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq, Eq, ToSchema)]
+pub struct AppSchedule {
+    /// Cron expression when to start the app, e.g. `0 8 * * Mon-Fri`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_at: Option<String>,
+    /// Cron expression when to stop the app, e.g. `0 19 * * Mon-Fri`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stop_at: Option<String>,
+    /// Timezone the expressions are evaluated in, e.g. `Europe/Berlin`, defaults to UTC
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ScheduledAction {
+    Start,
+    Stop,
+}
+
+impl std::fmt::Display for ScheduledAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ScheduledAction::Start => write!(f, "start"),
+            ScheduledAction::Stop => write!(f, "stop"),
+        }
+    }
+}
+
+/// A point in time at which the schedule starts or stops the app
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScheduledTransition {
+    pub action: ScheduledAction,
+    pub at: DateTime<Utc>,
+}
+
+impl AppSchedule {
+    pub fn is_empty(&self) -> bool {
+        self.start_at.is_none() && self.stop_at.is_none()
+    }
+
+    /// Parse a cron expression. Expressions with five fields (as used by
+    /// crontab) are extended with a leading seconds field.
+    ///
+    /// The cron crate counts the days of the week from 1 for Sunday, crontab
+    /// from 0 (or 7) for Sunday. Numeric days of five field expressions are
+    /// translated to names, expressions with seconds only accept names.
+    pub fn parse_expression(expression: &str) -> anyhow::Result<cron::Schedule> {
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        let expression = match fields.as_slice() {
+            [minute, hour, day, month, day_of_week] => format!(
+                "0 {minute} {hour} {day} {month} {}",
+                Self::translate_day_of_week(day_of_week).map_err(|e| anyhow::anyhow!(
+                    "Invalid cron expression '{}': {}",
+                    expression,
+                    e
+                ))?
+            ),
+            _ => {
+                if fields
+                    .get(5)
+                    .is_some_and(|day_of_week| Self::has_numeric_day(day_of_week))
+                {
+                    return Err(anyhow::anyhow!(
+                        "Invalid cron expression '{}': use names for the day of the week, e.g. MON-FRI",
+                        expression.trim()
+                    ));
+                }
+                expression.trim().to_string()
+            }
+        };
+        cron::Schedule::from_str(&expression)
+            .map_err(|e| anyhow::anyhow!("Invalid cron expression '{}': {}", expression, e))
+    }
+
+    /// Whether a day of week field refers to a day by its number
+    fn has_numeric_day(field: &str) -> bool {
+        field.split(',').any(|element| {
+            let range = element.split_once('/').map_or(element, |(range, _)| range);
+            range.chars().any(|c| c.is_ascii_digit())
+        })
+    }
+
+    /// Replace the numeric days of a crontab day of week field by their names
+    fn translate_day_of_week(field: &str) -> anyhow::Result<String> {
+        const DAYS: [&str; 7] = ["SUN", "MON", "TUE", "WED", "THU", "FRI", "SAT"];
+        let parse_day = |day: &str| -> anyhow::Result<usize> {
+            match day.parse::<usize>() {
+                Ok(day) if day <= 7 => Ok(day),
+                _ => Err(anyhow::anyhow!("invalid day of the week '{}'", day)),
+            }
+        };
+
+        let elements = field
+            .split(',')
+            .map(|element| {
+                if !Self::has_numeric_day(element) {
+                    return Ok(element.to_string());
+                }
+                let (range, step) = match element.split_once('/') {
+                    Some((range, step)) => match step.parse::<usize>() {
+                        Ok(step) if step > 0 => (range, Some(step)),
+                        _ => return Err(anyhow::anyhow!("invalid step '{}'", step)),
+                    },
+                    None => (element, None),
+                };
+                let (start, end) = match range.split_once('-') {
+                    Some((start, end)) => (parse_day(start)?, parse_day(end)?),
+                    // `N/step` runs from N to the end of the week
+                    None if step.is_some() => (parse_day(range)?, 7),
+                    None => (parse_day(range)?, parse_day(range)?),
+                };
+                if start > end {
+                    return Err(anyhow::anyhow!("invalid range '{}'", range));
+                }
+                let mut days: Vec<&str> = Vec::new();
+                for day in (start..=end).step_by(step.unwrap_or(1)) {
+                    let name = DAYS[day % 7];
+                    if !days.contains(&name) {
+                        days.push(name);
+                    }
+                }
+                Ok(days.join(","))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        Ok(elements.join(","))
+    }
+
+    pub fn parse_timezone(timezone: &str) -> anyhow::Result<Tz> {
+        timezone
+            .parse::<Tz>()
+            .map_err(|_| anyhow::anyhow!("Unknown timezone '{}'", timezone))
+    }
+
+    /// Check that the expressions and the timezone can be parsed
+    pub fn validate(&self) -> anyhow::Result<()> {
+        self.timezone()?;
+        self.schedules()?;
+        Ok(())
+    }
+
+    fn timezone(&self) -> anyhow::Result<Tz> {
+        match &self.timezone {
+            Some(timezone) => Self::parse_timezone(timezone),
+            None => Ok(chrono_tz::UTC),
+        }
+    }
+
+    fn schedules(&self) -> anyhow::Result<Vec<(ScheduledAction, cron::Schedule)>> {
+        [
+            (ScheduledAction::Start, &self.start_at),
+            (ScheduledAction::Stop, &self.stop_at),
+        ]
+        .into_iter()
+        .filter_map(|(action, expression)| expression.as_ref().map(|e| (action, e)))
+        .map(|(action, expression)| Ok((action, Self::parse_expression(expression)?)))
+        .collect()
+    }
+
+    /// The first transition after `after`
+    pub fn next_transition(
+        &self,
+        after: DateTime<Utc>,
+    ) -> anyhow::Result<Option<ScheduledTransition>> {
+        let after = after.with_timezone(&self.timezone()?);
+        Ok(self
+            .schedules()?
+            .iter()
+            .filter_map(|(action, schedule)| {
+                schedule.after(&after).next().map(|at| ScheduledTransition {
+                    action: *action,
+                    at: at.with_timezone(&Utc),
+                })
+            })
+            .min_by_key(|transition| transition.at))
+    }
+
+    /// The latest transition after `since` and up to and including `until`.
+    ///
+    /// If a start and a stop happened in that period, only the later one
+    /// determines the state of the app.
+    pub fn due_transition(
+        &self,
+        since: DateTime<Utc>,
+        until: DateTime<Utc>,
+    ) -> anyhow::Result<Option<ScheduledTransition>> {
+        let since = since.with_timezone(&self.timezone()?);
+        Ok(self
+            .schedules()?
+            .iter()
+            .filter_map(|(action, schedule)| {
+                schedule
+                    .after(&since)
+                    .map(|at| at.with_timezone(&Utc))
+                    .take_while(|at| *at <= until)
+                    .last()
+                    .map(|at| ScheduledTransition {
+                        action: *action,
+                        at,
+                    })
+            })
+            .max_by_key(|transition| transition.at))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn office_hours() -> AppSchedule {
+        AppSchedule {
+            start_at: Some("0 8 * * Mon-Fri".to_string()),
+            stop_at: Some("0 19 * * Mon-Fri".to_string()),
+            timezone: Some("Europe/Berlin".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(office_hours().validate().is_ok());
+
+        let schedule = AppSchedule {
+            start_at: Some("every morning".to_string()),
+            ..Default::default()
+        };
+        assert!(schedule.validate().is_err());
+
+        let schedule = AppSchedule {
+            timezone: Some("Mars/Olympus".to_string()),
+            ..office_hours()
+        };
+        assert!(schedule.validate().is_err());
+    }
+
+    #[test]
+    fn test_next_transition_respects_timezone() {
+        // Friday, 2024-06-14 18:00 UTC is 20:00 in Berlin, the app was stopped already
+        let now = Utc.with_ymd_and_hms(2024, 6, 14, 18, 0, 0).unwrap();
+        let next = office_hours().next_transition(now).unwrap().unwrap();
+        assert_eq!(next.action, ScheduledAction::Start);
+        // Monday 08:00 in Berlin
+        assert_eq!(next.at, Utc.with_ymd_and_hms(2024, 6, 17, 6, 0, 0).unwrap());
+    }
+
+    #[test]
+    fn test_due_transition() {
+        let schedule = office_hours();
+        let since = Utc.with_ymd_and_hms(2024, 6, 14, 5, 59, 0).unwrap();
+
+        let until = Utc.with_ymd_and_hms(2024, 6, 14, 5, 59, 30).unwrap();
+        assert_eq!(schedule.due_transition(since, until).unwrap(), None);
+
+        let until = Utc.with_ymd_and_hms(2024, 6, 14, 6, 0, 0).unwrap();
+        let due = schedule.due_transition(since, until).unwrap().unwrap();
+        assert_eq!(due.action, ScheduledAction::Start);
+
+        // Both happened, the later one wins
+        let until = Utc.with_ymd_and_hms(2024, 6, 14, 17, 30, 0).unwrap();
+        let due = schedule.due_transition(since, until).unwrap().unwrap();
+        assert_eq!(due.action, ScheduledAction::Stop);
+    }
+
+    #[test]
+    fn test_numeric_days_of_week_count_from_sunday() {
+        // Every day at 08:00 from Saturday, 2024-06-15 on
+        let saturday = Utc.with_ymd_and_hms(2024, 6, 15, 0, 0, 0).unwrap();
+        let days = |expression: &str| {
+            AppSchedule::parse_expression(expression)
+                .unwrap()
+                .after(&saturday)
+                .take(7)
+                .map(|at| at.format("%a").to_string())
+                .collect::<Vec<_>>()
+        };
+
+        let weekdays = vec!["Mon", "Tue", "Wed", "Thu", "Fri", "Mon", "Tue"];
+        assert_eq!(days("0 8 * * 1-5"), weekdays);
+        assert_eq!(days("0 8 * * Mon-Fri"), weekdays);
+        assert_eq!(
+            days("0 8 * * 0,6"),
+            vec!["Sat", "Sun", "Sat", "Sun", "Sat", "Sun", "Sat"]
+        );
+        assert_eq!(days("0 8 * * 7"), days("0 8 * * 0"));
+        assert_eq!(days("0 8 * * 5-7"), days("0 8 * * Fri,Sat,Sun"));
+        assert_eq!(days("0 8 * * 1-5/2"), days("0 8 * * Mon,Wed,Fri"));
+
+        assert!(AppSchedule::parse_expression("0 8 * * 8").is_err());
+        assert!(AppSchedule::parse_expression("0 8 * * 5-1").is_err());
+        // Expressions with seconds follow the cron crate, numeric days are ambiguous
+        assert!(AppSchedule::parse_expression("0 0 8 * * 1-5").is_err());
+        assert!(AppSchedule::parse_expression("0 0 8 * * Mon-Fri").is_ok());
+    }
+
+    #[test]
+    fn test_roundtrip_yaml() {
+        let schedule = office_hours();
+        let yaml = serde_norway::to_string(&schedule).unwrap();
+        let deserialized: AppSchedule = serde_norway::from_str(&yaml).unwrap();
+        assert_eq!(schedule, deserialized);
+    }
+}
@@ -6,9 +6,60 @@ pub struct ServicePortMapping {
     pub service: String,
     pub port: u32,
     pub domains: Vec<String>,
+    /// Only route requests below this path prefix to the service, e.g. `/api`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// Remove the path prefix before the request is passed to the service
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub strip_path: bool,
 }
 
 impl ServicePortMapping {
+    /// Check that a path prefix can be used in a routing rule.
+    pub fn validate_path(path: &str) -> Result<(), String> {
+        if !path.starts_with('/') {
+            return Err(format!("Path prefix '{path}' must start with '/'"));
+        }
+        if path
+            .chars()
+            .any(|c| c.is_whitespace() || matches!(c, '`' | ',' | '"' | '\\'))
+        {
+            return Err(format!("Path prefix '{path}' contains invalid characters"));
+        }
+        Ok(())
+    }
+
+    /// Check that a domain is a valid hostname (RFC 1123), so it can be used
+    /// in routing rules and as a file name.
+    pub fn validate_domain(domain: &str) -> Result<(), String> {
+        let valid_label = |label: &str| {
+            !label.is_empty()
+                && label.len() <= 63
+                && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+        };
+        if domain.is_empty() || domain.len() > 253 || !domain.split('.').all(valid_label) {
+            return Err(format!("Domain '{domain}' is not a valid hostname"));
+        }
+        Ok(())
+    }
+
+    /// Validate the custom domains and the optional path prefix of the mapping
+    pub fn validate(&self) -> Result<(), String> {
+        for domain in &self.domains {
+            Self::validate_domain(domain)?;
+        }
+        match &self.path {
+            Some(path) => Self::validate_path(path),
+            None if self.strip_path => Err(format!(
+                "Service {} strips a path prefix, but has none",
+                self.service
+            )),
+            None => Ok(()),
+        }
+    }
+
     /// Returns the effective domains for this service.
     ///
     /// If custom domains are configured, returns those.
@@ -41,6 +92,10 @@ impl<'de> Deserialize<'de> for ServicePortMapping {
         struct Temp {
             service: String,
             port: u32,
+            #[serde(default)]
+            path: Option<String>,
+            #[serde(default)]
+            strip_path: bool,
             #[serde(flatten)]
             domain_field: Option<DomainField>,
         }
@@ -49,6 +104,8 @@ impl<'de> Deserialize<'de> for ServicePortMapping {
         let Temp {
             service,
             port,
+            path,
+            strip_path,
             domain_field,
         } = Temp::deserialize(deserializer)?;
 
@@ -63,6 +120,8 @@ impl<'de> Deserialize<'de> for ServicePortMapping {
             service,
             port,
             domains,
+            path,
+            strip_path,
         })
     }
 }
@@ -105,6 +164,41 @@ mod tests {
         assert_eq!(mapping.service, "api");
         assert_eq!(mapping.port, 3000);
         assert_eq!(mapping.domains, vec!["api1.com", "api2.com"]);
+        assert_eq!(mapping.path, None);
+
+        // Test path prefix
+        let json = json!({
+            "service": "api",
+            "port": 3000,
+            "domain": "example.com",
+            "path": "/api",
+            "strip_path": true
+        });
+        let mapping: ServicePortMapping = serde_json::from_value(json).unwrap();
+        assert_eq!(mapping.domains, vec!["example.com"]);
+        assert_eq!(mapping.path.as_deref(), Some("/api"));
+        assert!(mapping.strip_path);
+    }
+
+    #[test]
+    fn test_validate_domain() {
+        assert!(ServicePortMapping::validate_domain("example.com").is_ok());
+        assert!(ServicePortMapping::validate_domain("web-1.my-app.example.com").is_ok());
+        assert!(ServicePortMapping::validate_domain("localhost").is_ok());
+        assert!(ServicePortMapping::validate_domain("").is_err());
+        assert!(ServicePortMapping::validate_domain("../../etc/x").is_err());
+        assert!(ServicePortMapping::validate_domain("example.com/x").is_err());
+        assert!(ServicePortMapping::validate_domain("-example.com").is_err());
+        assert!(ServicePortMapping::validate_domain("example..com").is_err());
+        assert!(ServicePortMapping::validate_domain(&"a".repeat(64)).is_err());
+    }
+
+    #[test]
+    fn test_validate_path() {
+        assert!(ServicePortMapping::validate_path("/api").is_ok());
+        assert!(ServicePortMapping::validate_path("/api/v1").is_ok());
+        assert!(ServicePortMapping::validate_path("api").is_err());
+        assert!(ServicePortMapping::validate_path("/api`) || Host(`evil").is_err());
     }
 
     #[test]
@@ -113,6 +207,8 @@ mod tests {
             service: "web".to_string(),
             port: 8080,
             domains: vec!["custom.example.com".to_string()],
+            path: None,
+            strip_path: false,
         };
         assert_eq!(
             mapping.get_domains("myapp.apps.example.com"),
@@ -126,6 +222,8 @@ mod tests {
             service: "web".to_string(),
             port: 8080,
             domains: vec![],
+            path: None,
+            strip_path: false,
         };
         assert_eq!(
             mapping.get_domains("myapp.apps.example.com"),
@@ -139,6 +237,8 @@ mod tests {
             service: "web".to_string(),
             port: 8080,
             domains: vec![],
+            path: None,
+            strip_path: false,
         };
         let result: Vec<String> = vec![];
         assert_eq!(mapping.get_domains(""), result);
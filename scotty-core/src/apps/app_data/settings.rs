@@ -1,4 +1,7 @@
-use std::{collections::HashMap, collections::HashSet, fs::File, io::BufReader, path::Path};
+use std::{
+    collections::BTreeMap, collections::HashMap, collections::HashSet, fs::File, io::BufReader,
+    path::Path,
+};
 
 use anyhow;
 use serde::{Deserialize, Serialize};
@@ -13,12 +16,81 @@ use crate::{
 };
 
 use super::super::create_app_request::CustomDomainMapping;
-use super::{service::ServicePortMapping, ttl::AppTtl};
+use super::{
+    basic_auth::{deserialize_basic_auth, BasicAuthUser},
+    http::RedirectRule,
+    schedule::AppSchedule,
+    service::ServicePortMapping,
+    tcp_service::TcpServiceMapping,
+    tls::AppTlsSettings,
+    ttl::AppTtl,
+};
 
 fn default_scopes() -> Vec<String> {
     vec!["default".to_string()]
 }
 
+/// How a rebuild replaces the running containers of an app
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Default, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RebuildStrategy {
+    /// Stop the old containers, then start the new ones
+    #[default]
+    Recreate,
+    /// Start the new containers next to the old ones and switch over once they are ready
+    BlueGreen,
+}
+
+/// An HTTP request which has to succeed before a service counts as ready
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq, ToSchema)]
+pub struct HttpProbe {
+    /// Name of the service to probe
+    pub service: String,
+    /// Path to request, e.g. `/health`
+    #[serde(default = "HttpProbe::default_path")]
+    pub path: String,
+    /// Port of the container to request, defaults to the public port of the service
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub port: Option<u32>,
+}
+
+impl HttpProbe {
+    fn default_path() -> String {
+        "/".to_string()
+    }
+}
+
+/// Controls when the containers of an app count as ready after a start
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq, Eq, ToSchema)]
+pub struct ReadinessSettings {
+    /// Seconds to wait for the containers to become ready
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_seconds: Option<u64>,
+    /// HTTP probes which have to succeed in addition to the container healthchecks
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub http_probes: Vec<HttpProbe>,
+}
+
+impl ReadinessSettings {
+    pub fn is_empty(&self) -> bool {
+        self.timeout_seconds.is_none() && self.http_probes.is_empty()
+    }
+
+    /// Fill the values not set in these settings from `fallback`, e.g. a blueprint.
+    pub fn or(&self, fallback: Option<&ReadinessSettings>) -> ReadinessSettings {
+        let Some(fallback) = fallback else {
+            return self.clone();
+        };
+        ReadinessSettings {
+            timeout_seconds: self.timeout_seconds.or(fallback.timeout_seconds),
+            http_probes: match self.http_probes.is_empty() {
+                true => fallback.http_probes.clone(),
+                false => self.http_probes.clone(),
+            },
+        }
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize, Clone, ToSchema, ToResponse)]
 pub struct AppSettings {
     pub public_services: Vec<ServicePortMapping>,
@@ -26,8 +98,30 @@ pub struct AppSettings {
     pub time_to_live: AppTtl,
     #[serde(default)]
     pub destroy_on_ttl: bool,
-    pub basic_auth: Option<(String, String)>,
+    /// Users allowed to access the app via basic auth
+    #[serde(
+        default,
+        deserialize_with = "deserialize_basic_auth",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub basic_auth: Vec<BasicAuthUser>,
+    /// Addresses and CIDR ranges allowed to reach the app, all if empty
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ip_allowlist: Vec<String>,
+    /// Only let users through who are logged in to scotty and may view the app
+    #[serde(default)]
+    pub forward_auth: bool,
     pub disallow_robots: bool,
+    /// Additional headers added to all responses of the app, e.g. HSTS
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub response_headers: BTreeMap<String, String>,
+    /// Redirects from domains of the app to other domains
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub redirects: Vec<RedirectRule>,
+    /// Requests are answered by scotty with a maintenance page instead of
+    /// reaching the app
+    #[serde(default)]
+    pub maintenance: bool,
     #[schema(value_type = HashMap<String, String>)]
     pub environment: SecretHashMap,
     pub registry: Option<String>,
@@ -41,6 +135,23 @@ pub struct AppSettings {
     /// Custom actions defined for this app (action_name -> CustomAction)
     #[serde(default, skip_serializing_if = "HashMap::is_empty")]
     pub custom_actions: HashMap<String, CustomAction>,
+    #[serde(default)]
+    pub rebuild_strategy: RebuildStrategy,
+    #[serde(default, skip_serializing_if = "ReadinessSettings::is_empty")]
+    pub readiness: ReadinessSettings,
+    /// Set when the TTL was extended, the TTL counts from the later of this
+    /// and the start of the app's containers.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ttl_started_at: Option<chrono::DateTime<chrono::Local>>,
+    /// Start and stop the app at fixed times
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schedule: Option<AppSchedule>,
+    /// TCP and UDP services reachable through the load balancer
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tcp_services: Vec<TcpServiceMapping>,
+    /// Certificate resolvers and uploaded certificates of the app's domains
+    #[serde(default, skip_serializing_if = "AppTlsSettings::is_empty")]
+    pub tls: AppTlsSettings,
 }
 
 impl Default for AppSettings {
@@ -50,8 +161,13 @@ impl Default for AppSettings {
             domain: "".to_string(),
             time_to_live: AppTtl::Days(7),
             destroy_on_ttl: false,
-            basic_auth: None,
+            basic_auth: Vec::new(),
+            ip_allowlist: Vec::new(),
+            forward_auth: false,
             disallow_robots: true,
+            response_headers: BTreeMap::new(),
+            redirects: Vec::new(),
+            maintenance: false,
             environment: SecretHashMap::new(),
             registry: None,
             app_blueprint: None,
@@ -59,6 +175,12 @@ impl Default for AppSettings {
             middlewares: Vec::new(),
             scopes: default_scopes(),
             custom_actions: HashMap::new(),
+            rebuild_strategy: RebuildStrategy::default(),
+            readiness: ReadinessSettings::default(),
+            ttl_started_at: None,
+            schedule: None,
+            tcp_services: Vec::new(),
+            tls: AppTlsSettings::default(),
         }
     }
 }
@@ -86,6 +208,8 @@ impl AppSettings {
                             service: service.clone(),
                             port: *port as u32,
                             domains: vec![],
+                            path: None,
+                            strip_path: false,
                         })
                         .collect();
                     return Ok(new_settings);
@@ -104,7 +228,9 @@ impl AppSettings {
             let mut found = false;
             for service in &mut new_settings.public_services {
                 if service.service == custom_domain.service {
-                    service.domains.push(custom_domain.domain.clone());
+                    if !service.domains.contains(&custom_domain.domain) {
+                        service.domains.push(custom_domain.domain.clone());
+                    }
                     found = true;
                 }
             }
@@ -119,6 +245,21 @@ impl AppSettings {
         Ok(new_settings)
     }
 
+    /// Replace the fields present in `changes`, all other fields keep their
+    /// current value.
+    pub fn with_changes(
+        &self,
+        changes: &serde_json::Map<String, serde_json::Value>,
+    ) -> anyhow::Result<AppSettings> {
+        let mut settings = serde_json::to_value(self)?;
+        if let Some(fields) = settings.as_object_mut() {
+            for (field, value) in changes {
+                fields.insert(field.clone(), value.clone());
+            }
+        }
+        Ok(serde_json::from_value(settings)?)
+    }
+
     pub fn from_file(settings_path: &Path) -> anyhow::Result<Option<AppSettings>> {
         if settings_path.exists() {
             info!(
@@ -283,4 +424,54 @@ mod tests {
         assert!(is_sensitive("API_KEY"));
         assert!(!is_sensitive("NORMAL_VAR"));
     }
+
+    #[test]
+    fn test_with_changes_keeps_other_fields() {
+        let mut environment = SecretHashMap::new();
+        environment.insert("API_KEY".to_string(), "secret".to_string());
+        let settings = AppSettings {
+            time_to_live: AppTtl::Forever,
+            environment,
+            middlewares: vec!["compress".to_string()],
+            ..Default::default()
+        };
+
+        let changes = serde_json::json!({ "middlewares": [], "destroy_on_ttl": true });
+        let updated = settings.with_changes(changes.as_object().unwrap()).unwrap();
+        assert!(updated.middlewares.is_empty());
+        assert!(updated.destroy_on_ttl);
+        assert_eq!(updated.time_to_live, AppTtl::Forever);
+        assert_eq!(
+            updated.environment.get("API_KEY").unwrap().expose_secret(),
+            "secret"
+        );
+
+        let invalid = serde_json::json!({ "time_to_live": "soon" });
+        assert!(settings.with_changes(invalid.as_object().unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_readiness_falls_back_to_blueprint() {
+        let blueprint = ReadinessSettings {
+            timeout_seconds: Some(600),
+            http_probes: vec![HttpProbe {
+                service: "web".to_string(),
+                path: "/health".to_string(),
+                port: None,
+            }],
+        };
+
+        let app = ReadinessSettings {
+            timeout_seconds: Some(120),
+            ..Default::default()
+        };
+        let merged = app.or(Some(&blueprint));
+        assert_eq!(merged.timeout_seconds, Some(120));
+        assert_eq!(merged.http_probes, blueprint.http_probes);
+
+        assert_eq!(
+            ReadinessSettings::default().or(None),
+            ReadinessSettings::default()
+        );
+    }
 }
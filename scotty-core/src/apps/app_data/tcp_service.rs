@@ -0,0 +1,118 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Default, PartialEq, Eq, Hash, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TransportProtocol {
+    #[default]
+    Tcp,
+    Udp,
+}
+
+impl std::fmt::Display for TransportProtocol {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            TransportProtocol::Tcp => write!(f, "tcp"),
+            TransportProtocol::Udp => write!(f, "udp"),
+        }
+    }
+}
+
+/// How the load balancer routes connections to a TCP or UDP service
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Default, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TcpRouting {
+    /// Shared entrypoint, connections are told apart by the SNI of their TLS
+    /// handshake. Clients have to connect via TLS.
+    #[default]
+    Sni,
+    /// Dedicated port allocated from the port pool of the server
+    Port,
+}
+
+/// A non-HTTP service reachable from outside, e.g. a database of a review app
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq, ToSchema)]
+pub struct TcpServiceMapping {
+    pub service: String,
+    pub port: u32,
+    #[serde(default)]
+    pub protocol: TransportProtocol,
+    #[serde(default)]
+    pub routing: TcpRouting,
+    /// Port under which the service is reachable, set by the server
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub public_port: Option<u16>,
+}
+
+impl TcpServiceMapping {
+    pub fn new(service: &str, port: u32, protocol: TransportProtocol, routing: TcpRouting) -> Self {
+        TcpServiceMapping {
+            service: service.to_string(),
+            port,
+            protocol,
+            routing,
+            public_port: None,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.protocol == TransportProtocol::Udp && self.routing == TcpRouting::Sni {
+            return Err(format!(
+                "UDP service {} can't be routed by SNI, use a dedicated port",
+                self.service
+            ));
+        }
+        Ok(())
+    }
+
+    /// Host under which the service is reachable, the same as the
+    /// auto-generated domain of a public service.
+    pub fn get_host(&self, app_domain: &str) -> Option<String> {
+        (!app_domain.is_empty()).then(|| format!("{}.{}", self.service, app_domain))
+    }
+
+    /// Endpoint of the service, e.g. `tcp://postgres.myapp.example.com:20001`
+    pub fn get_endpoint(&self, app_domain: &str) -> Option<String> {
+        let host = self.get_host(app_domain)?;
+        let port = self.public_port?;
+        Some(format!("{}://{}:{}", self.protocol, host, port))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate() {
+        let mapping = TcpServiceMapping::new("db", 5432, TransportProtocol::Tcp, TcpRouting::Sni);
+        assert!(mapping.validate().is_ok());
+        let mapping = TcpServiceMapping::new("dns", 53, TransportProtocol::Udp, TcpRouting::Port);
+        assert!(mapping.validate().is_ok());
+        let mapping = TcpServiceMapping::new("dns", 53, TransportProtocol::Udp, TcpRouting::Sni);
+        assert!(mapping.validate().is_err());
+    }
+
+    #[test]
+    fn test_get_endpoint() {
+        let mut mapping =
+            TcpServiceMapping::new("db", 5432, TransportProtocol::Tcp, TcpRouting::Port);
+        assert_eq!(mapping.get_endpoint("myapp.example.com"), None);
+
+        mapping.public_port = Some(20001);
+        assert_eq!(
+            mapping.get_endpoint("myapp.example.com").unwrap(),
+            "tcp://db.myapp.example.com:20001"
+        );
+        assert_eq!(mapping.get_endpoint(""), None);
+    }
+
+    #[test]
+    fn test_deserialize_defaults() {
+        let mapping: TcpServiceMapping =
+            serde_norway::from_str("service: db\nport: 5432\n").unwrap();
+        assert_eq!(mapping.protocol, TransportProtocol::Tcp);
+        assert_eq!(mapping.routing, TcpRouting::Sni);
+        assert_eq!(mapping.public_port, None);
+    }
+}
@@ -0,0 +1,179 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::{ToResponse, ToSchema};
+
+/// A certificate to request for a main domain and additional SANs, e.g. a
+/// wildcard certificate which requires a DNS challenge
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq, Eq, ToSchema)]
+pub struct TlsDomain {
+    pub main: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sans: Vec<String>,
+}
+
+impl TlsDomain {
+    /// Whether a certificate for this domain is valid for `domain`
+    pub fn covers(&self, domain: &str) -> bool {
+        std::iter::once(&self.main)
+            .chain(&self.sans)
+            .any(|name| match name.strip_prefix("*.") {
+                Some(parent) => domain
+                    .split_once('.')
+                    .is_some_and(|(_, rest)| rest == parent),
+                None => name == domain,
+            })
+    }
+}
+
+/// TLS settings of a single domain
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq, Eq, ToSchema)]
+pub struct DomainTlsSettings {
+    /// Certificate resolver for this domain
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub certresolver: Option<String>,
+    /// Name of a certificate uploaded to scotty, used instead of a resolver
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub certificate: Option<String>,
+}
+
+/// TLS settings of an app, overriding the global settings of the load balancer
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq, Eq, ToSchema)]
+pub struct AppTlsSettings {
+    /// Certificate resolver for all domains of the app
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub certresolver: Option<String>,
+    /// Certificates to request instead of one per domain
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub domains: Vec<TlsDomain>,
+    /// Settings of single domains, keyed by domain
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub per_domain: BTreeMap<String, DomainTlsSettings>,
+}
+
+/// Where the certificate of a domain comes from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CertificateSource {
+    Resolver(String),
+    Uploaded(String),
+    /// TLS without a resolver, the load balancer picks a certificate from its store
+    Default,
+}
+
+impl AppTlsSettings {
+    pub fn is_empty(&self) -> bool {
+        self.certresolver.is_none() && self.domains.is_empty() && self.per_domain.is_empty()
+    }
+
+    /// Source of the certificate of `domain`, falling back to the global resolver
+    pub fn certificate_source(
+        &self,
+        domain: &str,
+        global_certresolver: Option<&str>,
+    ) -> CertificateSource {
+        let domain_settings = self.per_domain.get(domain);
+        if let Some(certificate) = domain_settings.and_then(|s| s.certificate.as_ref()) {
+            return CertificateSource::Uploaded(certificate.clone());
+        }
+        domain_settings
+            .and_then(|s| s.certresolver.as_deref())
+            .or(self.certresolver.as_deref())
+            .or(global_certresolver)
+            .map(|resolver| CertificateSource::Resolver(resolver.to_string()))
+            .unwrap_or(CertificateSource::Default)
+    }
+
+    /// Certificates to request for `domain`, only those covering it
+    pub fn domains_for(&self, domain: &str) -> Vec<&TlsDomain> {
+        self.domains.iter().filter(|d| d.covers(domain)).collect()
+    }
+
+    /// Names of the uploaded certificates the app refers to
+    pub fn certificates(&self) -> impl Iterator<Item = &String> {
+        self.per_domain
+            .values()
+            .filter_map(|settings| settings.certificate.as_ref())
+    }
+}
+
+/// Certificate state of a domain of an app
+#[derive(Debug, Deserialize, Serialize, Clone, ToSchema, ToResponse)]
+pub struct DomainCertificateStatus {
+    pub domain: String,
+    /// Resolver which issues the certificate
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub certresolver: Option<String>,
+    /// Uploaded certificate used for the domain
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub certificate: Option<String>,
+    /// Expiry of the certificate currently served
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<DateTime<Utc>>,
+    /// Why the expiry could not be determined
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, ToSchema, ToResponse)]
+pub struct AppCertificatesResponse {
+    pub certificates: Vec<DomainCertificateStatus>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tls_domain_covers() {
+        let domain = TlsDomain {
+            main: "example.com".to_string(),
+            sans: vec!["*.example.com".to_string()],
+        };
+        assert!(domain.covers("example.com"));
+        assert!(domain.covers("app.example.com"));
+        assert!(!domain.covers("web.app.example.com"));
+        assert!(!domain.covers("example.org"));
+    }
+
+    #[test]
+    fn test_certificate_source() {
+        let settings = AppTlsSettings {
+            certresolver: Some("dns".to_string()),
+            per_domain: BTreeMap::from([
+                (
+                    "custom.example.org".to_string(),
+                    DomainTlsSettings {
+                        certificate: Some("custom".to_string()),
+                        ..Default::default()
+                    },
+                ),
+                (
+                    "http.example.org".to_string(),
+                    DomainTlsSettings {
+                        certresolver: Some("http".to_string()),
+                        ..Default::default()
+                    },
+                ),
+            ]),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            settings.certificate_source("custom.example.org", Some("global")),
+            CertificateSource::Uploaded("custom".to_string())
+        );
+        assert_eq!(
+            settings.certificate_source("http.example.org", Some("global")),
+            CertificateSource::Resolver("http".to_string())
+        );
+        assert_eq!(
+            settings.certificate_source("web.example.com", Some("global")),
+            CertificateSource::Resolver("dns".to_string())
+        );
+        assert_eq!(
+            AppTlsSettings::default().certificate_source("web.example.com", None),
+            CertificateSource::Default
+        );
+    }
+}
@@ -6,6 +6,8 @@ pub enum AppTtl {
     Hours(u32),
     Days(u32),
     Forever,
+    /// Stop the app after the given number of hours without HTTP traffic
+    Idle(u32),
 }
 
 impl From<AppTtl> for u32 {
@@ -14,6 +16,7 @@ impl From<AppTtl> for u32 {
             AppTtl::Hours(h) => h * 3600,
             AppTtl::Days(d) => d * 86400,
             AppTtl::Forever => u32::MAX,
+            AppTtl::Idle(h) => h * 3600,
         }
     }
 }
@@ -37,10 +40,18 @@ impl Serialize for AppTtl {
             AppTtl::Hours(h) => serializer.serialize_newtype_variant("AppTtl", 0, "Hours", &h),
             AppTtl::Days(d) => serializer.serialize_newtype_variant("AppTtl", 1, "Days", &d),
             AppTtl::Forever => serializer.serialize_unit_variant("AppTtl", 2, "Forever"),
+            AppTtl::Idle(h) => serializer.serialize_newtype_variant("AppTtl", 3, "Idle", &h),
         }
     }
 }
 
+/// Request body for extending the TTL of an app.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ExtendTtlRequest {
+    /// New TTL of the app, counting from now
+    pub time_to_live: AppTtl,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -104,6 +115,15 @@ mod tests {
         assert_eq!(original, deserialized);
     }
 
+    #[test]
+    fn test_roundtrip_idle() {
+        let original = AppTtl::Idle(8);
+        let yaml = serde_norway::to_string(&original).unwrap();
+        assert_eq!(yaml.trim(), "!Idle 8");
+        let deserialized: AppTtl = serde_norway::from_str(&yaml).unwrap();
+        assert_eq!(original, deserialized);
+    }
+
     #[test]
     fn test_roundtrip_forever() {
         let original = AppTtl::Forever;
@@ -1,4 +1,6 @@
 pub mod app_data;
 pub mod create_app_request;
 pub mod file_list;
+pub mod revision;
 pub mod shared_app_list;
+pub mod update_app_request;
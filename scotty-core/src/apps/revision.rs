@@ -0,0 +1,33 @@
+use chrono::{DateTime, Utc};
+use scotty_types::uuid::Uuid;
+use serde::{Deserialize, Serialize};
+
+/// A stored snapshot of the configuration of an app.
+///
+/// Revisions are created right before scotty applies new files or settings,
+/// so every revision describes the app as it was before that change.
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct RevisionInfo {
+    /// Number of the revision, counting up per app
+    pub revision: u32,
+    pub created_at: DateTime<Utc>,
+    /// Operation which created the revision, e.g. `create` or `rebuild`
+    pub reason: String,
+    /// Task which created the revision
+    #[serde(default)]
+    pub task_id: Option<Uuid>,
+    /// Files stored in the revision, relative to the app directory
+    #[serde(default)]
+    pub files: Vec<String>,
+    /// Files the operation was about to write, relative to the app directory
+    #[serde(default)]
+    pub applied_files: Vec<String>,
+}
+
+/// Request body for rolling back an app.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct RollbackRequest {
+    /// Revision to restore, defaults to the latest revision
+    #[serde(default)]
+    pub revision: Option<u32>,
+}
@@ -177,6 +177,8 @@ mod tests {
                 service: "web".to_string(),
                 port: 8080,
                 domains: vec!["custom.example.com".to_string()],
+                path: None,
+                strip_path: false,
             }],
         );
         list.add_app(app).await.unwrap();
@@ -196,6 +198,8 @@ mod tests {
                 service: "web".to_string(),
                 port: 8080,
                 domains: vec![],
+                path: None,
+                strip_path: false,
             }],
         );
         list.add_app(app).await.unwrap();
@@ -222,6 +226,7 @@ mod tests {
                 used_registry: None,
                 basic_auth: None,
                 exit_code: None,
+                health: None,
             }],
         );
         list.add_app(app).await.unwrap();
@@ -241,6 +246,8 @@ mod tests {
                 service: "web".to_string(),
                 port: 8080,
                 domains: vec![],
+                path: None,
+                strip_path: false,
             }],
         );
         list.add_app(app).await.unwrap();
@@ -266,6 +273,8 @@ mod tests {
                 service: "web".to_string(),
                 port: 8080,
                 domains: vec!["Custom.Example.COM".to_string()],
+                path: None,
+                strip_path: false,
             }],
         );
         let app_auto = make_app_with_settings(
@@ -275,6 +284,8 @@ mod tests {
                 service: "api".to_string(),
                 port: 3000,
                 domains: vec![],
+                path: None,
+                strip_path: false,
             }],
         );
         list.add_app(app_custom).await.unwrap();
@@ -0,0 +1,15 @@
+use super::{create_app_request::CustomDomainMapping, file_list::FileList};
+
+/// Request to push new files and optionally new settings to an existing app.
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize, utoipa::ToSchema)]
+pub struct UpdateAppRequest {
+    /// Fields of the app settings to change, e.g. `{"time_to_live": "Forever"}`.
+    /// Fields which are left out keep their current value, the scopes and the
+    /// maintenance mode of the app can't be changed by an update.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Object>)]
+    pub settings: Option<serde_json::Map<String, serde_json::Value>>,
+    pub files: FileList,
+    #[serde(default)]
+    pub custom_domains: Vec<CustomDomainMapping>,
+}
@@ -275,6 +275,46 @@ impl HttpClient {
         .await
     }
 
+    /// Make a PUT request and deserialize JSON response with retry logic
+    pub async fn put_json<T, R>(&self, url: &str, body: &T) -> Result<R, RetryError>
+    where
+        T: Serialize,
+        R: for<'de> Deserialize<'de>,
+    {
+        with_retry(
+            || async {
+                let response = self
+                    .client
+                    .put(url)
+                    .timeout(self.default_timeout)
+                    .json(body)
+                    .send()
+                    .await
+                    .map_err(HttpError::from)?;
+
+                // Check for redirects first - these should not be retried
+                if let Some(redirect_err) = Self::check_redirect(&response) {
+                    return Err(redirect_err);
+                }
+
+                if !response.status().is_success() {
+                    let status = response.status().as_u16();
+                    let error_msg = Self::extract_error_message(response).await;
+                    return Err(HttpError::http(status, error_msg));
+                }
+
+                let json = response
+                    .json::<R>()
+                    .await
+                    .map_err(|e| HttpError::ParseError(e.to_string()))?;
+
+                Ok(json)
+            },
+            &self.retry_config,
+        )
+        .await
+    }
+
     /// Make a request with custom method
     pub async fn request(&self, method: Method, url: &str) -> Result<Response, RetryError> {
         info!("{} request to {}", method, url);
@@ -5,13 +5,40 @@ use serde::{Deserialize, Serialize};
 
 use crate::apps::app_data::AppData;
 use crate::settings::app_blueprint::ActionName;
+use crate::utils::format::format_chrono_duration;
 use crate::utils::serde::{deserialize_app_name, serialize_app_name};
+use scotty_types::uuid::Uuid;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, utoipa::ToSchema, Hash, Eq)]
 pub struct GitlabContext {
     pub service_id: String,
     pub project_id: String,
     pub mr_id: u64,
+    /// Commit to report a status or deployment for
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, utoipa::ToSchema, Hash, Eq)]
+pub struct GithubContext {
+    pub service_id: String,
+    /// Repository as `owner/name`
+    pub repo: String,
+    pub pr: u64,
+    /// Commit to report a status or deployment for
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, utoipa::ToSchema, Hash, Eq)]
+pub struct GiteaContext {
+    pub service_id: String,
+    /// Repository as `owner/name`
+    pub repo: String,
+    pub pr: u64,
+    /// Commit to report a status for
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, utoipa::ToSchema, Hash, Eq)]
@@ -25,12 +52,67 @@ pub struct MattermostContext {
     pub channel: String,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, utoipa::ToSchema, Hash, Eq)]
+pub struct SlackContext {
+    pub service_id: String,
+    pub channel: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, utoipa::ToSchema, Hash, Eq)]
+pub struct MatrixContext {
+    pub service_id: String,
+    pub room_id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, utoipa::ToSchema, Hash, Eq)]
+pub struct TeamsContext {
+    pub service_id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, utoipa::ToSchema, Hash, Eq)]
+pub struct EmailContext {
+    pub service_id: String,
+    pub recipients: Vec<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, utoipa::ToSchema, Hash, Eq)]
 pub enum NotificationReceiver {
     Log,
     Webhook(WebhookContext),
     Gitlab(GitlabContext),
     Mattermost(MattermostContext),
+    Slack(SlackContext),
+    Matrix(MatrixContext),
+    Teams(TeamsContext),
+    Email(EmailContext),
+    Github(GithubContext),
+    Gitea(GiteaContext),
+}
+
+impl NotificationReceiver {
+    /// Whether both receivers notify the same channel, merge request or pull
+    /// request, regardless of the commit they report on.
+    pub fn same_target(&self, other: &NotificationReceiver) -> bool {
+        self.without_sha() == other.without_sha()
+    }
+
+    fn without_sha(&self) -> NotificationReceiver {
+        match self {
+            NotificationReceiver::Gitlab(ctx) => NotificationReceiver::Gitlab(GitlabContext {
+                sha: None,
+                ..ctx.clone()
+            }),
+            NotificationReceiver::Github(ctx) => NotificationReceiver::Github(GithubContext {
+                sha: None,
+                ..ctx.clone()
+            }),
+            NotificationReceiver::Gitea(ctx) => NotificationReceiver::Gitea(GiteaContext {
+                sha: None,
+                ..ctx.clone()
+            }),
+            other => other.clone(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -41,10 +123,49 @@ pub enum MessageType {
     AppDestroyed,
     AppPurged,
     AppRebuilt,
+    AppRolledBack,
+    AppUpdated,
+    /// The TTL of the app runs out in the given number of seconds
+    AppTtlExpiring(u64),
+    /// The load balancer config of the app differs from the generated one
+    AppLoadBalancerDrift {
+        issues: Vec<String>,
+        repairing: bool,
+    },
     AppCustomActionCompleted(ActionName),
     Custom(String),
 }
 impl MessageType {
+    /// Short name of the event, e.g. for message titles
+    pub fn event_name(&self) -> &'static str {
+        match self {
+            MessageType::AppStarted => "Started",
+            MessageType::AppStopped => "Stopped",
+            MessageType::AppCreated => "Created",
+            MessageType::AppDestroyed => "Destroyed",
+            MessageType::AppPurged => "Purged",
+            MessageType::AppRebuilt => "Rebuilt",
+            MessageType::AppRolledBack => "Rolled back",
+            MessageType::AppUpdated => "Updated",
+            MessageType::AppTtlExpiring(_) => "TTL expiring",
+            MessageType::AppLoadBalancerDrift { .. } => "Load balancer drift",
+            MessageType::AppCustomActionCompleted(_) => "Custom action completed",
+            MessageType::Custom(_) => "Notification",
+        }
+    }
+
+    /// Whether the event (re)deployed the app
+    pub fn is_deployment(&self) -> bool {
+        matches!(
+            self,
+            MessageType::AppCreated
+                | MessageType::AppStarted
+                | MessageType::AppRebuilt
+                | MessageType::AppRolledBack
+                | MessageType::AppUpdated
+        )
+    }
+
     fn get_message(&self, app: &AppData) -> String {
         match &self {
             MessageType::AppStarted => format!("App {} started", app.name),
@@ -53,6 +174,26 @@ impl MessageType {
             MessageType::AppDestroyed => format!("App {} destroyed", app.name),
             MessageType::AppPurged => format!("App {} purged", app.name),
             MessageType::AppRebuilt => format!("App {} rebuilt", app.name),
+            MessageType::AppRolledBack => format!("App {} rolled back", app.name),
+            MessageType::AppUpdated => format!("App {} updated", app.name),
+            MessageType::AppTtlExpiring(seconds) => format!(
+                "App {} will be {} in {}",
+                app.name,
+                match app.settings.as_ref().is_some_and(|s| s.destroy_on_ttl) {
+                    true => "destroyed",
+                    false => "stopped",
+                },
+                format_chrono_duration(&chrono::Duration::seconds(*seconds as i64))
+            ),
+            MessageType::AppLoadBalancerDrift { issues, repairing } => format!(
+                "Load balancer config of app {} drifted: {}{}",
+                app.name,
+                issues.join(", "),
+                match repairing {
+                    true => ", repairing it",
+                    false => "",
+                }
+            ),
             MessageType::AppCustomActionCompleted(action_name) => format!(
                 "Executed custom action {:?} on app {}",
                 action_name, app.name
@@ -68,6 +209,9 @@ pub struct Message {
     pub app_name: String,
     pub message: String,
     pub urls: Vec<String>,
+    /// The task which triggered the message, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub task_id: Option<Uuid>,
 }
 
 impl Message {
@@ -77,6 +221,14 @@ impl Message {
             app_name: app.name.clone(),
             message: message_type.get_message(app),
             urls: app.urls(),
+            task_id: None,
+        }
+    }
+
+    pub fn with_task_id(self, task_id: Uuid) -> Message {
+        Message {
+            task_id: Some(task_id),
+            ..self
         }
     }
 }
@@ -103,6 +255,134 @@ mod tests {
             "!Mattermost\nservice_id: mattermost\nchannel: test\n"
         );
     }
+
+    #[test]
+    fn test_slack_receiver_serialization() {
+        let value = NotificationReceiver::Slack(SlackContext {
+            service_id: "slack".to_string(),
+            channel: "#deployments".to_string(),
+        });
+        let yaml_string = serde_norway::to_string(&value).expect("Failed to serialize to YAML");
+
+        assert_eq!(
+            yaml_string,
+            "!Slack\nservice_id: slack\nchannel: '#deployments'\n"
+        );
+        let parsed: NotificationReceiver = serde_norway::from_str(&yaml_string).unwrap();
+        assert_eq!(parsed, value);
+    }
+
+    #[test]
+    fn test_matrix_receiver_serialization() {
+        let value = NotificationReceiver::Matrix(MatrixContext {
+            service_id: "matrix".to_string(),
+            room_id: "!abcdef:matrix.example.com".to_string(),
+        });
+        let yaml_string = serde_norway::to_string(&value).expect("Failed to serialize to YAML");
+
+        assert_eq!(
+            yaml_string,
+            "!Matrix\nservice_id: matrix\nroom_id: '!abcdef:matrix.example.com'\n"
+        );
+        let parsed: NotificationReceiver = serde_norway::from_str(&yaml_string).unwrap();
+        assert_eq!(parsed, value);
+    }
+
+    #[test]
+    fn test_teams_receiver_serialization() {
+        let value = NotificationReceiver::Teams(TeamsContext {
+            service_id: "teams".to_string(),
+        });
+        let yaml_string = serde_norway::to_string(&value).expect("Failed to serialize to YAML");
+
+        assert_eq!(yaml_string, "!Teams\nservice_id: teams\n");
+        let parsed: NotificationReceiver = serde_norway::from_str(&yaml_string).unwrap();
+        assert_eq!(parsed, value);
+    }
+
+    #[test]
+    fn test_gitlab_receiver_without_sha_deserialization() {
+        let parsed: NotificationReceiver = serde_norway::from_str(
+            "!Gitlab\nservice_id: gitlab\nproject_id: group/project\nmr_id: 42\n",
+        )
+        .unwrap();
+        assert_eq!(
+            parsed,
+            NotificationReceiver::Gitlab(GitlabContext {
+                service_id: "gitlab".to_string(),
+                project_id: "group/project".to_string(),
+                mr_id: 42,
+                sha: None,
+            })
+        );
+    }
+
+    #[test]
+    fn test_github_receiver_serialization() {
+        let value = NotificationReceiver::Github(GithubContext {
+            service_id: "github".to_string(),
+            repo: "factorial-io/scotty".to_string(),
+            pr: 12,
+            sha: Some("a1b2c3d".to_string()),
+        });
+        let yaml_string = serde_norway::to_string(&value).expect("Failed to serialize to YAML");
+
+        assert_eq!(
+            yaml_string,
+            "!Github\nservice_id: github\nrepo: factorial-io/scotty\npr: 12\nsha: a1b2c3d\n"
+        );
+        let parsed: NotificationReceiver = serde_norway::from_str(&yaml_string).unwrap();
+        assert_eq!(parsed, value);
+    }
+
+    #[test]
+    fn test_gitea_receiver_serialization() {
+        let value = NotificationReceiver::Gitea(GiteaContext {
+            service_id: "gitea".to_string(),
+            repo: "ops/website".to_string(),
+            pr: 3,
+            sha: None,
+        });
+        let yaml_string = serde_norway::to_string(&value).expect("Failed to serialize to YAML");
+
+        assert_eq!(
+            yaml_string,
+            "!Gitea\nservice_id: gitea\nrepo: ops/website\npr: 3\n"
+        );
+        let parsed: NotificationReceiver = serde_norway::from_str(&yaml_string).unwrap();
+        assert_eq!(parsed, value);
+    }
+
+    #[test]
+    fn test_same_target_ignores_sha() {
+        let receiver = |sha: Option<&str>| {
+            NotificationReceiver::Github(GithubContext {
+                service_id: "github".to_string(),
+                repo: "factorial-io/scotty".to_string(),
+                pr: 12,
+                sha: sha.map(|sha| sha.to_string()),
+            })
+        };
+        assert!(receiver(Some("a1b2c3d")).same_target(&receiver(Some("e4f5a6b"))));
+        assert!(receiver(None).same_target(&receiver(Some("e4f5a6b"))));
+        assert!(!receiver(None).same_target(&NotificationReceiver::Log));
+    }
+
+    #[test]
+    fn test_email_receiver_serialization() {
+        let value = NotificationReceiver::Email(EmailContext {
+            service_id: "smtp".to_string(),
+            recipients: vec!["pm@example.com".to_string(), "qa@example.com".to_string()],
+        });
+        let yaml_string = serde_norway::to_string(&value).expect("Failed to serialize to YAML");
+
+        assert_eq!(
+            yaml_string,
+            "!Email\nservice_id: smtp\nrecipients:\n- pm@example.com\n- qa@example.com\n"
+        );
+        let parsed: NotificationReceiver = serde_norway::from_str(&yaml_string).unwrap();
+        assert_eq!(parsed, value);
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
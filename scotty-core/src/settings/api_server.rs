@@ -54,6 +54,48 @@ impl Default for OAuthSettings {
     }
 }
 
+/// Settings of the forward-auth endpoint protecting apps with `forward_auth`
+#[derive(Debug, Deserialize, Clone)]
+#[allow(unused)]
+#[readonly::make]
+pub struct ForwardAuthSettings {
+    /// Name of the cookie holding the login of a user on the domains of the apps
+    #[serde(default = "default_forward_auth_cookie_name")]
+    pub cookie_name: String,
+    /// Parent domain of the apps the cookie is set for, e.g. `example.com`.
+    /// Defaults to `apps.domain_suffix`.
+    #[serde(default)]
+    pub cookie_domain: Option<String>,
+    /// How long a login via the cookie is valid, in seconds
+    #[serde(default = "default_forward_auth_cookie_ttl_seconds")]
+    pub cookie_ttl_seconds: u64,
+}
+
+fn default_forward_auth_cookie_name() -> String {
+    "scotty_forward_auth".to_string()
+}
+
+fn default_forward_auth_cookie_ttl_seconds() -> u64 {
+    12 * 3600
+}
+
+impl ForwardAuthSettings {
+    /// Get the cookie TTL as a Duration
+    pub fn cookie_ttl(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.cookie_ttl_seconds)
+    }
+}
+
+impl Default for ForwardAuthSettings {
+    fn default() -> Self {
+        Self {
+            cookie_name: default_forward_auth_cookie_name(),
+            cookie_domain: None,
+            cookie_ttl_seconds: default_forward_auth_cookie_ttl_seconds(),
+        }
+    }
+}
+
 #[derive(Debug, Deserialize, Clone)]
 #[allow(unused)]
 #[readonly::make]
@@ -77,6 +119,12 @@ pub struct ApiServer {
     /// back to Scotty's domain.
     #[serde(default)]
     pub base_url: Option<String>,
+    #[serde(default)]
+    pub forward_auth: ForwardAuthSettings,
+    /// Key signing the tokens scotty hands out, e.g. to the load balancer for
+    /// reporting requests to apps with an idle TTL.
+    #[serde(default, skip_serializing)]
+    pub secret_key: Option<SecretString>,
 }
 
 fn default_oauth_redirect_url() -> String {
@@ -201,6 +249,8 @@ impl Default for ApiServer {
             bearer_tokens: HashMap::new(),
             rate_limiting: RateLimitingConfig::default(),
             base_url: None,
+            forward_auth: ForwardAuthSettings::default(),
+            secret_key: None,
         }
     }
 }
@@ -2,7 +2,7 @@ use std::collections::HashMap;
 
 use serde::{Deserialize, Serialize};
 
-use crate::authorization::Permission;
+use crate::{apps::app_data::ReadinessSettings, authorization::Permission};
 
 #[derive(Clone, Debug, Hash, Eq, PartialEq, utoipa::ToSchema, utoipa::ToResponse)]
 pub enum ActionType {
@@ -146,6 +146,9 @@ pub struct AppBlueprint {
     pub actions: HashMap<ActionName, Action>,
     pub required_services: Vec<String>,
     pub public_services: Option<HashMap<String, u16>>,
+    /// Readiness defaults for apps using this blueprint
+    #[serde(default)]
+    pub readiness: Option<ReadinessSettings>,
 }
 
 #[derive(Debug)]
@@ -2,7 +2,7 @@ use std::collections::HashMap;
 
 use serde::Deserialize;
 
-use super::app_blueprint::AppBlueprintMap;
+use super::{app_blueprint::AppBlueprintMap, scheduler_interval::SchedulerInterval};
 
 #[derive(Debug, Deserialize, Clone)]
 #[allow(unused)]
@@ -11,6 +11,28 @@ pub struct Apps {
     pub max_depth: u32,
     pub domain_suffix: String,
     pub blueprints: AppBlueprintMap,
+    /// What happens when a lifecycle operation is requested while another
+    /// one is still running for the same app.
+    #[serde(default)]
+    pub operation_conflict: OperationConflictStrategy,
+    /// How long before the TTL of an app runs out its notification receivers
+    /// are warned. No warnings are sent if unset.
+    #[serde(default)]
+    pub ttl_warning_lead_time: Option<SchedulerInterval>,
+    /// Rewrite the load balancer config of apps whose config drifted from the
+    /// generated one. Drift is only reported if unset.
+    #[serde(default)]
+    pub repair_load_balancer_drift: bool,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum OperationConflictStrategy {
+    /// Wait until the running operation finished
+    #[default]
+    Queue,
+    /// Reject the request with a conflict error
+    Reject,
 }
 
 impl Default for Apps {
@@ -20,6 +42,9 @@ impl Default for Apps {
             max_depth: 3,
             domain_suffix: "".to_string(),
             blueprints: HashMap::new(),
+            operation_conflict: OperationConflictStrategy::default(),
+            ttl_warning_lead_time: None,
+            repair_load_balancer_drift: false,
         }
     }
 }
@@ -6,6 +6,8 @@ use serde::Deserialize;
 pub enum LoadBalancerType {
     HaproxyConfig,
     Traefik,
+    Caddy,
+    NginxProxy,
 }
 
 /// Default name of the Traefik container that Scotty connects to each
@@ -20,6 +22,74 @@ pub fn default_traefik_network() -> String {
     "proxy".to_string()
 }
 
+/// Default folder for the dynamic configuration files of the Traefik file provider
+pub fn default_traefik_dynamic_config_folder() -> String {
+    "./traefik/dynamic".to_string()
+}
+
+/// Default folder for certificates uploaded to Scotty
+pub fn default_traefik_certificates_folder() -> String {
+    "./traefik/certificates".to_string()
+}
+
+/// How Scotty passes the routing of an app to Traefik
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum TraefikProvider {
+    /// Labels on the containers, read by Traefik's docker provider
+    #[default]
+    Docker,
+    /// One dynamic configuration file per app, read by Traefik's file provider
+    File,
+}
+
+fn default_tcp_entrypoint() -> String {
+    "tcp".to_string()
+}
+
+fn default_tcp_public_port() -> u16 {
+    443
+}
+
+/// Range of ports handed out to TCP and UDP services with a dedicated port,
+/// both bounds included.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct PortPool {
+    pub start: u16,
+    pub end: u16,
+}
+
+impl PortPool {
+    pub fn ports(&self) -> std::ops::RangeInclusive<u16> {
+        self.start..=self.end
+    }
+}
+
+/// Routing of TCP and UDP services through Traefik
+#[derive(Debug, Deserialize, Clone, PartialEq, Eq)]
+pub struct TraefikTcpSettings {
+    /// Entrypoint for TLS connections routed by their SNI
+    #[serde(default = "default_tcp_entrypoint")]
+    pub entrypoint: String,
+    /// Port under which the SNI entrypoint is reachable from outside
+    #[serde(default = "default_tcp_public_port")]
+    pub public_port: u16,
+    /// Ports for services with a dedicated port. Traefik needs an entrypoint
+    /// named `tcp-<port>` respectively `udp-<port>` for every port of the pool.
+    #[serde(default)]
+    pub port_pool: Option<PortPool>,
+}
+
+impl Default for TraefikTcpSettings {
+    fn default() -> Self {
+        Self {
+            entrypoint: default_tcp_entrypoint(),
+            public_port: default_tcp_public_port(),
+            port_pool: None,
+        }
+    }
+}
+
 #[derive(Debug, Deserialize, Clone)]
 pub struct TraefikSettings {
     pub use_tls: bool,
@@ -32,6 +102,23 @@ pub struct TraefikSettings {
     /// app's public services without sharing a single global network.
     #[serde(default = "default_traefik_container_name")]
     pub container_name: String,
+    /// Base URL under which Traefik reaches Scotty to report requests to apps
+    /// with an idle TTL. Defaults to the public base URL of Scotty.
+    #[serde(default)]
+    pub activity_url: Option<String>,
+    /// Whether routing is passed via container labels or via files
+    #[serde(default)]
+    pub provider: TraefikProvider,
+    /// Folder watched by Traefik's file provider, only used with `provider: file`
+    #[serde(default = "default_traefik_dynamic_config_folder")]
+    pub dynamic_config_folder: String,
+    /// Folder for uploaded certificates. Traefik loads them from
+    /// `dynamic_config_folder`, which it has to watch in both provider modes.
+    #[serde(default = "default_traefik_certificates_folder")]
+    pub certificates_folder: String,
+    /// Routing of TCP and UDP services
+    #[serde(default)]
+    pub tcp: TraefikTcpSettings,
 }
 
 impl Default for TraefikSettings {
@@ -46,6 +133,11 @@ impl Default for TraefikSettings {
             certresolver: None,
             allowed_middlewares: Vec::new(),
             container_name: default_traefik_container_name(),
+            activity_url: None,
+            provider: TraefikProvider::default(),
+            dynamic_config_folder: default_traefik_dynamic_config_folder(),
+            certificates_folder: default_traefik_certificates_folder(),
+            tcp: TraefikTcpSettings::default(),
         }
     }
 }
@@ -64,6 +156,88 @@ impl TraefikSettings {
             certresolver,
             allowed_middlewares,
             container_name,
+            activity_url: None,
+            provider: TraefikProvider::default(),
+            dynamic_config_folder: default_traefik_dynamic_config_folder(),
+            certificates_folder: default_traefik_certificates_folder(),
+            tcp: TraefikTcpSettings::default(),
+        }
+    }
+}
+
+/// Default name of the network shared by caddy-docker-proxy and the public
+/// services of all apps.
+pub fn default_caddy_network() -> String {
+    "caddy".to_string()
+}
+
+fn default_use_tls() -> bool {
+    true
+}
+
+/// Settings for Caddy with caddy-docker-proxy, which reads the `caddy.*`
+/// labels of the containers.
+#[derive(Debug, Deserialize, Clone)]
+pub struct CaddySettings {
+    /// Serve the apps via https, Caddy obtains the certificates on its own
+    #[serde(default = "default_use_tls")]
+    pub use_tls: bool,
+    /// External network which Caddy uses to reach the public services
+    #[serde(default = "default_caddy_network")]
+    pub network: String,
+    /// Base URL under which Caddy reaches Scotty to report requests to apps
+    /// with an idle TTL. Defaults to the public base URL of Scotty.
+    #[serde(default)]
+    pub activity_url: Option<String>,
+}
+
+impl Default for CaddySettings {
+    fn default() -> Self {
+        Self {
+            use_tls: default_use_tls(),
+            network: default_caddy_network(),
+            activity_url: None,
+        }
+    }
+}
+
+/// Default name of the network shared by nginx-proxy and the public services
+/// of all apps.
+pub fn default_nginx_proxy_network() -> String {
+    "nginx-proxy".to_string()
+}
+
+/// Default folder for the htpasswd files, mounted into nginx-proxy at
+/// `/etc/nginx/htpasswd`.
+pub fn default_nginx_proxy_htpasswd_folder() -> String {
+    "./nginx-proxy/htpasswd".to_string()
+}
+
+/// Settings for the nginx-proxy image, optionally with acme-companion, which
+/// read the `VIRTUAL_*` and `LETSENCRYPT_*` environment of the containers.
+#[derive(Debug, Deserialize, Clone)]
+pub struct NginxProxySettings {
+    /// Request certificates from acme-companion via `LETSENCRYPT_HOST`
+    #[serde(default = "default_use_tls")]
+    pub use_tls: bool,
+    /// Contact address for the certificates, passed as `LETSENCRYPT_EMAIL`
+    #[serde(default)]
+    pub letsencrypt_email: Option<String>,
+    /// External network which nginx-proxy uses to reach the public services
+    #[serde(default = "default_nginx_proxy_network")]
+    pub network: String,
+    /// Folder where Scotty writes one htpasswd file per domain of apps with basic auth
+    #[serde(default = "default_nginx_proxy_htpasswd_folder")]
+    pub htpasswd_folder: String,
+}
+
+impl Default for NginxProxySettings {
+    fn default() -> Self {
+        Self {
+            use_tls: default_use_tls(),
+            letsencrypt_email: None,
+            network: default_nginx_proxy_network(),
+            htpasswd_folder: default_nginx_proxy_htpasswd_folder(),
         }
     }
 }
@@ -8,8 +8,10 @@ pub mod loadbalancer;
 pub mod notification_services;
 pub mod output;
 pub mod rate_limiting;
+pub mod revisions;
 pub mod scheduler_interval;
 pub mod shell;
+pub mod task_history;
 
 #[cfg(test)]
 mod custom_action_tests;
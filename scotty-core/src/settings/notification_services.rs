@@ -36,6 +36,40 @@ impl<'de> Deserialize<'de> for NotificationServiceSettings {
                     Ok(settings) => NotificationServiceType::Webhook(settings),
                     Err(e) => return Err(serde::de::Error::custom(e.to_string())),
                 },
+                Some("slack") => match SlackSettings::deserialize(value) {
+                    Ok(settings) if settings.webhook_url.is_some() == settings.token.is_some() => {
+                        return Err(serde::de::Error::custom(format!(
+                            "Slack service {key} needs either a webhook_url or a token"
+                        )))
+                    }
+                    Ok(settings) => NotificationServiceType::Slack(settings),
+                    Err(e) => return Err(serde::de::Error::custom(e.to_string())),
+                },
+                Some("matrix") => match MatrixSettings::deserialize(value) {
+                    Ok(settings) => NotificationServiceType::Matrix(settings),
+                    Err(e) => return Err(serde::de::Error::custom(e.to_string())),
+                },
+                Some("teams") => match TeamsSettings::deserialize(value) {
+                    Ok(settings) => NotificationServiceType::Teams(settings),
+                    Err(e) => return Err(serde::de::Error::custom(e.to_string())),
+                },
+                Some("github") => match GithubSettings::deserialize(value) {
+                    Ok(settings) => NotificationServiceType::Github(settings),
+                    Err(e) => return Err(serde::de::Error::custom(e.to_string())),
+                },
+                Some("gitea") => match GiteaSettings::deserialize(value) {
+                    Ok(settings) if settings.commit_report == CommitReport::Deployment => {
+                        return Err(serde::de::Error::custom(format!(
+                            "Gitea service {key} can't report deployments, use commit_report: status"
+                        )))
+                    }
+                    Ok(settings) => NotificationServiceType::Gitea(settings),
+                    Err(e) => return Err(serde::de::Error::custom(e.to_string())),
+                },
+                Some("email") => match EmailSettings::deserialize(value) {
+                    Ok(settings) => NotificationServiceType::Email(settings),
+                    Err(e) => return Err(serde::de::Error::custom(e.to_string())),
+                },
                 _ => {
                     return Err(serde::de::Error::custom(format!(
                         "Unknown service type: {service_type}"
@@ -71,12 +105,60 @@ impl NotificationServiceSettings {
         }
     }
 
+    pub fn get_slack(&self, service_id: &str) -> Option<&SlackSettings> {
+        match self.services.get(service_id) {
+            Some(NotificationServiceType::Slack(settings)) => Some(settings),
+            _ => None,
+        }
+    }
+
+    pub fn get_matrix(&self, service_id: &str) -> Option<&MatrixSettings> {
+        match self.services.get(service_id) {
+            Some(NotificationServiceType::Matrix(settings)) => Some(settings),
+            _ => None,
+        }
+    }
+
+    pub fn get_teams(&self, service_id: &str) -> Option<&TeamsSettings> {
+        match self.services.get(service_id) {
+            Some(NotificationServiceType::Teams(settings)) => Some(settings),
+            _ => None,
+        }
+    }
+
+    pub fn get_github(&self, service_id: &str) -> Option<&GithubSettings> {
+        match self.services.get(service_id) {
+            Some(NotificationServiceType::Github(settings)) => Some(settings),
+            _ => None,
+        }
+    }
+
+    pub fn get_gitea(&self, service_id: &str) -> Option<&GiteaSettings> {
+        match self.services.get(service_id) {
+            Some(NotificationServiceType::Gitea(settings)) => Some(settings),
+            _ => None,
+        }
+    }
+
+    pub fn get_email(&self, service_id: &str) -> Option<&EmailSettings> {
+        match self.services.get(service_id) {
+            Some(NotificationServiceType::Email(settings)) => Some(settings),
+            _ => None,
+        }
+    }
+
     pub fn contains(&self, service_id: &NotificationReceiver) -> bool {
         match service_id {
             NotificationReceiver::Log => true,
             NotificationReceiver::Mattermost(id) => self.services.contains_key(&id.service_id),
             NotificationReceiver::Gitlab(id) => self.services.contains_key(&id.service_id),
             NotificationReceiver::Webhook(id) => self.services.contains_key(&id.service_id),
+            NotificationReceiver::Slack(id) => self.services.contains_key(&id.service_id),
+            NotificationReceiver::Matrix(id) => self.services.contains_key(&id.service_id),
+            NotificationReceiver::Teams(id) => self.services.contains_key(&id.service_id),
+            NotificationReceiver::Email(id) => self.services.contains_key(&id.service_id),
+            NotificationReceiver::Github(id) => self.services.contains_key(&id.service_id),
+            NotificationReceiver::Gitea(id) => self.services.contains_key(&id.service_id),
         }
     }
 }
@@ -93,6 +175,59 @@ pub struct MattermostSettings {
 pub struct GitlabSettings {
     pub host: String,
     pub token: MaskedSecret,
+    #[serde(default)]
+    pub commit_report: CommitReport,
+    #[serde(default)]
+    pub notes: GitlabNotes,
+}
+
+/// How events are posted to a merge request
+#[derive(Debug, Deserialize, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum GitlabNotes {
+    /// Keep a single note per app with a status table, updated on every event
+    #[default]
+    Update,
+    /// Post a new note for every event
+    PerEvent,
+}
+
+/// What a forge notification reports on the commit of a receiver, besides
+/// the comment on the merge or pull request.
+#[derive(Debug, Deserialize, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CommitReport {
+    #[default]
+    None,
+    /// A commit status linking to the app
+    Status,
+    /// A deployment environment named after the app
+    Deployment,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[readonly::make]
+pub struct GithubSettings {
+    #[serde(default = "GithubSettings::default_api_url")]
+    pub api_url: String,
+    pub token: MaskedSecret,
+    #[serde(default)]
+    pub commit_report: CommitReport,
+}
+
+impl GithubSettings {
+    fn default_api_url() -> String {
+        "https://api.github.com".to_string()
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[readonly::make]
+pub struct GiteaSettings {
+    pub host: String,
+    pub token: MaskedSecret,
+    #[serde(default)]
+    pub commit_report: CommitReport,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -102,9 +237,73 @@ pub struct WebhookSettings {
     pub method: String,
 }
 
+/// Slack is reached either via an incoming webhook, which posts to the
+/// channel it was created for, or via a bot token, which may post to every
+/// channel the bot was invited to.
+#[derive(Debug, Deserialize, Clone)]
+#[readonly::make]
+pub struct SlackSettings {
+    pub webhook_url: Option<MaskedSecret>,
+    pub token: Option<MaskedSecret>,
+    #[serde(default = "SlackSettings::default_api_url")]
+    pub api_url: String,
+}
+
+impl SlackSettings {
+    fn default_api_url() -> String {
+        "https://slack.com/api".to_string()
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[readonly::make]
+pub struct MatrixSettings {
+    pub homeserver: String,
+    pub access_token: MaskedSecret,
+}
+
+/// A Teams incoming webhook or a Workflows "post to a channel when a webhook
+/// request is received" URL, both accept Adaptive Cards.
+#[derive(Debug, Deserialize, Clone)]
+#[readonly::make]
+pub struct TeamsSettings {
+    pub webhook_url: MaskedSecret,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[readonly::make]
+pub struct EmailSettings {
+    pub host: String,
+    #[serde(default = "EmailSettings::default_port")]
+    pub port: u16,
+    /// Upgrade the connection with STARTTLS, only disable it for local relays
+    #[serde(default = "EmailSettings::default_starttls")]
+    pub starttls: bool,
+    pub username: Option<MaskedSecret>,
+    pub password: Option<MaskedSecret>,
+    /// Sender address, e.g. `Scotty <scotty@example.com>`
+    pub from: String,
+}
+
+impl EmailSettings {
+    fn default_port() -> u16 {
+        587
+    }
+
+    fn default_starttls() -> bool {
+        true
+    }
+}
+
 #[derive(Debug, Deserialize, Clone)]
 pub enum NotificationServiceType {
     Mattermost(MattermostSettings),
     Gitlab(GitlabSettings),
     Webhook(WebhookSettings),
+    Slack(SlackSettings),
+    Matrix(MatrixSettings),
+    Teams(TeamsSettings),
+    Email(EmailSettings),
+    Github(GithubSettings),
+    Gitea(GiteaSettings),
 }
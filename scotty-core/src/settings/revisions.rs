@@ -0,0 +1,52 @@
+use serde::Deserialize;
+
+/// Default folder of the app revisions, relative to the apps root folder.
+pub const DEFAULT_REVISIONS_FOLDER: &str = ".scotty-revisions";
+
+/// Settings for app revisions.
+///
+/// Before scotty applies new files or settings to an app, it stores the
+/// current configuration as a revision, so a failed change can be rolled back.
+#[derive(Debug, Deserialize, Clone)]
+pub struct RevisionSettings {
+    /// Whether revisions are created and failed changes are rolled back.
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// Folder holding the revisions. Defaults to
+    /// `<apps.root_folder>/.scotty-revisions`.
+    #[serde(default)]
+    pub folder: Option<String>,
+    /// Number of revisions kept per app.
+    ///
+    /// Configurable via `SCOTTY__REVISIONS__KEEP`.
+    #[serde(default = "default_keep")]
+    pub keep: u32,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+fn default_keep() -> u32 {
+    10
+}
+
+impl Default for RevisionSettings {
+    fn default() -> Self {
+        Self {
+            enabled: default_enabled(),
+            folder: None,
+            keep: default_keep(),
+        }
+    }
+}
+
+impl RevisionSettings {
+    /// Resolve the revisions folder against the apps root folder.
+    pub fn get_folder(&self, root_folder: &str) -> std::path::PathBuf {
+        match &self.folder {
+            Some(folder) => std::path::PathBuf::from(folder),
+            None => std::path::Path::new(root_folder).join(DEFAULT_REVISIONS_FOLDER),
+        }
+    }
+}
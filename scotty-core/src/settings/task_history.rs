@@ -0,0 +1,52 @@
+use serde::Deserialize;
+
+/// Default file name of the task history, relative to the apps root folder.
+pub const DEFAULT_TASK_HISTORY_FILE: &str = ".scotty-task-history.jsonl";
+
+/// Settings for the persistent task history.
+///
+/// Finished tasks are appended to a JSONL file so they survive server
+/// restarts and the in-memory cleanup done by the task manager.
+#[derive(Debug, Deserialize, Clone)]
+pub struct TaskHistorySettings {
+    /// Whether finished tasks are written to the history file.
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// Path of the history file. Defaults to
+    /// `<apps.root_folder>/.scotty-task-history.jsonl`.
+    #[serde(default)]
+    pub file: Option<String>,
+    /// Number of days a finished task is kept in the history.
+    ///
+    /// Configurable via `SCOTTY__TASK_HISTORY__RETENTION_DAYS`.
+    #[serde(default = "default_retention_days")]
+    pub retention_days: u32,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+fn default_retention_days() -> u32 {
+    30
+}
+
+impl Default for TaskHistorySettings {
+    fn default() -> Self {
+        Self {
+            enabled: default_enabled(),
+            file: None,
+            retention_days: default_retention_days(),
+        }
+    }
+}
+
+impl TaskHistorySettings {
+    /// Resolve the history file path against the apps root folder.
+    pub fn get_file_path(&self, root_folder: &str) -> std::path::PathBuf {
+        match &self.file {
+            Some(file) => std::path::PathBuf::from(file),
+            None => std::path::Path::new(root_folder).join(DEFAULT_TASK_HISTORY_FILE),
+        }
+    }
+}
@@ -73,6 +73,32 @@ pub fn get_override_file(compose_path: &Path) -> Option<PathBuf> {
     Some(parent_dir.join(override_file_name))
 }
 
+/// Returns the project name Docker Compose uses for the given compose file.
+///
+/// This is the top-level `name` of the compose file if set, otherwise the name of
+/// the directory containing it, normalized the same way Docker Compose does
+/// (lowercase, only `a-z`, `0-9`, `-` and `_`, starting with a letter or digit).
+pub fn get_project_name(compose_path: &Path) -> Option<String> {
+    let name_from_file = std::fs::read_to_string(compose_path)
+        .ok()
+        .and_then(|content| serde_norway::from_str::<serde_norway::Value>(&content).ok())
+        .and_then(|yaml| yaml.get("name")?.as_str().map(String::from));
+
+    let name = match name_from_file {
+        Some(name) => name,
+        None => compose_path.parent()?.file_name()?.to_str()?.to_string(),
+    };
+
+    let normalized: String = name
+        .to_lowercase()
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '-' || *c == '_')
+        .collect();
+    let normalized = normalized.trim_start_matches(['-', '_']).to_string();
+
+    (!normalized.is_empty()).then_some(normalized)
+}
+
 /// Finds all standard configuration files in a directory tree, returning only the highest priority file per directory.
 ///
 /// This function walks through the directory structure starting from `root_folder` up to `max_depth`
@@ -326,4 +352,18 @@ mod tests {
             "Should find compose.yml in root"
         );
     }
+
+    #[test]
+    fn test_get_project_name() {
+        let temp_dir = TempDir::new().unwrap();
+        let app_dir = temp_dir.path().join("My.App_1");
+        std::fs::create_dir_all(&app_dir).unwrap();
+
+        let compose_path = app_dir.join("compose.yml");
+        std::fs::write(&compose_path, "services: {}").unwrap();
+        assert_eq!(get_project_name(&compose_path).unwrap(), "myapp_1");
+
+        std::fs::write(&compose_path, "name: Custom-Project\nservices: {}").unwrap();
+        assert_eq!(get_project_name(&compose_path).unwrap(), "custom-project");
+    }
 }
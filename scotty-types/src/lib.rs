@@ -226,9 +226,11 @@ impl TaskOutput {
 )]
 #[ts(export)]
 pub enum State {
+    Queued,
     Running,
     Finished,
     Failed,
+    Cancelled,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, TS, utoipa::ToResponse, utoipa::ToSchema)]
@@ -244,6 +246,12 @@ pub struct TaskDetails {
     pub finish_time: Option<DateTime<Utc>>,
     pub last_exit_code: Option<i32>,
     pub app_name: Option<String>,
+    /// User who started the task, if known
+    #[serde(default)]
+    pub requested_by: Option<String>,
+    /// Position in the operation queue of the app while the task is queued
+    #[serde(default)]
+    pub queue_position: Option<usize>,
     pub output_collection_active: bool,
     /// Embedded task output (not serialized by default)
     #[serde(skip)]
@@ -261,6 +269,8 @@ impl Default for TaskDetails {
             finish_time: None,
             last_exit_code: None,
             app_name: None,
+            requested_by: None,
+            queue_position: None,
             output_collection_active: true,
             output: TaskOutput::default(),
         }
@@ -278,6 +288,8 @@ impl TaskDetails {
             finish_time: None,
             last_exit_code: None,
             app_name,
+            requested_by: None,
+            queue_position: None,
             output_collection_active: true,
             output: TaskOutput::default(),
         }
@@ -299,6 +311,8 @@ impl TaskDetails {
             finish_time: None,
             last_exit_code: None,
             app_name,
+            requested_by: None,
+            queue_position: None,
             output_collection_active: true,
             output: TaskOutput::new_with_limits(max_lines, max_line_length),
         }
@@ -557,6 +571,12 @@ pub enum WebSocketMessage {
         task_id: Uuid,
         reason: String, // "completed", "failed", "expired", "deleted"
     },
+
+    // Task control messages (client → server)
+    CancelTask {
+        #[ts(type = "string")]
+        task_id: Uuid,
+    },
 }
 
 impl fmt::Display for WebSocketMessage {
@@ -700,6 +720,9 @@ impl fmt::Display for WebSocketMessage {
             WebSocketMessage::TaskOutputStreamEnded { task_id, reason } => {
                 write!(f, "Task {} output stream ended: {}", task_id, reason)
             }
+            WebSocketMessage::CancelTask { task_id } => {
+                write!(f, "Cancel task {}", task_id)
+            }
         }
     }
 }
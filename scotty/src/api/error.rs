@@ -32,6 +32,12 @@ pub enum AppError {
     #[error("Task not found: {0}")]
     TaskNotFound(Uuid),
 
+    #[error("Task {0} is not running")]
+    TaskNotRunning(Uuid),
+
+    #[error("App {0} is busy, task {1} is still running")]
+    AppOperationInProgress(String, Uuid),
+
     #[error("File content could not be decoded!")]
     FileContentDecodingError,
 
@@ -77,6 +83,12 @@ pub enum AppError {
     #[error("App is not running: {0}")]
     AppNotRunning(String),
 
+    #[error("Revision {1} not found for app {0}")]
+    RevisionNotFound(String, u32),
+
+    #[error("No revisions available for app {0}")]
+    NoRevisionsAvailable(String),
+
     #[error("{0}")]
     ActionNotFound(String),
 
@@ -127,7 +139,11 @@ impl AppError {
             AppError::InternalServerError(_) => StatusCode::INTERNAL_SERVER_ERROR,
             AppError::AppNotFound(_) => StatusCode::NOT_FOUND,
             AppError::TaskNotFound(_) => StatusCode::NOT_FOUND,
+            AppError::TaskNotRunning(_) => StatusCode::CONFLICT,
+            AppError::AppOperationInProgress(_, _) => StatusCode::CONFLICT,
             AppError::AppSettingsNotFound(_) => StatusCode::NOT_FOUND,
+            AppError::RevisionNotFound(_, _) => StatusCode::NOT_FOUND,
+            AppError::NoRevisionsAvailable(_) => StatusCode::NOT_FOUND,
             AppError::CantCreateAppWithScottyYmlFile => StatusCode::BAD_REQUEST,
             AppError::BadRequest(_) => StatusCode::BAD_REQUEST,
             AppError::FileCompressionCorrupted(_, _) => StatusCode::BAD_REQUEST,
@@ -6,6 +6,7 @@ pub mod rate_limiting;
 pub mod rest;
 pub mod router;
 pub mod secure_response;
+pub mod signing;
 pub mod websocket;
 
 // test_utils is public for integration tests
@@ -59,6 +59,11 @@ async fn create_scotty_app_with_mock_oauth(mock_server_url: &str) -> axum::Route
         ),
         task_output_service: crate::tasks::output_streaming::TaskOutputStreamingService::new(),
         messenger: create_test_websocket_messenger(),
+        activity: crate::services::ActivityTracker::new(),
+        ttl_warnings: crate::services::TtlWarnings::new(),
+        drift_reports: crate::services::DriftReports::new(),
+        forward_auth: crate::services::ForwardAuthCache::new(),
+        tcp_ports: crate::services::TcpPortReservations::new(),
         settings,
     });
 
@@ -552,6 +557,11 @@ async fn test_complete_oauth_web_flow_with_appstate_session_management() {
         ),
         task_output_service: crate::tasks::output_streaming::TaskOutputStreamingService::new(),
         messenger: create_test_websocket_messenger(),
+        activity: crate::services::ActivityTracker::new(),
+        ttl_warnings: crate::services::TtlWarnings::new(),
+        drift_reports: crate::services::DriftReports::new(),
+        forward_auth: crate::services::ForwardAuthCache::new(),
+        tcp_ports: crate::services::TcpPortReservations::new(),
         settings,
     });
 
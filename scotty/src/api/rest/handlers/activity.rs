@@ -0,0 +1,102 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use scotty_core::utils::slugify::slugify;
+use serde::Deserialize;
+use tracing::warn;
+
+use crate::api::signing::verify_activity_token;
+use crate::app_state::SharedAppState;
+
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+pub struct ActivityQuery {
+    /// Token of the app, signed with `api.secret_key`
+    #[serde(default)]
+    pub token: String,
+}
+
+/// Record a request to an app.
+///
+/// Used as a forward-auth endpoint of the load balancer for apps with an idle
+/// TTL. Only reports carrying the token of the app are accepted, so nobody
+/// else can keep an app running. A request with a valid token is never
+/// denied, also for unknown apps. Forward-auth fails closed though: while
+/// scotty is unreachable the load balancer rejects requests to the app.
+#[utoipa::path(
+    get,
+    path = "/api/v1/activity/{app_name}",
+    params(ActivityQuery),
+    responses(
+    (status = 200, description = "Activity recorded"),
+    (status = 401, description = "Invalid token")
+    )
+)]
+pub async fn record_activity_handler(
+    Path(app_name): Path<String>,
+    Query(query): Query<ActivityQuery>,
+    State(state): State<SharedAppState>,
+) -> impl IntoResponse {
+    let app_name = slugify(&app_name);
+    if !verify_activity_token(&state.settings, &app_name, &query.token) {
+        warn!(
+            "Rejected activity report for app {} with invalid token",
+            app_name
+        );
+        return StatusCode::UNAUTHORIZED;
+    }
+    if state.apps.get_app(&app_name).await.is_some() {
+        state.activity.record(&app_name).await;
+    }
+    StatusCode::OK
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::signing::activity_token;
+    use crate::api::test_utils::{create_test_app_state_with_settings, settings_with_secret_key};
+    use crate::settings::config::Settings;
+    use axum::response::IntoResponse;
+    use scotty_core::apps::app_data::AppData;
+
+    async fn report(state: &SharedAppState, token: &str) -> StatusCode {
+        record_activity_handler(
+            Path("my-app".to_string()),
+            Query(ActivityQuery {
+                token: token.to_string(),
+            }),
+            State(state.clone()),
+        )
+        .await
+        .into_response()
+        .status()
+    }
+
+    #[tokio::test]
+    async fn test_record_activity_needs_token_of_app() {
+        let state = create_test_app_state_with_settings(
+            settings_with_secret_key(Settings::default(), "secret"),
+            None,
+        )
+        .await;
+        state
+            .apps
+            .add_app(AppData {
+                name: "my-app".to_string(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(report(&state, "").await, StatusCode::UNAUTHORIZED);
+        let other_token = activity_token(&state.settings, "other-app").unwrap();
+        assert_eq!(report(&state, &other_token).await, StatusCode::UNAUTHORIZED);
+        assert!(state.activity.last_activity("my-app").await.is_none());
+
+        let token = activity_token(&state.settings, "my-app").unwrap();
+        assert_eq!(report(&state, &token).await, StatusCode::OK);
+        assert!(state.activity.last_activity("my-app").await.is_some());
+    }
+}
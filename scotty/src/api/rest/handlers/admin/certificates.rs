@@ -0,0 +1,121 @@
+use crate::api::basic_auth::CurrentUser;
+use crate::docker::loadbalancer::certificates::{
+    list_certificates, remove_certificate, store_certificate, validate_name,
+};
+use crate::{api::error::AppError, app_state::SharedAppState};
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    Extension, Json,
+};
+use scotty_core::admin::{
+    CertificateInfo, CertificatesListResponse, SuccessResponse, UploadCertificateRequest,
+};
+use scotty_core::settings::loadbalancer::LoadBalancerType;
+use tracing::info;
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/authenticated/admin/certificates",
+    responses(
+        (status = 200, response = inline(CertificatesListResponse)),
+        (status = 401, description = "Access token is missing or invalid"),
+        (status = 403, description = "Insufficient permissions - AdminRead required"),
+    ),
+    security(
+        ("bearerAuth" = [])
+    )
+)]
+pub async fn list_certificates_handler(
+    State(state): State<SharedAppState>,
+) -> Result<impl IntoResponse, AppError> {
+    let certificates = list_certificates(&state.settings)?;
+    Ok(Json(CertificatesListResponse { certificates }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/authenticated/admin/certificates",
+    request_body = UploadCertificateRequest,
+    responses(
+        (status = 200, response = inline(CertificateInfo)),
+        (status = 400, description = "Invalid certificate or key"),
+        (status = 401, description = "Access token is missing or invalid"),
+        (status = 403, description = "Insufficient permissions - AdminWrite required"),
+    ),
+    security(
+        ("bearerAuth" = [])
+    )
+)]
+pub async fn upload_certificate_handler(
+    State(state): State<SharedAppState>,
+    Extension(user): Extension<CurrentUser>,
+    Json(request): Json<UploadCertificateRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    info!(
+        "Admin uploading certificate '{}' for user: {}",
+        request.name, user.email
+    );
+
+    // Only Traefik serves certificates from its file provider
+    if state.settings.load_balancer_type != LoadBalancerType::Traefik {
+        return Err(AppError::BadRequest(format!(
+            "{:?} does not support uploaded certificates",
+            state.settings.load_balancer_type
+        )));
+    }
+
+    let certificate = store_certificate(&state.settings, &request)
+        .map_err(|e| AppError::BadRequest(e.to_string()))?;
+    Ok(Json(certificate))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/authenticated/admin/certificates/{name}",
+    responses(
+        (status = 200, response = inline(SuccessResponse)),
+        (status = 400, description = "Certificate is still used by an app"),
+        (status = 401, description = "Access token is missing or invalid"),
+        (status = 403, description = "Insufficient permissions - AdminWrite required"),
+    ),
+    security(
+        ("bearerAuth" = [])
+    )
+)]
+pub async fn remove_certificate_handler(
+    Path(name): Path<String>,
+    State(state): State<SharedAppState>,
+    Extension(user): Extension<CurrentUser>,
+) -> Result<impl IntoResponse, AppError> {
+    info!(
+        "Admin removing certificate '{}' for user: {}",
+        name, user.email
+    );
+    validate_name(&name).map_err(|e| AppError::BadRequest(e.to_string()))?;
+
+    let apps = state.apps.get_apps().await;
+    let users: Vec<&str> = apps
+        .apps
+        .iter()
+        .filter(|app| {
+            app.settings
+                .as_ref()
+                .is_some_and(|settings| settings.tls.certificates().any(|c| c == &name))
+        })
+        .map(|app| app.name.as_str())
+        .collect();
+    if !users.is_empty() {
+        return Err(AppError::BadRequest(format!(
+            "Certificate {} is still used by {}",
+            name,
+            users.join(", ")
+        )));
+    }
+
+    remove_certificate(&state.settings, &name)?;
+    Ok(Json(SuccessResponse {
+        success: true,
+        message: format!("Certificate '{}' removed", name),
+    }))
+}
@@ -1,4 +1,5 @@
 pub mod assignments;
+pub mod certificates;
 pub mod custom_actions;
 pub mod permissions;
 pub mod roles;
@@ -0,0 +1,169 @@
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    Extension, Json,
+};
+use scotty_core::{
+    apps::app_data::{
+        AddBasicAuthUserRequest, AppData, AppSettings, BasicAuthUser, BasicAuthUserInfo,
+        BasicAuthUsersResponse,
+    },
+    tasks::running_app_context::RunningAppContext,
+    utils::slugify::slugify,
+};
+use tracing::error;
+
+use super::create::prepare_basic_auth;
+use crate::{
+    api::{basic_auth::CurrentUser, error::AppError, secure_response::SecureJson},
+    app_state::SharedAppState,
+    docker::update_app::update_app_settings,
+};
+
+async fn get_app_with_settings(
+    state: &SharedAppState,
+    app_name: &str,
+) -> Result<(AppData, AppSettings), AppError> {
+    let app = state
+        .apps
+        .get_app(app_name)
+        .await
+        .ok_or_else(|| AppError::AppNotFound(app_name.to_string()))?;
+    let settings = app
+        .settings
+        .clone()
+        .ok_or_else(|| AppError::AppSettingsNotFound(app_name.to_string()))?;
+    Ok((app, settings))
+}
+
+/// Store the new users of an app and apply them to the load balancer
+async fn apply_basic_auth(
+    state: &SharedAppState,
+    user: &CurrentUser,
+    app: &AppData,
+    settings: AppSettings,
+) -> Result<SecureJson<RunningAppContext>, AppError> {
+    match update_app_settings(state.clone(), app, settings, Some(&user.email)).await {
+        Ok(app_data) => Ok(SecureJson(app_data)),
+        Err(e) => {
+            error!("Updating basic auth of app {} failed: {:?}", app.name, e);
+            Err(AppError::from(e))
+        }
+    }
+}
+
+/// List the basic auth users of an app
+#[utoipa::path(
+    get,
+    path = "/api/v1/authenticated/apps/{app_name}/auth",
+    responses(
+    (status = 200, response = inline(BasicAuthUsersResponse)),
+    (status = 401, description = "Access token is missing or invalid"),
+    (status = 404, description = "App not found"),
+    ),
+    security(
+        ("bearerAuth" = [])
+    )
+)]
+pub async fn list_basic_auth_users_handler(
+    Path(app_name): Path<String>,
+    State(state): State<SharedAppState>,
+) -> Result<impl IntoResponse, AppError> {
+    let (_, settings) = get_app_with_settings(&state, &slugify(&app_name)).await?;
+    Ok(Json(BasicAuthUsersResponse {
+        users: settings
+            .basic_auth
+            .iter()
+            .map(BasicAuthUserInfo::from)
+            .collect(),
+    }))
+}
+
+/// Add a basic auth user to an app or change the password of an existing one
+#[utoipa::path(
+    post,
+    path = "/api/v1/authenticated/apps/{app_name}/auth",
+    request_body = AddBasicAuthUserRequest,
+    responses(
+    (status = 200, response = inline(RunningAppContext)),
+    (status = 400, description = "Invalid user name or password"),
+    (status = 401, description = "Access token is missing or invalid"),
+    (status = 404, description = "App not found"),
+    ),
+    security(
+        ("bearerAuth" = [])
+    )
+)]
+pub async fn add_basic_auth_user_handler(
+    Path(app_name): Path<String>,
+    State(state): State<SharedAppState>,
+    Extension(user): Extension<CurrentUser>,
+    Json(payload): Json<AddBasicAuthUserRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let (app, settings) = get_app_with_settings(&state, &slugify(&app_name)).await?;
+
+    let mut users: Vec<BasicAuthUser> = settings
+        .basic_auth
+        .iter()
+        .filter(|existing| existing.username != payload.username)
+        .cloned()
+        .collect();
+    users.push(BasicAuthUser::new(&payload.username, &payload.password));
+    let basic_auth = prepare_basic_auth(&state, &users, Some(&settings.basic_auth))?;
+
+    apply_basic_auth(
+        &state,
+        &user,
+        &app,
+        AppSettings {
+            basic_auth,
+            ..settings
+        },
+    )
+    .await
+}
+
+/// Remove a basic auth user from an app
+#[utoipa::path(
+    delete,
+    path = "/api/v1/authenticated/apps/{app_name}/auth/{username}",
+    responses(
+    (status = 200, response = inline(RunningAppContext)),
+    (status = 401, description = "Access token is missing or invalid"),
+    (status = 404, description = "App or user not found"),
+    ),
+    security(
+        ("bearerAuth" = [])
+    )
+)]
+pub async fn remove_basic_auth_user_handler(
+    Path((app_name, username)): Path<(String, String)>,
+    State(state): State<SharedAppState>,
+    Extension(user): Extension<CurrentUser>,
+) -> Result<impl IntoResponse, AppError> {
+    let (app, settings) = get_app_with_settings(&state, &slugify(&app_name)).await?;
+    if !settings
+        .basic_auth
+        .iter()
+        .any(|existing| existing.username == username)
+    {
+        return Err(AppError::NotFound);
+    }
+
+    let basic_auth = settings
+        .basic_auth
+        .iter()
+        .filter(|existing| existing.username != username)
+        .cloned()
+        .collect();
+    apply_basic_auth(
+        &state,
+        &user,
+        &app,
+        AppSettings {
+            basic_auth,
+            ..settings
+        },
+    )
+    .await
+}
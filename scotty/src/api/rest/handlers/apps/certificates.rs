@@ -0,0 +1,92 @@
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    Json,
+};
+use futures_util::future::join_all;
+use scotty_core::{
+    apps::app_data::{AppCertificatesResponse, CertificateSource, DomainCertificateStatus},
+    utils::slugify::slugify,
+};
+
+use crate::{
+    api::error::AppError,
+    app_state::SharedAppState,
+    docker::loadbalancer::certificates::{probe_certificate_expiry, read_certificate},
+};
+
+/// Port on which the load balancer serves HTTPS
+const HTTPS_PORT: u16 = 443;
+
+/// Certificate and its expiry for every domain of an app
+#[utoipa::path(
+    get,
+    path = "/api/v1/authenticated/apps/{app_name}/certificates",
+    responses(
+    (status = 200, response = inline(AppCertificatesResponse)),
+    (status = 401, description = "Access token is missing or invalid"),
+    (status = 404, description = "App not found"),
+    ),
+    security(
+        ("bearerAuth" = [])
+    )
+)]
+pub async fn app_certificates_handler(
+    Path(app_name): Path<String>,
+    State(state): State<SharedAppState>,
+) -> Result<impl IntoResponse, AppError> {
+    let app_name = slugify(&app_name);
+    let app_data = state
+        .apps
+        .get_app(&app_name)
+        .await
+        .ok_or_else(|| AppError::AppNotFound(app_name.clone()))?;
+    let settings = app_data
+        .settings
+        .ok_or_else(|| AppError::AppSettingsNotFound(app_name.clone()))?;
+
+    let traefik = &state.settings.traefik;
+    let domains: Vec<String> = settings
+        .public_services
+        .iter()
+        .flat_map(|service| service.get_domains(&settings.domain))
+        .collect();
+
+    let certificates = join_all(domains.into_iter().map(|domain| {
+        let source = settings
+            .tls
+            .certificate_source(&domain, traefik.certresolver.as_deref());
+        let state = state.clone();
+        async move {
+            let mut status = DomainCertificateStatus {
+                domain: domain.clone(),
+                certresolver: None,
+                certificate: None,
+                expires_at: None,
+                error: None,
+            };
+            let expires_at = match source {
+                _ if !state.settings.traefik.use_tls => Err(anyhow::anyhow!("TLS is disabled")),
+                CertificateSource::Uploaded(name) => {
+                    let expires_at =
+                        read_certificate(&state.settings, &name).map(|info| info.expires_at);
+                    status.certificate = Some(name);
+                    expires_at
+                }
+                CertificateSource::Resolver(certresolver) => {
+                    status.certresolver = Some(certresolver);
+                    probe_certificate_expiry(&domain, HTTPS_PORT).await
+                }
+                CertificateSource::Default => probe_certificate_expiry(&domain, HTTPS_PORT).await,
+            };
+            match expires_at {
+                Ok(expires_at) => status.expires_at = Some(expires_at),
+                Err(e) => status.error = Some(e.to_string()),
+            }
+            status
+        }
+    }))
+    .await;
+
+    Ok(Json(AppCertificatesResponse { certificates }))
+}
@@ -2,8 +2,15 @@ use crate::{
     api::error::AppError,
     api::middleware::authorization::AuthorizationContext,
     api::secure_response::SecureJson,
+    api::signing::activity_token,
     app_state::SharedAppState,
-    docker::create_app::create_app,
+    docker::{
+        create_app::create_app,
+        loadbalancer::{
+            certificates::certificate_exists, ensure_host_routing_only, ensure_no_idle_ttl,
+            hash_password,
+        },
+    },
     services::{authorization::Permission, AuthorizationService},
 };
 use axum::{debug_handler, extract::State, response::IntoResponse, Extension, Json};
@@ -11,12 +18,17 @@ use base64::prelude::*;
 use flate2::read::GzDecoder;
 use scotty_core::{
     apps::{
-        create_app_request::CreateAppRequest,
+        app_data::{
+            validate_response_header, validate_source_range, AppSettings, AppTtl, BasicAuthUser,
+            TcpRouting,
+        },
+        create_app_request::{CreateAppRequest, CustomDomainMapping},
         file_list::{File, FileList},
     },
-    settings::loadbalancer::LoadBalancerType,
+    settings::loadbalancer::{LoadBalancerType, TraefikTcpSettings},
     tasks::running_app_context::RunningAppContext,
 };
+use std::collections::HashSet;
 use std::io::Read;
 use tracing::error;
 
@@ -66,10 +78,39 @@ pub async fn create_app_handler(
         return Err(AppError::CantCreateAppWithScottyYmlFile);
     }
 
-    let max_decompressed_size = state.settings.api.create_app_max_size;
+    let file_list = decode_files(&payload.files, state.settings.api.create_app_max_size)?;
+    let settings = prepare_settings(
+        &state,
+        &payload.app_name,
+        &payload.settings,
+        None,
+        &payload.custom_domains,
+    )?;
+    let settings = assign_tcp_ports(&state, &payload.app_name, settings).await?;
+
+    match create_app(
+        state.clone(),
+        &payload.app_name,
+        &settings,
+        &file_list,
+        Some(&auth_context.user.email),
+    )
+    .await
+    {
+        Ok(app_data) => Ok(SecureJson(app_data)),
+        Err(e) => {
+            error!("App create failed with: {:?}", e);
+            Err(AppError::from(e))
+        }
+    }
+}
 
-    let files: Result<Vec<File>, AppError> = payload
-        .files
+/// Decode the base64 encoded and optionally gzipped files of a request.
+pub(crate) fn decode_files(
+    files: &FileList,
+    max_decompressed_size: usize,
+) -> Result<FileList, AppError> {
+    let files: Result<Vec<File>, AppError> = files
         .files
         .iter()
         .map(|f| {
@@ -116,19 +157,26 @@ pub async fn create_app_handler(
         })
         .collect();
 
-    let files = files?;
-
-    let file_list = FileList { files };
+    Ok(FileList { files: files? })
+}
 
+/// Apply global settings, blueprint and custom domains to the settings of a
+/// request and check the requested middlewares.
+pub(crate) fn prepare_settings(
+    state: &SharedAppState,
+    app_name: &str,
+    settings: &AppSettings,
+    current: Option<&AppSettings>,
+    custom_domains: &[CustomDomainMapping],
+) -> Result<AppSettings, AppError> {
     // Set the default settings for the app.
-    let settings = payload.settings.clone();
-    let settings = settings.merge_with_global_settings(&state.settings.apps, &payload.app_name);
+    let settings = settings.merge_with_global_settings(&state.settings.apps, app_name);
 
     // Apply blueprint settings, if any.
     let settings = settings.apply_blueprint(&state.settings.apps.blueprints)?;
 
     // Apply custom domains, if any.
-    let settings = settings.apply_custom_domains(&payload.custom_domains)?;
+    let settings = settings.apply_custom_domains(&custom_domains.to_vec())?;
 
     if state.settings.load_balancer_type == LoadBalancerType::Traefik
         && !settings.middlewares.is_empty()
@@ -146,13 +194,238 @@ pub async fn create_app_handler(
         }
     }
 
-    match create_app(state, &payload.app_name, &settings, &file_list).await {
-        Ok(app_data) => Ok(SecureJson(app_data)),
-        Err(e) => {
-            error!("App create failed with: {:?}", e);
-            Err(AppError::from(e))
+    for service in &settings.public_services {
+        service.validate().map_err(AppError::BadRequest)?;
+    }
+    for (idx, service) in settings.tcp_services.iter().enumerate() {
+        service.validate().map_err(AppError::BadRequest)?;
+        if settings.tcp_services[..idx]
+            .iter()
+            .any(|other| other.service == service.service && other.protocol == service.protocol)
+        {
+            return Err(AppError::BadRequest(format!(
+                "{} service {} is declared twice",
+                service.protocol.to_string().to_uppercase(),
+                service.service
+            )));
+        }
+    }
+    for range in &settings.ip_allowlist {
+        validate_source_range(range).map_err(AppError::BadRequest)?;
+    }
+    for (name, value) in &settings.response_headers {
+        validate_response_header(name, value).map_err(AppError::BadRequest)?;
+    }
+    for rule in &settings.redirects {
+        rule.validate().map_err(AppError::BadRequest)?;
+        if !settings
+            .public_services
+            .iter()
+            .any(|service| service.get_domains(&settings.domain).contains(&rule.from))
+        {
+            return Err(AppError::BadRequest(format!(
+                "Redirect from {} needs a service with that domain",
+                rule.from
+            )));
+        }
+    }
+    let settings = AppSettings {
+        basic_auth: prepare_basic_auth(
+            state,
+            &settings.basic_auth,
+            current.map(|current| current.basic_auth.as_slice()),
+        )?,
+        ..settings
+    };
+    // Only Traefik routes by path or TCP, fail before any file is written
+    if state.settings.load_balancer_type != LoadBalancerType::Traefik {
+        ensure_host_routing_only(
+            &format!("{:?}", state.settings.load_balancer_type),
+            &settings,
+        )
+        .map_err(|e| AppError::BadRequest(e.to_string()))?;
+    }
+    // Only Traefik and Caddy report requests of apps with an idle TTL, signed
+    // with the secret key
+    match state.settings.load_balancer_type {
+        LoadBalancerType::Traefik | LoadBalancerType::Caddy => {
+            if let AppTtl::Idle(_) = settings.time_to_live {
+                activity_token(&state.settings, app_name)
+                    .map_err(|e| AppError::BadRequest(e.to_string()))?;
+            }
+        }
+        LoadBalancerType::HaproxyConfig | LoadBalancerType::NginxProxy => {
+            ensure_no_idle_ttl(
+                &format!("{:?}", state.settings.load_balancer_type),
+                &settings,
+            )
+            .map_err(|e| AppError::BadRequest(e.to_string()))?;
+        }
+    }
+    for certificate in settings.tls.certificates() {
+        if !certificate_exists(&state.settings, certificate) {
+            return Err(AppError::BadRequest(format!(
+                "Certificate {} does not exist, upload it first",
+                certificate
+            )));
+        }
+    }
+
+    if let Some(schedule) = &settings.schedule {
+        schedule
+            .validate()
+            .map_err(|e| AppError::BadRequest(e.to_string()))?;
+    }
+
+    Ok(settings)
+}
+
+/// Validate the basic auth users of an app and hash clear-text passwords, so
+/// only hashes and 1Password references end up in its settings.
+///
+/// Users of the `current` settings keep their hash if the password did not
+/// change, otherwise every update would write a new hash.
+pub(crate) fn prepare_basic_auth(
+    state: &SharedAppState,
+    users: &[BasicAuthUser],
+    current: Option<&[BasicAuthUser]>,
+) -> Result<Vec<BasicAuthUser>, AppError> {
+    for (idx, user) in users.iter().enumerate() {
+        user.validate().map_err(AppError::BadRequest)?;
+        if users[..idx]
+            .iter()
+            .any(|other| other.username == user.username)
+        {
+            return Err(AppError::BadRequest(format!(
+                "Basic auth user {} is declared twice",
+                user.username
+            )));
+        }
+    }
+    // HAProxy gets the password in clear text, so it can't be stored hashed
+    if state.settings.load_balancer_type == LoadBalancerType::HaproxyConfig
+        && (users.len() > 1 || users.iter().any(|user| !user.is_reference()))
+    {
+        return Err(AppError::BadRequest(
+            "HAProxy only supports a single basic auth user with a op:// reference as password"
+                .to_string(),
+        ));
+    }
+
+    users
+        .iter()
+        .map(|user| {
+            if user.is_hashed() || user.is_reference() {
+                return Ok(user.clone());
+            }
+            let unchanged = current
+                .unwrap_or_default()
+                .iter()
+                .find(|current| current.username == user.username && current.is_hashed())
+                .filter(|current| {
+                    bcrypt::verify(&user.password, &current.password).unwrap_or(false)
+                });
+            match unchanged {
+                Some(current) => Ok(current.clone()),
+                None => Ok(BasicAuthUser::new(
+                    &user.username,
+                    &hash_password(&user.password)
+                        .map_err(|e| AppError::InternalServerError(e.to_string()))?,
+                )),
+            }
+        })
+        .collect()
+}
+
+/// Hand out the public ports of the TCP and UDP services of an app.
+///
+/// Services keep the port they got before, new ones get the lowest port of
+/// the pool no other app uses.
+pub(crate) async fn assign_tcp_ports(
+    state: &SharedAppState,
+    app_name: &str,
+    settings: AppSettings,
+) -> Result<AppSettings, AppError> {
+    if settings.tcp_services.is_empty() {
+        return Ok(settings);
+    }
+    let mut reserved = state.tcp_ports.lock().await;
+    let apps = state.apps.get_apps().await;
+    let current = apps
+        .apps
+        .iter()
+        .find(|app| app.name == app_name)
+        .and_then(|app| app.settings.as_ref());
+    let used: HashSet<u16> = apps
+        .apps
+        .iter()
+        .filter(|app| app.name != app_name)
+        .filter_map(|app| app.settings.as_ref())
+        .flat_map(|settings| &settings.tcp_services)
+        .filter(|service| service.routing == TcpRouting::Port)
+        .filter_map(|service| service.public_port)
+        .chain(
+            reserved
+                .iter()
+                .filter(|(name, _)| name.as_str() != app_name)
+                .flat_map(|(_, ports)| ports.iter().copied()),
+        )
+        .collect();
+
+    let settings = assign_ports(settings, current, used, &state.settings.traefik.tcp)
+        .map_err(AppError::BadRequest)?;
+    reserved.insert(
+        app_name.to_string(),
+        settings
+            .tcp_services
+            .iter()
+            .filter(|service| service.routing == TcpRouting::Port)
+            .filter_map(|service| service.public_port)
+            .collect(),
+    );
+    Ok(settings)
+}
+
+fn assign_ports(
+    mut settings: AppSettings,
+    current: Option<&AppSettings>,
+    mut used: HashSet<u16>,
+    tcp_settings: &TraefikTcpSettings,
+) -> Result<AppSettings, String> {
+    for service in settings.tcp_services.iter_mut() {
+        if service.routing == TcpRouting::Sni {
+            service.public_port = Some(tcp_settings.public_port);
+            continue;
         }
+
+        let pool = tcp_settings.port_pool.ok_or_else(|| {
+            format!(
+                "No port pool configured, can't expose service {} on a dedicated port",
+                service.service
+            )
+        })?;
+        let previous = current
+            .into_iter()
+            .flat_map(|current| &current.tcp_services)
+            .find(|other| {
+                other.service == service.service
+                    && other.protocol == service.protocol
+                    && other.routing == TcpRouting::Port
+            })
+            .and_then(|other| other.public_port)
+            .filter(|port| pool.ports().contains(port) && !used.contains(port));
+        let port = previous
+            .or_else(|| pool.ports().find(|port| !used.contains(port)))
+            .ok_or_else(|| {
+                format!(
+                    "No free port left in the port pool for service {}",
+                    service.service
+                )
+            })?;
+        used.insert(port);
+        service.public_port = Some(port);
     }
+    Ok(settings)
 }
 
 #[cfg(test)]
@@ -305,4 +578,224 @@ mod tests {
             "Decompressed content should match original"
         );
     }
+
+    #[tokio::test]
+    async fn test_prepare_basic_auth() {
+        let state = crate::api::test_utils::create_test_app_state_with_settings(
+            crate::settings::config::Settings::default(),
+            None,
+        )
+        .await;
+        let users = vec![
+            BasicAuthUser::new("alice", "secret"),
+            BasicAuthUser::new("bob", "op://token/vault/item/password"),
+        ];
+
+        let prepared = prepare_basic_auth(&state, &users, None).unwrap();
+        assert!(prepared[0].is_hashed());
+        assert!(bcrypt::verify("secret", &prepared[0].password).unwrap());
+        assert_eq!(prepared[1], users[1]);
+
+        // An unchanged password keeps its hash
+        let again = prepare_basic_auth(&state, &users, Some(&prepared)).unwrap();
+        assert_eq!(again, prepared);
+
+        let twice = vec![users[0].clone(), users[0].clone()];
+        assert!(prepare_basic_auth(&state, &twice, None).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_prepare_settings_rejects_invalid_custom_domains() {
+        use scotty_core::apps::app_data::ServicePortMapping;
+
+        let state = crate::api::test_utils::create_test_app_state_with_settings(
+            crate::settings::config::Settings::default(),
+            None,
+        )
+        .await;
+        let settings = AppSettings {
+            public_services: vec![ServicePortMapping {
+                service: "web".to_string(),
+                port: 80,
+                domains: vec![],
+                path: None,
+                strip_path: false,
+            }],
+            ..Default::default()
+        };
+        let custom_domain = |domain: &str| {
+            vec![CustomDomainMapping {
+                domain: domain.to_string(),
+                service: "web".to_string(),
+            }]
+        };
+
+        assert!(prepare_settings(
+            &state,
+            "my-app",
+            &settings,
+            None,
+            &custom_domain("web.test")
+        )
+        .is_ok());
+        assert!(prepare_settings(
+            &state,
+            "my-app",
+            &settings,
+            None,
+            &custom_domain("../../etc/x")
+        )
+        .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_prepare_settings_idle_ttl() {
+        use crate::api::test_utils::settings_with_secret_key;
+
+        let settings = AppSettings {
+            time_to_live: AppTtl::Idle(8),
+            ..Default::default()
+        };
+
+        // Reports of the load balancer can't be signed without a secret key
+        let state = crate::api::test_utils::create_test_app_state_with_settings(
+            crate::settings::config::Settings::default(),
+            None,
+        )
+        .await;
+        assert!(prepare_settings(&state, "my-app", &settings, None, &[]).is_err());
+
+        let state = crate::api::test_utils::create_test_app_state_with_settings(
+            settings_with_secret_key(crate::settings::config::Settings::default(), "secret"),
+            None,
+        )
+        .await;
+        assert!(prepare_settings(&state, "my-app", &settings, None, &[]).is_ok());
+
+        // nginx-proxy does not report requests at all
+        let state = crate::api::test_utils::create_test_app_state_with_settings(
+            crate::settings::config::Settings {
+                load_balancer_type: LoadBalancerType::NginxProxy,
+                ..settings_with_secret_key(crate::settings::config::Settings::default(), "secret")
+            },
+            None,
+        )
+        .await;
+        assert!(prepare_settings(&state, "my-app", &settings, None, &[]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_assign_tcp_ports_reserves_ports() {
+        use scotty_core::apps::app_data::{TcpServiceMapping, TransportProtocol};
+        use scotty_core::settings::loadbalancer::{PortPool, TraefikSettings};
+
+        let state = crate::api::test_utils::create_test_app_state_with_settings(
+            crate::settings::config::Settings {
+                traefik: TraefikSettings {
+                    tcp: TraefikTcpSettings {
+                        port_pool: Some(PortPool {
+                            start: 20000,
+                            end: 20010,
+                        }),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            None,
+        )
+        .await;
+        let settings = AppSettings {
+            tcp_services: vec![TcpServiceMapping::new(
+                "redis",
+                6379,
+                TransportProtocol::Tcp,
+                TcpRouting::Port,
+            )],
+            ..Default::default()
+        };
+        let port = |settings: &AppSettings| settings.tcp_services[0].public_port;
+
+        // Neither app is in the app list yet, the second one still gets another port
+        let first = assign_tcp_ports(&state, "first", settings.clone())
+            .await
+            .unwrap();
+        let second = assign_tcp_ports(&state, "second", settings.clone())
+            .await
+            .unwrap();
+        assert!(port(&first).is_some());
+        assert_ne!(port(&first), port(&second));
+
+        // The port of a destroyed app can be handed out again
+        state.tcp_ports.release("first").await;
+        let third = assign_tcp_ports(&state, "third", settings.clone())
+            .await
+            .unwrap();
+        assert_eq!(port(&first), port(&third));
+
+        // A failed create frees its port as well
+        let fourth = assign_tcp_ports(&state, "fourth", settings.clone())
+            .await
+            .unwrap();
+        let empty = FileList { files: vec![] };
+        assert!(create_app(state.clone(), "fourth", &fourth, &empty, None)
+            .await
+            .is_err());
+        let fifth = assign_tcp_ports(&state, "fifth", settings).await.unwrap();
+        assert_eq!(port(&fourth), port(&fifth));
+    }
+
+    #[test]
+    fn test_assign_ports() {
+        use scotty_core::apps::app_data::{TcpServiceMapping, TransportProtocol};
+        use scotty_core::settings::loadbalancer::PortPool;
+
+        let tcp_settings = TraefikTcpSettings {
+            port_pool: Some(PortPool {
+                start: 20000,
+                end: 20002,
+            }),
+            ..Default::default()
+        };
+        let settings = AppSettings {
+            tcp_services: vec![
+                TcpServiceMapping::new("db", 5432, TransportProtocol::Tcp, TcpRouting::Sni),
+                TcpServiceMapping::new("redis", 6379, TransportProtocol::Tcp, TcpRouting::Port),
+                TcpServiceMapping::new("dns", 53, TransportProtocol::Udp, TcpRouting::Port),
+            ],
+            ..Default::default()
+        };
+
+        let used = HashSet::from([20000]);
+        let assigned = assign_ports(settings.clone(), None, used.clone(), &tcp_settings).unwrap();
+        let ports: Vec<_> = assigned
+            .tcp_services
+            .iter()
+            .map(|service| service.public_port)
+            .collect();
+        assert_eq!(ports, vec![Some(443), Some(20001), Some(20002)]);
+
+        // Services keep their port on updates
+        let mut current = assigned.clone();
+        current.tcp_services[1].public_port = Some(20002);
+        current.tcp_services[2].public_port = Some(20001);
+        let reassigned =
+            assign_ports(settings.clone(), Some(&current), used, &tcp_settings).unwrap();
+        assert_eq!(reassigned.tcp_services[1].public_port, Some(20002));
+        assert_eq!(reassigned.tcp_services[2].public_port, Some(20001));
+
+        // Pool exhausted
+        let used = HashSet::from([20000, 20001]);
+        assert!(assign_ports(settings.clone(), None, used, &tcp_settings).is_err());
+
+        // No pool configured
+        assert!(assign_ports(
+            settings,
+            None,
+            HashSet::new(),
+            &TraefikTcpSettings::default()
+        )
+        .is_err());
+    }
 }
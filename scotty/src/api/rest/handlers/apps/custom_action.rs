@@ -136,7 +136,13 @@ pub async fn run_custom_action_handler(
     );
 
     // Create a task for running the custom action
-    let app_data = run_app_custom_action(state, &app, action_name).await?;
+    let app_data = run_app_custom_action(
+        state.clone(),
+        &app,
+        action_name,
+        Some(&auth_context.user.email),
+    )
+    .await?;
 
     Ok(Json(app_data))
 }
@@ -205,6 +205,7 @@ m = user_match(r.sub, p.sub) && g2(r.app, p.group) && r.act == p.act
             services: Vec::new(),
             settings: Some(frontend_settings),
             last_checked: None,
+            drift: None,
         };
 
         let backend_app = AppData {
@@ -215,6 +216,7 @@ m = user_match(r.sub, p.sub) && g2(r.app, p.group) && r.act == p.act
             services: Vec::new(),
             settings: Some(backend_settings),
             last_checked: None,
+            drift: None,
         };
 
         let fullstack_app = AppData {
@@ -225,6 +227,7 @@ m = user_match(r.sub, p.sub) && g2(r.app, p.group) && r.act == p.act
             services: Vec::new(),
             settings: Some(fullstack_settings),
             last_checked: None,
+            drift: None,
         };
 
         let staging_app = AppData {
@@ -235,6 +238,7 @@ m = user_match(r.sub, p.sub) && g2(r.app, p.group) && r.act == p.act
             services: Vec::new(),
             settings: Some(staging_settings),
             last_checked: None,
+            drift: None,
         };
 
         // Add apps to shared list
@@ -257,6 +261,11 @@ m = user_match(r.sub, p.sub) && g2(r.app, p.group) && r.act == p.act
         let app_state = Arc::new(AppState {
             stop_flag: stop_flag::StopFlag::new(),
             messenger: create_test_websocket_messenger(),
+            activity: crate::services::ActivityTracker::new(),
+            ttl_warnings: crate::services::TtlWarnings::new(),
+            drift_reports: crate::services::DriftReports::new(),
+            forward_auth: crate::services::ForwardAuthCache::new(),
+            tcp_ports: crate::services::TcpPortReservations::new(),
             apps: shared_app_list,
             docker: docker.clone(),
             task_manager: crate::tasks::manager::TaskManager::new(create_test_websocket_messenger()),
@@ -0,0 +1,68 @@
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    Extension, Json,
+};
+use scotty_core::{
+    apps::app_data::{AppSettings, SetMaintenanceRequest},
+    tasks::running_app_context::RunningAppContext,
+    utils::slugify::slugify,
+};
+use tracing::error;
+
+use crate::{
+    api::{basic_auth::CurrentUser, error::AppError, secure_response::SecureJson},
+    app_state::SharedAppState,
+    docker::update_app::update_app_settings,
+};
+
+/// Turn the maintenance mode of an app on or off.
+///
+/// In maintenance the load balancer sends all requests of the app to scotty,
+/// which answers them with a maintenance page. Only the routing changes, the
+/// containers keep running and are not recreated.
+#[utoipa::path(
+    post,
+    path = "/api/v1/authenticated/apps/{app_name}/maintenance",
+    request_body = SetMaintenanceRequest,
+    responses(
+    (status = 200, response = inline(RunningAppContext)),
+    (status = 401, description = "Access token is missing or invalid"),
+    (status = 404, description = "App not found"),
+    ),
+    security(
+        ("bearerAuth" = [])
+    )
+)]
+pub async fn set_maintenance_handler(
+    Path(app_name): Path<String>,
+    State(state): State<SharedAppState>,
+    Extension(user): Extension<CurrentUser>,
+    Json(payload): Json<SetMaintenanceRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let app_name = slugify(&app_name);
+    let app = state
+        .apps
+        .get_app(&app_name)
+        .await
+        .ok_or_else(|| AppError::AppNotFound(app_name.clone()))?;
+    let settings = app
+        .settings
+        .clone()
+        .ok_or_else(|| AppError::AppSettingsNotFound(app_name.clone()))?;
+
+    let settings = AppSettings {
+        maintenance: payload.enabled,
+        ..settings
+    };
+    match update_app_settings(state.clone(), &app, settings, Some(&user.email)).await {
+        Ok(app_data) => Ok(SecureJson(app_data)),
+        Err(e) => {
+            error!(
+                "Changing maintenance mode of app {} failed: {:?}",
+                app_name, e
+            );
+            Err(AppError::from(e))
+        }
+    }
+}
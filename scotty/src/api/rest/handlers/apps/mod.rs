@@ -1,6 +1,12 @@
+pub mod basic_auth;
+pub mod certificates;
 pub mod create;
 pub mod custom_action;
 pub mod custom_action_management;
 pub mod list;
+pub mod maintenance;
 pub mod notify;
+pub mod revisions;
 pub mod run;
+pub mod ttl;
+pub mod update;
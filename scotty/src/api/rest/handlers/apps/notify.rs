@@ -49,6 +49,12 @@ pub async fn add_notification_handler(
                 NotificationReceiver::Mattermost(id) => &id.service_id,
                 NotificationReceiver::Gitlab(id) => &id.service_id,
                 NotificationReceiver::Webhook(id) => &id.service_id,
+                NotificationReceiver::Slack(id) => &id.service_id,
+                NotificationReceiver::Matrix(id) => &id.service_id,
+                NotificationReceiver::Teams(id) => &id.service_id,
+                NotificationReceiver::Email(id) => &id.service_id,
+                NotificationReceiver::Github(id) => &id.service_id,
+                NotificationReceiver::Gitea(id) => &id.service_id,
             })
             .collect::<Vec<_>>();
         return Err(AppError::InvalidNotificationServiceIds(
@@ -0,0 +1,81 @@
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    Extension, Json,
+};
+use scotty_core::{
+    apps::revision::{RevisionInfo, RollbackRequest},
+    tasks::running_app_context::RunningAppContext,
+    utils::slugify::slugify,
+};
+
+use crate::{
+    api::{basic_auth::CurrentUser, error::AppError, secure_response::SecureJson},
+    app_state::SharedAppState,
+    docker::rollback_app::{list_revisions, rollback_app},
+};
+
+/// List the stored revisions of an app, oldest first
+#[utoipa::path(
+    get,
+    path = "/api/v1/authenticated/apps/{app_name}/revisions",
+    responses(
+    (status = 200, description = "Stored revisions of the app", body = Vec<RevisionInfo>),
+    (status = 401, description = "Access token is missing or invalid"),
+    (status = 404, description = "App not found"),
+    ),
+    security(
+        ("bearerAuth" = [])
+    )
+)]
+pub async fn list_revisions_handler(
+    Path(app_name): Path<String>,
+    State(state): State<SharedAppState>,
+) -> Result<impl IntoResponse, AppError> {
+    let app_name = slugify(&app_name);
+    let app_data = state
+        .apps
+        .get_app(&app_name)
+        .await
+        .ok_or_else(|| AppError::AppNotFound(app_name.clone()))?;
+
+    let revisions = list_revisions(&state, &app_data).await?;
+    Ok(Json(revisions))
+}
+
+/// Restore a revision of an app and bring it up again
+#[utoipa::path(
+    post,
+    path = "/api/v1/authenticated/apps/{app_name}/rollback",
+    request_body = RollbackRequest,
+    responses(
+    (status = 200, response = inline(RunningAppContext)),
+    (status = 401, description = "Access token is missing or invalid"),
+    (status = 404, description = "App or revision not found"),
+    ),
+    security(
+        ("bearerAuth" = [])
+    )
+)]
+pub async fn rollback_app_handler(
+    Path(app_name): Path<String>,
+    State(state): State<SharedAppState>,
+    Extension(user): Extension<CurrentUser>,
+    Json(payload): Json<RollbackRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let app_name = slugify(&app_name);
+    let app_data = state
+        .apps
+        .get_app(&app_name)
+        .await
+        .ok_or_else(|| AppError::AppNotFound(app_name.clone()))?;
+
+    let app_data = rollback_app(
+        state.clone(),
+        &app_data,
+        payload.revision,
+        Some(&user.email),
+    )
+    .await?;
+    Ok(SecureJson(app_data))
+}
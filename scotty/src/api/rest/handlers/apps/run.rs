@@ -2,6 +2,7 @@ use crate::api::secure_response::SecureJson;
 use axum::{
     extract::{Path, State},
     response::IntoResponse,
+    Extension,
 };
 use scotty_core::{
     apps::app_data::AppData,
@@ -10,7 +11,7 @@ use scotty_core::{
 };
 
 use crate::{
-    api::error::AppError,
+    api::{basic_auth::CurrentUser, error::AppError},
     app_state::SharedAppState,
     docker::{
         destroy_app::destroy_app,
@@ -36,6 +37,7 @@ use crate::{
 pub async fn run_app_handler(
     Path(app_id): Path<String>,
     State(state): State<SharedAppState>,
+    Extension(user): Extension<CurrentUser>,
 ) -> Result<impl IntoResponse, AppError> {
     let app_id = slugify(&app_id);
     let app_data = state.apps.get_app(&app_id).await;
@@ -43,7 +45,7 @@ pub async fn run_app_handler(
         return Err(AppError::AppNotFound(app_id.clone()));
     }
     let app_data = app_data.unwrap();
-    let app_data = run_app(state, &app_data).await?;
+    let app_data = run_app(state.clone(), &app_data, Some(&user.email)).await?;
     Ok(SecureJson(app_data))
 }
 
@@ -61,6 +63,7 @@ pub async fn run_app_handler(
 pub async fn stop_app_handler(
     Path(app_id): Path<String>,
     State(state): State<SharedAppState>,
+    Extension(user): Extension<CurrentUser>,
 ) -> Result<impl IntoResponse, AppError> {
     let app_id = slugify(&app_id);
     let app_data = state.apps.get_app(&app_id).await;
@@ -68,7 +71,7 @@ pub async fn stop_app_handler(
         return Err(AppError::AppNotFound(app_id.clone()));
     }
     let app_data = app_data.unwrap();
-    let app_data = stop_app(state, &app_data).await?;
+    let app_data = stop_app(state.clone(), &app_data, Some(&user.email)).await?;
     Ok(SecureJson(app_data))
 }
 
@@ -86,6 +89,7 @@ pub async fn stop_app_handler(
 pub async fn purge_app_handler(
     Path(app_id): Path<String>,
     State(state): State<SharedAppState>,
+    Extension(user): Extension<CurrentUser>,
 ) -> Result<impl IntoResponse, AppError> {
     let app_id = slugify(&app_id);
     let app_data = state.apps.get_app(&app_id).await;
@@ -93,7 +97,7 @@ pub async fn purge_app_handler(
         return Err(AppError::AppNotFound(app_id.clone()));
     }
     let app_data = app_data.unwrap();
-    let app_data = purge_app(state, &app_data).await?;
+    let app_data = purge_app(state.clone(), &app_data, Some(&user.email)).await?;
     Ok(SecureJson(app_data))
 }
 
@@ -117,7 +121,10 @@ pub async fn info_app_handler(
     if app_data.is_none() {
         return Err(AppError::AppNotFound(app_id.clone()));
     }
-    let app_data = app_data.unwrap();
+    let app_data = AppData {
+        drift: state.drift_reports.get(&app_id).await,
+        ..app_data.unwrap()
+    };
     Ok(SecureJson(app_data))
 }
 
@@ -135,6 +142,7 @@ pub async fn info_app_handler(
 pub async fn rebuild_app_handler(
     Path(app_id): Path<String>,
     State(state): State<SharedAppState>,
+    Extension(user): Extension<CurrentUser>,
 ) -> Result<impl IntoResponse, AppError> {
     let app_id = slugify(&app_id);
     let app_data = state.apps.get_app(&app_id).await;
@@ -142,7 +150,7 @@ pub async fn rebuild_app_handler(
         return Err(AppError::AppNotFound(app_id.clone()));
     }
     let app_data = app_data.unwrap();
-    let app_data = rebuild_app(state, &app_data).await?;
+    let app_data = rebuild_app(state.clone(), &app_data, Some(&user.email)).await?;
     Ok(SecureJson(app_data))
 }
 
@@ -161,6 +169,7 @@ pub async fn rebuild_app_handler(
 pub async fn destroy_app_handler(
     Path(app_id): Path<String>,
     State(state): State<SharedAppState>,
+    Extension(user): Extension<CurrentUser>,
 ) -> Result<impl IntoResponse, AppError> {
     let app_id = slugify(&app_id);
     let app_data = state.apps.get_app(&app_id).await;
@@ -171,7 +180,7 @@ pub async fn destroy_app_handler(
     if app_data.settings.is_none() {
         return Err(AppError::CantDestroyUnmanagedApp(app_id.clone()));
     }
-    let app_data = destroy_app(state, &app_data).await?;
+    let app_data = destroy_app(state.clone(), &app_data, Some(&user.email)).await?;
     Ok(SecureJson(app_data))
 }
 
@@ -0,0 +1,153 @@
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    Json,
+};
+use scotty_core::{
+    apps::app_data::{AppData, AppSettings, AppTtl, ExtendTtlRequest},
+    utils::slugify::slugify,
+};
+
+use crate::{
+    api::{error::AppError, secure_response::SecureJson},
+    app_state::SharedAppState,
+    docker::helper::with_app_lock,
+};
+
+/// Set a new TTL for an app and restart its clock
+#[utoipa::path(
+    post,
+    path = "/api/v1/authenticated/apps/{app_name}/ttl",
+    request_body = ExtendTtlRequest,
+    responses(
+    (status = 200, response = inline(AppData)),
+    (status = 400, description = "The TTL can't be changed this way"),
+    (status = 401, description = "Access token is missing or invalid"),
+    (status = 404, description = "App not found"),
+    ),
+    security(
+        ("bearerAuth" = [])
+    )
+)]
+pub async fn extend_ttl_handler(
+    Path(app_name): Path<String>,
+    State(state): State<SharedAppState>,
+    Json(payload): Json<ExtendTtlRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let app_name = slugify(&app_name);
+    // Another operation might change the settings meanwhile, so the TTL is
+    // changed while holding the lock of the app.
+    let app = with_app_lock(&state, &app_name, || async {
+        let app = state
+            .apps
+            .get_app(&app_name)
+            .await
+            .ok_or_else(|| AppError::AppNotFound(app_name.clone()))?;
+        let settings = app
+            .settings
+            .clone()
+            .ok_or_else(|| AppError::AppSettingsNotFound(app_name.clone()))?;
+
+        // The load balancer only reports requests of apps with an idle TTL, so
+        // switching between both kinds needs a new load balancer config.
+        let is_idle = |ttl: &AppTtl| matches!(ttl, AppTtl::Idle(_));
+        if is_idle(&settings.time_to_live) != is_idle(&payload.time_to_live) {
+            return Err(AppError::BadRequest(format!(
+                "Can't switch app {} between an idle and a fixed TTL, update the app instead",
+                app_name
+            )));
+        }
+
+        let app = AppData {
+            settings: Some(AppSettings {
+                time_to_live: payload.time_to_live.clone(),
+                ttl_started_at: Some(chrono::Local::now()),
+                ..settings
+            }),
+            ..app
+        };
+        app.save_settings().await?;
+        state.apps.update_app(app.clone()).await?;
+        Ok(app)
+    })
+    .await?;
+
+    Ok(SecureJson(app))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::test_utils::create_test_app_state_with_settings;
+    use crate::settings::config::Settings;
+    use scotty_core::settings::apps::{Apps, OperationConflictStrategy};
+
+    async fn extend(
+        state: &SharedAppState,
+        time_to_live: AppTtl,
+    ) -> Result<axum::response::Response, AppError> {
+        extend_ttl_handler(
+            Path("my-app".to_string()),
+            State(state.clone()),
+            Json(ExtendTtlRequest { time_to_live }),
+        )
+        .await
+        .map(IntoResponse::into_response)
+    }
+
+    #[tokio::test]
+    async fn test_extend_ttl_waits_for_lock_of_app() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = create_test_app_state_with_settings(
+            Settings {
+                apps: Apps {
+                    operation_conflict: OperationConflictStrategy::Reject,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            None,
+        )
+        .await;
+        state
+            .apps
+            .add_app(AppData {
+                name: "my-app".to_string(),
+                root_directory: dir.path().display().to_string(),
+                settings: Some(AppSettings {
+                    time_to_live: AppTtl::Days(1),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        // Another operation holds the lock of the app
+        let queue = state.task_manager.app_queue();
+        let holder = uuid::Uuid::new_v4();
+        queue.try_acquire("my-app", &holder).await.unwrap();
+        assert!(matches!(
+            extend(&state, AppTtl::Days(3)).await,
+            Err(AppError::AppOperationInProgress(_, id)) if id == holder
+        ));
+        queue.release("my-app", &holder).await;
+
+        extend(&state, AppTtl::Days(3)).await.unwrap();
+        let settings = state
+            .apps
+            .get_app("my-app")
+            .await
+            .unwrap()
+            .settings
+            .unwrap();
+        assert_eq!(settings.time_to_live, AppTtl::Days(3));
+        assert!(settings.ttl_started_at.is_some());
+        assert!(dir.path().join(".scotty.yml").exists());
+
+        assert!(matches!(
+            extend(&state, AppTtl::Idle(8)).await,
+            Err(AppError::BadRequest(_))
+        ));
+    }
+}
@@ -0,0 +1,170 @@
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    Extension, Json,
+};
+use scotty_core::{
+    apps::update_app_request::UpdateAppRequest, tasks::running_app_context::RunningAppContext,
+    utils::slugify::slugify,
+};
+use tracing::error;
+
+use super::create::{assign_tcp_ports, decode_files, prepare_settings};
+use crate::{
+    api::{basic_auth::CurrentUser, error::AppError, secure_response::SecureJson},
+    app_state::SharedAppState,
+    docker::update_app::update_app,
+};
+
+/// Push new files and optionally new settings to an existing app
+#[utoipa::path(
+    put,
+    path = "/api/v1/authenticated/apps/{app_id}",
+    request_body(content = UpdateAppRequest, content_type = "application/json"),
+    responses(
+    (status = 200, response = inline(RunningAppContext)),
+    (status = 401, description = "Access token is missing or invalid"),
+    (status = 404, description = "App not found"),
+    ),
+    security(
+        ("bearerAuth" = [])
+    )
+)]
+pub async fn update_app_handler(
+    Path(app_id): Path<String>,
+    State(state): State<SharedAppState>,
+    Extension(user): Extension<CurrentUser>,
+    Json(payload): Json<UpdateAppRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let app_id = slugify(&app_id);
+    let app_data = state
+        .apps
+        .get_app(&app_id)
+        .await
+        .ok_or_else(|| AppError::AppNotFound(app_id.clone()))?;
+
+    // Settings are passed as part of the request, not as a file
+    if payload
+        .files
+        .files
+        .iter()
+        .any(|f| f.name.ends_with(".scotty.yml"))
+    {
+        return Err(AppError::BadRequest(
+            "Can't update an app with a .scotty.yml file, pass the settings instead".to_string(),
+        ));
+    }
+
+    let file_list = decode_files(&payload.files, state.settings.api.create_app_max_size)?;
+    let settings = match (&payload.settings, payload.custom_domains.is_empty()) {
+        (None, true) => None,
+        (changes, _) => {
+            let current = app_data
+                .settings
+                .as_ref()
+                .ok_or_else(|| AppError::AppSettingsNotFound(app_data.name.clone()))?;
+            let settings = current
+                .with_changes(&changes.clone().unwrap_or_default())
+                .map_err(|e| AppError::BadRequest(format!("Invalid settings: {}", e)))?;
+            let settings = prepare_settings(
+                &state,
+                &app_data.name,
+                &settings,
+                Some(current),
+                &payload.custom_domains,
+            )?;
+            Some(assign_tcp_ports(&state, &app_data.name, settings).await?)
+        }
+    };
+
+    match update_app(
+        state.clone(),
+        &app_data,
+        settings,
+        &file_list,
+        Some(&user.email),
+    )
+    .await
+    {
+        Ok(app_data) => Ok(SecureJson(app_data)),
+        Err(e) => {
+            error!("App update failed with: {:?}", e);
+            // Free the ports assigned for the new settings
+            state.tcp_ports.release(&app_data.name).await;
+            Err(AppError::from(e))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::test_utils::create_test_app_state_with_settings;
+    use crate::settings::config::Settings;
+    use scotty_core::apps::app_data::{
+        AppData, AppSettings, AppStatus, TcpRouting, TcpServiceMapping, TransportProtocol,
+    };
+    use scotty_core::apps::file_list::FileList;
+    use scotty_core::settings::loadbalancer::{PortPool, TraefikSettings, TraefikTcpSettings};
+
+    #[tokio::test]
+    async fn test_failed_update_releases_tcp_ports() {
+        let state = create_test_app_state_with_settings(
+            Settings {
+                traefik: TraefikSettings {
+                    tcp: TraefikTcpSettings {
+                        port_pool: Some(PortPool {
+                            start: 20000,
+                            end: 20010,
+                        }),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            None,
+        )
+        .await;
+        state
+            .apps
+            .add_app(AppData {
+                name: "my-app".to_string(),
+                status: AppStatus::Running,
+                settings: Some(AppSettings::default()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        let tcp_services = serde_json::to_value(vec![TcpServiceMapping::new(
+            "redis",
+            6379,
+            TransportProtocol::Tcp,
+            TcpRouting::Port,
+        )])
+        .unwrap();
+        let mut settings = serde_json::Map::new();
+        settings.insert("tcp_services".to_string(), tcp_services);
+
+        // Without a compose file the update fails after the ports got assigned
+        let result = update_app_handler(
+            Path("my-app".to_string()),
+            State(state.clone()),
+            Extension(CurrentUser {
+                email: "test@example.com".to_string(),
+                name: "Test".to_string(),
+                picture: None,
+                access_token: None,
+            }),
+            Json(UpdateAppRequest {
+                settings: Some(settings),
+                files: FileList { files: vec![] },
+                custom_domains: vec![],
+            }),
+        )
+        .await;
+        assert!(result.is_err());
+        assert!(!state.tcp_ports.lock().await.contains_key("my-app"));
+    }
+}
@@ -0,0 +1,414 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Redirect, Response},
+};
+use scotty_core::settings::api_server::AuthMode;
+use scotty_core::utils::slugify::slugify;
+use serde::Deserialize;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::{debug, warn};
+use url::Url;
+
+use crate::api::auth_core::{authenticate_dev_user, authenticate_user_from_token, CurrentUser};
+use crate::api::error::AppError;
+use crate::api::signing::{forward_auth_token, secret_key, verify_forward_auth_token};
+use crate::app_state::SharedAppState;
+use crate::oauth::handlers::begin_web_flow;
+use crate::oauth::OAuthError;
+use crate::services::authorization::Permission;
+use crate::services::AuthorizationService;
+
+/// Parent domain of the apps for which the forward-auth cookie is set
+fn cookie_domain(state: &SharedAppState) -> Option<String> {
+    let domain = state
+        .settings
+        .api
+        .forward_auth
+        .cookie_domain
+        .as_deref()
+        .unwrap_or(&state.settings.apps.domain_suffix)
+        .trim_start_matches('.');
+    (!domain.is_empty()).then(|| domain.to_string())
+}
+
+/// Whether `url` is an http(s) URL on `domain` or one of its subdomains
+fn is_below_domain(url: &Url, domain: &str) -> bool {
+    matches!(url.scheme(), "http" | "https")
+        && url
+            .host_str()
+            .is_some_and(|host| host == domain || host.ends_with(&format!(".{domain}")))
+}
+
+/// Parse the URL to return to after the login. It has to be below the cookie
+/// domain, so the login can't be abused to redirect anywhere.
+fn checked_redirect(state: &SharedAppState, rd: &str) -> Result<Url, AppError> {
+    let domain = cookie_domain(state).ok_or_else(|| {
+        AppError::BadRequest("No cookie domain configured for forward auth".to_string())
+    })?;
+    let url =
+        Url::parse(rd).map_err(|_| AppError::BadRequest(format!("Invalid redirect URL {rd}")))?;
+    if !is_below_domain(&url, &domain) {
+        return Err(AppError::BadRequest(format!(
+            "Redirect URL {} is not below {}",
+            rd, domain
+        )));
+    }
+    Ok(url)
+}
+
+/// Ensure the cookie set after the login reaches the apps. Browsers reject
+/// cookies for a domain the host setting them is not part of.
+fn check_cookie_domain(state: &SharedAppState, domain: &str) -> Result<(), AppError> {
+    let base_url = state.settings.api.public_base_url();
+    match Url::parse(&base_url) {
+        Ok(url) if is_below_domain(&url, domain) => Ok(()),
+        _ => Err(AppError::BadRequest(format!(
+            "Scotty at {} is not below the forward auth cookie domain {}",
+            base_url, domain
+        ))),
+    }
+}
+
+fn unix_time(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
+
+/// Bearer token of the request, from the `Authorization` header
+fn bearer_token(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(str::to_string)
+}
+
+/// Value of the cookie `cookie_name` of the request
+fn cookie_from_headers(headers: &HeaderMap, cookie_name: &str) -> Option<String> {
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|cookies| cookies.split(';'))
+        .filter_map(|cookie| cookie.trim().split_once('='))
+        .find(|(name, _)| *name == cookie_name)
+        .map(|(_, value)| value.to_string())
+}
+
+/// URL of the request Traefik asks about
+fn original_url(headers: &HeaderMap) -> Option<Url> {
+    let forwarded = |name: &str| headers.get(name).and_then(|value| value.to_str().ok());
+    let proto = forwarded("x-forwarded-proto").unwrap_or("https");
+    let host = forwarded("x-forwarded-host")?;
+    let uri = forwarded("x-forwarded-uri").unwrap_or("/");
+    Url::parse(&format!("{proto}://{host}{uri}")).ok()
+}
+
+/// User of a request, from its bearer token or the cookie set after the login
+async fn authenticate(state: &SharedAppState, headers: &HeaderMap) -> Option<CurrentUser> {
+    if state.settings.api.auth_mode == AuthMode::Development {
+        return Some(authenticate_dev_user(state));
+    }
+    if let Some(token) = bearer_token(headers) {
+        if let Some(user) = state.forward_auth.get(&token).await {
+            return Some(user);
+        }
+        let user = authenticate_user_from_token(state, &token).await.ok()?;
+        state.forward_auth.insert(&token, user.clone()).await;
+        return Some(user);
+    }
+
+    let cookie = cookie_from_headers(headers, &state.settings.api.forward_auth.cookie_name)?;
+    let email = verify_forward_auth_token(&state.settings, &cookie, unix_time(SystemTime::now()))?;
+    Some(CurrentUser {
+        name: email.clone(),
+        email,
+        picture: None,
+        access_token: None,
+    })
+}
+
+/// Send a browser to the login, only possible with OAuth
+fn login_redirect(state: &SharedAppState, headers: &HeaderMap) -> Option<Response> {
+    if state.settings.api.auth_mode != AuthMode::OAuth {
+        return None;
+    }
+    let original = original_url(headers)?;
+    let login = Url::parse_with_params(
+        &format!(
+            "{}/api/v1/forward-auth/login",
+            state.settings.api.public_base_url()
+        ),
+        &[("rd", original.as_str())],
+    )
+    .ok()?;
+    Some(Redirect::to(login.as_str()).into_response())
+}
+
+/// Check a request to an app protected by forward auth.
+///
+/// Used as a Traefik forward-auth endpoint for apps with `forward_auth`. Only
+/// users with the view permission on the app get through. Browsers without a
+/// valid token are sent to the login, which sets a cookie for the domains of
+/// the apps. The cookie only identifies the user to this check.
+#[utoipa::path(
+    get,
+    path = "/api/v1/forward-auth/check/{app_name}",
+    responses(
+    (status = 200, description = "Request allowed"),
+    (status = 303, description = "Redirect to the login"),
+    (status = 401, description = "Not logged in"),
+    (status = 403, description = "No permission to view the app"),
+    )
+)]
+pub async fn forward_auth_handler(
+    Path(app_name): Path<String>,
+    State(state): State<SharedAppState>,
+    headers: HeaderMap,
+) -> Response {
+    let app_name = slugify(&app_name);
+    let Some(user) = authenticate(&state, &headers).await else {
+        debug!("Forward auth for {}: not logged in", app_name);
+        return login_redirect(&state, &headers)
+            .unwrap_or_else(|| StatusCode::UNAUTHORIZED.into_response());
+    };
+
+    let user_id = AuthorizationService::get_user_id_for_authorization(&user);
+    if !state
+        .auth_service
+        .check_permission(&user_id, &app_name, &Permission::View)
+        .await
+    {
+        warn!(
+            "Forward auth denied: user {} lacks view permission on {}",
+            user.email, app_name
+        );
+        return StatusCode::FORBIDDEN.into_response();
+    }
+
+    (StatusCode::OK, [("X-Forwarded-User", user.email)]).into_response()
+}
+
+#[derive(Deserialize, utoipa::IntoParams)]
+pub struct ForwardAuthLoginQuery {
+    /// URL to return to after the login
+    pub rd: String,
+}
+
+/// Log in to reach an app protected by forward auth
+#[utoipa::path(
+    get,
+    path = "/api/v1/forward-auth/login",
+    params(ForwardAuthLoginQuery),
+    responses(
+    (status = 303, description = "Redirect to the OAuth provider"),
+    (status = 400, description = "Redirect URL or scotty not below the cookie domain"),
+    )
+)]
+pub async fn forward_auth_login_handler(
+    State(state): State<SharedAppState>,
+    Query(query): Query<ForwardAuthLoginQuery>,
+) -> Result<Response, AppError> {
+    let rd = checked_redirect(&state, &query.rd)?;
+    // cookie_domain is set, checked_redirect succeeded
+    check_cookie_domain(&state, &cookie_domain(&state).unwrap_or_default())?;
+    if secret_key(&state.settings).is_none() {
+        return Err(AppError::InternalServerError(
+            "Logins for forward auth need api.secret_key to be configured".to_string(),
+        ));
+    }
+    let oauth_state = state
+        .oauth_state
+        .as_ref()
+        .ok_or(AppError::from(OAuthError::OauthNotConfigured))?;
+
+    let callback = Url::parse_with_params(
+        &format!(
+            "{}/api/v1/forward-auth/callback",
+            state.settings.api.public_base_url()
+        ),
+        &[("rd", rd.as_str())],
+    )
+    .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+    let auth_url = begin_web_flow(
+        oauth_state,
+        state.settings.api.oauth.redirect_url.clone(),
+        Some(callback.to_string()),
+    )?;
+    Ok(Redirect::to(auth_url.as_str()).into_response())
+}
+
+#[derive(Deserialize, utoipa::IntoParams)]
+pub struct ForwardAuthCallbackQuery {
+    /// URL to return to after the login
+    pub rd: String,
+    /// OAuth session created by the OAuth callback
+    pub session_id: String,
+}
+
+/// Finish the login, set the cookie for the domains of the apps and return to
+/// the app
+#[utoipa::path(
+    get,
+    path = "/api/v1/forward-auth/callback",
+    params(ForwardAuthCallbackQuery),
+    responses(
+    (status = 303, description = "Redirect back to the app"),
+    (status = 400, description = "Redirect URL not below the cookie domain"),
+    (status = 404, description = "Session not found"),
+    )
+)]
+pub async fn forward_auth_callback_handler(
+    State(state): State<SharedAppState>,
+    Query(query): Query<ForwardAuthCallbackQuery>,
+) -> Result<Response, AppError> {
+    let rd = checked_redirect(&state, &query.rd)?;
+    let oauth_state = state
+        .oauth_state
+        .as_ref()
+        .ok_or(AppError::from(OAuthError::OauthNotConfigured))?;
+
+    // One-time use, like the session exchange of the frontend
+    let session = oauth_state
+        .session_store
+        .lock()
+        .unwrap()
+        .remove(&query.session_id)
+        .ok_or(AppError::from(OAuthError::SessionNotFound))?;
+    if SystemTime::now() > session.expires_at {
+        return Err(OAuthError::ExpiredSession.into());
+    }
+
+    // The apps receive the cookie, so it holds a login only good for the
+    // forward-auth check instead of the OIDC token of the user
+    let user = authenticate_user_from_token(&state, &session.oidc_token)
+        .await
+        .map_err(|_| AppError::from(OAuthError::SessionNotFound))?;
+    let ttl = state.settings.api.forward_auth.cookie_ttl();
+    let token = forward_auth_token(
+        &state.settings,
+        &user.email,
+        unix_time(SystemTime::now() + ttl),
+    )
+    .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+
+    // cookie_domain is set, checked_redirect succeeded
+    let domain = cookie_domain(&state).unwrap_or_default();
+    let cookie = format!(
+        "{}={}; Domain={}; Path=/; Max-Age={}; HttpOnly; SameSite=Lax{}",
+        state.settings.api.forward_auth.cookie_name,
+        token,
+        domain,
+        ttl.as_secs(),
+        if rd.scheme() == "https" {
+            "; Secure"
+        } else {
+            ""
+        }
+    );
+    Ok(([(header::SET_COOKIE, cookie)], Redirect::to(rd.as_str())).into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    #[test]
+    fn test_is_below_domain() {
+        let domain = "example.com";
+        let url = |url: &str| Url::parse(url).unwrap();
+        assert!(is_below_domain(
+            &url("https://app.example.com/page"),
+            domain
+        ));
+        assert!(is_below_domain(&url("http://example.com"), domain));
+        assert!(!is_below_domain(
+            &url("https://example.com.evil.org"),
+            domain
+        ));
+        assert!(!is_below_domain(&url("https://notexample.com"), domain));
+        assert!(!is_below_domain(
+            &url("javascript://app.example.com"),
+            domain
+        ));
+    }
+
+    #[test]
+    fn test_bearer_token_and_cookie() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::COOKIE,
+            HeaderValue::from_static("theme=dark; scotty_forward_auth=abc"),
+        );
+        assert_eq!(
+            cookie_from_headers(&headers, "scotty_forward_auth").as_deref(),
+            Some("abc")
+        );
+        assert!(cookie_from_headers(&headers, "other").is_none());
+
+        // Basic auth of the app itself is not a token
+        headers.insert(
+            header::AUTHORIZATION,
+            HeaderValue::from_static("Basic dXNlcg=="),
+        );
+        assert!(bearer_token(&headers).is_none());
+
+        headers.insert(
+            header::AUTHORIZATION,
+            HeaderValue::from_static("Bearer xyz"),
+        );
+        assert_eq!(bearer_token(&headers).as_deref(), Some("xyz"));
+    }
+
+    #[tokio::test]
+    async fn test_authenticate_with_cookie() {
+        use crate::api::test_utils::{
+            create_test_app_state_with_settings, settings_with_secret_key,
+        };
+        use crate::settings::config::Settings;
+
+        let state = create_test_app_state_with_settings(
+            settings_with_secret_key(Settings::default(), "secret"),
+            None,
+        )
+        .await;
+        let cookie = |value: &str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(
+                header::COOKIE,
+                HeaderValue::from_str(&format!("scotty_forward_auth={value}")).unwrap(),
+            );
+            headers
+        };
+
+        let expires_at = unix_time(SystemTime::now()) + 60;
+        let token = forward_auth_token(&state.settings, "user@example.com", expires_at).unwrap();
+        let user = authenticate(&state, &cookie(&token)).await.unwrap();
+        assert_eq!(user.email, "user@example.com");
+        assert!(user.access_token.is_none());
+
+        let expired = forward_auth_token(&state.settings, "user@example.com", 1).unwrap();
+        assert!(authenticate(&state, &cookie(&expired)).await.is_none());
+        assert!(authenticate(&state, &cookie("garbage")).await.is_none());
+    }
+
+    #[test]
+    fn test_original_url() {
+        let mut headers = HeaderMap::new();
+        assert!(original_url(&headers).is_none());
+
+        headers.insert("x-forwarded-proto", HeaderValue::from_static("https"));
+        headers.insert(
+            "x-forwarded-host",
+            HeaderValue::from_static("app.example.com"),
+        );
+        headers.insert("x-forwarded-uri", HeaderValue::from_static("/page?id=1"));
+        assert_eq!(
+            original_url(&headers).unwrap().as_str(),
+            "https://app.example.com/page?id=1"
+        );
+    }
+}
@@ -1,7 +1,7 @@
 use std::sync::Once;
 
 use axum::body::Body;
-use axum::extract::State;
+use axum::extract::{Path, State};
 use axum::http::{HeaderMap, Response, StatusCode, Uri};
 use url::Url;
 
@@ -9,6 +9,7 @@ use crate::app_state::SharedAppState;
 use crate::static_files::serve_embedded_file;
 
 use scotty_core::apps::app_data::AppStatus;
+use scotty_core::utils::slugify::slugify;
 
 /// Build a Response with no-cache headers to prevent browsers and proxies
 /// from caching redirect or error responses for stopped apps.
@@ -19,6 +20,20 @@ fn no_cache_response() -> axum::http::response::Builder {
         .header("Expires", "0")
 }
 
+/// Page shown for all requests of an app in maintenance mode.
+fn maintenance_response() -> Response<Body> {
+    no_cache_response()
+        .status(StatusCode::SERVICE_UNAVAILABLE)
+        .header("content-type", "text/html; charset=utf-8")
+        .header("Retry-After", "60")
+        .body(Body::from(
+            "<h1>Down for Maintenance</h1>\
+             <p>The application is undergoing maintenance. \
+             Please try again in a few minutes.</p>",
+        ))
+        .expect("BUG: response builder failed")
+}
+
 /// Extract the hostname from the request's Host header.
 /// Handles both plain hostnames, host:port pairs, and bracketed IPv6 addresses.
 fn extract_hostname(headers: &HeaderMap) -> Option<String> {
@@ -45,8 +60,9 @@ fn extract_hostname(headers: &HeaderMap) -> Option<String> {
 
 /// Fallback handler that inspects the Host header to decide whether to:
 /// 1. Serve the embedded SvelteKit frontend (request is for Scotty's own domain)
-/// 2. Redirect to the landing page (request is for a stopped app's domain)
-/// 3. Return 404 (unknown domain)
+/// 2. Show a maintenance page (request is for an app in maintenance mode)
+/// 3. Redirect to the landing page (request is for a stopped app's domain)
+/// 4. Return 404 (unknown domain)
 ///
 /// This enables the "default backend" flow where Traefik routes unmatched
 /// domains to Scotty, and Scotty redirects to its landing page.
@@ -67,6 +83,12 @@ pub async fn landing_or_frontend_handler(
 
     // Check if this domain belongs to a known app
     if let Some(app) = state.apps.find_app_by_domain(&hostname).await {
+        // Apps in maintenance routed via files have no routes, so all their
+        // requests end up here
+        if app.settings.as_ref().is_some_and(|s| s.maintenance) {
+            return maintenance_response();
+        }
+
         // Only redirect to the landing page if the app is actually stopped.
         if app.status != AppStatus::Stopped {
             let message = match app.status {
@@ -153,6 +175,32 @@ pub async fn landing_or_frontend_handler(
         .expect("BUG: response builder failed")
 }
 
+/// Maintenance page of an app routed via container labels.
+///
+/// The labels of the app stay in place while it is in maintenance, Traefik
+/// sends all its requests here with routers of a higher priority instead.
+pub async fn maintenance_page_handler(
+    State(state): State<SharedAppState>,
+    Path(app_name): Path<String>,
+) -> Response<Body> {
+    let in_maintenance = state
+        .apps
+        .get_app(&slugify(&app_name))
+        .await
+        .and_then(|app| app.settings)
+        .is_some_and(|settings| settings.maintenance);
+    if in_maintenance {
+        return maintenance_response();
+    }
+    no_cache_response()
+        .status(StatusCode::NOT_FOUND)
+        .header("content-type", "text/html; charset=utf-8")
+        .body(Body::from(
+            "<h1>Not Found</h1><p>The application is not in maintenance.</p>",
+        ))
+        .expect("BUG: response builder failed")
+}
+
 /// Check if the given hostname matches Scotty's own domain.
 fn is_scotty_domain(state: &SharedAppState, hostname: &str) -> bool {
     let Some(base_url) = state.settings.api.configured_base_url() else {
@@ -243,6 +291,50 @@ mod tests {
         assert_eq!(extract_hostname(&headers), None);
     }
 
+    #[test]
+    fn test_maintenance_response() {
+        let response = maintenance_response();
+
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(response.headers().get("Retry-After").unwrap(), "60");
+        assert_eq!(
+            response.headers().get("Cache-Control").unwrap(),
+            "no-store, no-cache, must-revalidate"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_maintenance_page_handler() {
+        use scotty_core::apps::app_data::{AppData, AppSettings};
+
+        let state = crate::api::test_utils::create_test_app_state_with_settings(
+            crate::settings::config::Settings::default(),
+            None,
+        )
+        .await;
+        let page = |app_name: &str| {
+            maintenance_page_handler(State(state.clone()), Path(app_name.to_string()))
+        };
+        state
+            .apps
+            .add_app(AppData {
+                name: "my-app".to_string(),
+                settings: Some(AppSettings {
+                    maintenance: true,
+                    ..Default::default()
+                }),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(
+            page("my-app").await.status(),
+            StatusCode::SERVICE_UNAVAILABLE
+        );
+        assert_eq!(page("other-app").await.status(), StatusCode::NOT_FOUND);
+    }
+
     #[test]
     fn test_no_cache_response_has_correct_headers() {
         let response = no_cache_response()
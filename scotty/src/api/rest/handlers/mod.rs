@@ -1,7 +1,9 @@
+pub mod activity;
 pub mod admin;
 pub mod apps;
 pub mod blueprints;
 pub mod files;
+pub mod forward_auth;
 pub mod health;
 pub mod info;
 pub mod landing;
@@ -1,14 +1,19 @@
 use axum::{
     debug_handler,
-    extract::{Path, State},
+    extract::{Path, Query, State},
     response::IntoResponse,
-    Json,
+    Extension, Json,
 };
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use crate::{api::error::AppError, app_state::SharedAppState};
-use scotty_core::tasks::task_details::TaskDetails;
+use crate::{
+    api::{error::AppError, middleware::authorization::AuthorizationContext},
+    app_state::SharedAppState,
+    services::{authorization::Permission, AuthorizationService},
+    tasks::history::TaskFilter,
+};
+use scotty_core::tasks::task_details::{State as TaskState, TaskDetails};
 
 #[utoipa::path(
     get,
@@ -41,6 +46,7 @@ pub struct TaskList {
 #[utoipa::path(
     get,
     path = "/api/v1/authenticated/tasks",
+    params(TaskFilter),
     responses(
     (status = 200, response = inline(TaskList)),
     (status = 401, description = "Access token is missing or invalid"),
@@ -52,10 +58,71 @@ pub struct TaskList {
 #[debug_handler]
 pub async fn task_list_handler(
     State(state): State<SharedAppState>,
+    Query(filter): Query<TaskFilter>,
 ) -> Result<Json<TaskList>, AppError> {
     let task_list = TaskList {
-        tasks: state.task_manager.get_task_list().await,
+        tasks: state.task_manager.get_filtered_task_list(&filter).await,
     };
     let json = Json(task_list);
     Ok(json)
 }
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/authenticated/tasks/{uuid}",
+    responses(
+    (status = 200, response = inline(TaskDetails)),
+    (status = 401, description = "Access token is missing or invalid"),
+    (status = 403, description = "Insufficient permissions to cancel the task"),
+    (status = 404, description = "Task not found"),
+    (status = 409, description = "Task is not running"),
+    ),
+    security(
+        ("bearerAuth" = [])
+    )
+)]
+pub async fn task_cancel_handler(
+    Path(uuid): Path<Uuid>,
+    State(state): State<SharedAppState>,
+    Extension(auth_context): Extension<AuthorizationContext>,
+) -> Result<Json<TaskDetails>, AppError> {
+    let task_detail = state
+        .task_manager
+        .get_task_details(&uuid)
+        .await
+        .ok_or(AppError::TaskNotFound(uuid))?;
+
+    // Cancelling a task requires the manage permission on its app
+    if let Some(app_name) = &task_detail.app_name {
+        let user_id = AuthorizationService::get_user_id_for_authorization(&auth_context.user);
+        if !state
+            .auth_service
+            .check_permission(&user_id, app_name, &Permission::Manage)
+            .await
+        {
+            return Err(AppError::ScopeAccessDenied(format!(
+                "Insufficient permission to cancel task {} of app '{}'",
+                uuid, app_name
+            )));
+        }
+    }
+
+    if !matches!(task_detail.state, TaskState::Running | TaskState::Queued) {
+        return Err(AppError::TaskNotRunning(uuid));
+    }
+
+    let task_detail = state
+        .task_manager
+        .cancel_task(&uuid)
+        .await
+        .ok_or(AppError::TaskNotFound(uuid))?;
+
+    state
+        .messenger
+        .broadcast_to_all(
+            scotty_core::websocket::message::WebSocketMessage::TaskInfoUpdated(task_detail.clone()),
+        )
+        .await;
+
+    Ok(Json(task_detail))
+}
@@ -1,17 +1,30 @@
 use axum::extract::DefaultBodyLimit;
 use axum::middleware;
-use axum::routing::{get, post, put};
+use axum::routing::{any, delete, get, post, put};
 use axum::Router;
+use scotty_core::apps::app_data::AppCertificatesResponse;
 use scotty_core::apps::app_data::AppData;
 use scotty_core::apps::app_data::AppSettings;
 use scotty_core::apps::app_data::AppStatus;
+use scotty_core::apps::app_data::AppTlsSettings;
 use scotty_core::apps::app_data::AppTtl;
 use scotty_core::apps::app_data::ContainerState;
+use scotty_core::apps::app_data::DomainCertificateStatus;
+use scotty_core::apps::app_data::DomainTlsSettings;
+use scotty_core::apps::app_data::ExtendTtlRequest;
+use scotty_core::apps::app_data::RedirectRule;
 use scotty_core::apps::app_data::ServicePortMapping;
+use scotty_core::apps::app_data::SetMaintenanceRequest;
+use scotty_core::apps::app_data::TlsDomain;
+use scotty_core::apps::app_data::{
+    AddBasicAuthUserRequest, BasicAuthUser, BasicAuthUserInfo, BasicAuthUsersResponse,
+};
 use scotty_core::apps::create_app_request::CreateAppRequest;
 use scotty_core::apps::file_list::File;
 use scotty_core::apps::file_list::FileList;
+use scotty_core::apps::revision::{RevisionInfo, RollbackRequest};
 use scotty_core::apps::shared_app_list::AppDataVec;
+use scotty_core::apps::update_app_request::UpdateAppRequest;
 use scotty_core::notification_types::AddNotificationRequest;
 use scotty_core::notification_types::GitlabContext;
 use scotty_core::notification_types::MattermostContext;
@@ -29,6 +42,12 @@ use utoipa_rapidoc::RapiDoc;
 use utoipa_redoc::{Redoc, Servable};
 use utoipa_swagger_ui::SwaggerUi;
 
+use crate::api::rest::handlers::activity::__path_record_activity_handler;
+use crate::api::rest::handlers::apps::basic_auth::{
+    __path_add_basic_auth_user_handler, __path_list_basic_auth_users_handler,
+    __path_remove_basic_auth_user_handler,
+};
+use crate::api::rest::handlers::apps::certificates::__path_app_certificates_handler;
 use crate::api::rest::handlers::apps::create::__path_create_app_handler;
 use crate::api::rest::handlers::apps::custom_action::__path_run_custom_action_handler;
 use crate::api::rest::handlers::apps::custom_action_management::{
@@ -37,8 +56,12 @@ use crate::api::rest::handlers::apps::custom_action_management::{
 };
 use crate::api::rest::handlers::apps::list::__path_list_apps_handler;
 use crate::api::rest::handlers::apps::list::list_apps_handler;
+use crate::api::rest::handlers::apps::maintenance::__path_set_maintenance_handler;
 use crate::api::rest::handlers::apps::notify::__path_add_notification_handler;
 use crate::api::rest::handlers::apps::notify::__path_remove_notification_handler;
+use crate::api::rest::handlers::apps::revisions::{
+    __path_list_revisions_handler, __path_rollback_app_handler,
+};
 use crate::api::rest::handlers::apps::run::__path_adopt_app_handler;
 use crate::api::rest::handlers::apps::run::__path_destroy_app_handler;
 use crate::api::rest::handlers::apps::run::__path_info_app_handler;
@@ -46,6 +69,12 @@ use crate::api::rest::handlers::apps::run::__path_purge_app_handler;
 use crate::api::rest::handlers::apps::run::__path_rebuild_app_handler;
 use crate::api::rest::handlers::apps::run::__path_run_app_handler;
 use crate::api::rest::handlers::apps::run::__path_stop_app_handler;
+use crate::api::rest::handlers::apps::ttl::__path_extend_ttl_handler;
+use crate::api::rest::handlers::apps::update::__path_update_app_handler;
+use crate::api::rest::handlers::forward_auth::{
+    __path_forward_auth_callback_handler, __path_forward_auth_handler,
+    __path_forward_auth_login_handler,
+};
 use crate::api::rest::handlers::health::__path_health_checker_handler;
 use crate::api::rest::handlers::info::__path_info_handler;
 use crate::api::rest::handlers::login::__path_login_handler;
@@ -58,11 +87,19 @@ use crate::oauth::handlers::{AuthorizeQuery, CallbackQuery, DeviceFlowResponse,
 use scotty_core::api::{OAuthConfig, ServerInfo};
 use scotty_core::settings::api_server::AuthMode;
 
-use super::rest::handlers::landing::landing_or_frontend_handler;
+use super::rest::handlers::activity::record_activity_handler;
+use super::rest::handlers::forward_auth::{
+    forward_auth_callback_handler, forward_auth_handler, forward_auth_login_handler,
+};
+use super::rest::handlers::landing::{landing_or_frontend_handler, maintenance_page_handler};
 use crate::api::rest::handlers::admin::assignments::{
     __path_create_assignment_handler, __path_list_assignments_handler,
     __path_remove_assignment_handler,
 };
+use crate::api::rest::handlers::admin::certificates::{
+    __path_list_certificates_handler, __path_remove_certificate_handler,
+    __path_upload_certificate_handler,
+};
 use crate::api::rest::handlers::admin::custom_actions::{
     __path_approve_action_handler, __path_get_action_details_handler,
     __path_list_pending_actions_handler, __path_reject_action_handler,
@@ -85,6 +122,7 @@ use crate::api::rest::handlers::files::{
 };
 use crate::api::rest::handlers::health::health_checker_handler;
 use crate::api::rest::handlers::scopes::list::__path_list_user_scopes_handler;
+use crate::api::rest::handlers::tasks::__path_task_cancel_handler;
 use crate::api::rest::handlers::tasks::__path_task_detail_handler;
 use crate::api::rest::handlers::tasks::__path_task_list_handler;
 use crate::api::rest::handlers::tasks::TaskList;
@@ -100,6 +138,9 @@ use super::rate_limiting::{
 use super::rest::handlers::admin::assignments::{
     create_assignment_handler, list_assignments_handler, remove_assignment_handler,
 };
+use super::rest::handlers::admin::certificates::{
+    list_certificates_handler, remove_certificate_handler, upload_certificate_handler,
+};
 use super::rest::handlers::admin::custom_actions::{
     approve_action_handler, get_action_details_handler, list_pending_actions_handler,
     reject_action_handler, revoke_action_handler,
@@ -109,14 +150,20 @@ use super::rest::handlers::admin::permissions::{
 };
 use super::rest::handlers::admin::roles::{create_role_handler, list_roles_handler};
 use super::rest::handlers::admin::scopes::{create_scope_handler, list_scopes_handler};
+use super::rest::handlers::apps::basic_auth::{
+    add_basic_auth_user_handler, list_basic_auth_users_handler, remove_basic_auth_user_handler,
+};
+use super::rest::handlers::apps::certificates::app_certificates_handler;
 use super::rest::handlers::apps::create::create_app_handler;
 use super::rest::handlers::apps::custom_action::run_custom_action_handler;
 use super::rest::handlers::apps::custom_action_management::{
     create_custom_action_handler, delete_custom_action_handler, get_custom_action_handler,
     list_custom_actions_handler,
 };
+use super::rest::handlers::apps::maintenance::set_maintenance_handler;
 use super::rest::handlers::apps::notify::add_notification_handler;
 use super::rest::handlers::apps::notify::remove_notification_handler;
+use super::rest::handlers::apps::revisions::{list_revisions_handler, rollback_app_handler};
 use super::rest::handlers::apps::run::adopt_app_handler;
 use super::rest::handlers::apps::run::destroy_app_handler;
 use super::rest::handlers::apps::run::info_app_handler;
@@ -124,6 +171,8 @@ use super::rest::handlers::apps::run::purge_app_handler;
 use super::rest::handlers::apps::run::rebuild_app_handler;
 use super::rest::handlers::apps::run::run_app_handler;
 use super::rest::handlers::apps::run::stop_app_handler;
+use super::rest::handlers::apps::ttl::extend_ttl_handler;
+use super::rest::handlers::apps::update::update_app_handler;
 use super::rest::handlers::blueprints::blueprints_handler;
 use super::rest::handlers::info::info_handler;
 use super::rest::handlers::login::login_handler;
@@ -131,22 +180,28 @@ use super::rest::handlers::login::validate_token_handler;
 use super::rest::handlers::scopes::list::{
     list_user_scopes_handler, ScopeInfo, UserScopesResponse,
 };
+use super::rest::handlers::tasks::task_cancel_handler;
 use super::rest::handlers::tasks::task_detail_handler;
 use super::rest::handlers::tasks::task_list_handler;
 use crate::services::authorization::types::Assignment;
 use crate::services::authorization::Permission;
 use scotty_core::admin::{
-    AssignmentInfo, AssignmentsListResponse, AvailablePermissionsResponse, CreateAssignmentRequest,
-    CreateAssignmentResponse, CreateRoleRequest, CreateRoleResponse, CreateScopeRequest,
-    CreateScopeResponse, RemoveAssignmentRequest, RemoveAssignmentResponse, RoleInfo,
-    RolesListResponse, ScopeInfo as AdminScopeInfo, ScopesListResponse, TestPermissionRequest,
-    TestPermissionResponse, UserPermissionsResponse,
+    AssignmentInfo, AssignmentsListResponse, AvailablePermissionsResponse, CertificateInfo,
+    CertificatesListResponse, CreateAssignmentRequest, CreateAssignmentResponse, CreateRoleRequest,
+    CreateRoleResponse, CreateScopeRequest, CreateScopeResponse, RemoveAssignmentRequest,
+    RemoveAssignmentResponse, RoleInfo, RolesListResponse, ScopeInfo as AdminScopeInfo,
+    ScopesListResponse, TestPermissionRequest, TestPermissionResponse, UploadCertificateRequest,
+    UserPermissionsResponse,
 };
 
 #[derive(OpenApi)]
 #[openapi(
     paths(
         health_checker_handler,
+        record_activity_handler,
+        forward_auth_handler,
+        forward_auth_login_handler,
+        forward_auth_callback_handler,
         list_apps_handler,
         run_app_handler,
         stop_app_handler,
@@ -155,7 +210,9 @@ use scotty_core::admin::{
         task_detail_handler,
         rebuild_app_handler,
         create_app_handler,
+        update_app_handler,
         task_list_handler,
+        task_cancel_handler,
         destroy_app_handler,
         validate_token_handler,
         login_handler,
@@ -165,6 +222,14 @@ use scotty_core::admin::{
         add_notification_handler,
         remove_notification_handler,
         adopt_app_handler,
+        list_revisions_handler,
+        rollback_app_handler,
+        app_certificates_handler,
+        list_basic_auth_users_handler,
+        add_basic_auth_user_handler,
+        remove_basic_auth_user_handler,
+        extend_ttl_handler,
+        set_maintenance_handler,
         run_custom_action_handler,
         // Custom action management endpoints
         create_custom_action_handler,
@@ -182,6 +247,9 @@ use scotty_core::admin::{
         test_permission_handler,
         get_user_permissions_handler,
         list_available_permissions_handler,
+        list_certificates_handler,
+        upload_certificate_handler,
+        remove_certificate_handler,
         // Admin custom action endpoints
         list_pending_actions_handler,
         get_action_details_handler,
@@ -194,9 +262,12 @@ use scotty_core::admin::{
     components(
         schemas(
             GitlabContext, WebhookContext, MattermostContext, NotificationReceiver,
-            AddNotificationRequest, TaskList, File, FileList, CreateAppRequest,
+            AddNotificationRequest, TaskList, File, FileList, CreateAppRequest, UpdateAppRequest,
             AppData, AppDataVec, TaskDetails, ContainerState, AppSettings,
             AppStatus, AppTtl, ServicePortMapping, RunningAppContext,
+            RevisionInfo, RollbackRequest, ExtendTtlRequest, SetMaintenanceRequest, RedirectRule,
+            AppTlsSettings, TlsDomain, DomainTlsSettings, DomainCertificateStatus, AppCertificatesResponse,
+            BasicAuthUser, AddBasicAuthUserRequest, BasicAuthUserInfo, BasicAuthUsersResponse,
             OAuthConfig, ServerInfo, AuthMode, DeviceFlowResponse, TokenResponse, AuthorizeQuery, CallbackQuery,
             ScopeInfo, UserScopesResponse,
             // Admin API schemas
@@ -205,6 +276,7 @@ use scotty_core::admin::{
             AssignmentInfo, AssignmentsListResponse, CreateAssignmentRequest, CreateAssignmentResponse,
             RemoveAssignmentRequest, RemoveAssignmentResponse, Assignment,
             TestPermissionRequest, TestPermissionResponse, UserPermissionsResponse, AvailablePermissionsResponse,
+            CertificateInfo, CertificatesListResponse, UploadCertificateRequest,
             // Custom action schemas
             CustomAction, ActionStatus, CreateCustomActionRequest, CustomActionList, ReviewActionRequest,
             PendingActionInfo, PendingActionsResponse
@@ -297,6 +369,75 @@ impl ApiRoutes {
                     require_permission(Permission::Create),
                 )),
             )
+            .route(
+                "/api/v1/authenticated/apps/{app_id}",
+                put(update_app_handler)
+                    .layer(DefaultBodyLimit::max(
+                        state.settings.api.create_app_max_size,
+                    ))
+                    .layer(middleware::from_fn_with_state(
+                        state.clone(),
+                        require_permission(Permission::Manage),
+                    )),
+            )
+            .route(
+                "/api/v1/authenticated/apps/{app_name}/revisions",
+                get(list_revisions_handler).layer(middleware::from_fn_with_state(
+                    state.clone(),
+                    require_permission(Permission::View),
+                )),
+            )
+            .route(
+                "/api/v1/authenticated/apps/{app_name}/certificates",
+                get(app_certificates_handler).layer(middleware::from_fn_with_state(
+                    state.clone(),
+                    require_permission(Permission::View),
+                )),
+            )
+            // Listing needs `view`, changing the users `manage`, see the
+            // file transfer endpoints below for the `route_layer` + `merge`
+            .route(
+                "/api/v1/authenticated/apps/{app_name}/auth",
+                get(list_basic_auth_users_handler)
+                    .route_layer(middleware::from_fn_with_state(
+                        state.clone(),
+                        require_permission(Permission::View),
+                    ))
+                    .merge(post(add_basic_auth_user_handler).route_layer(
+                        middleware::from_fn_with_state(
+                            state.clone(),
+                            require_permission(Permission::Manage),
+                        ),
+                    )),
+            )
+            .route(
+                "/api/v1/authenticated/apps/{app_name}/auth/{username}",
+                delete(remove_basic_auth_user_handler).layer(middleware::from_fn_with_state(
+                    state.clone(),
+                    require_permission(Permission::Manage),
+                )),
+            )
+            .route(
+                "/api/v1/authenticated/apps/{app_name}/rollback",
+                post(rollback_app_handler).layer(middleware::from_fn_with_state(
+                    state.clone(),
+                    require_permission(Permission::Manage),
+                )),
+            )
+            .route(
+                "/api/v1/authenticated/apps/{app_name}/ttl",
+                post(extend_ttl_handler).layer(middleware::from_fn_with_state(
+                    state.clone(),
+                    require_permission(Permission::Manage),
+                )),
+            )
+            .route(
+                "/api/v1/authenticated/apps/{app_name}/maintenance",
+                post(set_maintenance_handler).layer(middleware::from_fn_with_state(
+                    state.clone(),
+                    require_permission(Permission::Manage),
+                )),
+            )
             .route(
                 "/api/v1/authenticated/apps/create",
                 post(create_app_handler).layer(DefaultBodyLimit::max(
@@ -304,6 +445,10 @@ impl ApiRoutes {
                 )),
             )
             .route("/api/v1/authenticated/tasks", get(task_list_handler))
+            .route(
+                "/api/v1/authenticated/tasks/{uuid}",
+                delete(task_cancel_handler),
+            )
             .route(
                 "/api/v1/authenticated/task/{uuid}",
                 get(task_detail_handler),
@@ -437,6 +582,27 @@ impl ApiRoutes {
                             )),
                     ),
             )
+            .route(
+                "/api/v1/authenticated/admin/certificates",
+                get(list_certificates_handler)
+                    .route_layer(middleware::from_fn_with_state(
+                        state.clone(),
+                        require_permission(Permission::AdminRead),
+                    ))
+                    .merge(post(upload_certificate_handler).route_layer(
+                        middleware::from_fn_with_state(
+                            state.clone(),
+                            require_permission(Permission::AdminWrite),
+                        ),
+                    )),
+            )
+            .route(
+                "/api/v1/authenticated/admin/certificates/{name}",
+                delete(remove_certificate_handler).layer(middleware::from_fn_with_state(
+                    state.clone(),
+                    require_permission(Permission::AdminWrite),
+                )),
+            )
             .route(
                 "/api/v1/authenticated/admin/permissions",
                 get(list_available_permissions_handler).layer(middleware::from_fn_with_state(
@@ -541,6 +707,14 @@ impl ApiRoutes {
             .route("/oauth/authorize", get(start_authorization_flow))
             .route("/api/oauth/callback", get(handle_oauth_callback))
             .route("/oauth/exchange", post(exchange_session_for_token))
+            .route(
+                "/api/v1/forward-auth/login",
+                get(forward_auth_login_handler),
+            )
+            .route(
+                "/api/v1/forward-auth/callback",
+                get(forward_auth_callback_handler),
+            )
             .with_state(state.clone());
 
         // Apply rate limiting to OAuth endpoints if enabled
@@ -559,6 +733,15 @@ impl ApiRoutes {
         let public_router = Router::new()
             .route("/api/v1/health", get(health_checker_handler))
             .route("/api/v1/info", get(info_handler))
+            .route("/api/v1/activity/{app_name}", get(record_activity_handler))
+            .route(
+                "/api/v1/forward-auth/check/{app_name}",
+                get(forward_auth_handler),
+            )
+            .route(
+                "/api/v1/maintenance/{app_name}",
+                any(maintenance_page_handler),
+            )
             .route("/ws", get(ws_handler))
             .merge(SwaggerUi::new("/swagger-ui").url("/api-docs/openapi.json", api.clone()))
             .merge(Redoc::with_url("/redoc", api.clone()))
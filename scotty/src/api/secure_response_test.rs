@@ -26,7 +26,7 @@ fn create_app_settings_with_env_vars(env_vars: HashMap<String, String>) -> AppSe
         domain: "test.example.com".to_string(),
         time_to_live: AppTtl::Days(7),
         destroy_on_ttl: false,
-        basic_auth: None,
+        basic_auth: vec![],
         disallow_robots: true,
         environment: SecretHashMap::from_hashmap(env_vars),
         ..Default::default()
@@ -42,6 +42,7 @@ fn create_test_app_data(settings: AppSettings) -> AppData {
         root_directory: "/path/to/app".to_string(),
         status: AppStatus::Running,
         last_checked: None,
+        drift: None,
     }
 }
 
@@ -250,6 +251,8 @@ async fn test_secure_json_masks_running_app_context_env_vars() {
         finish_time: Some(DateTime::from(std::time::SystemTime::now())),
         last_exit_code: Some(0),
         app_name: Some("test-app".to_string()),
+        requested_by: None,
+        queue_position: None,
         output_collection_active: false,
         output: Default::default(),
     };
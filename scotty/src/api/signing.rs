@@ -0,0 +1,152 @@
+//! Signatures for the tokens scotty hands out to load balancers and browsers.
+//!
+//! Tokens are signed with HMAC-SHA256 and the `api.secret_key` setting, so
+//! they can be checked without storing them and stop working when the key
+//! changes.
+
+use base64::prelude::*;
+use hmac::digest::KeyInit;
+use hmac::{Hmac, Mac};
+use secrecy::ExposeSecret;
+use sha2::Sha256;
+
+use crate::settings::config::Settings;
+
+type HmacSha256 = Hmac<Sha256>;
+
+fn mac(key: &str, message: &str) -> HmacSha256 {
+    let mut mac = HmacSha256::new_from_slice(key.as_bytes()).expect("HMAC takes any key length");
+    mac.update(message.as_bytes());
+    mac
+}
+
+/// Hex encoded signature of `message`.
+pub fn sign(key: &str, message: &str) -> String {
+    hex::encode(mac(key, message).finalize().into_bytes())
+}
+
+/// Checks the signature of `message` in constant time.
+pub fn verify(key: &str, message: &str, signature: &str) -> bool {
+    hex::decode(signature).is_ok_and(|signature| mac(key, message).verify_slice(&signature).is_ok())
+}
+
+/// The configured secret key, if any.
+pub fn secret_key(settings: &Settings) -> Option<&str> {
+    settings
+        .api
+        .secret_key
+        .as_ref()
+        .map(|key| key.expose_secret())
+        .filter(|key| !key.is_empty())
+}
+
+fn activity_message(app_name: &str) -> String {
+    format!("activity:{}", app_name)
+}
+
+/// Token the load balancer passes when it reports a request to `app_name`.
+pub fn activity_token(settings: &Settings, app_name: &str) -> anyhow::Result<String> {
+    let key = secret_key(settings).ok_or_else(|| {
+        anyhow::anyhow!("Apps with an idle TTL need api.secret_key to be configured")
+    })?;
+    Ok(sign(key, &activity_message(app_name)))
+}
+
+/// Checks the token of an activity report for `app_name`.
+pub fn verify_activity_token(settings: &Settings, app_name: &str, token: &str) -> bool {
+    secret_key(settings).is_some_and(|key| verify(key, &activity_message(app_name), token))
+}
+
+fn forward_auth_message(email: &str, expires_at: u64) -> String {
+    format!("forward-auth:{}:{}", email, expires_at)
+}
+
+/// Login of a browser for the forward-auth check of apps, valid until
+/// `expires_at` (seconds since the epoch).
+///
+/// Unlike the OIDC token of the user it is good for nothing but the check, so
+/// apps receiving the cookie can't act as the user.
+pub fn forward_auth_token(
+    settings: &Settings,
+    email: &str,
+    expires_at: u64,
+) -> anyhow::Result<String> {
+    let key = secret_key(settings).ok_or_else(|| {
+        anyhow::anyhow!("Logins for forward auth need api.secret_key to be configured")
+    })?;
+    Ok(format!(
+        "{}.{}.{}",
+        BASE64_URL_SAFE_NO_PAD.encode(email),
+        expires_at,
+        sign(key, &forward_auth_message(email, expires_at))
+    ))
+}
+
+/// Email of the user of a forward-auth login, if it is valid at `now`
+/// (seconds since the epoch).
+pub fn verify_forward_auth_token(settings: &Settings, token: &str, now: u64) -> Option<String> {
+    let key = secret_key(settings)?;
+    let mut parts = token.splitn(3, '.');
+    let email = BASE64_URL_SAFE_NO_PAD.decode(parts.next()?).ok()?;
+    let email = String::from_utf8(email).ok()?;
+    let expires_at: u64 = parts.next()?.parse().ok()?;
+    let signature = parts.next()?;
+    (now < expires_at && verify(key, &forward_auth_message(&email, expires_at), signature))
+        .then_some(email)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hmac_sha256_matches_rfc4231() {
+        // Test case 2 of RFC 4231
+        assert_eq!(
+            sign("Jefe", "what do ya want for nothing?"),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn test_verify() {
+        let signature = sign("key", "activity:my-app");
+        assert!(verify("key", "activity:my-app", &signature));
+        assert!(!verify("key", "activity:other-app", &signature));
+        assert!(!verify("other-key", "activity:my-app", &signature));
+        assert!(!verify("key", "activity:my-app", ""));
+    }
+
+    #[test]
+    fn test_forward_auth_token() {
+        let settings =
+            crate::api::test_utils::settings_with_secret_key(Settings::default(), "secret");
+        assert!(forward_auth_token(&Settings::default(), "user@example.com", 100).is_err());
+
+        let token = forward_auth_token(&settings, "user@example.com", 100).unwrap();
+        assert_eq!(
+            verify_forward_auth_token(&settings, &token, 99).as_deref(),
+            Some("user@example.com")
+        );
+        // Expired
+        assert!(verify_forward_auth_token(&settings, &token, 100).is_none());
+
+        // The expiry and the user can't be changed
+        let (_, signature) = token.rsplit_once('.').unwrap();
+        let extended = format!(
+            "{}.200.{}",
+            BASE64_URL_SAFE_NO_PAD.encode("user@example.com"),
+            signature
+        );
+        assert!(verify_forward_auth_token(&settings, &extended, 99).is_none());
+        let other = format!(
+            "{}.100.{}",
+            BASE64_URL_SAFE_NO_PAD.encode("admin@example.com"),
+            signature
+        );
+        assert!(verify_forward_auth_token(&settings, &other, 99).is_none());
+
+        // Neither can an OIDC token be passed instead
+        assert!(verify_forward_auth_token(&settings, "oidc-access-token", 0).is_none());
+    }
+}
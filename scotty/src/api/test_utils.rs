@@ -49,6 +49,23 @@ pub async fn create_test_app_state_with_config(
     create_test_app_state_with_settings(settings, oauth_state).await
 }
 
+/// Settings with `api.secret_key` set, for tests of signed tokens
+#[allow(dead_code)]
+pub fn settings_with_secret_key(
+    settings: crate::settings::config::Settings,
+    secret_key: &str,
+) -> crate::settings::config::Settings {
+    crate::settings::config::Settings {
+        api: serde_json::from_value(serde_json::json!({
+            "bind_address": settings.api.bind_address,
+            "create_app_max_size": settings.api.create_app_max_size.to_string(),
+            "secret_key": secret_key,
+        }))
+        .unwrap(),
+        ..settings
+    }
+}
+
 /// Create test AppState with provided settings
 ///
 /// # Arguments
@@ -67,6 +84,11 @@ pub async fn create_test_app_state_with_settings(
     Arc::new(AppState {
         stop_flag: crate::stop_flag::StopFlag::new(),
         messenger: create_test_websocket_messenger(),
+        activity: crate::services::ActivityTracker::new(),
+        ttl_warnings: crate::services::TtlWarnings::new(),
+        drift_reports: crate::services::DriftReports::new(),
+        forward_auth: crate::services::ForwardAuthCache::new(),
+        tcp_ports: crate::services::TcpPortReservations::new(),
         apps: scotty_core::apps::shared_app_list::SharedAppList::new(),
         docker: docker.clone(),
         task_manager: crate::tasks::manager::TaskManager::new(create_test_websocket_messenger()),
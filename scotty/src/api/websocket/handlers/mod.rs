@@ -139,6 +139,10 @@ pub async fn handle_websocket_message(
             tasks::handle_stop_task_output_stream(state, client_id, *task_id).await;
         }
 
+        WebSocketMessage::CancelTask { task_id } => {
+            tasks::handle_cancel_task(state, client_id, *task_id).await;
+        }
+
         WebSocketMessage::CreateShellSession(request) => {
             shell::handle_create_shell_session(state, client_id, request).await;
         }
@@ -1,3 +1,5 @@
+use scotty_core::tasks::task_details::State;
+use scotty_core::websocket::message::WebSocketMessage;
 use tracing::info;
 use uuid::Uuid;
 
@@ -86,3 +88,63 @@ pub async fn handle_stop_task_output_stream(
     // With the new self-contained streaming approach, streams terminate naturally
     // when the client disconnects or the task completes. No explicit stop needed.
 }
+
+/// Handle cancelling a running task via WebSocket
+pub async fn handle_cancel_task(state: &SharedAppState, client_id: Uuid, task_id: Uuid) {
+    info!(
+        "Task cancellation requested by client {} for task {}",
+        client_id, task_id
+    );
+
+    let user = state.messenger.get_user_for_client(client_id).await;
+    if user.is_none() {
+        state
+            .messenger
+            .send_error(
+                client_id,
+                "Authentication required for cancelling tasks".to_string(),
+            )
+            .await;
+        return;
+    }
+
+    let Some(details) = state.task_manager.get_task_details(&task_id).await else {
+        state
+            .messenger
+            .send_error(client_id, format!("Task not found: {}", task_id))
+            .await;
+        return;
+    };
+
+    if let Some(app_name) = &details.app_name {
+        let auth_result = check_websocket_authorization(
+            state,
+            client_id,
+            &user,
+            app_name,
+            Permission::Manage,
+            "task cancellation",
+        )
+        .await;
+
+        if !matches!(auth_result, WebSocketAuthResult::Authorized(_)) {
+            handle_websocket_auth_failure(state, client_id, auth_result, "task cancellation").await;
+            return;
+        }
+    }
+
+    if !matches!(details.state, State::Running | State::Queued) {
+        state
+            .messenger
+            .send_error(client_id, format!("Task {} is not running", task_id))
+            .await;
+        return;
+    }
+
+    if let Some(details) = state.task_manager.cancel_task(&task_id).await {
+        state
+            .messenger
+            .broadcast_to_all(WebSocketMessage::TaskInfoUpdated(details))
+            .await;
+    }
+}
@@ -14,9 +14,13 @@ use crate::oauth::handlers::OAuthState;
 use crate::oauth::{
     self, create_device_flow_store, create_oauth_session_store, create_web_flow_store,
 };
-use crate::services::{authorization::fallback::FallbackService, AuthorizationService};
+use crate::services::{
+    activity::ACTIVITY_FILE, authorization::fallback::FallbackService, ActivityTracker,
+    AuthorizationService, DriftReports, ForwardAuthCache, TcpPortReservations, TtlWarnings,
+};
 use crate::settings::config::Settings;
 use crate::stop_flag;
+use crate::tasks::history::TaskHistory;
 use crate::tasks::manager;
 use crate::tasks::output_streaming::TaskOutputStreamingService;
 
@@ -54,6 +58,11 @@ pub struct AppState {
     pub shell_service: ShellService,
     pub task_output_service: TaskOutputStreamingService,
     pub messenger: WebSocketMessenger,
+    pub activity: ActivityTracker,
+    pub ttl_warnings: TtlWarnings,
+    pub drift_reports: DriftReports,
+    pub forward_auth: ForwardAuthCache,
+    pub tcp_ports: TcpPortReservations,
 }
 
 pub type SharedAppState = Arc<AppState>;
@@ -117,18 +126,29 @@ impl AppState {
         let clients = Arc::new(Mutex::new(HashMap::new()));
         let messenger = WebSocketMessenger::new(clients.clone());
 
+        let task_history =
+            TaskHistory::from_settings(&settings.task_history, &settings.apps.root_folder);
+        let activity = ActivityTracker::with_file(
+            std::path::Path::new(&settings.apps.root_folder).join(ACTIVITY_FILE),
+        );
+
         let state = Arc::new(AppState {
             settings,
             stop_flag: stop_flag.clone(),
             apps: SharedAppList::new(),
             docker,
-            task_manager: manager::TaskManager::new(messenger.clone()),
+            task_manager: manager::TaskManager::new(messenger.clone()).with_history(task_history),
             oauth_state,
             auth_service,
             logs_service,
             shell_service,
             task_output_service: TaskOutputStreamingService::new(),
             messenger,
+            activity,
+            ttl_warnings: TtlWarnings::new(),
+            drift_reports: DriftReports::new(),
+            forward_auth: ForwardAuthCache::new(),
+            tcp_ports: TcpPortReservations::new(),
         });
 
         Ok(state)
@@ -153,6 +173,11 @@ impl AppState {
             shell_service: ShellService::new(docker, settings.shell.clone()),
             task_output_service: TaskOutputStreamingService::new(),
             messenger,
+            activity: ActivityTracker::new(),
+            ttl_warnings: TtlWarnings::new(),
+            drift_reports: DriftReports::new(),
+            forward_auth: ForwardAuthCache::new(),
+            tcp_ports: TcpPortReservations::new(),
         }))
     }
 }
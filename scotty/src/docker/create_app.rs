@@ -21,6 +21,10 @@ use super::rebuild_app::rebuild_app_prepare;
 use super::state_machine_handlers::context::Context;
 use super::state_machine_handlers::create_directory_handler::CreateDirectoryHandler;
 use super::state_machine_handlers::create_load_balancer_config::CreateLoadBalancerConfig;
+use super::state_machine_handlers::release_tcp_ports_handler::ReleaseTcpPortsHandler;
+use super::state_machine_handlers::revision_handler::{
+    CreateRevisionHandler, RollbackRevisionHandler,
+};
 use super::state_machine_handlers::run_post_actions_handler::RunPostActionsHandler;
 use super::state_machine_handlers::save_files_handler::SaveFilesHandler;
 use super::state_machine_handlers::save_settings_handler::SaveSettingsHandler;
@@ -57,6 +61,7 @@ impl StateHandler<CreateAppStates, Context> for RunDockerComposeBuildHandler<Cre
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 enum CreateAppStates {
     CreateDirectory,
+    CreateRevision,
     SaveSettings,
     SaveFiles,
     CreateLoadBalancerConfig,
@@ -79,7 +84,19 @@ async fn create_app_prepare(
     sm.add_handler(
         CreateAppStates::CreateDirectory,
         Arc::new(CreateDirectoryHandler::<CreateAppStates> {
+            next_state: CreateAppStates::CreateRevision,
+        }),
+    );
+    // Creating an app over an existing one replaces its files, keep the
+    // previous configuration so a failed create can be rolled back.
+    sm.add_handler(
+        CreateAppStates::CreateRevision,
+        Arc::new(CreateRevisionHandler::<CreateAppStates> {
             next_state: CreateAppStates::SaveSettings,
+            reason: "create".to_string(),
+            applied_files: std::iter::once(".scotty.yml".to_string())
+                .chain(files.files.iter().map(|file| file.name.clone()))
+                .collect(),
         }),
     );
     sm.add_handler(
@@ -142,12 +159,16 @@ async fn create_app_prepare(
     );
     sm.add_handler(
         CreateAppStates::SetFailed,
-        Arc::new(TaskCompletionHandler::failure(CreateAppStates::Done, None)),
+        Arc::new(ReleaseTcpPortsHandler {
+            completion: RollbackRevisionHandler {
+                completion: TaskCompletionHandler::failure(CreateAppStates::Done, None),
+            },
+        }),
     );
     Ok(sm)
 }
 
-async fn validate_app(
+pub(crate) async fn validate_app(
     app_state: SharedAppState,
     settings: &AppSettings,
     files: &FileList,
@@ -230,6 +251,7 @@ async fn validate_app(
 /// # Errors
 ///
 /// Returns an error if validation fails, required files are missing, or any step in the creation workflow encounters an error.
+/// The TCP ports reserved for the app are released in that case.
 ///
 /// # Examples
 ///
@@ -239,7 +261,7 @@ async fn validate_app(
 /// # let app_state = todo!();
 /// # let settings = todo!();
 /// # let files = todo!();
-/// let result = create_app(app_state, "my-app", &settings, &files).await;
+/// let result = create_app(app_state, "my-app", &settings, &files, None).await;
 /// assert!(result.is_ok());
 /// # }
 /// ```
@@ -248,6 +270,21 @@ pub async fn create_app(
     app_name: &str,
     settings: &AppSettings,
     files: &FileList,
+    requested_by: Option<&str>,
+) -> anyhow::Result<RunningAppContext> {
+    let result = start_create_app(app_state.clone(), app_name, settings, files, requested_by).await;
+    if result.is_err() {
+        app_state.tcp_ports.release(app_name).await;
+    }
+    result
+}
+
+async fn start_create_app(
+    app_state: SharedAppState,
+    app_name: &str,
+    settings: &AppSettings,
+    files: &FileList,
+    requested_by: Option<&str>,
 ) -> anyhow::Result<RunningAppContext> {
     info!("Creating app: {}", app_name);
     let candidate = validate_app(app_state.clone(), settings, files).await?;
@@ -264,7 +301,8 @@ pub async fn create_app(
         root_directory,
         status: scotty_core::apps::app_data::AppStatus::Creating,
         last_checked: None,
+        drift: None,
     };
     let sm = create_app_prepare(app_state.clone(), &app_data, settings, files).await?;
-    run_sm(app_state, &app_data, sm).await
+    run_sm(app_state, &app_data, sm, requested_by).await
 }
@@ -2,6 +2,7 @@ use std::sync::Arc;
 
 use anyhow::Context as _;
 use tokio::sync::RwLock;
+use tracing::warn;
 
 use crate::api::error::AppError;
 use crate::app_state::SharedAppState;
@@ -14,8 +15,10 @@ use scotty_core::notification_types::MessageType;
 use scotty_core::tasks::running_app_context::RunningAppContext;
 
 use super::helper::run_sm;
+use super::loadbalancer::factory::LoadBalancerFactory;
 use super::purge_app::purge_app_prepare;
 use super::purge_app::PurgeAppMethod;
+use super::revisions::RevisionStore;
 use super::state_machine_handlers::context::Context;
 use super::state_machine_handlers::remove_directory_handler::RemoveDirectoryHandler;
 use super::state_machine_handlers::task_completion_handler::TaskCompletionHandler;
@@ -62,8 +65,31 @@ impl StateHandler<DestroyAppStates, Context> for RemoveAppDataHandler {
         _from: &DestroyAppStates,
         _context: Arc<RwLock<Context>>,
     ) -> anyhow::Result<DestroyAppStates> {
-        let app_state = _context.read().await.app_state.clone();
+        let (app_state, settings) = {
+            let context = _context.read().await;
+            (context.app_state.clone(), context.app_data.settings.clone())
+        };
         app_state.apps.remove_app(&self.app_id).await?;
+        app_state.activity.remove(&self.app_id).await;
+        app_state.ttl_warnings.remove(&self.app_id).await;
+        app_state.tcp_ports.release(&self.app_id).await;
+
+        if let Some(settings) = settings {
+            if let Err(e) = LoadBalancerFactory::create(&app_state.settings.load_balancer_type)
+                .remove_support_files(&app_state.settings, &self.app_id, &settings)
+            {
+                warn!(
+                    "Failed to remove load balancer files of app {}: {}",
+                    self.app_id, e
+                );
+            }
+        }
+
+        if let Some(store) = RevisionStore::for_app(&app_state.settings, &self.app_id) {
+            if let Err(e) = store.remove_all().await {
+                warn!("Failed to remove revisions of app {}: {}", self.app_id, e);
+            }
+        }
 
         Ok(self.next_state)
     }
@@ -136,10 +162,11 @@ async fn destroy_app_prepare(
 pub async fn destroy_app(
     app_state: SharedAppState,
     app: &AppData,
+    requested_by: Option<&str>,
 ) -> anyhow::Result<RunningAppContext> {
     if app.status == AppStatus::Unsupported {
         return Err(AppError::OperationNotSupportedForLegacyApp(app.name.clone()).into());
     }
     let sm = destroy_app_prepare(app).await?;
-    run_sm(app_state, app, sm).await
+    run_sm(app_state, app, sm, requested_by).await
 }
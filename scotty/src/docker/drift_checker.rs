@@ -0,0 +1,358 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use bollard::query_parameters::InspectContainerOptions;
+use regex::Regex;
+use tracing::{info, instrument, warn};
+
+use crate::app_state::SharedAppState;
+use crate::docker::helper::run_sm;
+use crate::docker::loadbalancer::app_proxy_network_name;
+use crate::docker::state_machine_handlers::{
+    create_load_balancer_config::{
+        generate_docker_compose_override, CreateLoadBalancerConfig, OverrideSecrets,
+    },
+    network_handler::EnsureAppNetworkHandler,
+    run_docker_compose_handler::RunDockerComposeHandler,
+    task_completion_handler::TaskCompletionHandler,
+    update_app_data_handler::UpdateAppDataHandler,
+};
+use crate::notification::notify::notify;
+use crate::state_machine::StateMachine;
+use scotty_core::apps::app_data::{AppData, AppSettings, AppStatus, DriftIssue};
+use scotty_core::notification_types::{Message, MessageType};
+use scotty_core::settings::loadbalancer::LoadBalancerType;
+use scotty_core::tasks::running_app_context::RunningAppContext;
+
+/// Compare the load balancer config of an app with the one scotty generates
+/// for it, report new drift and optionally repair it.
+#[instrument(skip(app_state, app), fields(app = %app.name))]
+pub async fn check_app_drift(app_state: SharedAppState, app: &AppData) -> anyhow::Result<()> {
+    let Some(settings) = app.settings.as_ref() else {
+        return Ok(());
+    };
+    if app.status == AppStatus::Unsupported {
+        return Ok(());
+    }
+    // A running operation rewrites the config anyway, check it next time
+    if let Some(holder) = app_state.task_manager.app_queue().holder(&app.name).await {
+        info!(
+            "Skipping drift check of {}, task {} is running",
+            app.name, holder
+        );
+        return Ok(());
+    }
+
+    let issues = find_drift(&app_state, app, settings).await?;
+    let repairing = !issues.is_empty() && app_state.settings.apps.repair_load_balancer_drift;
+    if !app_state
+        .drift_reports
+        .update(&app.name, issues.clone(), repairing)
+        .await
+    {
+        return Ok(());
+    }
+
+    let descriptions: Vec<String> = issues.iter().map(|issue| issue.to_string()).collect();
+    warn!(
+        "Load balancer config of {} drifted: {}",
+        app.name,
+        descriptions.join(", ")
+    );
+    let msg = Message::new(
+        MessageType::AppLoadBalancerDrift {
+            issues: descriptions,
+            repairing,
+        },
+        app,
+    );
+    if let Err(e) = notify(&app_state, settings.notify.iter(), &msg).await {
+        warn!("Failed to send drift notification for {}: {}", app.name, e);
+    }
+
+    if repairing {
+        repair_app_drift(app_state, app, settings).await?;
+    }
+    Ok(())
+}
+
+async fn find_drift(
+    app_state: &SharedAppState,
+    app: &AppData,
+    settings: &AppSettings,
+) -> anyhow::Result<Vec<DriftIssue>> {
+    let mut issues = vec![];
+
+    // Secrets are masked in the comparison anyway, so they are neither looked
+    // up in 1Password nor hashed on every check
+    let generated = generate_docker_compose_override(
+        &app_state.settings,
+        &app_state.settings.load_balancer_type,
+        &app.name,
+        Path::new(&app.root_directory),
+        settings,
+        OverrideSecrets::Placeholder,
+    )
+    .await?;
+    match tokio::fs::read_to_string(&generated.override_file).await {
+        Ok(on_disk) => {
+            let expected = serde_norway::to_value(&generated.config)?;
+            let on_disk: serde_norway::Value = serde_norway::from_str(&on_disk)?;
+            let environment: Vec<&String> =
+                settings.environment.iter().map(|(key, _)| key).collect();
+            if !same_override(expected, on_disk, &environment) {
+                issues.push(DriftIssue::OverrideChanged);
+            }
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            issues.push(DriftIssue::OverrideMissing);
+        }
+        Err(e) => return Err(e.into()),
+    }
+
+    // Stopped apps are connected again when they are started
+    if app_state.settings.load_balancer_type == LoadBalancerType::Traefik
+        && app.status == AppStatus::Running
+    {
+        let network = app_proxy_network_name(&app_state.settings.traefik.network, &app.name);
+        match app_state
+            .docker
+            .inspect_container(
+                &app_state.settings.traefik.container_name,
+                None::<InspectContainerOptions>,
+            )
+            .await
+        {
+            Ok(traefik) => {
+                let connected = traefik
+                    .network_settings
+                    .and_then(|settings| settings.networks)
+                    .is_some_and(|networks| networks.contains_key(&network));
+                if !connected {
+                    issues.push(DriftIssue::NetworkDetached(network));
+                }
+            }
+            Err(e) => warn!(
+                "Can't inspect Traefik container {}: {}",
+                app_state.settings.traefik.container_name, e
+            ),
+        }
+    }
+
+    Ok(issues)
+}
+
+/// Compare two overrides, ignoring the order of maps, bcrypt hashes and the
+/// values of the app's `environment`, which may hold resolved secrets.
+fn same_override(
+    mut expected: serde_norway::Value,
+    mut on_disk: serde_norway::Value,
+    environment: &[&String],
+) -> bool {
+    let bcrypt = Regex::new(r"\$?\$2[abxy]?\$?\$\d{2}\$?\$[./A-Za-z0-9]{53}").unwrap();
+    for value in [&mut expected, &mut on_disk] {
+        mask_hashes(value, &bcrypt);
+        mask_environment(value, environment);
+    }
+    expected == on_disk
+}
+
+fn mask_environment(value: &mut serde_norway::Value, keys: &[&String]) {
+    let Some(services) = value
+        .get_mut("services")
+        .and_then(|services| services.as_mapping_mut())
+    else {
+        return;
+    };
+    for service in services.values_mut() {
+        let Some(environment) = service
+            .get_mut("environment")
+            .and_then(|environment| environment.as_mapping_mut())
+        else {
+            continue;
+        };
+        for (key, value) in environment.iter_mut() {
+            if key
+                .as_str()
+                .is_some_and(|key| keys.iter().any(|k| *k == key))
+            {
+                *value = serde_norway::Value::String("<secret>".to_string());
+            }
+        }
+    }
+}
+
+fn mask_hashes(value: &mut serde_norway::Value, bcrypt: &Regex) {
+    match value {
+        serde_norway::Value::String(s) => {
+            *s = bcrypt.replace_all(s, "<bcrypt>").to_string();
+        }
+        serde_norway::Value::Sequence(values) => {
+            values.iter_mut().for_each(|v| mask_hashes(v, bcrypt));
+        }
+        serde_norway::Value::Mapping(mapping) => {
+            mapping.values_mut().for_each(|v| mask_hashes(v, bcrypt));
+        }
+        _ => {}
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+enum RepairDriftStates {
+    CreateLoadBalancerConfig,
+    EnsureAppNetwork,
+    RecreateContainers,
+    UpdateAppData,
+    SetFinished,
+    SetFailed,
+    Done,
+}
+
+/// Rewrite the load balancer config of an app and reconnect the load
+/// balancer. Compose only recreates the running containers whose labels
+/// differ from the rewritten override.
+async fn repair_app_drift(
+    app_state: SharedAppState,
+    app: &AppData,
+    settings: &AppSettings,
+) -> anyhow::Result<RunningAppContext> {
+    info!("Repairing load balancer config of {}", app.name);
+    let running = app.status == AppStatus::Running;
+
+    let mut sm = StateMachine::new(
+        RepairDriftStates::CreateLoadBalancerConfig,
+        RepairDriftStates::Done,
+    );
+    sm.set_error_state(RepairDriftStates::SetFailed);
+    sm.add_handler(
+        RepairDriftStates::CreateLoadBalancerConfig,
+        Arc::new(CreateLoadBalancerConfig::<RepairDriftStates> {
+            next_state: match running {
+                true => RepairDriftStates::EnsureAppNetwork,
+                false => RepairDriftStates::UpdateAppData,
+            },
+            load_balancer_type: app_state.settings.load_balancer_type.clone(),
+            settings: settings.clone(),
+        }),
+    );
+    sm.add_handler(
+        RepairDriftStates::EnsureAppNetwork,
+        Arc::new(EnsureAppNetworkHandler::<RepairDriftStates> {
+            next_state: RepairDriftStates::RecreateContainers,
+        }),
+    );
+    sm.add_handler(
+        RepairDriftStates::RecreateContainers,
+        Arc::new(RunDockerComposeHandler::<RepairDriftStates> {
+            next_state: RepairDriftStates::UpdateAppData,
+            command: ["up", "-d"].iter().map(|s| s.to_string()).collect(),
+            env: app.get_environment(),
+        }),
+    );
+    sm.add_handler(
+        RepairDriftStates::UpdateAppData,
+        Arc::new(UpdateAppDataHandler::<RepairDriftStates> {
+            next_state: RepairDriftStates::SetFinished,
+        }),
+    );
+    sm.add_handler(
+        RepairDriftStates::SetFinished,
+        Arc::new(TaskCompletionHandler::success(
+            RepairDriftStates::Done,
+            None,
+        )),
+    );
+    sm.add_handler(
+        RepairDriftStates::SetFailed,
+        Arc::new(TaskCompletionHandler::failure(
+            RepairDriftStates::Done,
+            None,
+        )),
+    );
+
+    run_sm(app_state, app, sm, None).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::docker::state_machine_handlers::create_load_balancer_config::PLACEHOLDER_PASSWORD_HASH;
+
+    fn yaml(s: &str) -> serde_norway::Value {
+        serde_norway::from_str(s).unwrap()
+    }
+
+    #[test]
+    fn test_same_override_ignores_order_and_hash_salt() {
+        let expected = yaml(
+            r#"
+services:
+  web:
+    labels:
+      traefik.enable: "true"
+      traefik.http.middlewares.web--app--basic-auth.basicauth.users: "user:$$2b$$12$$R9h/cIPz0gi.URNNX3kh2OPST9/PgBkqquzi.Ss7KIUgO2t0jWMUW"
+"#,
+        );
+        let on_disk = yaml(
+            r#"
+services:
+  web:
+    labels:
+      traefik.http.middlewares.web--app--basic-auth.basicauth.users: "user:$$2b$$12$$abcdefghijklmnopqrstuuOKxB8Z7uGqXa1vM3dPZ0N4w5lJ7qYxa"
+      traefik.enable: "true"
+"#,
+        );
+        assert!(same_override(expected.clone(), on_disk, &[]));
+
+        let changed = yaml(
+            r#"
+services:
+  web:
+    labels:
+      traefik.http.middlewares.web--app--basic-auth.basicauth.users: "other:$$2b$$12$$abcdefghijklmnopqrstuuOKxB8Z7uGqXa1vM3dPZ0N4w5lJ7qYxa"
+      traefik.enable: "true"
+"#,
+        );
+        assert!(!same_override(expected.clone(), changed, &[]));
+
+        let missing_label = yaml(
+            r#"
+services:
+  web:
+    labels:
+      traefik.enable: "true"
+"#,
+        );
+        assert!(!same_override(expected, missing_label, &[]));
+    }
+
+    #[test]
+    fn test_same_override_ignores_secrets() {
+        let placeholder = PLACEHOLDER_PASSWORD_HASH.replace('$', "$$");
+        let expected = yaml(&format!(
+            r#"
+services:
+  web:
+    environment:
+      DB_PASSWORD: "op://vault/item/password"
+      VIRTUAL_HOST: "app.example.com"
+    labels:
+      traefik.http.middlewares.web--app--basic-auth.basicauth.users: "user:{placeholder}"
+"#
+        ));
+        let on_disk = yaml(
+            r#"
+services:
+  web:
+    environment:
+      DB_PASSWORD: "resolved secret"
+      VIRTUAL_HOST: "app.example.com"
+    labels:
+      traefik.http.middlewares.web--app--basic-auth.basicauth.users: "user:$$2b$$12$$abcdefghijklmnopqrstuuOKxB8Z7uGqXa1vM3dPZ0N4w5lJ7qYxa"
+"#,
+        );
+        let secret = "DB_PASSWORD".to_string();
+        assert!(same_override(expected.clone(), on_disk.clone(), &[&secret]));
+        assert!(!same_override(expected, on_disk, &[]));
+    }
+}
@@ -4,7 +4,7 @@ use bollard::query_parameters::InspectContainerOptions;
 use chrono::{DateTime, Local};
 use futures_util::future::join_all;
 use scotty_core::apps::{
-    app_data::{AppData, AppSettings, AppStatus, ContainerState},
+    app_data::{AppData, AppSettings, AppStatus, ContainerHealth, ContainerState},
     shared_app_list::AppDataVec,
 };
 use serde_norway::Value;
@@ -244,6 +244,7 @@ async fn get_running_services(
                     used_registry: None,
                     basic_auth: None,
                     exit_code: None,
+                    health: None,
                 }
             }
         })
@@ -351,6 +352,7 @@ async fn inspect_docker_container(
             _ => None,
         },
         exit_code: state.exit_code,
+        health: ContainerHealth::from_docker(state.health.and_then(|health| health.status)),
     };
 
     Ok(container_state)
@@ -1,17 +1,24 @@
+use crate::api::error::AppError;
 use crate::{app_state::SharedAppState, state_machine::StateMachine};
 use anyhow::anyhow;
 use bollard::models::ContainerStateStatusEnum;
-use bollard::query_parameters::InspectContainerOptions;
-use scotty_core::apps::app_data::AppData;
+use bollard::query_parameters::{InspectContainerOptions, ListContainersOptions};
+use scotty_core::apps::app_data::{AppData, ContainerHealth};
+use scotty_core::settings::apps::OperationConflictStrategy;
 use scotty_core::tasks::running_app_context::RunningAppContext;
-use tracing::error;
+use scotty_core::tasks::task_details::State;
+use scotty_core::websocket::message::WebSocketMessage;
+use std::sync::atomic::AtomicBool;
+use tracing::{debug, error};
+use uuid::Uuid;
 
 use super::state_machine_handlers::context::Context;
 
 pub async fn run_sm<S>(
     app_state: SharedAppState,
     app: &AppData,
-    sm: StateMachine<S, Context>,
+    mut sm: StateMachine<S, Context>,
+    requested_by: Option<&str>,
 ) -> anyhow::Result<RunningAppContext>
 where
     S: Copy
@@ -23,16 +30,43 @@ where
         + std::marker::Send
         + std::fmt::Debug,
 {
-    let context = Context::create(app_state, app);
+    let conflict_strategy = app_state.settings.apps.operation_conflict;
+    // The app as it was known when the operation got requested, None for new apps
+    let stored_app = app_state.apps.get_app(&app.name).await;
+    let context = Context::create(app_state, app, requested_by);
     {
         let context = context.write().await;
         let task = context.task.clone();
         let task_id = task.read().await.id;
-        context
-            .app_state
-            .task_manager
-            .add_task(&task_id, task.clone(), None)
-            .await;
+        let task_manager = &context.app_state.task_manager;
+
+        // Only one lifecycle operation per app may run at a time
+        let position = match conflict_strategy {
+            OperationConflictStrategy::Reject => {
+                if let Err(holder) = task_manager
+                    .app_queue()
+                    .try_acquire(&app.name, &task_id)
+                    .await
+                {
+                    return Err(AppError::AppOperationInProgress(app.name.clone(), holder).into());
+                }
+                0
+            }
+            OperationConflictStrategy::Queue => {
+                task_manager.app_queue().enqueue(&app.name, &task_id).await
+            }
+        };
+
+        task_manager.add_task(&task_id, task.clone(), None).await;
+
+        // Let the state machine stop when the task gets cancelled
+        sm.set_cancel_flag(
+            context
+                .app_state
+                .task_manager
+                .get_cancel_flag(&task_id)
+                .await,
+        );
 
         // Add initial status message for the app operation
         context
@@ -40,22 +74,284 @@ where
             .task_manager
             .add_task_status(&task_id, format!("Starting app '{}'", app.name))
             .await;
+
+        if position > 0 {
+            let holder = task_manager.app_queue().holder(&app.name).await;
+            task_manager
+                .set_task_queue_position(&task_id, Some(position))
+                .await;
+            task_manager
+                .add_task_status(
+                    &task_id,
+                    format!(
+                        "Waiting for task {} to finish (position {} in queue)",
+                        holder.map(|id| id.to_string()).unwrap_or_default(),
+                        position
+                    ),
+                )
+                .await;
+        }
     }
     // TODO(scotty-f1dd): State machine handle is intentionally dropped here to allow immediate
     // API response. This means we cannot detect panics in the state machine task.
     // Errors are handled via TaskCompletionHandler, but panics are silently lost.
     // Future refactoring should support multiple handles in TaskState to track both
     // the state machine handle and any subprocess handles it spawns.
-    let _handle = sm.spawn(context.clone());
+    let app_name = app.name.clone();
+    let requested_app = app.clone();
+    let running_context = context.clone();
+    let _handle = crate::metrics::spawn_instrumented(async move {
+        let (app_state, task_id) = {
+            let context = running_context.read().await;
+            let task_id = context.task.read().await.id;
+            (context.app_state.clone(), task_id)
+        };
+        let task_manager = &app_state.task_manager;
+        let cancel_flag = task_manager.get_cancel_flag(&task_id).await;
+
+        if task_manager
+            .app_queue()
+            .wait_for_turn(&app_name, &task_id, &cancel_flag)
+            .await
+        {
+            if task_manager
+                .get_task_details(&task_id)
+                .await
+                .and_then(|t| t.queue_position)
+                .is_some()
+            {
+                task_manager.set_task_queue_position(&task_id, None).await;
+                broadcast_task_update(&app_state, &task_id).await;
+            }
+            // The app might have changed or vanished while the task was waiting
+            let current_app = match &stored_app {
+                Some(_) => app_state.apps.get_app(&app_name).await,
+                None => None,
+            };
+            match refreshed_app_data(&requested_app, stored_app.as_ref(), current_app) {
+                Some(app_data) => {
+                    running_context.write().await.app_data = app_data;
+                    let _ = sm.spawn(running_context.clone()).await;
+                }
+                None => {
+                    running_context
+                        .read()
+                        .await
+                        .complete_task(
+                            State::Failed,
+                            format!("App '{app_name}' no longer exists"),
+                            true,
+                        )
+                        .await;
+                }
+            }
+        } else {
+            running_context
+                .read()
+                .await
+                .complete_task(
+                    State::Cancelled,
+                    "Task cancelled while waiting in queue".to_string(),
+                    true,
+                )
+                .await;
+        }
+
+        task_manager.app_queue().release(&app_name, &task_id).await;
+        update_queue_positions(&app_state, &app_name).await;
+    });
 
     // Return immediately with the context, task is running in background
     Ok(context.clone().read().await.as_running_app_context().await)
 }
 
+/// Return the app data an operation should run with once it got its turn.
+///
+/// `stored` is the app as it was known when the operation got requested and
+/// `current` the app as it is known now. Settings passed with the operation,
+/// e.g. by an update, win over the current ones. Returns None if the app got
+/// removed in the meantime.
+fn refreshed_app_data(
+    requested: &AppData,
+    stored: Option<&AppData>,
+    current: Option<AppData>,
+) -> Option<AppData> {
+    let Some(stored) = stored else {
+        // A new app, nothing to refresh
+        return Some(requested.clone());
+    };
+    let current = current?;
+    let settings_requested = serde_json::to_value(&requested.settings).ok()
+        != serde_json::to_value(&stored.settings).ok();
+    Some(AppData {
+        settings: if settings_requested {
+            requested.settings.clone()
+        } else {
+            current.settings.clone()
+        },
+        ..current
+    })
+}
+
+/// Releases the operation lock of an app when dropped, also if the request
+/// holding it gets cancelled.
+struct AppLockGuard {
+    app_state: SharedAppState,
+    app_name: String,
+    lock_id: Option<Uuid>,
+}
+
+impl AppLockGuard {
+    async fn release(mut self) {
+        if let Some(lock_id) = self.lock_id.take() {
+            release_app_lock(&self.app_state, &self.app_name, &lock_id).await;
+        }
+    }
+}
+
+impl Drop for AppLockGuard {
+    fn drop(&mut self) {
+        if let Some(lock_id) = self.lock_id.take() {
+            let app_state = self.app_state.clone();
+            let app_name = self.app_name.clone();
+            tokio::spawn(async move {
+                release_app_lock(&app_state, &app_name, &lock_id).await;
+            });
+        }
+    }
+}
+
+async fn release_app_lock(app_state: &SharedAppState, app_name: &str, lock_id: &Uuid) {
+    app_state
+        .task_manager
+        .app_queue()
+        .release(app_name, lock_id)
+        .await;
+    update_queue_positions(app_state, app_name).await;
+}
+
+/// Run a change of an app which does not need a task, e.g. a new TTL, while
+/// holding the operation lock of the app.
+///
+/// Honours the conflict strategy like [`run_sm`]: the change either fails if
+/// another operation is running or waits for its turn. `operation` should
+/// read the app again, as it might have changed while waiting.
+pub async fn with_app_lock<F, Fut, T>(
+    app_state: &SharedAppState,
+    app_name: &str,
+    operation: F,
+) -> Result<T, AppError>
+where
+    F: FnOnce() -> Fut,
+    Fut: std::future::Future<Output = Result<T, AppError>>,
+{
+    let queue = app_state.task_manager.app_queue();
+    let lock_id = Uuid::new_v4();
+    match app_state.settings.apps.operation_conflict {
+        OperationConflictStrategy::Reject => {
+            if let Err(holder) = queue.try_acquire(app_name, &lock_id).await {
+                return Err(AppError::AppOperationInProgress(
+                    app_name.to_string(),
+                    holder,
+                ));
+            }
+        }
+        OperationConflictStrategy::Queue => {
+            queue.enqueue(app_name, &lock_id).await;
+        }
+    }
+    let guard = AppLockGuard {
+        app_state: app_state.clone(),
+        app_name: app_name.to_string(),
+        lock_id: Some(lock_id),
+    };
+    queue
+        .wait_for_turn(app_name, &lock_id, &AtomicBool::new(false))
+        .await;
+
+    let result = operation().await;
+    guard.release().await;
+    result
+}
+
+/// Publish the new positions of all tasks waiting for the app
+async fn update_queue_positions(app_state: &SharedAppState, app_name: &str) {
+    let task_manager = &app_state.task_manager;
+    for (task_id, position) in task_manager.app_queue().waiting(app_name).await {
+        task_manager
+            .set_task_queue_position(&task_id, Some(position))
+            .await;
+        broadcast_task_update(app_state, &task_id).await;
+    }
+}
+
+async fn broadcast_task_update(app_state: &SharedAppState, task_id: &Uuid) {
+    if let Some(details) = app_state.task_manager.get_task_details(task_id).await {
+        app_state
+            .messenger
+            .broadcast_to_all(WebSocketMessage::TaskInfoUpdated(details))
+            .await;
+    }
+}
+
+/// Get the ids of all containers belonging to a compose project, stopped ones included.
+pub async fn get_compose_project_container_ids(
+    app_state: &SharedAppState,
+    project: &str,
+) -> anyhow::Result<Vec<String>> {
+    let mut filters = std::collections::HashMap::new();
+    filters.insert(
+        "label".to_string(),
+        vec![format!("com.docker.compose.project={}", project)],
+    );
+    let containers = app_state
+        .docker
+        .list_containers(Some(ListContainersOptions {
+            all: true,
+            filters: Some(filters),
+            ..Default::default()
+        }))
+        .await?;
+
+    Ok(containers
+        .into_iter()
+        .filter_map(|container| container.id)
+        .collect())
+}
+
+/// State of a container after waiting for it to become ready.
+#[derive(Debug, Clone)]
+pub struct ContainerReadiness {
+    pub id: String,
+    /// Compose service of the container, if labeled
+    pub service: Option<String>,
+    pub status: ContainerStateStatusEnum,
+    pub health: Option<ContainerHealth>,
+    /// IP addresses of the container in all its networks
+    pub ip_addresses: Vec<String>,
+}
+
+impl ContainerReadiness {
+    /// A container is still starting while Docker creates or restarts it, or
+    /// while its healthcheck has not passed yet.
+    pub fn is_starting(&self) -> bool {
+        self.status == ContainerStateStatusEnum::CREATED
+            || self.status == ContainerStateStatusEnum::RESTARTING
+            || (self.status == ContainerStateStatusEnum::RUNNING
+                && self.health == Some(ContainerHealth::Starting))
+    }
+
+    /// Name used for the container in task output
+    pub fn display_name(&self) -> &str {
+        self.service.as_deref().unwrap_or(&self.id)
+    }
+}
+
 /// Wait for all containers to reach a non-starting state.
 ///
 /// This function waits until all the specified containers are either running successfully
-/// or have failed (not in 'created' or 'restarting' state).
+/// or have failed (not in 'created' or 'restarting' state). Containers with a Docker
+/// `HEALTHCHECK` are waited for until the healthcheck reports them healthy or unhealthy.
 ///
 /// # Arguments
 ///
@@ -65,7 +361,7 @@ where
 ///
 /// # Returns
 ///
-/// * `anyhow::Result<Vec<(String, ContainerStateStatusEnum)>>` - Container IDs and their states when they're all ready or an error
+/// * `anyhow::Result<Vec<ContainerReadiness>>` - The states of the containers when they're all ready or an error
 ///
 /// # Example
 ///
@@ -77,8 +373,8 @@ where
 ///     let container_ids = vec!["container1".to_string(), "container2".to_string()];
 ///     let container_states = wait_for_containers_ready(app_state, container_ids, Some(60)).await?;
 ///
-///     for (container_id, status) in container_states {
-///         println!("Container {} is in state: {:?}", container_id, status);
+///     for container in container_states {
+///         println!("Container {} is in state: {:?}", container.id, container.status);
 ///     }
 ///
 ///     Ok(())
@@ -88,7 +384,7 @@ pub async fn wait_for_containers_ready(
     app_state: &SharedAppState,
     container_ids: Vec<String>,
     timeout_seconds: Option<u64>,
-) -> anyhow::Result<Vec<(String, ContainerStateStatusEnum)>> {
+) -> anyhow::Result<Vec<ContainerReadiness>> {
     // Default timeout of 300 seconds (5 minutes) if not specified
     let timeout = timeout_seconds.unwrap_or(300);
     let timeout_duration = tokio::time::Duration::from_secs(timeout);
@@ -110,10 +406,32 @@ pub async fn wait_for_containers_ready(
                     .await
                 {
                     Ok(container_info) => {
+                        let service = container_info
+                            .config
+                            .and_then(|config| config.labels)
+                            .and_then(|labels| labels.get("com.docker.compose.service").cloned());
+                        let ip_addresses = container_info
+                            .network_settings
+                            .and_then(|settings| settings.networks)
+                            .map(|networks| {
+                                networks
+                                    .into_values()
+                                    .filter_map(|network| network.ip_address)
+                                    .filter(|ip| !ip.is_empty())
+                                    .collect()
+                            })
+                            .unwrap_or_default();
                         if let Some(state) = container_info.state {
                             if let Some(status) = state.status {
-                                // Store the container ID and its status directly
-                                current_states.push((container_id.clone(), status));
+                                current_states.push(ContainerReadiness {
+                                    id: container_id.clone(),
+                                    service,
+                                    status,
+                                    health: ContainerHealth::from_docker(
+                                        state.health.and_then(|health| health.status),
+                                    ),
+                                    ip_addresses,
+                                });
                             }
                         }
                     }
@@ -121,7 +439,13 @@ pub async fn wait_for_containers_ready(
                         // Log the error but continue with other containers
                         error!("Failed to inspect container {}: {}", container_id, e);
                         // Add a container in error state
-                        current_states.push((container_id.clone(), ContainerStateStatusEnum::DEAD));
+                        current_states.push(ContainerReadiness {
+                            id: container_id.clone(),
+                            service: None,
+                            status: ContainerStateStatusEnum::DEAD,
+                            health: None,
+                            ip_addresses: vec![],
+                        });
                     }
                 }
             }
@@ -129,10 +453,7 @@ pub async fn wait_for_containers_ready(
             // Check if any container is still in a starting state
             let starting_containers = current_states
                 .iter()
-                .filter(|(_, status)| {
-                    *status == ContainerStateStatusEnum::CREATED
-                        || *status == ContainerStateStatusEnum::RESTARTING
-                })
+                .filter(|container| container.is_starting())
                 .count();
 
             if starting_containers == 0 {
@@ -153,3 +474,87 @@ pub async fn wait_for_containers_ready(
         Err(_) => Err(anyhow!("Timeout waiting for containers to be ready")),
     }
 }
+
+/// Poll `url` until it answers with a success or redirect status.
+///
+/// Gives up with an error once `timeout` has passed.
+pub async fn wait_for_http_probe(url: &str, timeout: std::time::Duration) -> anyhow::Result<()> {
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(5))
+        .redirect(reqwest::redirect::Policy::none())
+        .build()?;
+
+    let result = tokio::time::timeout(timeout, async {
+        loop {
+            match client.get(url).send().await {
+                Ok(response)
+                    if response.status().is_success() || response.status().is_redirection() =>
+                {
+                    return;
+                }
+                Ok(response) => debug!("Probe {} returned {}", url, response.status()),
+                Err(e) => debug!("Probe {} failed: {}", url, e),
+            }
+            tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
+        }
+    })
+    .await;
+
+    result.map_err(|_| anyhow!("Timeout waiting for {} to respond", url))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use scotty_core::apps::app_data::{AppSettings, AppStatus};
+
+    fn app(domain: &str, status: AppStatus) -> AppData {
+        AppData {
+            name: "my-app".to_string(),
+            status,
+            settings: Some(AppSettings {
+                domain: domain.to_string(),
+                ..AppSettings::default()
+            }),
+            ..AppData::default()
+        }
+    }
+
+    fn domain(app: &AppData) -> &str {
+        &app.settings.as_ref().unwrap().domain
+    }
+
+    #[test]
+    fn test_refreshed_app_data_uses_current_app() {
+        let stored = app("old.example.com", AppStatus::Running);
+        let current = app("new.example.com", AppStatus::Stopped);
+
+        let refreshed = refreshed_app_data(&stored, Some(&stored), Some(current)).unwrap();
+        assert_eq!(refreshed.status, AppStatus::Stopped);
+        assert_eq!(domain(&refreshed), "new.example.com");
+    }
+
+    #[test]
+    fn test_refreshed_app_data_keeps_requested_settings() {
+        let stored = app("old.example.com", AppStatus::Running);
+        let requested = app("requested.example.com", AppStatus::Running);
+        let current = app("new.example.com", AppStatus::Stopped);
+
+        let refreshed = refreshed_app_data(&requested, Some(&stored), Some(current)).unwrap();
+        assert_eq!(refreshed.status, AppStatus::Stopped);
+        assert_eq!(domain(&refreshed), "requested.example.com");
+    }
+
+    #[test]
+    fn test_refreshed_app_data_fails_for_removed_app() {
+        let stored = app("old.example.com", AppStatus::Running);
+        assert!(refreshed_app_data(&stored, Some(&stored), None).is_none());
+    }
+
+    #[test]
+    fn test_refreshed_app_data_keeps_new_app() {
+        let requested = app("new.example.com", AppStatus::Creating);
+        let refreshed = refreshed_app_data(&requested, None, None).unwrap();
+        assert_eq!(refreshed.status, AppStatus::Creating);
+    }
+}
@@ -0,0 +1,412 @@
+use std::collections::HashMap;
+
+use bollard_stubs::models::ContainerInspectResponse;
+use regex::Regex;
+
+use crate::settings::config::Settings;
+use scotty_core::apps::app_data::{AppSettings, AppTtl};
+
+use super::types::{
+    DockerComposeConfig, DockerComposeNetworkConfig, DockerComposeServiceConfig, LoadBalancerImpl,
+    LoadBalancerInfo, ServiceNetworkAttachment,
+};
+use super::{activity_path, ensure_host_routing_only, htpasswd, ROBOTS_HEADER_VALUE};
+
+/// Load balancer for Caddy with caddy-docker-proxy.
+///
+/// caddy-docker-proxy builds a Caddyfile from the `caddy.*` labels of the
+/// running containers, every public service becomes its own site block.
+pub struct CaddyLoadBalancer;
+
+impl LoadBalancerImpl for CaddyLoadBalancer {
+    fn get_load_balancer_info(&self, insights: ContainerInspectResponse) -> LoadBalancerInfo {
+        let re_site = Regex::new(r"^caddy(_\d+)?$").unwrap();
+        let re_upstream = Regex::new(r"^caddy(_\d+)?\.reverse_proxy$").unwrap();
+        let re_port = Regex::new(r"\{\{\s*upstreams\s+(?:https?\s+)?(\d+)\s*\}\}").unwrap();
+        let re_basic_auth = Regex::new(r"^caddy(_\d+)?\.basic_?auth\.(.+)$").unwrap();
+
+        let mut result = LoadBalancerInfo {
+            ..Default::default()
+        };
+        if let Some(labels) = insights.config.unwrap().labels {
+            for (key, value) in labels.iter() {
+                if re_site.is_match(key) {
+                    for address in value.split(',').map(str::trim).filter(|a| !a.is_empty()) {
+                        match address.strip_prefix("http://") {
+                            Some(domain) => result.domains.push(domain.to_string()),
+                            None => {
+                                result.tls_enabled = true;
+                                result
+                                    .domains
+                                    .push(address.trim_start_matches("https://").to_string());
+                            }
+                        }
+                    }
+                }
+                if re_upstream.is_match(key) {
+                    if let Some(caps) = re_port.captures(value) {
+                        if let Ok(port) = caps[1].parse::<u32>() {
+                            result.port = Some(port);
+                        }
+                    }
+                }
+                if let Some(caps) = re_basic_auth.captures(key) {
+                    result.basic_auth_user = Some(caps[2].to_string());
+                }
+            }
+        }
+
+        result
+    }
+
+    fn get_docker_compose_override(
+        &self,
+        global_settings: &Settings,
+        app_name: &str,
+        settings: &AppSettings,
+        resolved_environment: &HashMap<String, String>,
+        all_services: &[String],
+    ) -> anyhow::Result<DockerComposeConfig> {
+        ensure_host_routing_only("Caddy", settings)?;
+
+        let mut config = DockerComposeConfig {
+            services: HashMap::new(),
+            networks: Some(HashMap::new()),
+        };
+
+        // Caddy reaches the public services over a shared network, which has
+        // to exist before the app is started.
+        let caddy_network = global_settings.caddy.network.clone();
+        let networks = config.networks.as_mut().unwrap();
+        networks.insert(
+            caddy_network.clone(),
+            DockerComposeNetworkConfig {
+                external: true,
+                name: Some(caddy_network.clone()),
+            },
+        );
+
+        // First, apply environment variables to all services
+        if !resolved_environment.is_empty() {
+            for service_name in all_services {
+                let mut service_config = DockerComposeServiceConfig {
+                    labels: None,
+                    environment: Some(HashMap::new()),
+                    networks: None,
+                };
+                let environment = service_config.environment.as_mut().unwrap();
+                for (key, value) in resolved_environment {
+                    environment.insert(key.clone(), value.clone());
+                }
+                config.services.insert(service_name.clone(), service_config);
+            }
+        }
+
+        // Then, add load balancer configuration for public services
+        for service in &settings.public_services {
+            // Get or create the service config (it may already exist from the all_services loop)
+            let service_config = config
+                .services
+                .entry(service.service.clone())
+                .or_insert_with(|| DockerComposeServiceConfig {
+                    labels: None,
+                    environment: None,
+                    networks: None,
+                });
+
+            if service_config.labels.is_none() {
+                service_config.labels = Some(HashMap::new());
+            }
+            if service_config.environment.is_none() {
+                service_config.environment = Some(HashMap::new());
+            }
+
+            let mut service_networks = HashMap::new();
+            service_networks.insert("default".to_string(), ServiceNetworkAttachment::default());
+            service_networks.insert(caddy_network.clone(), ServiceNetworkAttachment::default());
+            service_config.networks = Some(service_networks);
+
+            let labels = service_config.labels.as_mut().unwrap();
+
+            // The site addresses, without a scheme Caddy serves them via https
+            let domains = service.get_domains(&settings.domain);
+            let addresses: Vec<String> = domains
+                .iter()
+                .map(|domain| match global_settings.caddy.use_tls {
+                    true => domain.clone(),
+                    false => format!("http://{domain}"),
+                })
+                .collect();
+            labels.insert("caddy".to_string(), addresses.join(", "));
+            labels.insert(
+                "caddy.reverse_proxy".to_string(),
+                format!("{{{{upstreams {}}}}}", service.port),
+            );
+            // Tell caddy-docker-proxy which network to use to reach this container
+            labels.insert("caddy_ingress_network".to_string(), caddy_network.clone());
+
+            for user in &settings.basic_auth {
+                labels.insert(
+                    format!("caddy.basic_auth.{}", user.username),
+                    htpasswd(user, true)?,
+                );
+            }
+
+            // Report requests to Scotty so apps with an idle TTL are kept running
+            if let AppTtl::Idle(_) = settings.time_to_live {
+                let activity_url = global_settings
+                    .caddy
+                    .activity_url
+                    .clone()
+                    .unwrap_or_else(|| global_settings.api.public_base_url());
+                labels.insert(
+                    "caddy.forward_auth".to_string(),
+                    activity_url.trim_end_matches('/').to_string(),
+                );
+                labels.insert(
+                    "caddy.forward_auth.uri".to_string(),
+                    activity_path(global_settings, app_name)?,
+                );
+            }
+
+            if settings.disallow_robots {
+                labels.insert(
+                    "caddy.header".to_string(),
+                    format!("X-Robots-Tag \"{ROBOTS_HEADER_VALUE}\""),
+                );
+            }
+
+            // Environment variables are already added in the all_services loop above
+        }
+
+        Ok(config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bollard_stubs::models::ContainerConfig;
+    use maplit::hashmap;
+    use scotty_core::apps::app_data::{BasicAuthUser, ServicePortMapping};
+    use scotty_core::settings::loadbalancer::CaddySettings;
+    use scotty_core::utils::secret::SecretHashMap;
+
+    fn caddy_settings(use_tls: bool) -> Settings {
+        Settings {
+            caddy: CaddySettings {
+                use_tls,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_caddy_get_docker_compose_override() {
+        let global_settings = caddy_settings(true);
+
+        let app_settings = AppSettings {
+            domain: "example.com".to_string(),
+            public_services: vec![ServicePortMapping {
+                service: "web".to_string(),
+                port: 8080,
+                domains: vec![],
+                path: None,
+                strip_path: false,
+            }],
+            basic_auth: vec![BasicAuthUser::new("user", "pass")],
+            disallow_robots: true,
+            environment: SecretHashMap::from_hashmap(hashmap! {
+                "FOO".to_string() => "BAR".to_string(),
+            }),
+            ..Default::default()
+        };
+
+        let load_balancer = CaddyLoadBalancer;
+        let all_services = vec!["web".to_string()];
+        let exposed_env = app_settings.environment.expose_all();
+        let result = load_balancer
+            .get_docker_compose_override(
+                &global_settings,
+                "myapp",
+                &app_settings,
+                &exposed_env,
+                &all_services,
+            )
+            .unwrap();
+
+        let service_config = result.services.get("web").unwrap();
+        let labels = service_config.labels.as_ref().unwrap();
+        let environment = service_config.environment.as_ref().unwrap();
+
+        let networks = service_config.networks.as_ref().unwrap();
+        assert!(networks.contains_key("default"));
+        assert!(networks.contains_key("caddy"));
+        let compose_networks = result.networks.as_ref().unwrap();
+        assert!(compose_networks.get("caddy").unwrap().external);
+
+        assert_eq!(labels.get("caddy").unwrap(), "web.example.com");
+        assert_eq!(
+            labels.get("caddy.reverse_proxy").unwrap(),
+            "{{upstreams 8080}}"
+        );
+        assert_eq!(labels.get("caddy_ingress_network").unwrap(), "caddy");
+        assert_eq!(
+            labels.get("caddy.header").unwrap(),
+            &format!("X-Robots-Tag \"{ROBOTS_HEADER_VALUE}\"")
+        );
+
+        // The bcrypt hash is escaped for docker compose
+        let hash = labels.get("caddy.basic_auth.user").unwrap();
+        assert!(hash.starts_with("$$2"));
+        assert!(bcrypt::verify("pass", &hash.replace("$$", "$")).unwrap());
+
+        assert!(labels.get("caddy.forward_auth").is_none());
+        assert_eq!(environment.get("FOO").unwrap(), "BAR");
+    }
+
+    #[test]
+    fn test_caddy_custom_domains_without_tls() {
+        let global_settings = caddy_settings(false);
+
+        let app_settings = AppSettings {
+            domain: "example.com".to_string(),
+            public_services: vec![
+                ServicePortMapping {
+                    service: "web".to_string(),
+                    port: 80,
+                    domains: vec!["custom1.test".to_string(), "custom2.test".to_string()],
+                    path: None,
+                    strip_path: false,
+                },
+                ServicePortMapping {
+                    service: "api".to_string(),
+                    port: 9000,
+                    domains: vec![],
+                    path: None,
+                    strip_path: false,
+                },
+            ],
+            disallow_robots: false,
+            ..Default::default()
+        };
+
+        let load_balancer = CaddyLoadBalancer;
+        let all_services = vec!["web".to_string(), "api".to_string(), "db".to_string()];
+        let result = load_balancer
+            .get_docker_compose_override(
+                &global_settings,
+                "myapp",
+                &app_settings,
+                &HashMap::new(),
+                &all_services,
+            )
+            .unwrap();
+
+        let web_labels = result.services.get("web").unwrap().labels.as_ref().unwrap();
+        assert_eq!(
+            web_labels.get("caddy").unwrap(),
+            "http://custom1.test, http://custom2.test"
+        );
+        assert!(web_labels.get("caddy.header").is_none());
+        assert!(web_labels.get("caddy.basic_auth.user").is_none());
+
+        let api_labels = result.services.get("api").unwrap().labels.as_ref().unwrap();
+        assert_eq!(api_labels.get("caddy").unwrap(), "http://api.example.com");
+        assert_eq!(
+            api_labels.get("caddy.reverse_proxy").unwrap(),
+            "{{upstreams 9000}}"
+        );
+
+        // Services without public ports and without environment are left alone
+        assert!(!result.services.contains_key("db"));
+    }
+
+    #[test]
+    fn test_caddy_idle_ttl_reports_activity() {
+        let mut global_settings =
+            crate::api::test_utils::settings_with_secret_key(caddy_settings(true), "secret");
+        global_settings.caddy.activity_url = Some("http://scotty:21342/".to_string());
+
+        let app_settings = AppSettings {
+            domain: "example.com".to_string(),
+            public_services: vec![ServicePortMapping {
+                service: "web".to_string(),
+                port: 80,
+                domains: vec![],
+                path: None,
+                strip_path: false,
+            }],
+            time_to_live: AppTtl::Idle(8),
+            ..Default::default()
+        };
+
+        let result = CaddyLoadBalancer
+            .get_docker_compose_override(
+                &global_settings,
+                "myapp",
+                &app_settings,
+                &HashMap::new(),
+                &["web".to_string()],
+            )
+            .unwrap();
+
+        let labels = result.services.get("web").unwrap().labels.as_ref().unwrap();
+        assert_eq!(
+            labels.get("caddy.forward_auth").unwrap(),
+            "http://scotty:21342"
+        );
+        assert_eq!(
+            labels.get("caddy.forward_auth.uri").unwrap(),
+            &format!(
+                "/api/v1/activity/myapp?token={}",
+                crate::api::signing::activity_token(&global_settings, "myapp").unwrap()
+            )
+        );
+    }
+
+    #[test]
+    fn test_caddy_get_load_balancer_info() {
+        let insights = ContainerInspectResponse {
+            config: Some(ContainerConfig {
+                labels: Some(hashmap! {
+                    "caddy".to_string() => "web.example.com, custom.test".to_string(),
+                    "caddy.reverse_proxy".to_string() => "{{upstreams 8080}}".to_string(),
+                    "caddy.basic_auth.admin".to_string() => "$2a$14$hash".to_string(),
+                    "com.docker.compose.service".to_string() => "web".to_string(),
+                }),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        let info = CaddyLoadBalancer.get_load_balancer_info(insights);
+        let mut domains = info.domains.clone();
+        domains.sort();
+        assert_eq!(domains, vec!["custom.test", "web.example.com"]);
+        assert_eq!(info.port, Some(8080));
+        assert!(info.tls_enabled);
+        assert_eq!(info.basic_auth_user.as_deref(), Some("admin"));
+        assert_eq!(info.basic_auth_pass, None);
+    }
+
+    #[test]
+    fn test_caddy_get_load_balancer_info_without_tls() {
+        let insights = ContainerInspectResponse {
+            config: Some(ContainerConfig {
+                labels: Some(hashmap! {
+                    "caddy_0".to_string() => "http://web.example.com".to_string(),
+                    "caddy_0.reverse_proxy".to_string() => "{{upstreams http 3000}}".to_string(),
+                }),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        let info = CaddyLoadBalancer.get_load_balancer_info(insights);
+        assert_eq!(info.domains, vec!["web.example.com"]);
+        assert_eq!(info.port, Some(3000));
+        assert!(!info.tls_enabled);
+    }
+}
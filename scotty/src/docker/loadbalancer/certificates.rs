@@ -0,0 +1,387 @@
+//! Certificates uploaded to Scotty and the expiry of the certificates served
+//! for the domains of an app.
+//!
+//! Uploaded certificates are stored as PEM files in `certificates_folder`.
+//! Traefik gets them through one dynamic config file per certificate in
+//! `dynamic_config_folder` and picks them by SNI from its certificate store.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::crypto::CryptoProvider;
+use rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+use rustls::{DigitallySignedStruct, SignatureScheme};
+use serde::Serialize;
+use tokio_rustls::TlsConnector;
+use tracing::info;
+use x509_parser::extensions::GeneralName;
+use x509_parser::prelude::{FromDer, X509Certificate};
+
+use crate::settings::config::Settings;
+use scotty_core::admin::{CertificateInfo, UploadCertificateRequest};
+
+/// How long to wait for a TLS handshake when checking the expiry of a domain
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Serialize)]
+struct CertificateConfig {
+    tls: TlsConfig,
+}
+
+#[derive(Debug, Serialize)]
+struct TlsConfig {
+    certificates: Vec<TlsCertificate>,
+}
+
+/// Traefik accepts the PEM content instead of a path, so the file works no
+/// matter where Traefik mounts the certificates folder.
+#[derive(Debug, Serialize)]
+struct TlsCertificate {
+    #[serde(rename = "certFile")]
+    cert_file: String,
+    #[serde(rename = "keyFile")]
+    key_file: String,
+}
+
+fn certificate_file(global_settings: &Settings, name: &str) -> PathBuf {
+    PathBuf::from(&global_settings.traefik.certificates_folder).join(format!("{name}.crt"))
+}
+
+fn key_file(global_settings: &Settings, name: &str) -> PathBuf {
+    PathBuf::from(&global_settings.traefik.certificates_folder).join(format!("{name}.key"))
+}
+
+fn dynamic_config_file(global_settings: &Settings, name: &str) -> PathBuf {
+    PathBuf::from(&global_settings.traefik.dynamic_config_folder)
+        .join(format!("scotty-certificate-{name}.yml"))
+}
+
+pub fn validate_name(name: &str) -> anyhow::Result<()> {
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+    {
+        return Err(anyhow::anyhow!(
+            "Invalid certificate name '{}', use lowercase letters, digits, '-' and '_'",
+            name
+        ));
+    }
+    Ok(())
+}
+
+/// Names and expiry of a DER encoded certificate
+fn describe_certificate(name: &str, der: &[u8]) -> anyhow::Result<CertificateInfo> {
+    let (_, certificate) = X509Certificate::from_der(der)
+        .map_err(|e| anyhow::anyhow!("Invalid certificate: {}", e))?;
+
+    let mut domains: Vec<String> = certificate
+        .subject_alternative_name()
+        .ok()
+        .flatten()
+        .map(|san| {
+            san.value
+                .general_names
+                .iter()
+                .filter_map(|name| match name {
+                    GeneralName::DNSName(dns) => Some(dns.to_string()),
+                    _ => None,
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    if domains.is_empty() {
+        domains = certificate
+            .subject()
+            .iter_common_name()
+            .filter_map(|cn| cn.as_str().ok().map(str::to_string))
+            .collect();
+    }
+
+    let expires_at = DateTime::from_timestamp(certificate.validity().not_after.timestamp(), 0)
+        .ok_or_else(|| anyhow::anyhow!("Invalid expiry date of certificate"))?;
+
+    Ok(CertificateInfo {
+        name: name.to_string(),
+        domains,
+        expires_at,
+    })
+}
+
+/// Names and expiry of the first certificate of a PEM file
+pub fn parse_certificate(name: &str, pem: &str) -> anyhow::Result<CertificateInfo> {
+    let (_, pem) = x509_parser::pem::parse_x509_pem(pem.as_bytes())
+        .map_err(|e| anyhow::anyhow!("Invalid PEM certificate: {}", e))?;
+    if pem.label != "CERTIFICATE" {
+        return Err(anyhow::anyhow!(
+            "Expected a certificate, found a PEM block of type {}",
+            pem.label
+        ));
+    }
+    describe_certificate(name, &pem.contents)
+}
+
+fn validate_key(pem: &str) -> anyhow::Result<()> {
+    let (_, pem) = x509_parser::pem::parse_x509_pem(pem.as_bytes())
+        .map_err(|e| anyhow::anyhow!("Invalid PEM key: {}", e))?;
+    if !pem.label.ends_with("PRIVATE KEY") {
+        return Err(anyhow::anyhow!(
+            "Expected a private key, found a PEM block of type {}",
+            pem.label
+        ));
+    }
+    Ok(())
+}
+
+/// Write a file only the user running scotty can read, e.g. a private key.
+///
+/// The content goes to a fresh temporary file created with mode 0600, which
+/// then replaces the file, so the content is never readable by others.
+fn write_private_file(file: &Path, content: &str) -> anyhow::Result<()> {
+    let file_name = file
+        .file_name()
+        .ok_or_else(|| anyhow::anyhow!("Invalid file name {}", file.display()))?;
+    let tmp_file = file.with_file_name(format!(".{}.tmp", file_name.to_string_lossy()));
+    if tmp_file.exists() {
+        std::fs::remove_file(&tmp_file)?;
+    }
+
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut tmp = options.open(&tmp_file)?;
+    tmp.write_all(content.as_bytes())?;
+    tmp.sync_all()?;
+    std::fs::rename(&tmp_file, file)?;
+    Ok(())
+}
+
+/// Store an uploaded certificate and hand it to Traefik
+pub fn store_certificate(
+    global_settings: &Settings,
+    request: &UploadCertificateRequest,
+) -> anyhow::Result<CertificateInfo> {
+    validate_name(&request.name)?;
+    let info = parse_certificate(&request.name, &request.certificate)?;
+    validate_key(&request.key)?;
+
+    std::fs::create_dir_all(&global_settings.traefik.certificates_folder)?;
+    std::fs::write(
+        certificate_file(global_settings, &request.name),
+        &request.certificate,
+    )?;
+    write_private_file(&key_file(global_settings, &request.name), &request.key)?;
+
+    let config = CertificateConfig {
+        tls: TlsConfig {
+            certificates: vec![TlsCertificate {
+                cert_file: request.certificate.clone(),
+                key_file: request.key.clone(),
+            }],
+        },
+    };
+    // The config contains the private key as well
+    std::fs::create_dir_all(&global_settings.traefik.dynamic_config_folder)?;
+    write_private_file(
+        &dynamic_config_file(global_settings, &request.name),
+        &serde_norway::to_string(&config)?,
+    )?;
+
+    info!(
+        "Stored certificate {} for {}",
+        request.name,
+        info.domains.join(", ")
+    );
+    Ok(info)
+}
+
+pub fn certificate_exists(global_settings: &Settings, name: &str) -> bool {
+    validate_name(name).is_ok() && certificate_file(global_settings, name).exists()
+}
+
+pub fn read_certificate(global_settings: &Settings, name: &str) -> anyhow::Result<CertificateInfo> {
+    let pem = std::fs::read_to_string(certificate_file(global_settings, name))
+        .map_err(|e| anyhow::anyhow!("Can't read certificate {}: {}", name, e))?;
+    parse_certificate(name, &pem)
+}
+
+pub fn list_certificates(global_settings: &Settings) -> anyhow::Result<Vec<CertificateInfo>> {
+    let folder = PathBuf::from(&global_settings.traefik.certificates_folder);
+    if !folder.exists() {
+        return Ok(vec![]);
+    }
+    let mut names: Vec<String> = std::fs::read_dir(folder)?
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let path = entry.path();
+            (path.extension()? == "crt")
+                .then(|| path.file_stem()?.to_str().map(str::to_string))
+                .flatten()
+        })
+        .collect();
+    names.sort();
+    names
+        .iter()
+        .map(|name| read_certificate(global_settings, name))
+        .collect()
+}
+
+pub fn remove_certificate(global_settings: &Settings, name: &str) -> anyhow::Result<()> {
+    validate_name(name)?;
+    for file in [
+        dynamic_config_file(global_settings, name),
+        certificate_file(global_settings, name),
+        key_file(global_settings, name),
+    ] {
+        if file.exists() {
+            std::fs::remove_file(&file)?;
+        }
+    }
+    info!("Removed certificate {}", name);
+    Ok(())
+}
+
+/// Only reads the certificate of the peer, it is not trusted for anything
+#[derive(Debug)]
+struct AcceptAnyCertificate(Arc<CryptoProvider>);
+
+impl ServerCertVerifier for AcceptAnyCertificate {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls12_signature(
+            message,
+            cert,
+            dss,
+            &self.0.signature_verification_algorithms,
+        )
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls13_signature(
+            message,
+            cert,
+            dss,
+            &self.0.signature_verification_algorithms,
+        )
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.0.signature_verification_algorithms.supported_schemes()
+    }
+}
+
+/// Expiry of the certificate served for `domain`, read via a TLS handshake
+pub async fn probe_certificate_expiry(domain: &str, port: u16) -> anyhow::Result<DateTime<Utc>> {
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let config = rustls::ClientConfig::builder_with_provider(provider.clone())
+        .with_safe_default_protocol_versions()?
+        .dangerous()
+        .with_custom_certificate_verifier(Arc::new(AcceptAnyCertificate(provider)))
+        .with_no_client_auth();
+    let server_name = ServerName::try_from(domain.to_string())?;
+
+    let handshake = async {
+        let stream = tokio::net::TcpStream::connect((domain, port)).await?;
+        let stream = TlsConnector::from(Arc::new(config))
+            .connect(server_name, stream)
+            .await?;
+        let (_, connection) = stream.get_ref();
+        let certificate = connection
+            .peer_certificates()
+            .and_then(|certificates| certificates.first())
+            .ok_or_else(|| anyhow::anyhow!("No certificate served for {}", domain))?;
+        Ok::<_, anyhow::Error>(describe_certificate(domain, certificate)?.expires_at)
+    };
+    tokio::time::timeout(PROBE_TIMEOUT, handshake)
+        .await
+        .map_err(|_| anyhow::anyhow!("Timeout while connecting to {}:{}", domain, port))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CERTIFICATE: &str = include_str!("../../../tests/fixtures/test-certificate.pem");
+    const KEY: &str = include_str!("../../../tests/fixtures/test-certificate-key.pem");
+
+    fn settings(folder: &std::path::Path) -> Settings {
+        let mut settings = Settings::default();
+        settings.traefik.certificates_folder = folder.join("certs").to_str().unwrap().to_string();
+        settings.traefik.dynamic_config_folder =
+            folder.join("dynamic").to_str().unwrap().to_string();
+        settings
+    }
+
+    #[test]
+    fn test_parse_certificate() {
+        let info = parse_certificate("example", CERTIFICATE).unwrap();
+        assert_eq!(info.domains, vec!["example.com", "*.example.com"]);
+        assert_eq!(info.expires_at.to_rfc3339(), "2126-09-23T06:28:28+00:00");
+
+        assert!(parse_certificate("example", KEY).is_err());
+        assert!(validate_key(KEY).is_ok());
+        assert!(validate_key(CERTIFICATE).is_err());
+    }
+
+    #[test]
+    fn test_store_list_and_remove_certificate() {
+        let folder = tempfile::tempdir().unwrap();
+        let settings = settings(folder.path());
+        let request = UploadCertificateRequest {
+            name: "wildcard".to_string(),
+            certificate: CERTIFICATE.to_string(),
+            key: KEY.to_string(),
+        };
+
+        store_certificate(&settings, &request).unwrap();
+        assert!(certificate_exists(&settings, "wildcard"));
+        let dynamic_config =
+            std::fs::read_to_string(dynamic_config_file(&settings, "wildcard")).unwrap();
+        assert!(dynamic_config.contains("certFile"));
+        assert!(dynamic_config.contains("BEGIN PRIVATE KEY"));
+
+        let certificates = list_certificates(&settings).unwrap();
+        assert_eq!(certificates.len(), 1);
+        assert_eq!(certificates[0].name, "wildcard");
+
+        remove_certificate(&settings, "wildcard").unwrap();
+        assert!(!certificate_exists(&settings, "wildcard"));
+        assert!(!dynamic_config_file(&settings, "wildcard").exists());
+        assert!(list_certificates(&settings).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_validate_name() {
+        assert!(validate_name("wildcard-example_com").is_ok());
+        assert!(validate_name("").is_err());
+        assert!(validate_name("../etc/passwd").is_err());
+    }
+}
@@ -1,6 +1,9 @@
 use scotty_core::settings::loadbalancer::LoadBalancerType;
 
-use super::{haproxy::HaproxyLoadBalancer, traefik::TraefikLoadBalancer, types::LoadBalancerImpl};
+use super::{
+    caddy::CaddyLoadBalancer, haproxy::HaproxyLoadBalancer, nginx_proxy::NginxProxyLoadBalancer,
+    traefik::TraefikLoadBalancer, types::LoadBalancerImpl,
+};
 
 pub struct LoadBalancerFactory;
 
@@ -9,6 +12,8 @@ impl LoadBalancerFactory {
         match load_balancer_type {
             LoadBalancerType::HaproxyConfig => Box::new(HaproxyLoadBalancer),
             LoadBalancerType::Traefik => Box::new(TraefikLoadBalancer),
+            LoadBalancerType::Caddy => Box::new(CaddyLoadBalancer),
+            LoadBalancerType::NginxProxy => Box::new(NginxProxyLoadBalancer),
         }
     }
 }
@@ -9,6 +9,7 @@ use scotty_core::apps::app_data::AppSettings;
 use super::types::{
     DockerComposeConfig, DockerComposeServiceConfig, LoadBalancerImpl, LoadBalancerInfo,
 };
+use super::{ensure_host_routing_only, ensure_no_idle_ttl};
 
 pub struct HaproxyLoadBalancer;
 
@@ -61,6 +62,9 @@ impl LoadBalancerImpl for HaproxyLoadBalancer {
         resolved_environment: &HashMap<String, String>,
         all_services: &[String],
     ) -> anyhow::Result<DockerComposeConfig> {
+        ensure_host_routing_only("HAProxy-config", settings)?;
+        ensure_no_idle_ttl("HAProxy-config", settings)?;
+
         let mut config = DockerComposeConfig {
             services: HashMap::new(),
             networks: None,
@@ -105,9 +109,18 @@ impl LoadBalancerImpl for HaproxyLoadBalancer {
             );
             environment.insert("VPORT".into(), format!("{}", service.port));
 
-            if let Some((basic_auth_user, basic_auth_pass)) = &settings.basic_auth {
-                environment.insert("HTTP_AUTH_USER".into(), basic_auth_user.clone());
-                environment.insert("HTTP_AUTH_PASS".into(), basic_auth_pass.clone());
+            // HAProxy takes a single user with a clear-text password
+            match settings.basic_auth.as_slice() {
+                [] => {}
+                [user] if !user.is_hashed() && !user.is_reference() => {
+                    environment.insert("HTTP_AUTH_USER".into(), user.username.clone());
+                    environment.insert("HTTP_AUTH_PASS".into(), user.password.clone());
+                }
+                _ => {
+                    return Err(anyhow::anyhow!(
+                        "HAProxy only supports a single basic auth user with a clear-text password"
+                    ))
+                }
             }
 
             if global_settings.haproxy.use_tls {
@@ -125,7 +138,7 @@ impl LoadBalancerImpl for HaproxyLoadBalancer {
 mod tests {
     use super::*;
     use maplit::hashmap;
-    use scotty_core::apps::app_data::{AppSettings, ServicePortMapping};
+    use scotty_core::apps::app_data::{AppSettings, BasicAuthUser, ServicePortMapping};
     use scotty_core::settings::loadbalancer::HaproxyConfigSettings;
     use scotty_core::utils::secret::SecretHashMap;
 
@@ -143,11 +156,15 @@ mod tests {
                     service: "web".to_string(),
                     port: 8080,
                     domains: vec!["custom1.test".to_string(), "custom2.test".to_string()],
+                    path: None,
+                    strip_path: false,
                 },
                 ServicePortMapping {
                     service: "api".to_string(),
                     port: 9000,
                     domains: vec!["api1.test".to_string(), "api2.test".to_string()],
+                    path: None,
+                    strip_path: false,
                 },
             ],
             ..Default::default()
@@ -189,6 +206,32 @@ mod tests {
         assert!(api_environment.get("HTTPS_ONLY").is_none());
     }
 
+    #[test]
+    fn test_haproxy_config_rejects_path_routing() {
+        let app_settings = AppSettings {
+            domain: "example.com".to_string(),
+            public_services: vec![ServicePortMapping {
+                service: "api".to_string(),
+                port: 8080,
+                domains: vec![],
+                path: Some("/api".to_string()),
+                strip_path: false,
+            }],
+            ..Default::default()
+        };
+
+        let err = HaproxyLoadBalancer
+            .get_docker_compose_override(
+                &Settings::default(),
+                "myapp",
+                &app_settings,
+                &HashMap::new(),
+                &["api".to_string()],
+            )
+            .unwrap_err();
+        assert!(err.to_string().contains("path-based routing"));
+    }
+
     #[test]
     fn test_haproxy_config_get_docker_compose_override() {
         let global_settings = Settings {
@@ -202,8 +245,10 @@ mod tests {
                 service: "web".to_string(),
                 port: 8080,
                 domains: vec![],
+                path: None,
+                strip_path: false,
             }],
-            basic_auth: Some(("user".to_string(), "pass".to_string())),
+            basic_auth: vec![BasicAuthUser::new("user", "pass")],
             disallow_robots: true,
             environment: SecretHashMap::from_hashmap(hashmap! {
                 "FOO".to_string() => "BAR".to_string(),
@@ -250,8 +295,10 @@ mod tests {
                 service: "web".to_string(),
                 port: 8080,
                 domains: vec![],
+                path: None,
+                strip_path: false,
             }],
-            basic_auth: None,
+            basic_auth: vec![],
             disallow_robots: false,
             environment: SecretHashMap::from_hashmap(hashmap! {
                 "FOO".to_string() => "BAR".to_string(),
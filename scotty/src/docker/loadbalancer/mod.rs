@@ -1,8 +1,22 @@
+pub mod caddy;
+pub mod certificates;
 pub mod factory;
 pub mod haproxy;
+pub mod nginx_proxy;
 pub mod traefik;
+pub mod traefik_file;
 pub mod types;
 
+use scotty_core::apps::app_data::{AppSettings, AppTtl, BasicAuthUser};
+
+use crate::api::signing::activity_token;
+use crate::settings::config::Settings;
+
+/// Value of the `X-Robots-Tag` header injected when `disallow_robots` is set.
+/// Shared by all load balancers, so production code and tests cannot drift apart.
+pub(crate) const ROBOTS_HEADER_VALUE: &str =
+    "none, noarchive, nosnippet, notranslate, noimageindex";
+
 /// Computes the name of the per-app Traefik proxy network.
 ///
 /// Each app gets its own dedicated external network (derived from the
@@ -23,3 +37,119 @@ pub mod types;
 pub fn app_proxy_network_name(base_network: &str, app_name: &str) -> String {
     format!("{base_network}--{app_name}")
 }
+
+/// Fail for load balancers which only route whole hosts via HTTP, and neither
+/// support path prefixes, TCP and UDP services, TLS settings per app, IP
+/// allowlists nor forward auth.
+pub(crate) fn ensure_host_routing_only(
+    load_balancer: &str,
+    settings: &AppSettings,
+) -> anyhow::Result<()> {
+    if let Some(service) = settings
+        .public_services
+        .iter()
+        .find(|service| service.path.is_some())
+    {
+        return Err(anyhow::anyhow!(
+            "{} does not support path-based routing, remove the path of service {}",
+            load_balancer,
+            service.service
+        ));
+    }
+    if let Some(service) = settings.tcp_services.first() {
+        return Err(anyhow::anyhow!(
+            "{} does not support {} services, remove service {}",
+            load_balancer,
+            service.protocol.to_string().to_uppercase(),
+            service.service
+        ));
+    }
+    if !settings.tls.is_empty() {
+        return Err(anyhow::anyhow!(
+            "{} does not support TLS settings per app, remove the tls settings",
+            load_balancer
+        ));
+    }
+    if !settings.ip_allowlist.is_empty() {
+        return Err(anyhow::anyhow!(
+            "{} does not support IP allowlists, remove the ip_allowlist",
+            load_balancer
+        ));
+    }
+    if settings.forward_auth {
+        return Err(anyhow::anyhow!(
+            "{} does not support forward auth, disable forward_auth",
+            load_balancer
+        ));
+    }
+    if !settings.response_headers.is_empty() {
+        return Err(anyhow::anyhow!(
+            "{} does not support custom response headers, remove the response_headers",
+            load_balancer
+        ));
+    }
+    if !settings.redirects.is_empty() {
+        return Err(anyhow::anyhow!(
+            "{} does not support redirects, remove the redirects",
+            load_balancer
+        ));
+    }
+    if settings.maintenance {
+        return Err(anyhow::anyhow!(
+            "{} does not support the maintenance mode",
+            load_balancer
+        ));
+    }
+    Ok(())
+}
+
+/// Fail for load balancers which can't report requests to scotty, so apps
+/// with an idle TTL would be stopped while in use.
+pub(crate) fn ensure_no_idle_ttl(
+    load_balancer: &str,
+    settings: &AppSettings,
+) -> anyhow::Result<()> {
+    if let AppTtl::Idle(_) = settings.time_to_live {
+        return Err(anyhow::anyhow!(
+            "{} does not report requests to scotty, use a time_to_live other than idle",
+            load_balancer
+        ));
+    }
+    Ok(())
+}
+
+/// Path of the endpoint the load balancer reports requests to `app_name` to,
+/// signed so nobody else can keep the app running.
+pub(crate) fn activity_path(global_settings: &Settings, app_name: &str) -> anyhow::Result<String> {
+    Ok(format!(
+        "/api/v1/activity/{}?token={}",
+        app_name,
+        activity_token(global_settings, app_name)?
+    ))
+}
+
+/// Bcrypt hash of a basic auth password, for storing it in the settings
+pub(crate) fn hash_password(password: &str) -> anyhow::Result<String> {
+    Ok(bcrypt::hash(password, bcrypt::DEFAULT_COST)?)
+}
+
+/// Bcrypt hash of the password of a basic auth user. Stored hashes are used as
+/// they are, clear-text passwords of older settings get hashed. Dollar signs are
+/// escaped when the hash ends up in a compose file, as compose would
+/// interpolate them otherwise.
+pub(crate) fn htpasswd(user: &BasicAuthUser, escape_dollars: bool) -> anyhow::Result<String> {
+    if user.is_reference() {
+        return Err(anyhow::anyhow!(
+            "Password of basic auth user {} was not resolved",
+            user.username
+        ));
+    }
+    let mut hashed = match user.is_hashed() {
+        true => user.password.clone(),
+        false => hash_password(&user.password)?,
+    };
+    if escape_dollars {
+        hashed = hashed.replace('$', "$$")
+    }
+    Ok(hashed)
+}
@@ -0,0 +1,476 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use bollard_stubs::models::ContainerInspectResponse;
+use regex::Regex;
+use tracing::info;
+
+use crate::settings::config::Settings;
+use scotty_core::apps::app_data::{AppSettings, ServicePortMapping};
+
+use super::types::{
+    DockerComposeConfig, DockerComposeNetworkConfig, DockerComposeServiceConfig, LoadBalancerImpl,
+    LoadBalancerInfo, ServiceNetworkAttachment,
+};
+use super::{ensure_host_routing_only, ensure_no_idle_ttl, htpasswd};
+
+/// Load balancer for the nginx-proxy image, optionally with acme-companion.
+///
+/// nginx-proxy reads the `VIRTUAL_*` environment of the containers, while
+/// basic auth is configured through one htpasswd file per domain, which is
+/// why Scotty writes those to a folder shared with nginx-proxy.
+pub struct NginxProxyLoadBalancer;
+
+impl NginxProxyLoadBalancer {
+    /// Path of the htpasswd file of a domain, which must stay inside the folder
+    fn htpasswd_file(folder: &Path, domain: &str) -> anyhow::Result<PathBuf> {
+        ServicePortMapping::validate_domain(domain).map_err(|e| anyhow::anyhow!(e))?;
+        let file = folder.join(domain);
+        if file.parent() != Some(folder) {
+            return Err(anyhow::anyhow!(
+                "htpasswd file of domain {} is outside of {}",
+                domain,
+                folder.display()
+            ));
+        }
+        Ok(file)
+    }
+
+    /// Paths of the htpasswd files for all domains of the app
+    fn htpasswd_files(
+        global_settings: &Settings,
+        settings: &AppSettings,
+    ) -> anyhow::Result<Vec<PathBuf>> {
+        let folder = PathBuf::from(&global_settings.nginx_proxy.htpasswd_folder);
+        settings
+            .public_services
+            .iter()
+            .flat_map(|service| service.get_domains(&settings.domain))
+            .map(|domain| Self::htpasswd_file(&folder, &domain))
+            .collect()
+    }
+
+    /// File listing the domains the htpasswd files of the app were written
+    /// for, so files of domains the app no longer has get removed. Hostnames
+    /// never start with a dot, so it can't clash with an htpasswd file.
+    fn domains_file(global_settings: &Settings, app_name: &str) -> PathBuf {
+        PathBuf::from(&global_settings.nginx_proxy.htpasswd_folder)
+            .join(format!(".{}.domains", app_name))
+    }
+
+    /// htpasswd files written for the app before
+    fn written_htpasswd_files(global_settings: &Settings, app_name: &str) -> Vec<PathBuf> {
+        let folder = PathBuf::from(&global_settings.nginx_proxy.htpasswd_folder);
+        std::fs::read_to_string(Self::domains_file(global_settings, app_name))
+            .unwrap_or_default()
+            .lines()
+            .filter_map(|domain| Self::htpasswd_file(&folder, domain.trim()).ok())
+            .collect()
+    }
+
+    fn remove_htpasswd_files(app_name: &str, files: &[PathBuf]) -> anyhow::Result<()> {
+        for file in files {
+            if file.exists() {
+                info!("Removing htpasswd file {} of {}", file.display(), app_name);
+                std::fs::remove_file(file)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl LoadBalancerImpl for NginxProxyLoadBalancer {
+    fn get_load_balancer_info(&self, insights: ContainerInspectResponse) -> LoadBalancerInfo {
+        let mut result = LoadBalancerInfo {
+            ..Default::default()
+        };
+        if let Some(env_vars) = insights.config.unwrap().env {
+            let re = Regex::new(r"^\s*([\w.-]+)\s*=\s*(.*)\s*$").unwrap();
+
+            for var in env_vars {
+                if let Some(caps) = re.captures(&var) {
+                    let key = caps.get(1).map_or("", |m| m.as_str());
+                    let value = caps.get(2).map_or("", |m| m.as_str());
+
+                    match key {
+                        "VIRTUAL_HOST" => {
+                            result.domains = value
+                                .split(',')
+                                .map(str::trim)
+                                .filter(|domain| !domain.is_empty())
+                                .map(|domain| domain.to_string())
+                                .collect();
+                        }
+                        "VIRTUAL_PORT" => {
+                            if let Ok(port) = value.parse::<u32>() {
+                                result.port = Some(port);
+                            }
+                        }
+                        "LETSENCRYPT_HOST" if !value.trim().is_empty() => {
+                            result.tls_enabled = true;
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+
+        result
+    }
+
+    fn get_docker_compose_override(
+        &self,
+        global_settings: &Settings,
+        _app_name: &str,
+        settings: &AppSettings,
+        resolved_environment: &HashMap<String, String>,
+        all_services: &[String],
+    ) -> anyhow::Result<DockerComposeConfig> {
+        ensure_host_routing_only("nginx-proxy", settings)?;
+        ensure_no_idle_ttl("nginx-proxy", settings)?;
+
+        let mut config = DockerComposeConfig {
+            services: HashMap::new(),
+            networks: Some(HashMap::new()),
+        };
+
+        // nginx-proxy reaches the public services over a shared network, which
+        // has to exist before the app is started.
+        let proxy_network = global_settings.nginx_proxy.network.clone();
+        let networks = config.networks.as_mut().unwrap();
+        networks.insert(
+            proxy_network.clone(),
+            DockerComposeNetworkConfig {
+                external: true,
+                name: Some(proxy_network.clone()),
+            },
+        );
+
+        // First, apply environment variables to all services
+        if !resolved_environment.is_empty() {
+            for service_name in all_services {
+                let mut service_config = DockerComposeServiceConfig {
+                    labels: None,
+                    environment: Some(HashMap::new()),
+                    networks: None,
+                };
+                let environment = service_config.environment.as_mut().unwrap();
+                for (key, value) in resolved_environment {
+                    environment.insert(key.clone(), value.clone());
+                }
+                config.services.insert(service_name.clone(), service_config);
+            }
+        }
+
+        // Then, add load balancer configuration for public services
+        for service in &settings.public_services {
+            // Get or create the service config (it may already exist from the all_services loop)
+            let service_config = config
+                .services
+                .entry(service.service.clone())
+                .or_insert_with(|| DockerComposeServiceConfig {
+                    labels: None,
+                    environment: None,
+                    networks: None,
+                });
+
+            if service_config.environment.is_none() {
+                service_config.environment = Some(HashMap::new());
+            }
+
+            let mut service_networks = HashMap::new();
+            service_networks.insert("default".to_string(), ServiceNetworkAttachment::default());
+            service_networks.insert(proxy_network.clone(), ServiceNetworkAttachment::default());
+            service_config.networks = Some(service_networks);
+
+            let domains = service.get_domains(&settings.domain).join(",");
+            let environment = service_config.environment.as_mut().unwrap();
+            environment.insert("VIRTUAL_HOST".into(), domains.clone());
+            environment.insert("VIRTUAL_PORT".into(), format!("{}", service.port));
+
+            if global_settings.nginx_proxy.use_tls {
+                environment.insert("LETSENCRYPT_HOST".into(), domains);
+                if let Some(email) = &global_settings.nginx_proxy.letsencrypt_email {
+                    environment.insert("LETSENCRYPT_EMAIL".into(), email.clone());
+                }
+            }
+
+            // Environment variables are already added in the all_services loop above
+        }
+
+        Ok(config)
+    }
+
+    fn write_support_files(
+        &self,
+        global_settings: &Settings,
+        app_name: &str,
+        settings: &AppSettings,
+    ) -> anyhow::Result<()> {
+        // Basic auth might have been removed from the app
+        if settings.basic_auth.is_empty() {
+            return self.remove_support_files(global_settings, app_name, settings);
+        }
+        let files = Self::htpasswd_files(global_settings, settings)?;
+        std::fs::create_dir_all(&global_settings.nginx_proxy.htpasswd_folder)?;
+
+        // Domains might have been removed from the app
+        let stale: Vec<PathBuf> = Self::written_htpasswd_files(global_settings, app_name)
+            .into_iter()
+            .filter(|file| !files.contains(file))
+            .collect();
+        Self::remove_htpasswd_files(app_name, &stale)?;
+
+        let mut content = String::new();
+        for user in &settings.basic_auth {
+            content.push_str(&format!("{}:{}\n", user.username, htpasswd(user, false)?));
+        }
+        let mut domains = String::new();
+        for file in files {
+            info!("Writing htpasswd file {} for {}", file.display(), app_name);
+            std::fs::write(&file, &content)?;
+            if let Some(domain) = file.file_name() {
+                domains.push_str(&format!("{}\n", domain.to_string_lossy()));
+            }
+        }
+        std::fs::write(Self::domains_file(global_settings, app_name), domains)?;
+        Ok(())
+    }
+
+    fn remove_support_files(
+        &self,
+        global_settings: &Settings,
+        app_name: &str,
+        settings: &AppSettings,
+    ) -> anyhow::Result<()> {
+        let mut files = Self::written_htpasswd_files(global_settings, app_name);
+        files.extend(Self::htpasswd_files(global_settings, settings)?);
+        Self::remove_htpasswd_files(app_name, &files)?;
+
+        let domains_file = Self::domains_file(global_settings, app_name);
+        if domains_file.exists() {
+            std::fs::remove_file(&domains_file)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bollard_stubs::models::ContainerConfig;
+    use maplit::hashmap;
+    use scotty_core::apps::app_data::BasicAuthUser;
+    use scotty_core::settings::loadbalancer::NginxProxySettings;
+    use scotty_core::utils::secret::SecretHashMap;
+
+    fn nginx_proxy_settings(use_tls: bool, htpasswd_folder: &str) -> Settings {
+        Settings {
+            nginx_proxy: NginxProxySettings {
+                use_tls,
+                letsencrypt_email: Some("admin@example.com".to_string()),
+                htpasswd_folder: htpasswd_folder.to_string(),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_nginx_proxy_get_docker_compose_override() {
+        let global_settings = nginx_proxy_settings(true, "./htpasswd");
+
+        let app_settings = AppSettings {
+            domain: "example.com".to_string(),
+            public_services: vec![
+                ServicePortMapping {
+                    service: "web".to_string(),
+                    port: 8080,
+                    domains: vec!["custom1.test".to_string(), "custom2.test".to_string()],
+                    path: None,
+                    strip_path: false,
+                },
+                ServicePortMapping {
+                    service: "api".to_string(),
+                    port: 9000,
+                    domains: vec![],
+                    path: None,
+                    strip_path: false,
+                },
+            ],
+            environment: SecretHashMap::from_hashmap(hashmap! {
+                "FOO".to_string() => "BAR".to_string(),
+            }),
+            ..Default::default()
+        };
+
+        let load_balancer = NginxProxyLoadBalancer;
+        let all_services = vec!["web".to_string(), "api".to_string(), "db".to_string()];
+        let exposed_env = app_settings.environment.expose_all();
+        let result = load_balancer
+            .get_docker_compose_override(
+                &global_settings,
+                "myapp",
+                &app_settings,
+                &exposed_env,
+                &all_services,
+            )
+            .unwrap();
+
+        let web_config = result.services.get("web").unwrap();
+        let web_env = web_config.environment.as_ref().unwrap();
+        assert_eq!(
+            web_env.get("VIRTUAL_HOST").unwrap(),
+            "custom1.test,custom2.test"
+        );
+        assert_eq!(web_env.get("VIRTUAL_PORT").unwrap(), "8080");
+        assert_eq!(
+            web_env.get("LETSENCRYPT_HOST").unwrap(),
+            "custom1.test,custom2.test"
+        );
+        assert_eq!(
+            web_env.get("LETSENCRYPT_EMAIL").unwrap(),
+            "admin@example.com"
+        );
+        assert_eq!(web_env.get("FOO").unwrap(), "BAR");
+        let networks = web_config.networks.as_ref().unwrap();
+        assert!(networks.contains_key("default"));
+        assert!(networks.contains_key("nginx-proxy"));
+
+        let api_env = result
+            .services
+            .get("api")
+            .unwrap()
+            .environment
+            .as_ref()
+            .unwrap();
+        assert_eq!(api_env.get("VIRTUAL_HOST").unwrap(), "api.example.com");
+        assert_eq!(api_env.get("VIRTUAL_PORT").unwrap(), "9000");
+
+        // Non-public services only get the environment
+        let db_env = result
+            .services
+            .get("db")
+            .unwrap()
+            .environment
+            .as_ref()
+            .unwrap();
+        assert_eq!(db_env.get("FOO").unwrap(), "BAR");
+        assert!(db_env.get("VIRTUAL_HOST").is_none());
+    }
+
+    #[test]
+    fn test_nginx_proxy_without_tls() {
+        let global_settings = nginx_proxy_settings(false, "./htpasswd");
+        let app_settings = AppSettings {
+            domain: "example.com".to_string(),
+            public_services: vec![ServicePortMapping {
+                service: "web".to_string(),
+                port: 80,
+                domains: vec![],
+                path: None,
+                strip_path: false,
+            }],
+            ..Default::default()
+        };
+
+        let result = NginxProxyLoadBalancer
+            .get_docker_compose_override(
+                &global_settings,
+                "myapp",
+                &app_settings,
+                &HashMap::new(),
+                &["web".to_string()],
+            )
+            .unwrap();
+
+        let env = result
+            .services
+            .get("web")
+            .unwrap()
+            .environment
+            .as_ref()
+            .unwrap();
+        assert_eq!(env.get("VIRTUAL_HOST").unwrap(), "web.example.com");
+        assert!(env.get("LETSENCRYPT_HOST").is_none());
+        assert!(env.get("LETSENCRYPT_EMAIL").is_none());
+    }
+
+    #[test]
+    fn test_nginx_proxy_htpasswd_files() {
+        let folder = tempfile::tempdir().unwrap();
+        let htpasswd_folder = folder.path().join("htpasswd");
+        let global_settings = nginx_proxy_settings(true, htpasswd_folder.to_str().unwrap());
+        let mut app_settings = AppSettings {
+            domain: "example.com".to_string(),
+            public_services: vec![ServicePortMapping {
+                service: "web".to_string(),
+                port: 80,
+                domains: vec!["custom.test".to_string()],
+                path: None,
+                strip_path: false,
+            }],
+            basic_auth: vec![BasicAuthUser::new("user", "pass")],
+            ..Default::default()
+        };
+
+        let load_balancer = NginxProxyLoadBalancer;
+        load_balancer
+            .write_support_files(&global_settings, "myapp", &app_settings)
+            .unwrap();
+
+        let content = std::fs::read_to_string(htpasswd_folder.join("custom.test")).unwrap();
+        let (user, hash) = content.trim().split_once(':').unwrap();
+        assert_eq!(user, "user");
+        assert!(bcrypt::verify("pass", hash).unwrap());
+
+        // Files of removed domains are removed on the next write
+        app_settings.public_services[0].domains = vec!["other.test".to_string()];
+        load_balancer
+            .write_support_files(&global_settings, "myapp", &app_settings)
+            .unwrap();
+        assert!(!htpasswd_folder.join("custom.test").exists());
+        assert!(htpasswd_folder.join("other.test").exists());
+
+        // Domains escaping the folder are rejected
+        let mut escaping = app_settings.clone();
+        escaping.public_services[0].domains = vec!["../../etc/x".to_string()];
+        assert!(load_balancer
+            .write_support_files(&global_settings, "myapp", &escaping)
+            .is_err());
+        assert!(load_balancer
+            .remove_support_files(&global_settings, "myapp", &escaping)
+            .is_err());
+        assert!(htpasswd_folder.join("other.test").exists());
+
+        // Removing basic auth removes the files again
+        app_settings.basic_auth = vec![];
+        load_balancer
+            .write_support_files(&global_settings, "myapp", &app_settings)
+            .unwrap();
+        assert!(!htpasswd_folder.join("other.test").exists());
+        assert!(!htpasswd_folder.join(".myapp.domains").exists());
+    }
+
+    #[test]
+    fn test_nginx_proxy_get_load_balancer_info() {
+        let insights = ContainerInspectResponse {
+            config: Some(ContainerConfig {
+                env: Some(vec![
+                    "VIRTUAL_HOST=web.example.com, custom.test".to_string(),
+                    "VIRTUAL_PORT=8080".to_string(),
+                    "LETSENCRYPT_HOST=web.example.com,custom.test".to_string(),
+                    "PATH=/usr/bin".to_string(),
+                ]),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        let info = NginxProxyLoadBalancer.get_load_balancer_info(insights);
+        assert_eq!(info.domains, vec!["web.example.com", "custom.test"]);
+        assert_eq!(info.port, Some(8080));
+        assert!(info.tls_enabled);
+        assert_eq!(info.basic_auth_user, None);
+    }
+}
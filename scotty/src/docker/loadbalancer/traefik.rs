@@ -4,24 +4,206 @@ use bollard_stubs::models::ContainerInspectResponse;
 use regex::Regex;
 
 use crate::settings::config::Settings;
-use scotty_core::apps::app_data::AppSettings;
+use scotty_core::apps::app_data::{
+    AppSettings, AppTtl, CertificateSource, RedirectRule, TcpRouting, TlsDomain, TransportProtocol,
+};
+use scotty_core::settings::loadbalancer::TraefikProvider;
 
-use super::app_proxy_network_name;
+use super::traefik_file::{
+    remove_dynamic_config, remove_maintenance_config, write_dynamic_config,
+    write_maintenance_config,
+};
 use super::types::{
     DockerComposeConfig, DockerComposeNetworkConfig, DockerComposeServiceConfig, LoadBalancerImpl,
     LoadBalancerInfo, ServiceNetworkAttachment,
 };
+use super::{activity_path, app_proxy_network_name, htpasswd, ROBOTS_HEADER_VALUE};
+
+/// Routing rule of a router, restricted to the path prefix of the service if set
+pub(super) fn router_rule(domain: &str, path: Option<&str>) -> String {
+    match path {
+        Some(path) => format!("Host(`{domain}`) && PathPrefix(`{path}`)"),
+        None => format!("Host(`{domain}`)"),
+    }
+}
+
+/// Priority of a router with a path prefix.
+///
+/// Traefik orders routers without an explicit priority by the length of their
+/// rule, so a whole-host router of a long domain could win over a path router
+/// of the same domain. Path routers get a fixed offset to stay above them, and
+/// longer prefixes win over shorter ones.
+pub(super) fn path_router_priority(path: &str) -> usize {
+    1000 + path.len()
+}
+
+/// Regex and replacement of a `redirectRegex` middleware for a redirect rule.
+/// Path and query of the request are appended to the new domain.
+pub(super) fn redirect_regex(rule: &RedirectRule, use_tls: bool) -> (String, String) {
+    (
+        format!("^https?://{}(.*)", regex::escape(&rule.from)),
+        format!(
+            "{}://{}${{1}}",
+            if use_tls { "https" } else { "http" },
+            rule.to
+        ),
+    )
+}
+
+/// URL of an endpoint of scotty, reachable from Traefik
+pub(super) fn scotty_endpoint(global_settings: &Settings, path: &str) -> String {
+    let base_url = global_settings
+        .traefik
+        .activity_url
+        .clone()
+        .unwrap_or_else(|| global_settings.api.public_base_url());
+    format!("{}{}", base_url.trim_end_matches('/'), path)
+}
+
+/// TLS settings of the router of a domain
+pub(super) struct RouterTlsSettings {
+    /// Resolver issuing the certificate, none if Traefik picks an uploaded
+    /// or default certificate from its store
+    pub certresolver: Option<String>,
+    /// Certificates to request from the resolver, e.g. wildcards
+    pub domains: Vec<TlsDomain>,
+}
 
-/// Value of the `X-Robots-Tag` header injected when `disallow_robots` is set.
-/// Kept as a single constant so production code and tests cannot drift apart.
-const ROBOTS_HEADER_VALUE: &str = "none, noarchive, nosnippet, notranslate, noimageindex";
+/// TLS settings of the router of `domain`, the settings of the app take
+/// precedence over the global resolver
+pub(super) fn router_tls(
+    global_settings: &Settings,
+    settings: &AppSettings,
+    domain: &str,
+) -> RouterTlsSettings {
+    match settings
+        .tls
+        .certificate_source(domain, global_settings.traefik.certresolver.as_deref())
+    {
+        CertificateSource::Resolver(certresolver) => RouterTlsSettings {
+            certresolver: Some(certresolver),
+            domains: settings
+                .tls
+                .domains_for(domain)
+                .into_iter()
+                .cloned()
+                .collect(),
+        },
+        CertificateSource::Uploaded(_) | CertificateSource::Default => RouterTlsSettings {
+            certresolver: None,
+            domains: vec![],
+        },
+    }
+}
+
+/// Router of a TCP or UDP service, shared by the docker and the file provider
+pub(super) struct TcpRoute {
+    /// Name of the router and its service, e.g. `db--myapp--tcp`
+    pub name: String,
+    pub protocol: TransportProtocol,
+    /// `HostSNI` rule, UDP routers have none
+    pub rule: Option<String>,
+    pub entrypoint: String,
+    /// Terminate TLS, only for routing by SNI
+    pub tls: Option<RouterTlsSettings>,
+    /// Alias of the service on the per-app proxy network
+    pub host: String,
+    pub port: u32,
+}
+
+/// Build the routers of the TCP and UDP services of an app
+pub(super) fn tcp_routes(
+    global_settings: &Settings,
+    app_name: &str,
+    settings: &AppSettings,
+) -> anyhow::Result<Vec<TcpRoute>> {
+    settings
+        .tcp_services
+        .iter()
+        .map(|service| {
+            let name = format!("{}--{}--{}", service.service, app_name, service.protocol);
+            let host = format!("{}--{}", service.service, app_name);
+            let route = match service.routing {
+                TcpRouting::Sni => {
+                    let domain = service.get_host(&settings.domain).ok_or_else(|| {
+                        anyhow::anyhow!("No domain to route service {} by SNI", service.service)
+                    })?;
+                    TcpRoute {
+                        name,
+                        protocol: service.protocol,
+                        rule: Some(format!("HostSNI(`{domain}`)")),
+                        entrypoint: global_settings.traefik.tcp.entrypoint.clone(),
+                        tls: Some(router_tls(global_settings, settings, &domain)),
+                        host,
+                        port: service.port,
+                    }
+                }
+                TcpRouting::Port => {
+                    let public_port = service.public_port.ok_or_else(|| {
+                        anyhow::anyhow!("No port allocated for service {}", service.service)
+                    })?;
+                    TcpRoute {
+                        name,
+                        protocol: service.protocol,
+                        rule: (service.protocol == TransportProtocol::Tcp)
+                            .then(|| "HostSNI(`*`)".to_string()),
+                        entrypoint: format!("{}-{}", service.protocol, public_port),
+                        tls: None,
+                        host,
+                        port: service.port,
+                    }
+                }
+            };
+            Ok(route)
+        })
+        .collect()
+}
+
+/// Labels of the certificate resolver and the requested certificates of a router
+fn insert_tls_labels(labels: &mut HashMap<String, String>, router: &str, tls: &RouterTlsSettings) {
+    if let Some(certresolver) = &tls.certresolver {
+        labels.insert(format!("{router}.tls.certresolver"), certresolver.clone());
+    }
+    for (idx, domain) in tls.domains.iter().enumerate() {
+        labels.insert(
+            format!("{router}.tls.domains[{idx}].main"),
+            domain.main.clone(),
+        );
+        if !domain.sans.is_empty() {
+            labels.insert(
+                format!("{router}.tls.domains[{idx}].sans"),
+                domain.sans.join(","),
+            );
+        }
+    }
+}
+
+/// Attach a service to its project `default` network and to the per-app proxy
+/// network. On the proxy network we set an explicit app-scoped alias so the
+/// service is reachable under a unique name (Compose still adds the bare
+/// service name as an alias, but the network itself is no longer shared, so
+/// that no longer collides).
+fn attach_to_app_network(
+    service_config: &mut DockerComposeServiceConfig,
+    app_network: &str,
+    alias: &str,
+) {
+    let networks = service_config.networks.get_or_insert_with(HashMap::new);
+    networks.insert("default".to_string(), ServiceNetworkAttachment::default());
+    networks.insert(
+        app_network.to_string(),
+        ServiceNetworkAttachment {
+            aliases: Some(vec![alias.to_string()]),
+        },
+    );
+}
 
 pub struct TraefikLoadBalancer;
 
 impl LoadBalancerImpl for TraefikLoadBalancer {
     fn get_load_balancer_info(&self, insights: ContainerInspectResponse) -> LoadBalancerInfo {
         let re_host =
-            Regex::new(r"traefik\.http\.routers\.[a-z-0-9]*\.rule=Host\(`(.*)`\)").unwrap();
+            Regex::new(r"traefik\.http\.routers\.[a-z-0-9]*\.rule=Host\(`([^`]*)`\)").unwrap();
         let re_port =
             Regex::new(r"traefik\.http\.services\.[a-z-0-9]*\.loadbalancer.server.port=(.*)")
                 .unwrap();
@@ -115,23 +297,19 @@ impl LoadBalancerImpl for TraefikLoadBalancer {
             }
             let service_name = format!("{}--{}", service.service, app_name);
 
-            // Attach the public service to its project `default` network and to
-            // the per-app proxy network. On the proxy network we set an explicit
-            // app-scoped alias so the service is reachable under a unique name
-            // (Compose still adds the bare service name as an alias, but the
-            // network itself is no longer shared, so that no longer collides).
-            let mut service_networks = std::collections::HashMap::new();
-            service_networks.insert("default".to_string(), ServiceNetworkAttachment::default());
-            service_networks.insert(
-                app_network.clone(),
-                ServiceNetworkAttachment {
-                    aliases: Some(vec![service_name.clone()]),
-                },
-            );
-            service_config.networks = Some(service_networks);
+            attach_to_app_network(service_config, &app_network, &service_name);
 
             let labels = service_config.labels.as_mut().unwrap();
 
+            // With the file provider the routing lives in the dynamic config
+            // file, keep the docker provider from picking up the container.
+            // The maintenance mode does not change the labels, it is applied
+            // with a file of its own, so the containers keep running.
+            if global_settings.traefik.provider == TraefikProvider::File {
+                labels.insert("traefik.enable".to_string(), "false".to_string());
+                continue;
+            }
+
             // Add Traefik labels
             labels.insert("traefik.enable".to_string(), "true".to_string());
 
@@ -145,8 +323,14 @@ impl LoadBalancerImpl for TraefikLoadBalancer {
             for (idx, domain) in domains.iter().enumerate() {
                 labels.insert(
                     format!("traefik.http.routers.{}-{}.rule", service_name, idx),
-                    format!("Host(`{domain}`)"),
+                    router_rule(domain, service.path.as_deref()),
                 );
+                if let Some(path) = &service.path {
+                    labels.insert(
+                        format!("traefik.http.routers.{}-{}.priority", service_name, idx),
+                        path_router_priority(path).to_string(),
+                    );
+                }
 
                 if global_settings.traefik.use_tls {
                     labels.insert(
@@ -154,15 +338,11 @@ impl LoadBalancerImpl for TraefikLoadBalancer {
                         "true".to_string(),
                     );
 
-                    if let Some(certresolver) = &global_settings.traefik.certresolver {
-                        labels.insert(
-                            format!(
-                                "traefik.http.routers.{}-{}.tls.certresolver",
-                                service_name, idx
-                            ),
-                            certresolver.clone(),
-                        );
-                    }
+                    insert_tls_labels(
+                        labels,
+                        &format!("traefik.http.routers.{}-{}", service_name, idx),
+                        &router_tls(global_settings, settings, domain),
+                    );
                 }
             }
 
@@ -176,14 +356,84 @@ impl LoadBalancerImpl for TraefikLoadBalancer {
 
             let mut middlewares = vec![];
 
-            if let Some((basic_auth_user, basic_auth_pass)) = &settings.basic_auth {
+            for (idx, rule) in settings.redirects.iter().enumerate() {
+                let middleware_name = format!("{}--redirect-{}", service_name, idx);
+                let (regex, replacement) = redirect_regex(rule, global_settings.traefik.use_tls);
+                labels.insert(
+                    format!(
+                        "traefik.http.middlewares.{}.redirectregex.regex",
+                        middleware_name
+                    ),
+                    regex,
+                );
+                labels.insert(
+                    format!(
+                        "traefik.http.middlewares.{}.redirectregex.replacement",
+                        middleware_name
+                    ),
+                    // Compose interpolates `$` in labels
+                    replacement.replace('$', "$$"),
+                );
+                labels.insert(
+                    format!(
+                        "traefik.http.middlewares.{}.redirectregex.permanent",
+                        middleware_name
+                    ),
+                    rule.permanent.to_string(),
+                );
+
+                middlewares.push(middleware_name.clone());
+            }
+
+            if !settings.ip_allowlist.is_empty() {
+                let middleware_name = format!("{}--{}", service_name, "ip-allowlist");
+                labels.insert(
+                    format!(
+                        "traefik.http.middlewares.{}.ipallowlist.sourcerange",
+                        middleware_name
+                    ),
+                    settings.ip_allowlist.join(","),
+                );
+
+                middlewares.push(middleware_name.clone());
+            }
+
+            if settings.forward_auth {
+                let middleware_name = format!("{}--{}", service_name, "forward-auth");
+                labels.insert(
+                    format!(
+                        "traefik.http.middlewares.{}.forwardauth.address",
+                        middleware_name
+                    ),
+                    scotty_endpoint(
+                        global_settings,
+                        &format!("/api/v1/forward-auth/check/{}", app_name),
+                    ),
+                );
+                labels.insert(
+                    format!(
+                        "traefik.http.middlewares.{}.forwardauth.authResponseHeaders",
+                        middleware_name
+                    ),
+                    "X-Forwarded-User".to_string(),
+                );
+
+                middlewares.push(middleware_name.clone());
+            }
+
+            if !settings.basic_auth.is_empty() {
                 let middleware_name = format!("{}--{}", service_name, "basic-auth");
+                let users = settings
+                    .basic_auth
+                    .iter()
+                    .map(|user| Ok(format!("{}:{}", user.username, htpasswd(user, true)?)))
+                    .collect::<anyhow::Result<Vec<_>>>()?;
                 labels.insert(
                     format!(
                         "traefik.http.middlewares.{}.basicauth.users",
                         middleware_name
                     ),
-                    format!("{}:{}", basic_auth_user, htpasswd(basic_auth_pass, true)?),
+                    users.join(","),
                 );
                 labels.insert(
                     format!(
@@ -196,6 +446,20 @@ impl LoadBalancerImpl for TraefikLoadBalancer {
                 middlewares.push(middleware_name.clone());
             }
 
+            // Report requests to Scotty so apps with an idle TTL are kept running
+            if let AppTtl::Idle(_) = settings.time_to_live {
+                let middleware_name = format!("{}--{}", service_name, "activity");
+                labels.insert(
+                    format!(
+                        "traefik.http.middlewares.{}.forwardauth.address",
+                        middleware_name
+                    ),
+                    scotty_endpoint(global_settings, &activity_path(global_settings, app_name)?),
+                );
+
+                middlewares.push(middleware_name.clone());
+            }
+
             if settings.disallow_robots {
                 let middleware_name = format!("{}--{}", service_name, "robots");
                 labels.insert(
@@ -209,11 +473,40 @@ impl LoadBalancerImpl for TraefikLoadBalancer {
                 middlewares.push(middleware_name.clone());
             }
 
+            if !settings.response_headers.is_empty() {
+                let middleware_name = format!("{}--{}", service_name, "headers");
+                for (name, value) in &settings.response_headers {
+                    labels.insert(
+                        format!(
+                            "traefik.http.middlewares.{}.headers.customresponseheaders.{}",
+                            middleware_name, name
+                        ),
+                        value.replace('$', "$$"),
+                    );
+                }
+
+                middlewares.push(middleware_name.clone());
+            }
+
             // Add custom middlewares from settings
             for middleware in &settings.middlewares {
                 middlewares.push(middleware.clone());
             }
 
+            // Strip the prefix last, so all other middlewares see the original path
+            if let Some(path) = service.path.as_ref().filter(|_| service.strip_path) {
+                let middleware_name = format!("{}--{}", service_name, "strip-path");
+                labels.insert(
+                    format!(
+                        "traefik.http.middlewares.{}.stripprefix.prefixes",
+                        middleware_name
+                    ),
+                    path.clone(),
+                );
+
+                middlewares.push(middleware_name.clone());
+            }
+
             // Connect the middleware to the router
             for (idx, _domain) in domains.iter().enumerate() {
                 labels.insert(
@@ -224,25 +517,102 @@ impl LoadBalancerImpl for TraefikLoadBalancer {
 
             // Environment variables are already added in the all_services loop above
         }
+
+        for (service, route) in
+            settings
+                .tcp_services
+                .iter()
+                .zip(tcp_routes(global_settings, app_name, settings)?)
+        {
+            let service_config = config
+                .services
+                .entry(service.service.clone())
+                .or_insert_with(|| DockerComposeServiceConfig {
+                    labels: None,
+                    environment: None,
+                    networks: None,
+                });
+            attach_to_app_network(service_config, &app_network, &route.host);
+
+            let labels = service_config.labels.get_or_insert_with(HashMap::new);
+            if global_settings.traefik.provider == TraefikProvider::File {
+                labels.insert("traefik.enable".to_string(), "false".to_string());
+                continue;
+            }
+            labels.insert("traefik.enable".to_string(), "true".to_string());
+            labels.insert("traefik.docker.network".to_string(), app_network.clone());
+
+            let prefix = format!("traefik.{}", route.protocol);
+            if let Some(rule) = &route.rule {
+                labels.insert(
+                    format!("{}.routers.{}.rule", prefix, route.name),
+                    rule.clone(),
+                );
+            }
+            labels.insert(
+                format!("{}.routers.{}.entrypoints", prefix, route.name),
+                route.entrypoint.clone(),
+            );
+            labels.insert(
+                format!("{}.routers.{}.service", prefix, route.name),
+                route.name.clone(),
+            );
+            if let Some(tls) = &route.tls {
+                labels.insert(
+                    format!("{}.routers.{}.tls", prefix, route.name),
+                    "true".to_string(),
+                );
+                insert_tls_labels(labels, &format!("{}.routers.{}", prefix, route.name), tls);
+            }
+            labels.insert(
+                format!(
+                    "{}.services.{}.loadbalancer.server.port",
+                    prefix, route.name
+                ),
+                route.port.to_string(),
+            );
+        }
         Ok(config)
     }
-}
 
-fn htpasswd(password: &str, escape_dollars: bool) -> anyhow::Result<String> {
-    use bcrypt::{hash, DEFAULT_COST};
+    fn write_support_files(
+        &self,
+        global_settings: &Settings,
+        app_name: &str,
+        settings: &AppSettings,
+    ) -> anyhow::Result<()> {
+        match global_settings.traefik.provider {
+            TraefikProvider::File => write_dynamic_config(global_settings, app_name, settings),
+            TraefikProvider::Docker => {
+                write_maintenance_config(global_settings, app_name, settings)
+            }
+        }
+    }
 
-    let mut hashed = hash(password, DEFAULT_COST)?;
-    if escape_dollars {
-        hashed = hashed.replace('$', "$$")
+    fn remove_support_files(
+        &self,
+        global_settings: &Settings,
+        app_name: &str,
+        _settings: &AppSettings,
+    ) -> anyhow::Result<()> {
+        match global_settings.traefik.provider {
+            TraefikProvider::File => remove_dynamic_config(global_settings, app_name),
+            TraefikProvider::Docker => remove_maintenance_config(global_settings, app_name),
+        }
+    }
+
+    fn applies_routing_without_recreate(&self, global_settings: &Settings) -> bool {
+        global_settings.traefik.provider == TraefikProvider::File
     }
-    Ok(hashed)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use maplit::hashmap;
-    use scotty_core::apps::app_data::{AppSettings, ServicePortMapping};
+    use scotty_core::apps::app_data::{
+        AppSettings, BasicAuthUser, RedirectRule, ServicePortMapping,
+    };
     use scotty_core::settings::loadbalancer::TraefikSettings;
     use scotty_core::utils::secret::SecretHashMap;
 
@@ -265,8 +635,10 @@ mod tests {
                 service: "web".to_string(),
                 port: 8080,
                 domains: vec![],
+                path: None,
+                strip_path: false,
             }],
-            basic_auth: Some(("user".to_string(), "pass".to_string())),
+            basic_auth: vec![BasicAuthUser::new("user", "pass")],
             disallow_robots: true,
             environment: SecretHashMap::from_hashmap(hashmap! {
                 "FOO".to_string() => "BAR".to_string(),
@@ -370,6 +742,453 @@ mod tests {
         assert_eq!(environment.get("API_KEY").unwrap(), "1234");
     }
 
+    #[test]
+    fn test_traefik_idle_ttl_adds_activity_middleware() {
+        let mut global_settings =
+            crate::api::test_utils::settings_with_secret_key(Settings::default(), "secret");
+        global_settings.traefik.activity_url = Some("http://scotty:21342/".into());
+
+        let app_settings = AppSettings {
+            domain: "example.com".to_string(),
+            public_services: vec![ServicePortMapping {
+                service: "web".to_string(),
+                port: 8080,
+                domains: vec![],
+                path: None,
+                strip_path: false,
+            }],
+            disallow_robots: false,
+            time_to_live: AppTtl::Idle(8),
+            ..Default::default()
+        };
+
+        let result = TraefikLoadBalancer
+            .get_docker_compose_override(
+                &global_settings,
+                "myapp",
+                &app_settings,
+                &HashMap::new(),
+                &["web".to_string()],
+            )
+            .unwrap();
+        let labels = result.services["web"].labels.as_ref().unwrap();
+
+        assert_eq!(
+            labels
+                .get("traefik.http.middlewares.web--myapp--activity.forwardauth.address")
+                .unwrap(),
+            &format!(
+                "http://scotty:21342/api/v1/activity/myapp?token={}",
+                crate::api::signing::activity_token(&global_settings, "myapp").unwrap()
+            )
+        );
+        assert_eq!(
+            labels
+                .get("traefik.http.routers.web--myapp-0.middlewares")
+                .unwrap(),
+            "web--myapp--activity"
+        );
+    }
+
+    #[test]
+    fn test_traefik_access_middlewares() {
+        let mut global_settings = Settings::default();
+        global_settings.traefik.activity_url = Some("http://scotty:21342/".into());
+
+        let app_settings = AppSettings {
+            domain: "example.com".to_string(),
+            public_services: vec![ServicePortMapping {
+                service: "web".to_string(),
+                port: 8080,
+                domains: vec![],
+                path: None,
+                strip_path: false,
+            }],
+            ip_allowlist: vec!["10.0.0.0/8".to_string(), "192.168.1.1".to_string()],
+            forward_auth: true,
+            basic_auth: vec![BasicAuthUser::new("user", "pass")],
+            disallow_robots: false,
+            ..Default::default()
+        };
+
+        let result = TraefikLoadBalancer
+            .get_docker_compose_override(
+                &global_settings,
+                "myapp",
+                &app_settings,
+                &HashMap::new(),
+                &["web".to_string()],
+            )
+            .unwrap();
+        let labels = result.services["web"].labels.as_ref().unwrap();
+
+        assert_eq!(
+            labels
+                .get("traefik.http.middlewares.web--myapp--ip-allowlist.ipallowlist.sourcerange")
+                .unwrap(),
+            "10.0.0.0/8,192.168.1.1"
+        );
+        assert_eq!(
+            labels
+                .get("traefik.http.middlewares.web--myapp--forward-auth.forwardauth.address")
+                .unwrap(),
+            "http://scotty:21342/api/v1/forward-auth/check/myapp"
+        );
+        assert_eq!(
+            labels
+                .get(
+                    "traefik.http.middlewares.web--myapp--forward-auth.forwardauth.authResponseHeaders"
+                )
+                .unwrap(),
+            "X-Forwarded-User"
+        );
+        assert_eq!(
+            labels
+                .get("traefik.http.routers.web--myapp-0.middlewares")
+                .unwrap(),
+            "web--myapp--ip-allowlist,web--myapp--forward-auth,web--myapp--basic-auth"
+        );
+    }
+
+    #[test]
+    fn test_traefik_headers_redirects_and_maintenance() {
+        let mut global_settings = Settings::default();
+        global_settings.traefik.use_tls = true;
+
+        let mut app_settings = AppSettings {
+            domain: "example.com".to_string(),
+            public_services: vec![ServicePortMapping {
+                service: "web".to_string(),
+                port: 8080,
+                domains: vec!["example.com".to_string(), "www.example.com".to_string()],
+                path: None,
+                strip_path: false,
+            }],
+            response_headers: [(
+                "Content-Security-Policy".to_string(),
+                "default-src 'self'".to_string(),
+            )]
+            .into(),
+            redirects: vec![RedirectRule::new("www.example.com", "example.com")],
+            disallow_robots: false,
+            ..Default::default()
+        };
+
+        let labels = |app_settings: &AppSettings| {
+            TraefikLoadBalancer
+                .get_docker_compose_override(
+                    &global_settings,
+                    "myapp",
+                    app_settings,
+                    &HashMap::new(),
+                    &["web".to_string()],
+                )
+                .unwrap()
+                .services["web"]
+                .labels
+                .clone()
+                .unwrap()
+        };
+
+        let result = labels(&app_settings);
+        assert_eq!(
+            result
+                .get("traefik.http.middlewares.web--myapp--redirect-0.redirectregex.regex")
+                .unwrap(),
+            r"^https?://www\.example\.com(.*)"
+        );
+        assert_eq!(
+            result
+                .get("traefik.http.middlewares.web--myapp--redirect-0.redirectregex.replacement")
+                .unwrap(),
+            "https://example.com$${1}"
+        );
+        assert_eq!(
+            result
+                .get("traefik.http.middlewares.web--myapp--headers.headers.customresponseheaders.Content-Security-Policy")
+                .unwrap(),
+            "default-src 'self'"
+        );
+        assert_eq!(
+            result
+                .get("traefik.http.routers.web--myapp-1.middlewares")
+                .unwrap(),
+            "web--myapp--redirect-0,web--myapp--headers"
+        );
+
+        // The maintenance mode leaves the labels alone, so the containers
+        // are not recreated
+        let before = labels(&app_settings);
+        app_settings.maintenance = true;
+        assert_eq!(labels(&app_settings), before);
+    }
+
+    #[test]
+    fn test_traefik_path_routing() {
+        let global_settings = Settings::default();
+        let app_settings = AppSettings {
+            domain: "example.com".to_string(),
+            public_services: vec![
+                ServicePortMapping {
+                    service: "web".to_string(),
+                    port: 80,
+                    domains: vec!["example.com".to_string()],
+                    path: None,
+                    strip_path: false,
+                },
+                ServicePortMapping {
+                    service: "api".to_string(),
+                    port: 8080,
+                    domains: vec!["example.com".to_string()],
+                    path: Some("/api".to_string()),
+                    strip_path: true,
+                },
+            ],
+            disallow_robots: false,
+            ..Default::default()
+        };
+
+        let result = TraefikLoadBalancer
+            .get_docker_compose_override(
+                &global_settings,
+                "myapp",
+                &app_settings,
+                &HashMap::new(),
+                &["web".to_string(), "api".to_string()],
+            )
+            .unwrap();
+
+        let web_labels = result.services["web"].labels.as_ref().unwrap();
+        assert_eq!(
+            web_labels
+                .get("traefik.http.routers.web--myapp-0.rule")
+                .unwrap(),
+            "Host(`example.com`)"
+        );
+        assert!(web_labels
+            .get("traefik.http.routers.web--myapp-0.priority")
+            .is_none());
+
+        let api_labels = result.services["api"].labels.as_ref().unwrap();
+        assert_eq!(
+            api_labels
+                .get("traefik.http.routers.api--myapp-0.rule")
+                .unwrap(),
+            "Host(`example.com`) && PathPrefix(`/api`)"
+        );
+        assert_eq!(
+            api_labels
+                .get("traefik.http.routers.api--myapp-0.priority")
+                .unwrap(),
+            "1004"
+        );
+        assert_eq!(
+            api_labels
+                .get("traefik.http.middlewares.api--myapp--strip-path.stripprefix.prefixes")
+                .unwrap(),
+            "/api"
+        );
+        assert_eq!(
+            api_labels
+                .get("traefik.http.routers.api--myapp-0.middlewares")
+                .unwrap(),
+            "api--myapp--strip-path"
+        );
+    }
+
+    #[test]
+    fn test_traefik_tcp_services() {
+        use scotty_core::apps::app_data::TcpServiceMapping;
+
+        let mut global_settings = Settings::default();
+        global_settings.traefik.certresolver = Some("myresolver".to_string());
+
+        let mut db = TcpServiceMapping::new("db", 5432, TransportProtocol::Tcp, TcpRouting::Sni);
+        db.public_port = Some(443);
+        let mut dns = TcpServiceMapping::new("dns", 53, TransportProtocol::Udp, TcpRouting::Port);
+        dns.public_port = Some(20001);
+        let app_settings = AppSettings {
+            domain: "myapp.example.com".to_string(),
+            tcp_services: vec![db, dns],
+            ..Default::default()
+        };
+
+        let result = TraefikLoadBalancer
+            .get_docker_compose_override(
+                &global_settings,
+                "myapp",
+                &app_settings,
+                &HashMap::new(),
+                &["db".to_string(), "dns".to_string()],
+            )
+            .unwrap();
+
+        let db = &result.services["db"];
+        let labels = db.labels.as_ref().unwrap();
+        assert_eq!(
+            labels
+                .get("traefik.tcp.routers.db--myapp--tcp.rule")
+                .unwrap(),
+            "HostSNI(`db.myapp.example.com`)"
+        );
+        assert_eq!(
+            labels
+                .get("traefik.tcp.routers.db--myapp--tcp.entrypoints")
+                .unwrap(),
+            "tcp"
+        );
+        assert_eq!(
+            labels
+                .get("traefik.tcp.routers.db--myapp--tcp.tls.certresolver")
+                .unwrap(),
+            "myresolver"
+        );
+        assert_eq!(
+            labels
+                .get("traefik.tcp.services.db--myapp--tcp.loadbalancer.server.port")
+                .unwrap(),
+            "5432"
+        );
+        assert!(db.networks.as_ref().unwrap().contains_key("proxy--myapp"));
+
+        let labels = result.services["dns"].labels.as_ref().unwrap();
+        assert!(labels
+            .get("traefik.udp.routers.dns--myapp--udp.rule")
+            .is_none());
+        assert_eq!(
+            labels
+                .get("traefik.udp.routers.dns--myapp--udp.entrypoints")
+                .unwrap(),
+            "udp-20001"
+        );
+        assert_eq!(
+            labels
+                .get("traefik.udp.services.dns--myapp--udp.loadbalancer.server.port")
+                .unwrap(),
+            "53"
+        );
+    }
+
+    #[test]
+    fn test_traefik_app_tls_settings() {
+        use scotty_core::apps::app_data::{AppTlsSettings, DomainTlsSettings, ServicePortMapping};
+        use std::collections::BTreeMap;
+
+        let mut global_settings = Settings::default();
+        global_settings.traefik.use_tls = true;
+        global_settings.traefik.certresolver = Some("myresolver".to_string());
+
+        let app_settings = AppSettings {
+            domain: "myapp.example.com".to_string(),
+            public_services: vec![ServicePortMapping {
+                service: "web".to_string(),
+                port: 8080,
+                domains: vec![
+                    "shop.example.org".to_string(),
+                    "web.myapp.example.com".to_string(),
+                ],
+                path: None,
+                strip_path: false,
+            }],
+            tls: AppTlsSettings {
+                certresolver: Some("dns".to_string()),
+                domains: vec![TlsDomain {
+                    main: "myapp.example.com".to_string(),
+                    sans: vec!["*.myapp.example.com".to_string()],
+                }],
+                per_domain: BTreeMap::from([(
+                    "shop.example.org".to_string(),
+                    DomainTlsSettings {
+                        certificate: Some("shop".to_string()),
+                        ..Default::default()
+                    },
+                )]),
+            },
+            ..Default::default()
+        };
+
+        let result = TraefikLoadBalancer
+            .get_docker_compose_override(
+                &global_settings,
+                "myapp",
+                &app_settings,
+                &HashMap::new(),
+                &["web".to_string()],
+            )
+            .unwrap();
+        let labels = result.services["web"].labels.as_ref().unwrap();
+
+        // The first router serves the uploaded certificate from Traefik's store
+        assert_eq!(
+            labels
+                .get("traefik.http.routers.web--myapp-0.rule")
+                .unwrap(),
+            "Host(`shop.example.org`)"
+        );
+        assert_eq!(
+            labels.get("traefik.http.routers.web--myapp-0.tls").unwrap(),
+            "true"
+        );
+        assert!(labels
+            .get("traefik.http.routers.web--myapp-0.tls.certresolver")
+            .is_none());
+
+        // The second domain gets the wildcard certificate of the app resolver
+        assert_eq!(
+            labels
+                .get("traefik.http.routers.web--myapp-1.tls.certresolver")
+                .unwrap(),
+            "dns"
+        );
+        assert_eq!(
+            labels
+                .get("traefik.http.routers.web--myapp-1.tls.domains[0].main")
+                .unwrap(),
+            "myapp.example.com"
+        );
+        assert_eq!(
+            labels
+                .get("traefik.http.routers.web--myapp-1.tls.domains[0].sans")
+                .unwrap(),
+            "*.myapp.example.com"
+        );
+    }
+
+    #[test]
+    fn test_traefik_file_provider_disables_labels() {
+        let mut global_settings = Settings::default();
+        global_settings.traefik.provider = TraefikProvider::File;
+
+        let app_settings = AppSettings {
+            domain: "example.com".to_string(),
+            public_services: vec![ServicePortMapping {
+                service: "web".to_string(),
+                port: 8080,
+                domains: vec![],
+                path: None,
+                strip_path: false,
+            }],
+            ..Default::default()
+        };
+
+        let result = TraefikLoadBalancer
+            .get_docker_compose_override(
+                &global_settings,
+                "myapp",
+                &app_settings,
+                &HashMap::new(),
+                &["web".to_string()],
+            )
+            .unwrap();
+        let web = &result.services["web"];
+        let labels = web.labels.as_ref().unwrap();
+
+        assert_eq!(labels.get("traefik.enable").unwrap(), "false");
+        assert!(!labels.keys().any(|key| key.starts_with("traefik.http.")));
+        assert!(web.networks.is_some());
+        assert!(TraefikLoadBalancer.applies_routing_without_recreate(&global_settings));
+    }
+
     #[test]
     fn test_traefik_override_serializes_to_valid_compose() {
         let global_settings = Settings {
@@ -382,6 +1201,8 @@ mod tests {
                 service: "nginx".to_string(),
                 port: 80,
                 domains: vec![],
+                path: None,
+                strip_path: false,
             }],
             ..Default::default()
         };
@@ -439,8 +1260,10 @@ mod tests {
                 service: "web".to_string(),
                 port: 8080,
                 domains: vec![],
+                path: None,
+                strip_path: false,
             }],
-            basic_auth: None,
+            basic_auth: vec![],
             disallow_robots: false,
             environment: SecretHashMap::from_hashmap(hashmap! {
                 "FOO".to_string() => "BAR".to_string(),
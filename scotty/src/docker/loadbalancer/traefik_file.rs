@@ -0,0 +1,797 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use tracing::info;
+
+use crate::settings::config::Settings;
+use scotty_core::apps::app_data::{AppSettings, AppTtl, TlsDomain};
+
+use super::traefik::{
+    path_router_priority, redirect_regex, router_rule, router_tls, scotty_endpoint, tcp_routes,
+    RouterTlsSettings,
+};
+use super::{activity_path, htpasswd, ROBOTS_HEADER_VALUE};
+use scotty_core::apps::app_data::TransportProtocol;
+
+/// Dynamic configuration of an app for Traefik's file provider.
+///
+/// Mirrors the labels written by the docker provider mode, so routers and
+/// middlewares keep their names when switching between both modes.
+#[derive(Debug, Serialize, Default)]
+pub struct DynamicConfig {
+    pub http: HttpConfig,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tcp: Option<StreamConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub udp: Option<StreamConfig>,
+}
+
+#[derive(Debug, Serialize, Default)]
+pub struct HttpConfig {
+    pub routers: BTreeMap<String, Router>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub middlewares: BTreeMap<String, Middleware>,
+    pub services: BTreeMap<String, Service>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Router {
+    pub rule: String,
+    pub service: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub priority: Option<usize>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub middlewares: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tls: Option<RouterTls>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RouterTls {
+    #[serde(rename = "certResolver", skip_serializing_if = "Option::is_none")]
+    pub cert_resolver: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub domains: Vec<TlsDomain>,
+}
+
+impl From<RouterTlsSettings> for RouterTls {
+    fn from(tls: RouterTlsSettings) -> Self {
+        RouterTls {
+            cert_resolver: tls.certresolver,
+            domains: tls.domains,
+        }
+    }
+}
+
+/// A middleware, exactly one of the fields is set.
+///
+/// Modelled as a struct as YAML serializers write externally tagged enums as
+/// YAML tags, which Traefik does not understand.
+#[derive(Debug, Serialize, Default)]
+pub struct Middleware {
+    #[serde(rename = "redirectRegex", skip_serializing_if = "Option::is_none")]
+    pub redirect_regex: Option<RedirectRegexMiddleware>,
+    #[serde(rename = "ipAllowList", skip_serializing_if = "Option::is_none")]
+    pub ip_allow_list: Option<IpAllowListMiddleware>,
+    #[serde(rename = "basicAuth", skip_serializing_if = "Option::is_none")]
+    pub basic_auth: Option<BasicAuthMiddleware>,
+    #[serde(rename = "forwardAuth", skip_serializing_if = "Option::is_none")]
+    pub forward_auth: Option<ForwardAuthMiddleware>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub headers: Option<HeadersMiddleware>,
+    #[serde(rename = "stripPrefix", skip_serializing_if = "Option::is_none")]
+    pub strip_prefix: Option<StripPrefixMiddleware>,
+    #[serde(rename = "replacePath", skip_serializing_if = "Option::is_none")]
+    pub replace_path: Option<ReplacePathMiddleware>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RedirectRegexMiddleware {
+    pub regex: String,
+    pub replacement: String,
+    pub permanent: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct IpAllowListMiddleware {
+    #[serde(rename = "sourceRange")]
+    pub source_range: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BasicAuthMiddleware {
+    pub users: Vec<String>,
+    #[serde(rename = "removeHeader")]
+    pub remove_header: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ForwardAuthMiddleware {
+    pub address: String,
+    #[serde(rename = "authResponseHeaders", skip_serializing_if = "Vec::is_empty")]
+    pub auth_response_headers: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HeadersMiddleware {
+    #[serde(rename = "customResponseHeaders")]
+    pub custom_response_headers: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StripPrefixMiddleware {
+    pub prefixes: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReplacePathMiddleware {
+    pub path: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Service {
+    #[serde(rename = "loadBalancer")]
+    pub load_balancer: ServiceLoadBalancer,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ServiceLoadBalancer {
+    pub servers: Vec<ServiceServer>,
+    #[serde(rename = "passHostHeader", skip_serializing_if = "Option::is_none")]
+    pub pass_host_header: Option<bool>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ServiceServer {
+    pub url: String,
+}
+
+/// Routers and services of TCP respectively UDP services
+#[derive(Debug, Serialize, Default)]
+pub struct StreamConfig {
+    pub routers: BTreeMap<String, StreamRouter>,
+    pub services: BTreeMap<String, StreamService>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StreamRouter {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rule: Option<String>,
+    #[serde(rename = "entryPoints")]
+    pub entry_points: Vec<String>,
+    pub service: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tls: Option<RouterTls>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StreamService {
+    #[serde(rename = "loadBalancer")]
+    pub load_balancer: StreamServiceLoadBalancer,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StreamServiceLoadBalancer {
+    pub servers: Vec<StreamServiceServer>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StreamServiceServer {
+    pub address: String,
+}
+
+/// Path of the dynamic configuration file of an app
+pub fn dynamic_config_file(global_settings: &Settings, app_name: &str) -> PathBuf {
+    PathBuf::from(&global_settings.traefik.dynamic_config_folder).join(format!("{app_name}.yml"))
+}
+
+/// Path of the file sending the requests of an app in maintenance to scotty
+pub fn maintenance_config_file(global_settings: &Settings, app_name: &str) -> PathBuf {
+    PathBuf::from(&global_settings.traefik.dynamic_config_folder)
+        .join(format!("scotty-maintenance-{app_name}.yml"))
+}
+
+/// Priority of the maintenance routers, above the routers of the app's
+/// containers, including the ones with a path prefix.
+const MAINTENANCE_ROUTER_PRIORITY: usize = 100_000;
+
+/// Build the routers, middlewares and services of all public services of an app.
+///
+/// Traefik reaches the services through their app-scoped alias on the
+/// per-app proxy network.
+pub fn build_dynamic_config(
+    global_settings: &Settings,
+    app_name: &str,
+    settings: &AppSettings,
+) -> anyhow::Result<DynamicConfig> {
+    let mut config = DynamicConfig::default();
+    let http = &mut config.http;
+
+    // Apps in maintenance have no routers, so their requests reach the
+    // default backend, i.e. scotty's maintenance page
+    let public_services = match settings.maintenance {
+        true => &[][..],
+        false => &settings.public_services[..],
+    };
+    for service in public_services {
+        let service_name = format!("{}--{}", service.service, app_name);
+
+        http.services.insert(
+            service_name.clone(),
+            Service {
+                load_balancer: ServiceLoadBalancer {
+                    servers: vec![ServiceServer {
+                        url: format!("http://{}:{}", service_name, service.port),
+                    }],
+                    pass_host_header: None,
+                },
+            },
+        );
+
+        let mut middlewares = vec![];
+
+        for (idx, rule) in settings.redirects.iter().enumerate() {
+            let middleware_name = format!("{}--redirect-{}", service_name, idx);
+            let (regex, replacement) = redirect_regex(rule, global_settings.traefik.use_tls);
+            http.middlewares.insert(
+                middleware_name.clone(),
+                Middleware {
+                    redirect_regex: Some(RedirectRegexMiddleware {
+                        regex,
+                        replacement,
+                        permanent: rule.permanent,
+                    }),
+                    ..Default::default()
+                },
+            );
+            middlewares.push(middleware_name);
+        }
+
+        if !settings.ip_allowlist.is_empty() {
+            let middleware_name = format!("{}--{}", service_name, "ip-allowlist");
+            http.middlewares.insert(
+                middleware_name.clone(),
+                Middleware {
+                    ip_allow_list: Some(IpAllowListMiddleware {
+                        source_range: settings.ip_allowlist.clone(),
+                    }),
+                    ..Default::default()
+                },
+            );
+            middlewares.push(middleware_name);
+        }
+
+        if settings.forward_auth {
+            let middleware_name = format!("{}--{}", service_name, "forward-auth");
+            http.middlewares.insert(
+                middleware_name.clone(),
+                Middleware {
+                    forward_auth: Some(ForwardAuthMiddleware {
+                        address: scotty_endpoint(
+                            global_settings,
+                            &format!("/api/v1/forward-auth/check/{}", app_name),
+                        ),
+                        auth_response_headers: vec!["X-Forwarded-User".to_string()],
+                    }),
+                    ..Default::default()
+                },
+            );
+            middlewares.push(middleware_name);
+        }
+
+        if !settings.basic_auth.is_empty() {
+            let middleware_name = format!("{}--{}", service_name, "basic-auth");
+            let users = settings
+                .basic_auth
+                .iter()
+                .map(|user| Ok(format!("{}:{}", user.username, htpasswd(user, false)?)))
+                .collect::<anyhow::Result<Vec<_>>>()?;
+            http.middlewares.insert(
+                middleware_name.clone(),
+                Middleware {
+                    basic_auth: Some(BasicAuthMiddleware {
+                        users,
+                        remove_header: true,
+                    }),
+                    ..Default::default()
+                },
+            );
+            middlewares.push(middleware_name);
+        }
+
+        if let AppTtl::Idle(_) = settings.time_to_live {
+            let middleware_name = format!("{}--{}", service_name, "activity");
+            http.middlewares.insert(
+                middleware_name.clone(),
+                Middleware {
+                    forward_auth: Some(ForwardAuthMiddleware {
+                        address: scotty_endpoint(
+                            global_settings,
+                            &activity_path(global_settings, app_name)?,
+                        ),
+                        auth_response_headers: vec![],
+                    }),
+                    ..Default::default()
+                },
+            );
+            middlewares.push(middleware_name);
+        }
+
+        if settings.disallow_robots {
+            let middleware_name = format!("{}--{}", service_name, "robots");
+            http.middlewares.insert(
+                middleware_name.clone(),
+                Middleware {
+                    headers: Some(HeadersMiddleware {
+                        custom_response_headers: BTreeMap::from([(
+                            "X-Robots-Tag".to_string(),
+                            ROBOTS_HEADER_VALUE.to_string(),
+                        )]),
+                    }),
+                    ..Default::default()
+                },
+            );
+            middlewares.push(middleware_name);
+        }
+
+        if !settings.response_headers.is_empty() {
+            let middleware_name = format!("{}--{}", service_name, "headers");
+            http.middlewares.insert(
+                middleware_name.clone(),
+                Middleware {
+                    headers: Some(HeadersMiddleware {
+                        custom_response_headers: settings.response_headers.clone(),
+                    }),
+                    ..Default::default()
+                },
+            );
+            middlewares.push(middleware_name);
+        }
+
+        middlewares.extend(settings.middlewares.iter().cloned());
+
+        if let Some(path) = service.path.as_ref().filter(|_| service.strip_path) {
+            let middleware_name = format!("{}--{}", service_name, "strip-path");
+            http.middlewares.insert(
+                middleware_name.clone(),
+                Middleware {
+                    strip_prefix: Some(StripPrefixMiddleware {
+                        prefixes: vec![path.clone()],
+                    }),
+                    ..Default::default()
+                },
+            );
+            middlewares.push(middleware_name);
+        }
+
+        for (idx, domain) in service.get_domains(&settings.domain).iter().enumerate() {
+            http.routers.insert(
+                format!("{}-{}", service_name, idx),
+                Router {
+                    rule: router_rule(domain, service.path.as_deref()),
+                    service: service_name.clone(),
+                    priority: service.path.as_deref().map(path_router_priority),
+                    middlewares: middlewares.clone(),
+                    tls: global_settings
+                        .traefik
+                        .use_tls
+                        .then(|| router_tls(global_settings, settings, domain).into()),
+                },
+            );
+        }
+    }
+
+    for route in tcp_routes(global_settings, app_name, settings)? {
+        let stream = match route.protocol {
+            TransportProtocol::Tcp => config.tcp.get_or_insert_with(Default::default),
+            TransportProtocol::Udp => config.udp.get_or_insert_with(Default::default),
+        };
+        stream.services.insert(
+            route.name.clone(),
+            StreamService {
+                load_balancer: StreamServiceLoadBalancer {
+                    servers: vec![StreamServiceServer {
+                        address: format!("{}:{}", route.host, route.port),
+                    }],
+                },
+            },
+        );
+        stream.routers.insert(
+            route.name.clone(),
+            StreamRouter {
+                rule: route.rule,
+                entry_points: vec![route.entrypoint],
+                service: route.name,
+                tls: route.tls.map(RouterTls::from),
+            },
+        );
+    }
+
+    Ok(config)
+}
+
+/// Build the routers sending all requests to the domains of an app to
+/// scotty's maintenance page.
+///
+/// Used while the app is routed via container labels, which stay in place, so
+/// the containers are not touched. The routers win by their priority and
+/// replace the path, scotty is reached without the host of the app, so the
+/// request can't end up at the maintenance routers again.
+pub fn build_maintenance_config(
+    global_settings: &Settings,
+    app_name: &str,
+    settings: &AppSettings,
+) -> DynamicConfig {
+    let mut config = DynamicConfig::default();
+    let http = &mut config.http;
+    let name = format!("maintenance--{}", app_name);
+
+    http.services.insert(
+        name.clone(),
+        Service {
+            load_balancer: ServiceLoadBalancer {
+                servers: vec![ServiceServer {
+                    url: scotty_endpoint(global_settings, ""),
+                }],
+                pass_host_header: Some(false),
+            },
+        },
+    );
+    http.middlewares.insert(
+        name.clone(),
+        Middleware {
+            replace_path: Some(ReplacePathMiddleware {
+                path: format!("/api/v1/maintenance/{}", app_name),
+            }),
+            ..Default::default()
+        },
+    );
+
+    let mut domains: Vec<String> = settings
+        .public_services
+        .iter()
+        .flat_map(|service| service.get_domains(&settings.domain))
+        .collect();
+    domains.sort();
+    domains.dedup();
+    for (idx, domain) in domains.iter().enumerate() {
+        http.routers.insert(
+            format!("{}-{}", name, idx),
+            Router {
+                rule: router_rule(domain, None),
+                service: name.clone(),
+                priority: Some(MAINTENANCE_ROUTER_PRIORITY),
+                middlewares: vec![name.clone()],
+                tls: global_settings
+                    .traefik
+                    .use_tls
+                    .then(|| router_tls(global_settings, settings, domain).into()),
+            },
+        );
+    }
+
+    config
+}
+
+/// Write a dynamic configuration file.
+///
+/// The file is written next to its final location and renamed afterwards, so
+/// Traefik never reads a partially written file.
+fn write_config_file(
+    global_settings: &Settings,
+    file: &Path,
+    config: &DynamicConfig,
+) -> anyhow::Result<()> {
+    std::fs::create_dir_all(&global_settings.traefik.dynamic_config_folder)?;
+
+    // Traefik only reads files ending in .yml, .yaml or .toml
+    let file_name = file
+        .file_name()
+        .ok_or_else(|| anyhow::anyhow!("Invalid file name {}", file.display()))?;
+    let tmp_file = file.with_file_name(format!(".{}.tmp", file_name.to_string_lossy()));
+    std::fs::write(&tmp_file, serde_norway::to_string(config)?)?;
+    std::fs::rename(&tmp_file, file)?;
+    info!("Wrote traefik dynamic config {}", file.display());
+    Ok(())
+}
+
+fn remove_config_file(file: &Path) -> anyhow::Result<()> {
+    if file.exists() {
+        info!("Removing traefik dynamic config {}", file.display());
+        std::fs::remove_file(file)?;
+    }
+    Ok(())
+}
+
+/// Write the dynamic configuration of an app.
+pub fn write_dynamic_config(
+    global_settings: &Settings,
+    app_name: &str,
+    settings: &AppSettings,
+) -> anyhow::Result<()> {
+    let config = build_dynamic_config(global_settings, app_name, settings)?;
+    write_config_file(
+        global_settings,
+        &dynamic_config_file(global_settings, app_name),
+        &config,
+    )
+}
+
+pub fn remove_dynamic_config(global_settings: &Settings, app_name: &str) -> anyhow::Result<()> {
+    remove_config_file(&dynamic_config_file(global_settings, app_name))
+}
+
+/// Write or remove the maintenance routers of an app routed via labels,
+/// depending on its maintenance mode.
+pub fn write_maintenance_config(
+    global_settings: &Settings,
+    app_name: &str,
+    settings: &AppSettings,
+) -> anyhow::Result<()> {
+    let file = maintenance_config_file(global_settings, app_name);
+    match settings.maintenance {
+        true => write_config_file(
+            global_settings,
+            &file,
+            &build_maintenance_config(global_settings, app_name, settings),
+        ),
+        false => remove_config_file(&file),
+    }
+}
+
+pub fn remove_maintenance_config(global_settings: &Settings, app_name: &str) -> anyhow::Result<()> {
+    remove_config_file(&maintenance_config_file(global_settings, app_name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use scotty_core::apps::app_data::{BasicAuthUser, RedirectRule, ServicePortMapping};
+    use scotty_core::settings::loadbalancer::{TraefikProvider, TraefikSettings};
+
+    fn file_provider_settings(folder: &str) -> Settings {
+        Settings {
+            traefik: TraefikSettings {
+                use_tls: true,
+                certresolver: Some("myresolver".to_string()),
+                provider: TraefikProvider::File,
+                dynamic_config_folder: folder.to_string(),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    fn app_settings() -> AppSettings {
+        AppSettings {
+            domain: "example.com".to_string(),
+            public_services: vec![ServicePortMapping {
+                service: "web".to_string(),
+                port: 8080,
+                domains: vec![],
+                path: None,
+                strip_path: false,
+            }],
+            basic_auth: vec![BasicAuthUser::new("user", "pass")],
+            disallow_robots: true,
+            middlewares: vec!["custom@file".to_string()],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_build_dynamic_config() {
+        let global_settings = file_provider_settings("./dynamic");
+        let config = build_dynamic_config(&global_settings, "myapp", &app_settings()).unwrap();
+        let yaml: serde_norway::Value =
+            serde_norway::from_str(&serde_norway::to_string(&config).unwrap()).unwrap();
+
+        let router = &yaml["http"]["routers"]["web--myapp-0"];
+        assert_eq!(router["rule"].as_str().unwrap(), "Host(`web.example.com`)");
+        assert_eq!(router["service"].as_str().unwrap(), "web--myapp");
+        assert_eq!(
+            router["tls"]["certResolver"].as_str().unwrap(),
+            "myresolver"
+        );
+        let middlewares: Vec<&str> = router["middlewares"]
+            .as_sequence()
+            .unwrap()
+            .iter()
+            .map(|m| m.as_str().unwrap())
+            .collect();
+        assert_eq!(
+            middlewares,
+            vec![
+                "web--myapp--basic-auth",
+                "web--myapp--robots",
+                "custom@file"
+            ]
+        );
+
+        assert_eq!(
+            yaml["http"]["services"]["web--myapp"]["loadBalancer"]["servers"][0]["url"]
+                .as_str()
+                .unwrap(),
+            "http://web--myapp:8080"
+        );
+
+        // Hashes are not escaped, the file is not interpolated by compose
+        let basic_auth = &yaml["http"]["middlewares"]["web--myapp--basic-auth"]["basicAuth"];
+        let user = basic_auth["users"][0].as_str().unwrap();
+        let (name, hash) = user.split_once(':').unwrap();
+        assert_eq!(name, "user");
+        assert!(bcrypt::verify("pass", hash).unwrap());
+        assert!(basic_auth["removeHeader"].as_bool().unwrap());
+
+        assert_eq!(
+            yaml["http"]["middlewares"]["web--myapp--robots"]["headers"]["customResponseHeaders"]
+                ["X-Robots-Tag"]
+                .as_str()
+                .unwrap(),
+            ROBOTS_HEADER_VALUE
+        );
+    }
+
+    #[test]
+    fn test_build_dynamic_config_access_middlewares() {
+        let global_settings = file_provider_settings("./dynamic");
+        let settings = AppSettings {
+            ip_allowlist: vec!["10.0.0.0/8".to_string(), "192.168.1.1".to_string()],
+            forward_auth: true,
+            basic_auth: vec![],
+            disallow_robots: false,
+            middlewares: vec![],
+            ..app_settings()
+        };
+        let config = build_dynamic_config(&global_settings, "myapp", &settings).unwrap();
+        let yaml: serde_norway::Value =
+            serde_norway::from_str(&serde_norway::to_string(&config).unwrap()).unwrap();
+
+        let middlewares: Vec<&str> = yaml["http"]["routers"]["web--myapp-0"]["middlewares"]
+            .as_sequence()
+            .unwrap()
+            .iter()
+            .map(|m| m.as_str().unwrap())
+            .collect();
+        assert_eq!(
+            middlewares,
+            vec!["web--myapp--ip-allowlist", "web--myapp--forward-auth"]
+        );
+
+        let source_range: Vec<&str> = yaml["http"]["middlewares"]["web--myapp--ip-allowlist"]
+            ["ipAllowList"]["sourceRange"]
+            .as_sequence()
+            .unwrap()
+            .iter()
+            .map(|r| r.as_str().unwrap())
+            .collect();
+        assert_eq!(source_range, vec!["10.0.0.0/8", "192.168.1.1"]);
+
+        let forward_auth = &yaml["http"]["middlewares"]["web--myapp--forward-auth"]["forwardAuth"];
+        assert!(forward_auth["address"]
+            .as_str()
+            .unwrap()
+            .ends_with("/api/v1/forward-auth/check/myapp"));
+        assert_eq!(
+            forward_auth["authResponseHeaders"][0].as_str().unwrap(),
+            "X-Forwarded-User"
+        );
+    }
+
+    #[test]
+    fn test_build_dynamic_config_headers_redirects_and_maintenance() {
+        let global_settings = file_provider_settings("./dynamic");
+        let mut settings = AppSettings {
+            response_headers: [(
+                "Strict-Transport-Security".to_string(),
+                "max-age=31536000".to_string(),
+            )]
+            .into(),
+            redirects: vec![RedirectRule {
+                permanent: false,
+                ..RedirectRule::new("www.example.com", "example.com")
+            }],
+            basic_auth: vec![],
+            disallow_robots: false,
+            middlewares: vec![],
+            ..app_settings()
+        };
+        let yaml = |settings: &AppSettings| -> serde_norway::Value {
+            let config = build_dynamic_config(&global_settings, "myapp", settings).unwrap();
+            serde_norway::from_str(&serde_norway::to_string(&config).unwrap()).unwrap()
+        };
+
+        let config = yaml(&settings);
+        let redirect = &config["http"]["middlewares"]["web--myapp--redirect-0"]["redirectRegex"];
+        assert_eq!(
+            redirect["regex"].as_str().unwrap(),
+            r"^https?://www\.example\.com(.*)"
+        );
+        assert_eq!(
+            redirect["replacement"].as_str().unwrap(),
+            "https://example.com${1}"
+        );
+        assert!(!redirect["permanent"].as_bool().unwrap());
+        assert_eq!(
+            config["http"]["middlewares"]["web--myapp--headers"]["headers"]
+                ["customResponseHeaders"]["Strict-Transport-Security"]
+                .as_str()
+                .unwrap(),
+            "max-age=31536000"
+        );
+        assert_eq!(
+            config["http"]["routers"]["web--myapp-0"]["middlewares"][0]
+                .as_str()
+                .unwrap(),
+            "web--myapp--redirect-0"
+        );
+
+        settings.maintenance = true;
+        let config = yaml(&settings);
+        assert!(config["http"]["routers"].as_mapping().unwrap().is_empty());
+        assert!(config["http"]["services"].as_mapping().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_write_and_remove_dynamic_config() {
+        let folder = tempfile::tempdir().unwrap();
+        let dynamic_folder = folder.path().join("dynamic");
+        let global_settings = file_provider_settings(dynamic_folder.to_str().unwrap());
+
+        write_dynamic_config(&global_settings, "myapp", &app_settings()).unwrap();
+        let file = dynamic_folder.join("myapp.yml");
+        assert!(file.exists());
+        assert!(!dynamic_folder.join(".myapp.yml.tmp").exists());
+
+        remove_dynamic_config(&global_settings, "myapp").unwrap();
+        assert!(!file.exists());
+        // Removing a missing file is fine
+        remove_dynamic_config(&global_settings, "myapp").unwrap();
+    }
+
+    #[test]
+    fn test_write_maintenance_config() {
+        let folder = tempfile::tempdir().unwrap();
+        let dynamic_folder = folder.path().join("dynamic");
+        let global_settings = Settings {
+            traefik: TraefikSettings {
+                dynamic_config_folder: dynamic_folder.to_str().unwrap().to_string(),
+                activity_url: Some("http://scotty:21342".to_string()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let mut settings = app_settings();
+        settings.maintenance = true;
+
+        write_maintenance_config(&global_settings, "myapp", &settings).unwrap();
+        let file = dynamic_folder.join("scotty-maintenance-myapp.yml");
+        let yaml: serde_norway::Value =
+            serde_norway::from_str(&std::fs::read_to_string(&file).unwrap()).unwrap();
+
+        let router = &yaml["http"]["routers"]["maintenance--myapp-0"];
+        assert_eq!(router["rule"].as_str().unwrap(), "Host(`web.example.com`)");
+        assert_eq!(
+            router["priority"].as_u64().unwrap(),
+            MAINTENANCE_ROUTER_PRIORITY as u64
+        );
+        assert_eq!(
+            yaml["http"]["middlewares"]["maintenance--myapp"]["replacePath"]["path"]
+                .as_str()
+                .unwrap(),
+            "/api/v1/maintenance/myapp"
+        );
+        let load_balancer = &yaml["http"]["services"]["maintenance--myapp"]["loadBalancer"];
+        assert_eq!(
+            load_balancer["servers"][0]["url"].as_str().unwrap(),
+            "http://scotty:21342"
+        );
+        assert!(!load_balancer["passHostHeader"].as_bool().unwrap());
+
+        // Leaving the maintenance removes the routers again
+        settings.maintenance = false;
+        write_maintenance_config(&global_settings, "myapp", &settings).unwrap();
+        assert!(!file.exists());
+    }
+}
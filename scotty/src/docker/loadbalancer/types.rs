@@ -76,4 +76,31 @@ pub trait LoadBalancerImpl {
         resolved_environment: &HashMap<String, String>,
         all_services: &[String],
     ) -> anyhow::Result<DockerComposeConfig>;
+
+    /// Write files the load balancer reads from disk instead of from the
+    /// containers, e.g. htpasswd files. Called whenever the override is written.
+    fn write_support_files(
+        &self,
+        _global_settings: &Settings,
+        _app_name: &str,
+        _settings: &AppSettings,
+    ) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    /// Whether routing changes like basic auth only need `write_support_files`
+    /// instead of recreating the containers.
+    fn applies_routing_without_recreate(&self, _global_settings: &Settings) -> bool {
+        false
+    }
+
+    /// Remove the files written by `write_support_files` when the app is destroyed.
+    fn remove_support_files(
+        &self,
+        _global_settings: &Settings,
+        _app_name: &str,
+        _settings: &AppSettings,
+    ) -> anyhow::Result<()> {
+        Ok(())
+    }
 }
@@ -1,16 +1,21 @@
 pub mod create_app;
 pub mod destroy_app;
 pub mod docker_compose;
+pub mod drift_checker;
 pub mod find_apps;
 pub mod helper;
 pub mod loadbalancer;
 pub mod purge_app;
 pub mod rebuild_app;
+pub mod revisions;
+pub mod rollback_app;
 pub mod run_app;
 pub mod run_app_custom_action;
+pub mod schedule_checker;
 pub mod services;
 pub mod setup;
 pub mod state_machine_handlers;
 pub mod stop_app;
 pub mod ttl_checker;
+pub mod update_app;
 pub mod validation;
@@ -87,10 +87,11 @@ pub async fn purge_app_prepare(
 pub async fn purge_app(
     app_state: SharedAppState,
     app: &AppData,
+    requested_by: Option<&str>,
 ) -> anyhow::Result<RunningAppContext> {
     if app.status == AppStatus::Unsupported {
         return Err(AppError::OperationNotSupportedForLegacyApp(app.name.clone()).into());
     }
     let sm = purge_app_prepare(app, PurgeAppMethod::Rm).await?;
-    run_sm(app_state, app, sm).await
+    run_sm(app_state, app, sm, requested_by).await
 }
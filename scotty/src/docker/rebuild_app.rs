@@ -3,6 +3,13 @@ use std::sync::Arc;
 use tracing::{info, instrument};
 
 use super::helper::run_sm;
+use crate::docker::state_machine_handlers::blue_green_handler::{
+    BlueGreenProject, BlueGreenRollbackHandler, RemoveBlueGreenCandidateHandler,
+    StartBlueGreenCandidateHandler,
+};
+use crate::docker::state_machine_handlers::revision_handler::{
+    CreateRevisionHandler, RollbackRevisionHandler,
+};
 use crate::docker::state_machine_handlers::wait_for_all_containers_handler::WaitForAllContainersHandler;
 use crate::{
     api::error::AppError,
@@ -16,27 +23,35 @@ use crate::{
         task_completion_handler::TaskCompletionHandler,
         update_app_data_handler::UpdateAppDataHandler,
     },
+    state_machine::StateHandler,
     state_machine::StateMachine,
 };
-use scotty_core::apps::app_data::{AppData, AppStatus};
+use scotty_core::apps::app_data::{AppData, AppStatus, RebuildStrategy};
 use scotty_core::notification_types::{Message, MessageType};
 use scotty_core::settings::app_blueprint::ActionName;
+use scotty_core::settings::loadbalancer::LoadBalancerType;
 use scotty_core::tasks::running_app_context::RunningAppContext;
 
 #[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
 pub enum RebuildAppStates {
+    CreateRevision,
     RecreateLoadBalancerConfig,
     RunDockerLogin,
     EnsureAppNetwork,
     RunDockerComposePull,
     RunDockerComposeBuild,
+    StartBlueGreenCandidate,
+    WaitForBlueGreenCandidate,
+    SwitchToBlueGreenCandidate,
     RunDockerComposeStop,
     RunDockerComposeRun,
     WaitForAllContainers,
+    RemoveBlueGreenCandidate,
     RunPostActions,
     UpdateAppData,
     SetFinished,
     SetFailed,
+    RollbackBlueGreen,
     Done,
 }
 
@@ -53,16 +68,48 @@ pub async fn rebuild_app_prepare(
 
     let start_with_recreate = app.settings.is_some() && recreate_load_balancer_config;
 
+    // Blue/green needs running containers to switch away from, otherwise a
+    // plain rebuild is just as fast and has no downtime to avoid. The switch
+    // relies on Traefik picking up the routers from the container labels.
+    let blue_green = match &app.settings {
+        Some(settings) => {
+            start_with_recreate
+                && settings.rebuild_strategy == RebuildStrategy::BlueGreen
+                && app.status == AppStatus::Running
+                && app_state.settings.load_balancer_type == LoadBalancerType::Traefik
+        }
+        None => false,
+    };
+    let blue_green_project = match blue_green {
+        true => Some(BlueGreenProject::new(std::path::Path::new(
+            &app.docker_compose_path,
+        ))?),
+        false => None,
+    };
+
     let mut sm = StateMachine::new(
         match start_with_recreate {
-            true => RebuildAppStates::RecreateLoadBalancerConfig,
+            true => RebuildAppStates::CreateRevision,
             false => RebuildAppStates::RunDockerLogin,
         },
         RebuildAppStates::Done,
     );
-    sm.set_error_state(RebuildAppStates::SetFailed);
+    sm.set_error_state(match blue_green {
+        true => RebuildAppStates::RollbackBlueGreen,
+        false => RebuildAppStates::SetFailed,
+    });
 
     if start_with_recreate {
+        // A nested rebuild (e.g. from create_app) is covered by the revision
+        // of the outer state machine.
+        sm.add_handler(
+            RebuildAppStates::CreateRevision,
+            Arc::new(CreateRevisionHandler::<RebuildAppStates> {
+                next_state: RebuildAppStates::RecreateLoadBalancerConfig,
+                reason: "rebuild".to_string(),
+                applied_files: vec![],
+            }),
+        );
         sm.add_handler(
             RebuildAppStates::RecreateLoadBalancerConfig,
             Arc::new(CreateLoadBalancerConfig::<RebuildAppStates> {
@@ -100,11 +147,79 @@ pub async fn rebuild_app_prepare(
     sm.add_handler(
         RebuildAppStates::RunDockerComposeBuild,
         Arc::new(RunDockerComposeHandler::<RebuildAppStates> {
-            next_state: RebuildAppStates::RunDockerComposeStop,
+            next_state: match blue_green {
+                true => RebuildAppStates::StartBlueGreenCandidate,
+                false => RebuildAppStates::RunDockerComposeStop,
+            },
             command: ["build"].iter().map(|s| s.to_string()).collect(),
             env: app.get_environment(),
         }),
     );
+    if let Some(project) = &blue_green_project {
+        // Start the new containers next to the old ones and only stop the old
+        // public services once the new set is healthy, so Traefik routes all
+        // traffic to the new set. The app's own containers are then recreated
+        // with the new version and take the traffic back.
+        let public_services: Vec<String> = app
+            .settings
+            .as_ref()
+            .map(|settings| {
+                settings
+                    .public_services
+                    .iter()
+                    .map(|service| service.service.clone())
+                    .collect()
+            })
+            .unwrap_or_default();
+        sm.add_handler(
+            RebuildAppStates::StartBlueGreenCandidate,
+            Arc::new(StartBlueGreenCandidateHandler::<RebuildAppStates> {
+                next_state: RebuildAppStates::WaitForBlueGreenCandidate,
+                project: project.clone(),
+                services: public_services.clone(),
+                env: app.get_environment(),
+            }),
+        );
+        sm.add_handler(
+            RebuildAppStates::WaitForBlueGreenCandidate,
+            Arc::new(WaitForAllContainersHandler::<RebuildAppStates> {
+                next_state: RebuildAppStates::SwitchToBlueGreenCandidate,
+                timeout_seconds: Some(300),
+                compose_project: Some(project.candidate_project.clone()),
+            }),
+        );
+        sm.add_handler(
+            RebuildAppStates::SwitchToBlueGreenCandidate,
+            Arc::new(RunDockerComposeHandler::<RebuildAppStates> {
+                next_state: RebuildAppStates::RunDockerComposeRun,
+                command: std::iter::once("stop".to_string())
+                    .chain(public_services.iter().cloned())
+                    .collect(),
+                env: app.get_environment(),
+            }),
+        );
+        sm.add_handler(
+            RebuildAppStates::RemoveBlueGreenCandidate,
+            Arc::new(RemoveBlueGreenCandidateHandler::<RebuildAppStates> {
+                next_state: RebuildAppStates::RunPostActions,
+                project: project.clone(),
+                env: app.get_environment(),
+            }),
+        );
+        // Restore the previous revision first, the new containers are only
+        // removed if the app's own containers run again afterwards.
+        sm.add_handler(
+            RebuildAppStates::RollbackBlueGreen,
+            Arc::new(RollbackRevisionHandler {
+                completion: BlueGreenRollbackHandler {
+                    project: project.clone(),
+                    services: public_services,
+                    env: app.get_environment(),
+                    completion: TaskCompletionHandler::failure(RebuildAppStates::Done, None),
+                },
+            }),
+        );
+    }
     sm.add_handler(
         RebuildAppStates::RunDockerComposeStop,
         Arc::new(RunDockerComposeHandler::<RebuildAppStates> {
@@ -124,8 +239,12 @@ pub async fn rebuild_app_prepare(
     sm.add_handler(
         RebuildAppStates::WaitForAllContainers,
         Arc::new(WaitForAllContainersHandler::<RebuildAppStates> {
-            next_state: RebuildAppStates::RunPostActions,
+            next_state: match blue_green {
+                true => RebuildAppStates::RemoveBlueGreenCandidate,
+                false => RebuildAppStates::RunPostActions,
+            },
             timeout_seconds: Some(300),
+            compose_project: None,
         }),
     );
     sm.add_handler(
@@ -149,10 +268,15 @@ pub async fn rebuild_app_prepare(
             Some(Message::new(MessageType::AppRebuilt, app)),
         )),
     );
-    sm.add_handler(
-        RebuildAppStates::SetFailed,
-        Arc::new(TaskCompletionHandler::failure(RebuildAppStates::Done, None)),
-    );
+    let failure = TaskCompletionHandler::failure(RebuildAppStates::Done, None);
+    let failure_handler: Arc<dyn StateHandler<RebuildAppStates, Context> + Send + Sync> =
+        match start_with_recreate {
+            true => Arc::new(RollbackRevisionHandler {
+                completion: failure,
+            }),
+            false => Arc::new(failure),
+        };
+    sm.add_handler(RebuildAppStates::SetFailed, failure_handler);
     Ok(sm)
 }
 
@@ -160,10 +284,11 @@ pub async fn rebuild_app_prepare(
 pub async fn rebuild_app(
     app_state: SharedAppState,
     app: &AppData,
+    requested_by: Option<&str>,
 ) -> anyhow::Result<RunningAppContext> {
     if app.status == AppStatus::Unsupported {
         return Err(AppError::OperationNotSupportedForLegacyApp(app.name.clone()).into());
     }
     let sm = rebuild_app_prepare(&app_state, app, true).await?;
-    run_sm(app_state, app, sm).await
+    run_sm(app_state, app, sm, requested_by).await
 }
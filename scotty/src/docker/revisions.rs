@@ -0,0 +1,475 @@
+use std::collections::BTreeSet;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use chrono::Utc;
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use scotty_core::apps::revision::RevisionInfo;
+use scotty_core::utils::slugify::slugify;
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+use crate::settings::config::Settings;
+
+/// File name of the app settings inside the app directory.
+const SETTINGS_FILE: &str = ".scotty.yml";
+
+/// Stores revisions of the configuration of a single app.
+///
+/// Every revision consists of a gzipped tar archive with the managed files of
+/// the app (compose file, generated override, `.scotty.yml` and uploaded
+/// files) and a JSON file with its metadata. Runtime data in the app
+/// directory is never part of a revision.
+#[derive(Debug, Clone)]
+pub struct RevisionStore {
+    folder: PathBuf,
+    keep: usize,
+}
+
+impl RevisionStore {
+    pub fn new(folder: PathBuf, keep: usize) -> Self {
+        Self { folder, keep }
+    }
+
+    /// Create the store for an app from settings, returns None if revisions are disabled.
+    pub fn for_app(settings: &Settings, app_name: &str) -> Option<Self> {
+        if !settings.revisions.enabled {
+            return None;
+        }
+        Some(Self::new(
+            settings
+                .revisions
+                .get_folder(&settings.apps.root_folder)
+                .join(slugify(app_name)),
+            settings.revisions.keep.max(1) as usize,
+        ))
+    }
+
+    fn archive_path(&self, revision: u32) -> PathBuf {
+        self.folder.join(format!("{revision}.tar.gz"))
+    }
+
+    fn metadata_path(&self, revision: u32) -> PathBuf {
+        self.folder.join(format!("{revision}.json"))
+    }
+
+    /// All revisions of the app, oldest first.
+    pub async fn list(&self) -> anyhow::Result<Vec<RevisionInfo>> {
+        let mut entries = match tokio::fs::read_dir(&self.folder).await {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut revisions = Vec::new();
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+                continue;
+            }
+            let content = tokio::fs::read_to_string(&path).await?;
+            match serde_json::from_str::<RevisionInfo>(&content) {
+                Ok(revision) => revisions.push(revision),
+                Err(e) => warn!("Skipping invalid revision {}: {}", path.display(), e),
+            }
+        }
+        revisions.sort_by_key(|revision| revision.revision);
+        Ok(revisions)
+    }
+
+    pub async fn get(&self, revision: u32) -> anyhow::Result<Option<RevisionInfo>> {
+        Ok(self
+            .list()
+            .await?
+            .into_iter()
+            .find(|info| info.revision == revision))
+    }
+
+    /// Revision created by the given task, if any.
+    pub async fn find_by_task(&self, task_id: &Uuid) -> anyhow::Result<Option<RevisionInfo>> {
+        Ok(self
+            .list()
+            .await?
+            .into_iter()
+            .rfind(|info| info.task_id.as_ref() == Some(task_id)))
+    }
+
+    /// Store the managed files of the app as a new revision.
+    ///
+    /// `applied_files` are the files the operation is about to write. They
+    /// are stored if they already exist and get removed again on restore if
+    /// they did not. Returns None if the app has no compose file yet, e.g.
+    /// for a new app.
+    pub async fn create(
+        &self,
+        app_directory: &Path,
+        compose_path: &Path,
+        reason: &str,
+        task_id: Option<Uuid>,
+        applied_files: &[String],
+    ) -> anyhow::Result<Option<RevisionInfo>> {
+        if !compose_path.exists() {
+            debug!(
+                "No compose file at {}, skipping revision",
+                compose_path.display()
+            );
+            return Ok(None);
+        }
+
+        let revisions = self.list().await?;
+        let latest = revisions.last();
+
+        let mut candidates: BTreeSet<String> = BTreeSet::new();
+        let mut add_candidate = |path: &Path| {
+            if let Ok(relative) = path.strip_prefix(app_directory) {
+                candidates.insert(relative.display().to_string());
+            }
+        };
+        add_candidate(compose_path);
+        if let Some(override_file) = scotty_core::utils::compose::get_override_file(compose_path) {
+            add_candidate(&override_file);
+        }
+        candidates.insert(SETTINGS_FILE.to_string());
+        if let Some(latest) = latest {
+            candidates.extend(latest.files.iter().cloned());
+            candidates.extend(latest.applied_files.iter().cloned());
+        }
+        candidates.extend(applied_files.iter().cloned());
+
+        let files: Vec<String> = candidates
+            .into_iter()
+            .filter(|file| {
+                resolve_path(app_directory, file)
+                    .map(|path| path.is_file())
+                    .unwrap_or(false)
+            })
+            .collect();
+
+        let info = RevisionInfo {
+            revision: latest.map(|info| info.revision + 1).unwrap_or(1),
+            created_at: Utc::now(),
+            reason: reason.to_string(),
+            task_id,
+            files,
+            applied_files: applied_files.to_vec(),
+        };
+
+        tokio::fs::create_dir_all(&self.folder).await?;
+        let archive_path = self.archive_path(info.revision);
+        let app_directory_buf = app_directory.to_path_buf();
+        let files = info.files.clone();
+        tokio::task::spawn_blocking(move || {
+            write_archive(&archive_path, &app_directory_buf, &files)
+        })
+        .await??;
+        tokio::fs::write(
+            self.metadata_path(info.revision),
+            serde_json::to_string_pretty(&info)?,
+        )
+        .await?;
+
+        info!(
+            "Created revision {} of {} with {} files",
+            info.revision,
+            app_directory.display(),
+            info.files.len()
+        );
+        self.prune().await?;
+        Ok(Some(info))
+    }
+
+    /// Restore the managed files of the app to the given revision.
+    ///
+    /// Files written by the operation which created the revision, or by any
+    /// later one, are removed if they are not part of the revision.
+    pub async fn restore(
+        &self,
+        app_directory: &Path,
+        revision: &RevisionInfo,
+    ) -> anyhow::Result<()> {
+        for path in self.added_after(app_directory, revision).await? {
+            info!("Removing {} added after revision", path.display());
+            tokio::fs::remove_file(&path).await?;
+        }
+
+        let archive_path = self.archive_path(revision.revision);
+        let app_directory_buf = app_directory.to_path_buf();
+        tokio::task::spawn_blocking(move || read_archive(&archive_path, &app_directory_buf))
+            .await??;
+
+        info!(
+            "Restored revision {} of {}",
+            revision.revision,
+            app_directory.display()
+        );
+        Ok(())
+    }
+
+    /// Check whether the files of the app are still the ones of the revision,
+    /// so restoring it would not change anything.
+    pub async fn matches_current(
+        &self,
+        app_directory: &Path,
+        revision: &RevisionInfo,
+    ) -> anyhow::Result<bool> {
+        if !self.added_after(app_directory, revision).await?.is_empty() {
+            return Ok(false);
+        }
+        let archive_path = self.archive_path(revision.revision);
+        let app_directory_buf = app_directory.to_path_buf();
+        tokio::task::spawn_blocking(move || archive_matches(&archive_path, &app_directory_buf))
+            .await?
+    }
+
+    /// Files applied by the revision or later ones which are not part of the
+    /// revision, but exist in the app directory.
+    async fn added_after(
+        &self,
+        app_directory: &Path,
+        revision: &RevisionInfo,
+    ) -> anyhow::Result<Vec<PathBuf>> {
+        let stored: BTreeSet<&String> = revision.files.iter().collect();
+        let mut added = vec![];
+        for later in self
+            .list()
+            .await?
+            .iter()
+            .filter(|info| info.revision >= revision.revision)
+        {
+            for file in later
+                .applied_files
+                .iter()
+                .filter(|file| !stored.contains(file))
+            {
+                if let Some(path) = resolve_path(app_directory, file) {
+                    if path.is_file() && !added.contains(&path) {
+                        added.push(path);
+                    }
+                }
+            }
+        }
+        Ok(added)
+    }
+
+    /// Remove the oldest revisions exceeding the configured limit.
+    async fn prune(&self) -> anyhow::Result<()> {
+        let revisions = self.list().await?;
+        if revisions.len() <= self.keep {
+            return Ok(());
+        }
+        for info in &revisions[..revisions.len() - self.keep] {
+            debug!("Pruning revision {}", info.revision);
+            let _ = tokio::fs::remove_file(self.archive_path(info.revision)).await;
+            tokio::fs::remove_file(self.metadata_path(info.revision)).await?;
+        }
+        Ok(())
+    }
+
+    /// Remove all revisions of the app.
+    pub async fn remove_all(&self) -> anyhow::Result<()> {
+        if self.folder.exists() {
+            tokio::fs::remove_dir_all(&self.folder).await?;
+        }
+        Ok(())
+    }
+}
+
+/// Resolve a file of a revision against the app directory, rejecting paths
+/// outside of it.
+fn resolve_path(app_directory: &Path, file: &str) -> Option<PathBuf> {
+    let path = path_clean::clean(app_directory.join(file));
+    path.starts_with(app_directory).then_some(path)
+}
+
+fn write_archive(
+    archive_path: &Path,
+    app_directory: &Path,
+    files: &[String],
+) -> anyhow::Result<()> {
+    let file = std::fs::File::create(archive_path)?;
+    let mut builder = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+    for name in files {
+        builder.append_path_with_name(app_directory.join(name), name)?;
+    }
+    builder.into_inner()?.finish()?;
+    Ok(())
+}
+
+fn read_archive(archive_path: &Path, app_directory: &Path) -> anyhow::Result<()> {
+    let file = std::fs::File::open(archive_path)?;
+    let mut archive = tar::Archive::new(GzDecoder::new(file));
+    archive.set_overwrite(true);
+    for entry in archive.entries()? {
+        // unpack_in refuses paths escaping the app directory
+        entry?.unpack_in(app_directory)?;
+    }
+    Ok(())
+}
+
+fn archive_matches(archive_path: &Path, app_directory: &Path) -> anyhow::Result<bool> {
+    let file = std::fs::File::open(archive_path)?;
+    let mut archive = tar::Archive::new(GzDecoder::new(file));
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let name = entry.path()?.display().to_string();
+        let Some(path) = resolve_path(app_directory, &name) else {
+            return Ok(false);
+        };
+        let mut stored = vec![];
+        entry.read_to_end(&mut stored)?;
+        match std::fs::read(&path) {
+            Ok(current) if current == stored => {}
+            _ => return Ok(false),
+        }
+    }
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup_app(dir: &Path) -> PathBuf {
+        let app_directory = dir.join("my-app");
+        std::fs::create_dir_all(&app_directory).unwrap();
+        std::fs::write(app_directory.join("compose.yml"), "services: {}").unwrap();
+        std::fs::write(app_directory.join("compose.override.yml"), "# generated").unwrap();
+        std::fs::write(app_directory.join(".scotty.yml"), "domain: old").unwrap();
+        app_directory
+    }
+
+    #[tokio::test]
+    async fn test_create_and_restore_revision() {
+        let dir = tempfile::tempdir().unwrap();
+        let app_directory = setup_app(dir.path());
+        std::fs::write(app_directory.join("data.db"), "runtime data").unwrap();
+        let store = RevisionStore::new(dir.path().join("revisions"), 10);
+
+        let task_id = Uuid::new_v4();
+        let applied = vec!["compose.yml".to_string(), "config/new.conf".to_string()];
+        let info = store
+            .create(
+                &app_directory,
+                &app_directory.join("compose.yml"),
+                "rebuild",
+                Some(task_id),
+                &applied,
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(info.revision, 1);
+        assert_eq!(
+            info.files,
+            vec![".scotty.yml", "compose.override.yml", "compose.yml"]
+        );
+
+        // Apply the change
+        std::fs::write(app_directory.join("compose.yml"), "services: {web: {}}").unwrap();
+        std::fs::write(app_directory.join(".scotty.yml"), "domain: new").unwrap();
+        std::fs::create_dir_all(app_directory.join("config")).unwrap();
+        std::fs::write(app_directory.join("config/new.conf"), "new").unwrap();
+
+        let found = store.find_by_task(&task_id).await.unwrap().unwrap();
+        store.restore(&app_directory, &found).await.unwrap();
+
+        assert_eq!(
+            std::fs::read_to_string(app_directory.join("compose.yml")).unwrap(),
+            "services: {}"
+        );
+        assert_eq!(
+            std::fs::read_to_string(app_directory.join(".scotty.yml")).unwrap(),
+            "domain: old"
+        );
+        assert!(!app_directory.join("config/new.conf").exists());
+        assert!(app_directory.join("data.db").exists());
+    }
+
+    #[tokio::test]
+    async fn test_matches_current() {
+        let dir = tempfile::tempdir().unwrap();
+        let app_directory = setup_app(dir.path());
+        let store = RevisionStore::new(dir.path().join("revisions"), 10);
+        let applied = vec!["config/new.conf".to_string()];
+        let info = store
+            .create(
+                &app_directory,
+                &app_directory.join("compose.yml"),
+                "update",
+                None,
+                &applied,
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(store.matches_current(&app_directory, &info).await.unwrap());
+
+        // A new file not part of the revision
+        std::fs::create_dir_all(app_directory.join("config")).unwrap();
+        std::fs::write(app_directory.join("config/new.conf"), "new").unwrap();
+        assert!(!store.matches_current(&app_directory, &info).await.unwrap());
+        std::fs::remove_file(app_directory.join("config/new.conf")).unwrap();
+
+        // A changed file
+        std::fs::write(app_directory.join(".scotty.yml"), "domain: new").unwrap();
+        assert!(!store.matches_current(&app_directory, &info).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_uploaded_files_carry_over_and_prune() {
+        let dir = tempfile::tempdir().unwrap();
+        let app_directory = setup_app(dir.path());
+        let compose_path = app_directory.join("compose.yml");
+        let store = RevisionStore::new(dir.path().join("revisions"), 2);
+
+        std::fs::write(app_directory.join("nginx.conf"), "v1").unwrap();
+        store
+            .create(
+                &app_directory,
+                &compose_path,
+                "create",
+                None,
+                &["nginx.conf".to_string()],
+            )
+            .await
+            .unwrap();
+        for _ in 0..2 {
+            let info = store
+                .create(&app_directory, &compose_path, "rebuild", None, &[])
+                .await
+                .unwrap()
+                .unwrap();
+            assert!(info.files.contains(&"nginx.conf".to_string()));
+        }
+
+        let revisions: Vec<u32> = store
+            .list()
+            .await
+            .unwrap()
+            .iter()
+            .map(|info| info.revision)
+            .collect();
+        assert_eq!(revisions, vec![2, 3]);
+        assert!(!store.archive_path(1).exists());
+    }
+
+    #[tokio::test]
+    async fn test_new_app_has_no_revision() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = RevisionStore::new(dir.path().join("revisions"), 10);
+        let app_directory = dir.path().join("new-app");
+
+        let info = store
+            .create(
+                &app_directory,
+                &app_directory.join("compose.yml"),
+                "create",
+                None,
+                &["compose.yml".to_string()],
+            )
+            .await
+            .unwrap();
+        assert!(info.is_none());
+        assert!(store.list().await.unwrap().is_empty());
+    }
+}
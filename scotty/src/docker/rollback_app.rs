@@ -0,0 +1,200 @@
+use std::sync::Arc;
+
+use tokio::sync::RwLock;
+use tracing::{info, instrument};
+
+use crate::{
+    api::error::AppError,
+    app_state::SharedAppState,
+    docker::{
+        revisions::RevisionStore,
+        state_machine_handlers::{
+            context::Context,
+            network_handler::EnsureAppNetworkHandler,
+            revision_handler::{
+                CreateRevisionHandler, RestoreRevisionHandler, RollbackRevisionHandler,
+            },
+            run_docker_login_handler::RunDockerLoginHandler,
+            run_task_and_wait::run_task_and_wait,
+            task_completion_handler::TaskCompletionHandler,
+            update_app_data_handler::UpdateAppDataHandler,
+            wait_for_all_containers_handler::WaitForAllContainersHandler,
+        },
+    },
+    state_machine::{StateHandler, StateMachine},
+};
+use scotty_core::apps::app_data::{AppData, AppStatus};
+use scotty_core::apps::revision::RevisionInfo;
+use scotty_core::notification_types::{Message, MessageType};
+use scotty_core::tasks::running_app_context::RunningAppContext;
+
+use super::helper::run_sm;
+
+/// Brings the app up with the restored files.
+///
+/// The environment is taken from the context, as restoring a revision also
+/// restores its settings.
+#[derive(Debug)]
+struct RunRestoredRevisionHandler {
+    next_state: RollbackAppStates,
+}
+
+#[async_trait::async_trait]
+impl StateHandler<RollbackAppStates, Context> for RunRestoredRevisionHandler {
+    async fn transition(
+        &self,
+        _from: &RollbackAppStates,
+        context: Arc<RwLock<Context>>,
+    ) -> anyhow::Result<RollbackAppStates> {
+        let context = context.read().await;
+        let docker_compose_path = std::path::PathBuf::from(&context.app_data.docker_compose_path);
+        let command = ["up", "-d", "--build", "--remove-orphans"];
+        run_task_and_wait(
+            &context,
+            &docker_compose_path,
+            "docker-compose",
+            &command,
+            &context.app_data.get_environment(),
+            &format!("docker-compose {}", command.join(" ")),
+        )
+        .await?;
+
+        Ok(self.next_state)
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+enum RollbackAppStates {
+    CreateRevision,
+    RestoreRevision,
+    RunDockerLogin,
+    EnsureAppNetwork,
+    RunDockerCompose,
+    WaitForAllContainers,
+    UpdateAppData,
+    SetFinished,
+    SetFailed,
+    Done,
+}
+
+#[instrument()]
+async fn rollback_app_prepare(
+    app: &AppData,
+    revision: &RevisionInfo,
+) -> anyhow::Result<StateMachine<RollbackAppStates, Context>> {
+    info!(
+        "Rolling back app {} to revision {}",
+        app.name, revision.revision
+    );
+
+    let mut sm = StateMachine::new(RollbackAppStates::CreateRevision, RollbackAppStates::Done);
+    sm.set_error_state(RollbackAppStates::SetFailed);
+
+    // The current configuration becomes a revision as well, so a rollback can
+    // be undone like any other change.
+    sm.add_handler(
+        RollbackAppStates::CreateRevision,
+        Arc::new(CreateRevisionHandler::<RollbackAppStates> {
+            next_state: RollbackAppStates::RestoreRevision,
+            reason: format!("rollback to revision {}", revision.revision),
+            applied_files: revision.files.clone(),
+        }),
+    );
+    sm.add_handler(
+        RollbackAppStates::RestoreRevision,
+        Arc::new(RestoreRevisionHandler::<RollbackAppStates> {
+            next_state: RollbackAppStates::RunDockerLogin,
+            revision: revision.revision,
+        }),
+    );
+    sm.add_handler(
+        RollbackAppStates::RunDockerLogin,
+        Arc::new(RunDockerLoginHandler::<RollbackAppStates> {
+            next_state: RollbackAppStates::EnsureAppNetwork,
+            registry: app.get_registry(),
+        }),
+    );
+    sm.add_handler(
+        RollbackAppStates::EnsureAppNetwork,
+        Arc::new(EnsureAppNetworkHandler::<RollbackAppStates> {
+            next_state: RollbackAppStates::RunDockerCompose,
+        }),
+    );
+    sm.add_handler(
+        RollbackAppStates::RunDockerCompose,
+        Arc::new(RunRestoredRevisionHandler {
+            next_state: RollbackAppStates::WaitForAllContainers,
+        }),
+    );
+    sm.add_handler(
+        RollbackAppStates::WaitForAllContainers,
+        Arc::new(WaitForAllContainersHandler::<RollbackAppStates> {
+            next_state: RollbackAppStates::UpdateAppData,
+            timeout_seconds: Some(300),
+            compose_project: None,
+        }),
+    );
+    sm.add_handler(
+        RollbackAppStates::UpdateAppData,
+        Arc::new(UpdateAppDataHandler::<RollbackAppStates> {
+            next_state: RollbackAppStates::SetFinished,
+        }),
+    );
+    sm.add_handler(
+        RollbackAppStates::SetFinished,
+        Arc::new(TaskCompletionHandler::success(
+            RollbackAppStates::Done,
+            Some(Message::new(MessageType::AppRolledBack, app)),
+        )),
+    );
+    sm.add_handler(
+        RollbackAppStates::SetFailed,
+        Arc::new(RollbackRevisionHandler {
+            completion: TaskCompletionHandler::failure(RollbackAppStates::Done, None),
+        }),
+    );
+
+    Ok(sm)
+}
+
+/// List the stored revisions of an app, oldest first.
+pub async fn list_revisions(
+    app_state: &SharedAppState,
+    app: &AppData,
+) -> anyhow::Result<Vec<RevisionInfo>> {
+    match RevisionStore::for_app(&app_state.settings, &app.name) {
+        Some(store) => store.list().await,
+        None => Ok(vec![]),
+    }
+}
+
+/// Restore a revision of the app and bring it up again.
+///
+/// Without an explicit revision the latest one is restored, which holds the
+/// configuration from before the last change.
+#[instrument(skip(app_state))]
+pub async fn rollback_app(
+    app_state: SharedAppState,
+    app: &AppData,
+    revision: Option<u32>,
+    requested_by: Option<&str>,
+) -> anyhow::Result<RunningAppContext> {
+    if app.status == AppStatus::Unsupported {
+        return Err(AppError::OperationNotSupportedForLegacyApp(app.name.clone()).into());
+    }
+
+    let revisions = list_revisions(&app_state, app).await?;
+    let revision = match revision {
+        Some(revision) => revisions
+            .into_iter()
+            .find(|info| info.revision == revision)
+            .ok_or_else(|| AppError::RevisionNotFound(app.name.clone(), revision))?,
+        None => revisions
+            .into_iter()
+            .next_back()
+            .ok_or_else(|| AppError::NoRevisionsAvailable(app.name.clone()))?,
+    };
+
+    let sm = rollback_app_prepare(app, &revision).await?;
+    run_sm(app_state, app, sm, requested_by).await
+}
@@ -70,6 +70,7 @@ async fn run_app_prepare(app: &AppData) -> anyhow::Result<StateMachine<RunAppSta
         Arc::new(WaitForAllContainersHandler::<RunAppStates> {
             next_state: RunAppStates::RunPostActions,
             timeout_seconds: Some(60),
+            compose_project: None,
         }),
     );
     sm.add_handler(
@@ -105,13 +106,14 @@ async fn run_app_prepare(app: &AppData) -> anyhow::Result<StateMachine<RunAppSta
 pub async fn run_app(
     app_state: SharedAppState,
     app: &AppData,
+    requested_by: Option<&str>,
 ) -> anyhow::Result<RunningAppContext> {
     if app.status == AppStatus::Unsupported {
         return Err(AppError::OperationNotSupportedForLegacyApp(app.name.clone()).into());
     }
 
     let sm = run_app_prepare(app).await?;
-    let result = run_sm(app_state.clone(), app, sm).await;
+    let result = run_sm(app_state.clone(), app, sm, requested_by).await;
 
     match &result {
         Ok(_) => {
@@ -168,11 +168,12 @@ pub async fn run_app_custom_action(
     app_state: SharedAppState,
     app: &AppData,
     action: ActionName,
+    requested_by: Option<&str>,
 ) -> anyhow::Result<RunningAppContext> {
     if app.status == AppStatus::Unsupported {
         return Err(AppError::OperationNotSupportedForLegacyApp(app.name.clone()).into());
     }
 
     let sm = run_app_custom_action_prepare(&app_state, app, action).await?;
-    run_sm(app_state, app, sm).await
+    run_sm(app_state, app, sm, requested_by).await
 }
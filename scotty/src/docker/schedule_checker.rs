@@ -0,0 +1,151 @@
+use chrono::{DateTime, Utc};
+use tracing::{info, instrument, warn};
+
+use crate::app_state::SharedAppState;
+use crate::docker::{run_app::run_app, stop_app::stop_app};
+use scotty_core::apps::app_data::{AppData, AppStatus, ScheduledAction, ScheduledTransition};
+
+/// How far back transitions missed while scotty was not running are looked up
+const MISSED_TRANSITION_LOOKBACK_DAYS: i64 = 7;
+
+/// Start or stop an app if its schedule has a transition between `since` and `until`.
+///
+/// Only transitions are acted upon, an app started manually outside of its
+/// schedule keeps running until the next scheduled stop.
+#[instrument(skip(app_state, app), fields(app = %app.name))]
+pub async fn check_app_schedule(
+    app_state: SharedAppState,
+    app: &AppData,
+    since: DateTime<Utc>,
+    until: DateTime<Utc>,
+) -> anyhow::Result<()> {
+    let Some(schedule) = app.settings.as_ref().and_then(|s| s.schedule.as_ref()) else {
+        return Ok(());
+    };
+    let Some(transition) = schedule.due_transition(since, until)? else {
+        return Ok(());
+    };
+    if !needs_transition(app, transition.action) {
+        info!(
+            "Skipping scheduled {} of {}, app is {}",
+            transition.action, app.name, app.status
+        );
+        return Ok(());
+    }
+
+    info!("Scheduled {} of {}", transition.action, app.name);
+    match transition.action {
+        ScheduledAction::Start => run_app(app_state.clone(), app, Some("scheduler")).await?,
+        ScheduledAction::Stop => stop_app(app_state.clone(), app, Some("scheduler")).await?,
+    };
+    Ok(())
+}
+
+/// Log the last transition of the schedule of an app if it was missed while
+/// scotty was not running, so the app is not in the state it should be in.
+///
+/// Missed transitions are not applied, as starting or stopping apps right
+/// after scotty started might come unexpected. The app keeps its state until
+/// the next scheduled transition.
+pub fn log_missed_transition(
+    app: &AppData,
+    now: DateTime<Utc>,
+) -> anyhow::Result<Option<ScheduledTransition>> {
+    let Some(schedule) = app.settings.as_ref().and_then(|s| s.schedule.as_ref()) else {
+        return Ok(None);
+    };
+    let since = now - chrono::Duration::days(MISSED_TRANSITION_LOOKBACK_DAYS);
+    let Some(transition) = schedule.due_transition(since, now)? else {
+        return Ok(None);
+    };
+    if !needs_transition(app, transition.action) {
+        return Ok(None);
+    }
+
+    let next = schedule
+        .next_transition(now)?
+        .map(|next| format!("{} at {}", next.action, next.at))
+        .unwrap_or_else(|| "none".to_string());
+    warn!(
+        "Missed scheduled {} of {} at {} while scotty was not running, the app stays {} until its next scheduled transition ({})",
+        transition.action, app.name, transition.at, app.status, next
+    );
+    Ok(Some(transition))
+}
+
+/// Whether the app is in a state where the scheduled action changes something
+fn needs_transition(app: &AppData, action: ScheduledAction) -> bool {
+    match action {
+        ScheduledAction::Start => app.status == AppStatus::Stopped,
+        ScheduledAction::Stop => matches!(app.status, AppStatus::Running | AppStatus::Starting),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use scotty_core::apps::app_data::{AppSchedule, AppSettings};
+
+    #[test]
+    fn test_log_missed_transition() {
+        let app = |status| AppData {
+            name: "my-app".to_string(),
+            status,
+            settings: Some(AppSettings {
+                schedule: Some(AppSchedule {
+                    start_at: Some("0 8 * * 1-5".to_string()),
+                    stop_at: Some("0 19 * * 1-5".to_string()),
+                    timezone: None,
+                }),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        // Monday, 2024-06-17 10:00 UTC, the app should have been started at 08:00
+        let now = Utc.with_ymd_and_hms(2024, 6, 17, 10, 0, 0).unwrap();
+
+        let missed = log_missed_transition(&app(AppStatus::Stopped), now)
+            .unwrap()
+            .unwrap();
+        assert_eq!(missed.action, ScheduledAction::Start);
+        assert_eq!(
+            missed.at,
+            Utc.with_ymd_and_hms(2024, 6, 17, 8, 0, 0).unwrap()
+        );
+
+        assert_eq!(
+            log_missed_transition(&app(AppStatus::Running), now).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_needs_transition() {
+        let app = |status| AppData {
+            status,
+            ..Default::default()
+        };
+
+        assert!(needs_transition(
+            &app(AppStatus::Stopped),
+            ScheduledAction::Start
+        ));
+        assert!(!needs_transition(
+            &app(AppStatus::Running),
+            ScheduledAction::Start
+        ));
+        assert!(needs_transition(
+            &app(AppStatus::Running),
+            ScheduledAction::Stop
+        ));
+        assert!(!needs_transition(
+            &app(AppStatus::Stopped),
+            ScheduledAction::Stop
+        ));
+        assert!(!needs_transition(
+            &app(AppStatus::Destroying),
+            ScheduledAction::Stop
+        ));
+    }
+}
@@ -1,8 +1,17 @@
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use tokio::sync::Mutex;
 use tracing::instrument;
 
 use crate::{
     app_state::SharedAppState,
-    docker::{find_apps::find_apps, ttl_checker::check_app_ttl},
+    docker::{
+        drift_checker::check_app_drift,
+        find_apps::find_apps,
+        schedule_checker::{check_app_schedule, log_missed_transition},
+        ttl_checker::check_app_ttl,
+    },
 };
 
 pub async fn setup_docker_integration(
@@ -11,6 +20,7 @@ pub async fn setup_docker_integration(
     // Find all running apps on startup.
 
     schedule_app_check(app_state.clone()).await;
+    log_missed_schedule_transitions(&app_state).await;
 
     // Setup the scheduler to check for running apps.
     let stop_flag = app_state.clone().stop_flag.clone();
@@ -47,6 +57,34 @@ pub async fn setup_docker_integration(
                 }
             });
     }
+    {
+        // Start and stop apps according to their schedules.
+        let app_state = app_state.clone();
+        let last_check = Arc::new(Mutex::new(Utc::now()));
+        scheduler
+            .every(app_state.settings.scheduler.schedule_check.clone().into())
+            .run(move || {
+                let app_state = app_state.clone();
+                let last_check = last_check.clone();
+                async move {
+                    let now = Utc::now();
+                    let since = std::mem::replace(&mut *last_check.lock().await, now);
+                    schedule_start_stop_check(app_state, since, now).await;
+                }
+            });
+    }
+    {
+        // Compare the load balancer config of all apps with the generated one.
+        let app_state = app_state.clone();
+        scheduler
+            .every(app_state.settings.scheduler.drift_check.clone().into())
+            .run(move || {
+                let app_state = app_state.clone();
+                async move {
+                    schedule_drift_check(app_state).await;
+                }
+            });
+    }
     {
         let app_state = app_state.clone();
         scheduler
@@ -138,12 +176,18 @@ pub async fn setup_docker_integration(
     // Handle the scheduler in a separate task.
     let handle = crate::metrics::spawn_instrumented({
         let stop_flag = stop_flag.clone();
+        let app_state = app_state.clone();
         async move {
             while !stop_flag.is_stopped() {
                 scheduler.run_pending().await;
                 tokio::time::sleep(std::time::Duration::from_millis(100)).await;
             }
 
+            // Keep the activity recorded since the last TTL check
+            if let Err(e) = app_state.activity.save().await {
+                tracing::error!("Failed to save app activity: {:?}", e);
+            }
+
             Ok(())
         }
     });
@@ -172,6 +216,9 @@ async fn schedule_app_check(app_state: SharedAppState) {
 #[instrument(skip(app_state))]
 async fn schedule_ttl_check(app_state: SharedAppState) {
     tracing::info!("Checking ttl on running apps");
+    if let Err(e) = app_state.activity.save().await {
+        tracing::error!("Failed to save app activity: {:?}", e);
+    }
     let apps = app_state.apps.get_apps().await;
     for app in apps.apps.iter() {
         match check_app_ttl(app_state.clone(), app).await {
@@ -184,3 +231,42 @@ async fn schedule_ttl_check(app_state: SharedAppState) {
         }
     }
 }
+
+#[instrument(skip(app_state))]
+async fn schedule_drift_check(app_state: SharedAppState) {
+    tracing::info!("Checking load balancer config of all apps");
+    let apps = app_state.apps.get_apps().await;
+    let app_names: Vec<String> = apps.apps.iter().map(|app| app.name.clone()).collect();
+    app_state.drift_reports.retain(&app_names).await;
+    for app in apps.apps.iter() {
+        if let Err(e) = check_app_drift(app_state.clone(), app).await {
+            tracing::error!("Drift check failed for app: {} - {:?}", app.name, e);
+        }
+    }
+}
+
+/// Schedules are only checked from now on, log the transitions apps missed
+/// while scotty was not running.
+async fn log_missed_schedule_transitions(app_state: &SharedAppState) {
+    let now = Utc::now();
+    let apps = app_state.apps.get_apps().await;
+    for app in apps.apps.iter() {
+        if let Err(e) = log_missed_transition(app, now) {
+            tracing::error!("Schedule check failed for app: {} - {:?}", app.name, e);
+        }
+    }
+}
+
+#[instrument(skip(app_state))]
+async fn schedule_start_stop_check(
+    app_state: SharedAppState,
+    since: DateTime<Utc>,
+    until: DateTime<Utc>,
+) {
+    let apps = app_state.apps.get_apps().await;
+    for app in apps.apps.iter() {
+        if let Err(e) = check_app_schedule(app_state.clone(), app, since, until).await {
+            tracing::error!("Schedule check failed for app: {} - {:?}", app.name, e);
+        }
+    }
+}
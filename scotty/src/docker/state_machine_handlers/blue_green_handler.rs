@@ -0,0 +1,529 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use scotty_core::utils::secret::SecretHashMap;
+use serde::Serialize;
+use tokio::sync::RwLock;
+use tracing::{info, instrument, warn};
+
+use crate::{
+    docker::docker_compose::run_docker_compose_now,
+    onepassword::lookup::resolve_environment_variables, state_machine::StateHandler,
+};
+
+use super::{context::Context, run_task_and_wait::run_task_and_wait};
+
+/// Suffix of the temporary compose project holding the new containers during a
+/// blue/green rebuild.
+pub const BLUE_GREEN_PROJECT_SUFFIX: &str = "--next";
+
+/// Compose file which attaches the temporary containers to the default network
+/// of the running app, so they can reach its other services, and lets them use
+/// the images and named volumes of the app.
+pub const BLUE_GREEN_COMPOSE_FILE: &str = ".scotty-blue-green.yml";
+
+/// A network or volume of the app, referenced by its full name.
+#[derive(Debug, Serialize, PartialEq)]
+struct ExternalResource {
+    external: bool,
+    name: String,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+struct BlueGreenServiceConfig {
+    image: String,
+}
+
+/// Content of [`BLUE_GREEN_COMPOSE_FILE`].
+#[derive(Debug, Serialize)]
+struct BlueGreenComposeConfig {
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    services: BTreeMap<String, BlueGreenServiceConfig>,
+    networks: BTreeMap<String, ExternalResource>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    volumes: BTreeMap<String, ExternalResource>,
+}
+
+/// The compose projects involved in a blue/green rebuild.
+#[derive(Debug, Clone)]
+pub struct BlueGreenProject {
+    /// Compose project of the running app
+    pub project: String,
+    /// Temporary compose project for the new containers
+    pub candidate_project: String,
+    compose_path: PathBuf,
+}
+
+impl BlueGreenProject {
+    pub fn new(compose_path: &Path) -> anyhow::Result<Self> {
+        let project =
+            scotty_core::utils::compose::get_project_name(compose_path).ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Unable to determine compose project name for {}",
+                    compose_path.display()
+                )
+            })?;
+        Ok(Self {
+            candidate_project: format!("{}{}", project, BLUE_GREEN_PROJECT_SUFFIX),
+            project,
+            compose_path: compose_path.to_path_buf(),
+        })
+    }
+
+    fn blue_green_compose_file(&self) -> PathBuf {
+        self.compose_path.with_file_name(BLUE_GREEN_COMPOSE_FILE)
+    }
+
+    /// Compose arguments selecting the temporary project and its compose files.
+    ///
+    /// Passing `-f` disables the automatic merge of the override file, so it is
+    /// added explicitly to keep the Traefik labels and the per-app network.
+    fn compose_args(&self) -> Vec<String> {
+        let mut args = vec![
+            "-p".to_string(),
+            self.candidate_project.clone(),
+            "-f".to_string(),
+            self.compose_path.display().to_string(),
+        ];
+        if let Some(override_file) =
+            scotty_core::utils::compose::get_override_file(&self.compose_path)
+                .filter(|path| path.exists())
+        {
+            args.push("-f".to_string());
+            args.push(override_file.display().to_string());
+        }
+        args.push("-f".to_string());
+        args.push(self.blue_green_compose_file().display().to_string());
+        args
+    }
+
+    /// The compose files of the app, merged by compose in this order.
+    fn app_compose_files(&self) -> Vec<serde_norway::Value> {
+        let override_file = scotty_core::utils::compose::get_override_file(&self.compose_path);
+        std::iter::once(self.compose_path.clone())
+            .chain(override_file)
+            .filter_map(|path| std::fs::read_to_string(path).ok())
+            .filter_map(|content| serde_norway::from_str(&content).ok())
+            .collect()
+    }
+
+    /// Builds the blue/green compose file.
+    ///
+    /// Services built from source use the image `docker compose build` tagged
+    /// for the app's project, so nothing gets built twice. Named volumes are
+    /// declared external with the name they have in the app's project, so the
+    /// new containers see the same data as the old ones.
+    fn blue_green_compose_config(&self) -> BlueGreenComposeConfig {
+        let files = self.app_compose_files();
+        let mapping = |file: &serde_norway::Value, key: &str| {
+            file.get(key)
+                .and_then(|value| value.as_mapping())
+                .cloned()
+                .unwrap_or_default()
+        };
+
+        let mut built = BTreeMap::new();
+        let mut volumes = BTreeMap::new();
+        for file in &files {
+            for (name, service) in mapping(file, "services") {
+                let Some(name) = name.as_str() else {
+                    continue;
+                };
+                let entry = built.entry(name.to_string()).or_insert((false, false));
+                entry.0 |= service.get("build").is_some();
+                entry.1 |= service.get("image").is_some();
+            }
+            for (name, volume) in mapping(file, "volumes") {
+                let Some(name) = name.as_str() else {
+                    continue;
+                };
+                let external = volume
+                    .get("external")
+                    .and_then(|external| external.as_bool())
+                    .unwrap_or(false);
+                let explicit_name = volume.get("name").is_some();
+                // External volumes and volumes with an explicit name are
+                // shared between projects already.
+                let shared = volumes.entry(name.to_string()).or_insert(false);
+                *shared |= external || explicit_name;
+            }
+        }
+
+        let services = built
+            .into_iter()
+            .filter(|(_, (has_build, has_image))| *has_build && !*has_image)
+            .map(|(name, _)| {
+                let image = format!("{}-{}", self.project, name);
+                (name, BlueGreenServiceConfig { image })
+            })
+            .collect();
+        let volumes = volumes
+            .into_iter()
+            .filter(|(_, shared)| !*shared)
+            .map(|(name, _)| {
+                let resource = ExternalResource {
+                    external: true,
+                    name: format!("{}_{}", self.project, name),
+                };
+                (name, resource)
+            })
+            .collect();
+        let networks = BTreeMap::from([(
+            "default".to_string(),
+            ExternalResource {
+                external: true,
+                name: format!("{}_default", self.project),
+            },
+        )]);
+
+        BlueGreenComposeConfig {
+            services,
+            networks,
+            volumes,
+        }
+    }
+}
+
+/// Stops and removes the temporary containers and their compose file.
+async fn remove_candidate(
+    context: &Context,
+    project: &BlueGreenProject,
+    env: &SecretHashMap,
+) -> anyhow::Result<()> {
+    let resolved_environment = resolve_environment_variables(&context.app_state.settings, env)
+        .await
+        .expose_all();
+    let mut args = project.compose_args();
+    args.push("down".to_string());
+
+    let compose_path = project.compose_path.clone();
+    tokio::task::spawn_blocking(move || {
+        let args: Vec<&str> = args.iter().map(AsRef::as_ref).collect();
+        run_docker_compose_now(&compose_path, &args, Some(&resolved_environment), false)
+    })
+    .await??;
+
+    let compose_file = project.blue_green_compose_file();
+    if compose_file.exists() {
+        tokio::fs::remove_file(&compose_file).await?;
+    }
+    Ok(())
+}
+
+/// Starts the public services of the app under a temporary compose project,
+/// next to the running containers.
+///
+/// The new containers carry the same Traefik routers and join the same per-app
+/// proxy network as the running ones. They take over the traffic once the old
+/// public services get stopped.
+#[derive(Debug)]
+pub struct StartBlueGreenCandidateHandler<S>
+where
+    S: Send + Sync + Clone + std::fmt::Debug,
+{
+    pub next_state: S,
+    pub project: BlueGreenProject,
+    pub services: Vec<String>,
+    pub env: SecretHashMap,
+}
+
+#[async_trait::async_trait]
+impl<S> StateHandler<S, Context> for StartBlueGreenCandidateHandler<S>
+where
+    S: Send + Sync + Clone + std::fmt::Debug,
+{
+    #[instrument(skip(context))]
+    async fn transition(&self, _from: &S, context: Arc<RwLock<Context>>) -> anyhow::Result<S> {
+        let context = context.read().await;
+        let task_id = context.task.read().await.id;
+
+        let compose_file = self.project.blue_green_compose_file();
+        info!(
+            "Saving blue/green compose file to {}",
+            compose_file.display()
+        );
+        let yaml = serde_norway::to_string(&self.project.blue_green_compose_config())?;
+        tokio::fs::write(&compose_file, yaml).await?;
+
+        context
+            .app_state
+            .task_manager
+            .add_task_status(
+                &task_id,
+                format!(
+                    "Starting new containers in project {}",
+                    self.project.candidate_project
+                ),
+            )
+            .await;
+
+        let mut args = self.project.compose_args();
+        args.extend(
+            ["up", "-d", "--no-deps", "--no-build"]
+                .iter()
+                .map(|s| s.to_string()),
+        );
+        args.extend(self.services.iter().cloned());
+
+        run_task_and_wait(
+            &context,
+            &self.project.compose_path,
+            "docker-compose",
+            args.iter()
+                .map(AsRef::as_ref)
+                .collect::<Vec<&str>>()
+                .as_slice(),
+            &self.env,
+            "docker-compose up (blue/green)",
+        )
+        .await?;
+
+        Ok(self.next_state.clone())
+    }
+}
+
+/// Removes the temporary containers once the app's own containers run the new version.
+///
+/// Only the temporary containers are removed, the images and volumes belong to
+/// the app.
+#[derive(Debug)]
+pub struct RemoveBlueGreenCandidateHandler<S>
+where
+    S: Send + Sync + Clone + std::fmt::Debug,
+{
+    pub next_state: S,
+    pub project: BlueGreenProject,
+    pub env: SecretHashMap,
+}
+
+#[async_trait::async_trait]
+impl<S> StateHandler<S, Context> for RemoveBlueGreenCandidateHandler<S>
+where
+    S: Send + Sync + Clone + std::fmt::Debug,
+{
+    #[instrument(skip(context))]
+    async fn transition(&self, _from: &S, context: Arc<RwLock<Context>>) -> anyhow::Result<S> {
+        let context = context.read().await;
+        let task_id = context.task.read().await.id;
+        let task_manager = &context.app_state.task_manager;
+
+        // The app is already switched over, a leftover candidate does not fail the rebuild
+        match remove_candidate(&context, &self.project, &self.env).await {
+            Ok(()) => {
+                task_manager
+                    .add_task_status(
+                        &task_id,
+                        format!(
+                            "Removed temporary containers of project {}",
+                            self.project.candidate_project
+                        ),
+                    )
+                    .await;
+            }
+            Err(e) => {
+                warn!(
+                    "Failed to remove blue/green project {}: {}",
+                    self.project.candidate_project, e
+                );
+                task_manager
+                    .add_task_status_error(
+                        &task_id,
+                        format!(
+                            "Failed to remove temporary containers of project {}: {}",
+                            self.project.candidate_project, e
+                        ),
+                    )
+                    .await;
+            }
+        }
+
+        Ok(self.next_state.clone())
+    }
+}
+
+/// Checks whether the given services of the app's own compose project are running.
+async fn app_services_running(
+    context: &Context,
+    project: &BlueGreenProject,
+    services: &[String],
+    env: &SecretHashMap,
+) -> anyhow::Result<bool> {
+    let resolved_environment = resolve_environment_variables(&context.app_state.settings, env)
+        .await
+        .expose_all();
+    let compose_path = project.compose_path.clone();
+    let output = tokio::task::spawn_blocking(move || {
+        run_docker_compose_now(
+            &compose_path,
+            &["ps", "--status", "running", "--services"],
+            Some(&resolved_environment),
+            false,
+        )
+    })
+    .await??;
+    let running: Vec<&str> = output.lines().map(str::trim).collect();
+    Ok(services
+        .iter()
+        .all(|service| running.contains(&service.as_str())))
+}
+
+/// Error handler of a blue/green rebuild, runs after the revision got rolled back.
+///
+/// The temporary containers are only removed if the app's own public services
+/// are running again. Otherwise they are the only containers serving the app
+/// and keep running until the next rebuild replaces them.
+#[derive(Debug)]
+pub struct BlueGreenRollbackHandler<H> {
+    pub project: BlueGreenProject,
+    pub services: Vec<String>,
+    pub env: SecretHashMap,
+    pub completion: H,
+}
+
+#[async_trait::async_trait]
+impl<S, H> StateHandler<S, Context> for BlueGreenRollbackHandler<H>
+where
+    S: Send + Sync + Clone + std::fmt::Debug,
+    H: StateHandler<S, Context> + Send + Sync + std::fmt::Debug,
+{
+    #[instrument(skip(context))]
+    async fn transition(&self, from: &S, context: Arc<RwLock<Context>>) -> anyhow::Result<S> {
+        {
+            let context = context.read().await;
+            let task_id = context.task.read().await.id;
+            let task_manager = &context.app_state.task_manager;
+
+            let app_running = match app_services_running(
+                &context,
+                &self.project,
+                &self.services,
+                &self.env,
+            )
+            .await
+            {
+                Ok(running) => running,
+                Err(e) => {
+                    warn!(
+                        "Failed to check the containers of project {}: {}",
+                        self.project.project, e
+                    );
+                    false
+                }
+            };
+            if app_running {
+                task_manager
+                    .add_task_status_error(
+                        &task_id,
+                        format!(
+                            "Rolling back, removing temporary containers of project {}",
+                            self.project.candidate_project
+                        ),
+                    )
+                    .await;
+                if let Err(e) = remove_candidate(&context, &self.project, &self.env).await {
+                    warn!(
+                        "Failed to roll back blue/green project {}: {}",
+                        self.project.candidate_project, e
+                    );
+                    task_manager
+                        .add_task_status_error(&task_id, format!("Rollback failed: {}", e))
+                        .await;
+                }
+            } else {
+                task_manager
+                    .add_task_status_error(
+                        &task_id,
+                        format!(
+                            "The app's containers are not running, the temporary containers of project {} keep serving the app until the next rebuild",
+                            self.project.candidate_project
+                        ),
+                    )
+                    .await;
+            }
+        }
+
+        self.completion.transition(from, context).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_blue_green_compose_args_and_network() {
+        let dir = tempfile::tempdir().unwrap();
+        let app_dir = dir.path().join("my-app");
+        std::fs::create_dir_all(&app_dir).unwrap();
+        let compose_path = app_dir.join("compose.yml");
+        std::fs::write(&compose_path, "services: {}").unwrap();
+        std::fs::write(app_dir.join("compose.override.yml"), "services: {}").unwrap();
+
+        let project = BlueGreenProject::new(&compose_path).unwrap();
+        assert_eq!(project.project, "my-app");
+        assert_eq!(project.candidate_project, "my-app--next");
+
+        let args = project.compose_args();
+        assert_eq!(&args[..2], &["-p".to_string(), "my-app--next".to_string()]);
+        assert!(args.contains(&app_dir.join("compose.override.yml").display().to_string()));
+        assert_eq!(
+            args.last().unwrap(),
+            &app_dir.join(BLUE_GREEN_COMPOSE_FILE).display().to_string()
+        );
+
+        let config = project.blue_green_compose_config();
+        let default_network = &config.networks["default"];
+        assert!(default_network.external);
+        assert_eq!(default_network.name, "my-app_default");
+    }
+
+    #[test]
+    fn test_blue_green_reuses_images_and_volumes() {
+        let dir = tempfile::tempdir().unwrap();
+        let app_dir = dir.path().join("my-app");
+        std::fs::create_dir_all(&app_dir).unwrap();
+        let compose_path = app_dir.join("compose.yml");
+        std::fs::write(
+            &compose_path,
+            r#"
+services:
+  web:
+    build: .
+    volumes:
+      - uploads:/uploads
+  worker:
+    build: .
+    image: registry.example.com/worker
+  db:
+    image: postgres
+    volumes:
+      - db:/var/lib/postgresql/data
+volumes:
+  uploads: {}
+  db:
+    name: shared-db
+  cache:
+    external: true
+"#,
+        )
+        .unwrap();
+
+        let project = BlueGreenProject::new(&compose_path).unwrap();
+        let config = project.blue_green_compose_config();
+
+        assert_eq!(
+            config.services.keys().collect::<Vec<_>>(),
+            vec!["web"],
+            "only services built without an image name get pinned"
+        );
+        assert_eq!(config.services["web"].image, "my-app-web");
+        assert_eq!(
+            config.volumes.keys().collect::<Vec<_>>(),
+            vec!["uploads"],
+            "volumes with an explicit name are shared already"
+        );
+        assert_eq!(config.volumes["uploads"].name, "my-app_uploads");
+        assert!(config.volumes["uploads"].external);
+    }
+}
@@ -26,12 +26,17 @@ impl Context {
         }
     }
 
-    pub fn create(app_state: SharedAppState, app_data: &AppData) -> Arc<RwLock<Self>> {
+    pub fn create(
+        app_state: SharedAppState,
+        app_data: &AppData,
+        requested_by: Option<&str>,
+    ) -> Arc<RwLock<Self>> {
         Arc::new(RwLock::new(Context {
             app_state: app_state.clone(),
             app_data: app_data.clone(),
             task: Arc::new(RwLock::new(TaskDetails {
                 app_name: Some(app_data.name.clone()),
+                requested_by: requested_by.map(|user| user.to_string()),
                 ..TaskDetails::default()
             })),
         }))
@@ -44,17 +49,31 @@ impl Context {
     /// - Updating task state, finish_time, and output_collection_active
     /// - Broadcasting the task update via WebSocket
     /// - Adding status messages to task output
+    /// - Recording the task in the persistent task history
     ///
     /// Used by both TaskCompletionHandler and helper.rs to ensure consistent behavior.
     ///
     /// # Arguments
-    /// * `target_state` - State::Finished or State::Failed
+    /// * `target_state` - State::Finished or State::Failed, replaced by State::Cancelled
+    ///   if the task got cancelled
     /// * `status_message` - Message to add to task output
     /// * `is_error` - Whether to use add_task_status_error (true) or add_task_status (false)
     pub async fn complete_task(&self, target_state: State, status_message: String, is_error: bool) {
         // Get task ID first
         let task_id = self.task.read().await.id;
 
+        // A cancelled task keeps its state, even if the error handler reports a failure
+        let target_state = if self
+            .app_state
+            .task_manager
+            .is_task_cancelled(&task_id)
+            .await
+        {
+            State::Cancelled
+        } else {
+            target_state
+        };
+
         // Add status message BEFORE marking output collection as inactive
         // This ensures the message is available for the WebSocket stream to send
         if is_error {
@@ -86,6 +105,12 @@ impl Context {
             task_details.clone()
         };
 
+        // Persist the finished task so it survives restarts and cleanup
+        self.app_state
+            .task_manager
+            .record_task_history(&task_id)
+            .await;
+
         // Broadcast task update via WebSocket
         self.app_state
             .messenger
@@ -1,7 +1,8 @@
 use std::sync::Arc;
 
 use scotty_core::{
-    apps::app_data::AppSettings, settings::loadbalancer::LoadBalancerType,
+    apps::app_data::{AppSettings, BasicAuthUser},
+    settings::loadbalancer::LoadBalancerType,
     utils::secret::SecretHashMap,
 };
 use tokio::sync::RwLock;
@@ -9,7 +10,7 @@ use tracing::info;
 
 use crate::{
     docker::loadbalancer::{factory::LoadBalancerFactory, types::DockerComposeConfig},
-    onepassword::lookup::resolve_environment_variables,
+    onepassword::lookup::{resolve_basic_auth_users, resolve_environment_variables},
     settings::config::Settings,
     state_machine::StateHandler,
 };
@@ -68,6 +69,93 @@ fn get_docker_compose_override(
     Ok(docker_compose_override)
 }
 
+/// Docker-compose override of an app as scotty generates it.
+pub(crate) struct GeneratedOverride {
+    pub override_file: std::path::PathBuf,
+    pub config: DockerComposeConfig,
+    /// Settings of the app with resolved basic auth passwords
+    pub settings: AppSettings,
+}
+
+/// Bcrypt hash standing in for the basic auth passwords of an app.
+pub(crate) const PLACEHOLDER_PASSWORD_HASH: &str =
+    "$2b$12$scotty.placeholder.hash.for.drift.checks.000000000000";
+
+/// How the secrets of an app end up in its generated override.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum OverrideSecrets {
+    /// Resolve 1Password references and hash basic auth passwords
+    Resolve,
+    /// Keep the environment as configured and use [`PLACEHOLDER_PASSWORD_HASH`]
+    /// for every basic auth user, so nothing gets looked up or hashed
+    Placeholder,
+}
+
+/// Generate the docker-compose override for the app in `root_directory`,
+/// handling the secrets of its environment and basic auth users as given.
+pub(crate) async fn generate_docker_compose_override(
+    global_settings: &Settings,
+    load_balancer_type: &LoadBalancerType,
+    app_name: &str,
+    root_directory: &std::path::Path,
+    settings: &AppSettings,
+    secrets: OverrideSecrets,
+) -> anyhow::Result<GeneratedOverride> {
+    let (resolved_environment, basic_auth) = match secrets {
+        OverrideSecrets::Resolve => (
+            resolve_environment_variables(global_settings, &settings.environment).await,
+            resolve_basic_auth_users(global_settings, &settings.basic_auth).await?,
+        ),
+        OverrideSecrets::Placeholder => (
+            settings.environment.clone(),
+            settings
+                .basic_auth
+                .iter()
+                .map(|user| BasicAuthUser::new(&user.username, PLACEHOLDER_PASSWORD_HASH))
+                .collect(),
+        ),
+    };
+    let settings = AppSettings {
+        basic_auth,
+        ..settings.clone()
+    };
+
+    // Find and read all service names from the compose file
+    let compose_path = scotty_core::utils::compose::find_config_file_in_dir(root_directory)
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "Folder {} does not contain a Docker Compose standard config file, such as docker-compose.yaml or compose.yaml.",
+                root_directory.display()
+            )
+        })?;
+
+    let all_services = get_service_names_from_compose(&compose_path).await?;
+
+    // Pass SecretHashMap - secrets will be exposed inside get_docker_compose_override
+    let config = get_docker_compose_override(
+        load_balancer_type,
+        global_settings,
+        app_name,
+        &settings,
+        &resolved_environment,
+        &all_services,
+    )?;
+
+    let override_file =
+        scotty_core::utils::compose::get_override_file(&compose_path).ok_or_else(|| {
+            anyhow::anyhow!(
+                "Unable to determine override file path from compose file: {}",
+                compose_path.display()
+            )
+        })?;
+
+    Ok(GeneratedOverride {
+        override_file,
+        config,
+        settings,
+    })
+}
+
 #[async_trait::async_trait]
 impl<S> StateHandler<S, Context> for CreateLoadBalancerConfig<S>
 where
@@ -76,42 +164,28 @@ where
     async fn transition(&self, _from: &S, context: Arc<RwLock<Context>>) -> anyhow::Result<S> {
         let context = context.read().await;
         let root_directory = std::path::PathBuf::from(&context.app_data.root_directory);
-        let resolved_environment =
-            resolve_environment_variables(&context.app_state.settings, &self.settings.environment)
-                .await;
-
-        // Find and read all service names from the compose file
-        let compose_path = scotty_core::utils::compose::find_config_file_in_dir(&root_directory)
-            .ok_or_else(|| {
-                anyhow::anyhow!(
-                    "Folder {} does not contain a Docker Compose standard config file, such as docker-compose.yaml or compose.yaml.",
-                    root_directory.display()
-                )
-            })?;
-
-        let all_services = get_service_names_from_compose(&compose_path).await?;
-
-        // Pass SecretHashMap - secrets will be exposed inside get_docker_compose_override
-        let docker_compose_override = get_docker_compose_override(
-            &self.load_balancer_type,
+        let generated = generate_docker_compose_override(
             &context.app_state.settings,
+            &self.load_balancer_type,
             &context.app_data.name,
+            &root_directory,
             &self.settings,
-            &resolved_environment,
-            &all_services,
-        )?;
+            OverrideSecrets::Resolve,
+        )
+        .await?;
 
-        let override_file = scotty_core::utils::compose::get_override_file(&compose_path)
-            .ok_or_else(|| {
-                anyhow::anyhow!(
-                    "Unable to determine override file path from compose file: {}",
-                    compose_path.display()
-                )
-            })?;
+        info!(
+            "Saving override file to {}",
+            generated.override_file.display()
+        );
+        let yaml = serde_norway::to_string(&generated.config)?;
+        tokio::fs::write(&generated.override_file, yaml).await?;
 
-        info!("Saving override file to {}", override_file.display());
-        let yaml = serde_norway::to_string(&docker_compose_override)?;
-        tokio::fs::write(&override_file, yaml).await?;
+        LoadBalancerFactory::create(&self.load_balancer_type).write_support_files(
+            &context.app_state.settings,
+            &context.app_data.name,
+            &generated.settings,
+        )?;
 
         Ok(self.next_state.clone())
     }
@@ -143,6 +217,8 @@ mod tests {
                 service: "web".to_string(),
                 port: 8080,
                 domains: vec![],
+                path: None,
+                strip_path: false,
             }],
             environment: environment.clone(),
             ..Default::default()
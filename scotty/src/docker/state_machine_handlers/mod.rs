@@ -1,8 +1,11 @@
+pub mod blue_green_handler;
 pub mod context;
 pub mod create_directory_handler;
 pub mod create_load_balancer_config;
 pub mod network_handler;
+pub mod release_tcp_ports_handler;
 pub mod remove_directory_handler;
+pub mod revision_handler;
 pub mod run_docker_compose_handler;
 pub mod run_docker_login_handler;
 pub mod run_post_actions_handler;
@@ -0,0 +1,35 @@
+use std::sync::Arc;
+
+use tokio::sync::RwLock;
+use tracing::instrument;
+
+use crate::state_machine::StateHandler;
+
+use super::context::Context;
+
+/// Error handler freeing the TCP ports reserved for the app when its task
+/// fails, so failed creates and updates don't use up the port pool.
+#[derive(Debug)]
+pub struct ReleaseTcpPortsHandler<H> {
+    pub completion: H,
+}
+
+#[async_trait::async_trait]
+impl<S, H> StateHandler<S, Context> for ReleaseTcpPortsHandler<H>
+where
+    S: Send + Sync + Clone + std::fmt::Debug,
+    H: StateHandler<S, Context> + Send + Sync + std::fmt::Debug,
+{
+    #[instrument(skip(context))]
+    async fn transition(&self, from: &S, context: Arc<RwLock<Context>>) -> anyhow::Result<S> {
+        {
+            let context = context.read().await;
+            context
+                .app_state
+                .tcp_ports
+                .release(&context.app_data.name)
+                .await;
+        }
+        self.completion.transition(from, context).await
+    }
+}
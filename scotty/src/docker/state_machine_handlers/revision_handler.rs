@@ -0,0 +1,231 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use scotty_core::apps::app_data::{AppData, AppSettings};
+use tokio::sync::RwLock;
+use tracing::{instrument, warn};
+
+use crate::{
+    docker::revisions::RevisionStore, onepassword::lookup::resolve_environment_variables,
+    state_machine::StateHandler,
+};
+
+use super::context::Context;
+
+/// Stores the current configuration of the app as a new revision, before the
+/// following states change it.
+#[derive(Debug)]
+pub struct CreateRevisionHandler<S>
+where
+    S: Send + Sync + Clone + std::fmt::Debug,
+{
+    pub next_state: S,
+    pub reason: String,
+    /// Files the operation is about to write, relative to the app directory
+    pub applied_files: Vec<String>,
+}
+
+#[async_trait::async_trait]
+impl<S> StateHandler<S, Context> for CreateRevisionHandler<S>
+where
+    S: Send + Sync + Clone + std::fmt::Debug,
+{
+    #[instrument(skip(context))]
+    async fn transition(&self, _from: &S, context: Arc<RwLock<Context>>) -> anyhow::Result<S> {
+        let context = context.read().await;
+        let Some(store) =
+            RevisionStore::for_app(&context.app_state.settings, &context.app_data.name)
+        else {
+            return Ok(self.next_state.clone());
+        };
+        let task_id = context.task.read().await.id;
+
+        let revision = store
+            .create(
+                Path::new(&context.app_data.root_directory),
+                Path::new(&context.app_data.docker_compose_path),
+                &self.reason,
+                Some(task_id),
+                &self.applied_files,
+            )
+            .await?;
+        if let Some(revision) = revision {
+            context
+                .app_state
+                .task_manager
+                .add_task_status(
+                    &task_id,
+                    format!(
+                        "Saved current configuration as revision {}",
+                        revision.revision
+                    ),
+                )
+                .await;
+        }
+
+        Ok(self.next_state.clone())
+    }
+}
+
+/// Restores the files of a revision and reloads the app settings from it.
+#[derive(Debug)]
+pub struct RestoreRevisionHandler<S>
+where
+    S: Send + Sync + Clone + std::fmt::Debug,
+{
+    pub next_state: S,
+    pub revision: u32,
+}
+
+#[async_trait::async_trait]
+impl<S> StateHandler<S, Context> for RestoreRevisionHandler<S>
+where
+    S: Send + Sync + Clone + std::fmt::Debug,
+{
+    #[instrument(skip(context))]
+    async fn transition(&self, _from: &S, context: Arc<RwLock<Context>>) -> anyhow::Result<S> {
+        let mut context = context.write().await;
+        let store = RevisionStore::for_app(&context.app_state.settings, &context.app_data.name)
+            .ok_or_else(|| anyhow::anyhow!("Revisions are disabled"))?;
+        let revision = store
+            .get(self.revision)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Revision {} not found", self.revision))?;
+
+        let task_id = context.task.read().await.id;
+        context
+            .app_state
+            .task_manager
+            .add_task_status(
+                &task_id,
+                format!("Restoring revision {}", revision.revision),
+            )
+            .await;
+        store
+            .restore(Path::new(&context.app_data.root_directory), &revision)
+            .await?;
+
+        context.app_data = restored_app_data(&context.app_data)?;
+        Ok(self.next_state.clone())
+    }
+}
+
+/// Seconds the compose command of a rollback may take before it gets killed.
+const ROLLBACK_TIMEOUT_SECS: u64 = 300;
+
+/// Error handler rolling back the changes of a failed task.
+///
+/// Restores the revision created by the task, if any, and brings the app up
+/// again with it, unless the files did not change. A failing rollback is reported in the task output, the
+/// wrapped completion handler runs in any case.
+#[derive(Debug)]
+pub struct RollbackRevisionHandler<H> {
+    pub completion: H,
+}
+
+#[async_trait::async_trait]
+impl<S, H> StateHandler<S, Context> for RollbackRevisionHandler<H>
+where
+    S: Send + Sync + Clone + std::fmt::Debug,
+    H: StateHandler<S, Context> + Send + Sync + std::fmt::Debug,
+{
+    #[instrument(skip(context))]
+    async fn transition(&self, from: &S, context: Arc<RwLock<Context>>) -> anyhow::Result<S> {
+        {
+            let context = context.read().await;
+            if let Err(e) = rollback_task_revision(&context).await {
+                warn!("Failed to roll back app {}: {}", context.app_data.name, e);
+                let task_id = context.task.read().await.id;
+                context
+                    .app_state
+                    .task_manager
+                    .add_task_status_error(&task_id, format!("Rollback failed: {}", e))
+                    .await;
+            }
+        }
+
+        self.completion.transition(from, context).await
+    }
+}
+
+/// Re-read the app settings after the files of a revision got restored.
+fn restored_app_data(app_data: &AppData) -> anyhow::Result<AppData> {
+    let settings_path = PathBuf::from(&app_data.docker_compose_path).with_file_name(".scotty.yml");
+    Ok(AppData {
+        settings: AppSettings::from_file(&settings_path)?,
+        ..app_data.clone()
+    })
+}
+
+async fn rollback_task_revision(context: &Context) -> anyhow::Result<()> {
+    let Some(store) = RevisionStore::for_app(&context.app_state.settings, &context.app_data.name)
+    else {
+        return Ok(());
+    };
+    let task_id = context.task.read().await.id;
+    let Some(revision) = store.find_by_task(&task_id).await? else {
+        return Ok(());
+    };
+    let task_manager = &context.app_state.task_manager;
+    let app_directory = Path::new(&context.app_data.root_directory);
+
+    if store.matches_current(app_directory, &revision).await? {
+        task_manager
+            .add_task_status(
+                &task_id,
+                format!(
+                    "Configuration still matches revision {}, nothing to roll back",
+                    revision.revision
+                ),
+            )
+            .await;
+        return Ok(());
+    }
+
+    task_manager
+        .add_task_status_error(
+            &task_id,
+            format!("Rolling back to revision {}", revision.revision),
+        )
+        .await;
+    store.restore(app_directory, &revision).await?;
+
+    // The task might have been cancelled, which keeps it from starting new
+    // processes, so the compose command runs without checking for it.
+    let app_data = restored_app_data(&context.app_data)?;
+    let environment =
+        resolve_environment_variables(&context.app_state.settings, &app_data.get_environment())
+            .await
+            .expose_all();
+    let compose_path = PathBuf::from(&app_data.docker_compose_path);
+    let args = ["up", "-d", "--build", "--remove-orphans"];
+    task_manager
+        .add_task_status(
+            &task_id,
+            format!("Running: docker-compose {}", args.join(" ")),
+        )
+        .await;
+    let exit_code = task_manager
+        .run_process_and_wait(
+            compose_path
+                .parent()
+                .ok_or_else(|| anyhow::anyhow!("Docker compose path has no parent directory"))?,
+            "docker-compose",
+            &args,
+            &environment,
+            context.task.clone(),
+            std::time::Duration::from_secs(ROLLBACK_TIMEOUT_SECS),
+        )
+        .await?;
+    if exit_code != 0 {
+        anyhow::bail!("docker-compose failed with exit code {}", exit_code);
+    }
+
+    task_manager
+        .add_task_status(
+            &task_id,
+            format!("Revision {} is up and running again", revision.revision),
+        )
+        .await;
+    Ok(())
+}
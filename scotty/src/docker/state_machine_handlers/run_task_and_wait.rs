@@ -76,6 +76,15 @@ pub async fn run_task_and_wait(
         .await
         .ok_or_else(|| anyhow::anyhow!("Task not found"))?;
 
+    if context
+        .app_state
+        .task_manager
+        .is_task_cancelled(&task_id)
+        .await
+    {
+        return Err(anyhow::anyhow!("{} was cancelled", msg));
+    }
+
     // Add completion status based on exit code
     if let Some(last_exit_code) = task.last_exit_code {
         if last_exit_code != 0 {
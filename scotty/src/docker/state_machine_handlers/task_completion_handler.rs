@@ -106,6 +106,7 @@ where
                     if let (Some(app_settings), Some(notification)) =
                         (&context.app_data.settings, notification)
                     {
+                        let notification = notification.with_task_id(context.task.read().await.id);
                         match crate::notification::notify::notify(
                             &context.app_state,
                             &app_settings.notify,
@@ -1,7 +1,12 @@
 use super::context::Context;
-use crate::docker::helper::wait_for_containers_ready;
+use crate::docker::helper::{
+    get_compose_project_container_ids, wait_for_containers_ready, wait_for_http_probe,
+    ContainerReadiness,
+};
 use crate::state_machine::StateHandler;
 use anyhow::Context as _;
+use bollard::models::ContainerStateStatusEnum;
+use scotty_core::apps::app_data::ContainerHealth;
 use scotty_core::websocket::message::WebSocketMessage;
 use std::sync::Arc;
 use tokio::sync::RwLock;
@@ -10,7 +15,13 @@ use tracing::{debug, info, instrument, warn};
 /// A state machine handler that waits for all containers in an application to reach a ready state.
 ///
 /// This handler finds all container IDs associated with the application's services and
-/// waits until none of them are in a starting state (Created or Restarting).
+/// waits until none of them are in a starting state (Created or Restarting) and every
+/// container healthcheck has finished starting. The wait fails if a healthcheck reports
+/// a container as unhealthy. Afterwards the HTTP probes configured in the app's readiness
+/// settings (or its blueprint) are polled until they respond.
+///
+/// When a compose project is given, the containers of that project are awaited instead,
+/// and every one of them has to end up running.
 #[derive(Debug)]
 pub struct WaitForAllContainersHandler<S>
 where
@@ -19,8 +30,12 @@ where
     /// The next state to transition to after all containers are ready
     pub next_state: S,
 
-    /// Optional timeout in seconds (defaults to 300 seconds if None)
+    /// Optional timeout in seconds (defaults to 300 seconds if None), the
+    /// readiness settings of the app take precedence
     pub timeout_seconds: Option<u64>,
+
+    /// Wait for the containers of this compose project instead of the app's services
+    pub compose_project: Option<String>,
 }
 
 #[async_trait::async_trait]
@@ -41,20 +56,43 @@ where
 
         debug!("Collecting container IDs for app {}", app_data.name);
 
-        // Collecting all container IDs from app services
-        let container_ids: Vec<String> = app_data
-            .services
-            .iter()
-            .filter_map(|service| service.id.clone())
-            .collect();
+        // Collecting all container IDs from app services or the given compose project
+        let container_ids: Vec<String> = match &self.compose_project {
+            Some(project) => get_compose_project_container_ids(&app_state, project)
+                .await
+                .with_context(|| format!("Failed to list containers of project {}", project))?,
+            None => app_data
+                .services
+                .iter()
+                .filter_map(|service| service.id.clone())
+                .collect(),
+        };
 
         if container_ids.is_empty() {
+            if let Some(project) = &self.compose_project {
+                anyhow::bail!("No containers found for project {}", project);
+            }
             warn!("No container IDs found for app {}", app_data.name);
             return Ok(self.next_state.clone());
         }
 
         debug!("Found {} containers to wait for", container_ids.len());
 
+        let readiness = app_data
+            .settings
+            .as_ref()
+            .map(|settings| {
+                let blueprint = settings
+                    .app_blueprint
+                    .as_ref()
+                    .and_then(|name| app_state.settings.apps.blueprints.get(name))
+                    .and_then(|blueprint| blueprint.readiness.as_ref());
+                settings.readiness.or(blueprint)
+            })
+            .unwrap_or_default();
+        let timeout_seconds = readiness.timeout_seconds.or(self.timeout_seconds);
+        let started = std::time::Instant::now();
+
         // Add progress message to task output for client visibility
         let task_id = task_clone.read().await.id;
         app_state
@@ -76,10 +114,88 @@ where
 
         // Wait for all containers to reach a non-starting state
         let container_states =
-            wait_for_containers_ready(&app_state, container_ids, self.timeout_seconds)
+            wait_for_containers_ready(&app_state, container_ids, timeout_seconds)
                 .await
                 .context("Failed to wait for containers to be ready")?;
 
+        if self.compose_project.is_some() {
+            let not_running: Vec<&String> = container_states
+                .iter()
+                .filter(|container| container.status != ContainerStateStatusEnum::RUNNING)
+                .map(|container| &container.id)
+                .collect();
+            if !not_running.is_empty() {
+                anyhow::bail!(
+                    "{} of {} containers are not running: {:?}",
+                    not_running.len(),
+                    container_states.len(),
+                    not_running
+                );
+            }
+        }
+
+        let unhealthy = unhealthy_containers(&container_states);
+        for name in &unhealthy {
+            warn!("Container {} of app {} is unhealthy", name, app_data.name);
+            app_state
+                .task_manager
+                .add_task_status_error(&task_id, format!("Service {} is unhealthy", name))
+                .await;
+        }
+        if !unhealthy.is_empty() {
+            anyhow::bail!("Containers are unhealthy: {}", unhealthy.join(", "));
+        }
+
+        for probe in &readiness.http_probes {
+            let Some(container) = container_states.iter().find(|container| {
+                container.service.as_deref() == Some(probe.service.as_str())
+                    && container.status == ContainerStateStatusEnum::RUNNING
+            }) else {
+                if self.compose_project.is_some() {
+                    // Only public services are part of a blue/green candidate
+                    continue;
+                }
+                anyhow::bail!(
+                    "No running container found for probed service {}",
+                    probe.service
+                );
+            };
+            let port = probe
+                .port
+                .or_else(|| {
+                    app_data.settings.as_ref().and_then(|settings| {
+                        settings
+                            .public_services
+                            .iter()
+                            .find(|service| service.service == probe.service)
+                            .map(|service| service.port)
+                    })
+                })
+                .ok_or_else(|| {
+                    anyhow::anyhow!("No port known to probe service {}", probe.service)
+                })?;
+            let ip = container.ip_addresses.first().ok_or_else(|| {
+                anyhow::anyhow!("Container of service {} has no IP address", probe.service)
+            })?;
+            let url = format!("http://{}:{}{}", ip, port, probe.path);
+
+            app_state
+                .task_manager
+                .add_task_progress(
+                    &task_id,
+                    format!(
+                        "Waiting for service {} to respond on {}",
+                        probe.service, probe.path
+                    ),
+                )
+                .await;
+            let remaining = std::time::Duration::from_secs(timeout_seconds.unwrap_or(300))
+                .saturating_sub(started.elapsed());
+            wait_for_http_probe(&url, remaining)
+                .await
+                .with_context(|| format!("Service {} is not ready", probe.service))?;
+        }
+
         // Add completion status message to task output for client visibility
         app_state
             .task_manager
@@ -100,3 +216,46 @@ where
         Ok(self.next_state.clone())
     }
 }
+
+/// Names of the containers whose healthcheck reports them as unhealthy.
+fn unhealthy_containers(container_states: &[ContainerReadiness]) -> Vec<&str> {
+    container_states
+        .iter()
+        .filter(|container| container.health == Some(ContainerHealth::Unhealthy))
+        .map(|container| container.display_name())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn container(service: &str, health: Option<ContainerHealth>) -> ContainerReadiness {
+        ContainerReadiness {
+            id: format!("{service}-id"),
+            service: Some(service.to_string()),
+            status: ContainerStateStatusEnum::RUNNING,
+            health,
+            ip_addresses: vec![],
+        }
+    }
+
+    #[test]
+    fn test_unhealthy_containers() {
+        let states = vec![
+            container("web", Some(ContainerHealth::Healthy)),
+            container("db", Some(ContainerHealth::Unhealthy)),
+            container("worker", None),
+        ];
+        assert_eq!(unhealthy_containers(&states), vec!["db"]);
+    }
+
+    #[test]
+    fn test_healthy_containers() {
+        let states = vec![
+            container("web", Some(ContainerHealth::Healthy)),
+            container("worker", None),
+        ];
+        assert!(unhealthy_containers(&states).is_empty());
+    }
+}
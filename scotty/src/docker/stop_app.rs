@@ -74,19 +74,21 @@ pub async fn stop_app_prepare(
 pub async fn stop_app(
     app_state: SharedAppState,
     app: &AppData,
+    requested_by: Option<&str>,
 ) -> anyhow::Result<RunningAppContext> {
     if app.status == AppStatus::Unsupported {
         return Err(AppError::OperationNotSupportedForLegacyApp(app.name.clone()).into());
     }
     let sm = stop_app_prepare(app).await?;
-    run_sm(app_state, app, sm).await
+    run_sm(app_state, app, sm, requested_by).await
 }
 
 #[instrument(skip(app_state))]
 pub async fn force_stop_app(
     app_state: SharedAppState,
     app: &AppData,
+    requested_by: Option<&str>,
 ) -> anyhow::Result<RunningAppContext> {
     let sm = stop_app_prepare(app).await?;
-    run_sm(app_state, app, sm).await
+    run_sm(app_state, app, sm, requested_by).await
 }
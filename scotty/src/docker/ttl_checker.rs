@@ -1,8 +1,11 @@
-use tracing::{info, instrument};
+use chrono::{DateTime, Local};
+use tracing::{info, instrument, warn};
 
 use crate::docker::destroy_app::destroy_app;
+use crate::notification::notify::notify;
 use crate::{app_state::SharedAppState, docker::stop_app::force_stop_app};
 use scotty_core::apps::app_data::{AppData, AppStatus, AppTtl};
+use scotty_core::notification_types::{Message, MessageType};
 use scotty_core::utils::format::format_chrono_duration;
 
 #[instrument(skip(app_state))]
@@ -12,47 +15,186 @@ pub async fn check_app_ttl(app_state: SharedAppState, app: &AppData) -> anyhow::
         info!("Ignoring {} as it is allowed to live forever", &app.name);
         return Ok(());
     }
-    let app_ttl_seconds: u32 = app_ttl.into();
     let now = chrono::Local::now();
-    let mut needs_termination = false;
-    let mut remaining_ttl = 0;
-    for service in &app.services {
-        if !service.is_running() {
-            continue;
+    let recorded_activity = app_state.activity.last_activity(&app.name).await;
+    let Some(deadline) = ttl_deadline(app, recorded_activity) else {
+        return Ok(());
+    };
+
+    let remaining = deadline - now;
+    info!(
+        "{} has {} remaining before being stopped ({:?})",
+        app.name,
+        format_chrono_duration(&remaining),
+        app_ttl
+    );
+    if remaining <= chrono::Duration::zero() {
+        info!("TTL {:?} reached for {}", app_ttl, app.name);
+        return terminate_app(app_state, app).await;
+    }
+
+    if let Some(lead_time) = &app_state.settings.apps.ttl_warning_lead_time {
+        let lead_time: chrono::Duration = lead_time.clone().into();
+        if remaining <= lead_time
+            && app_state
+                .ttl_warnings
+                .should_warn(&app.name, deadline)
+                .await
+        {
+            warn_ttl_expiring(&app_state, app, remaining).await;
+        }
+    }
+    Ok(())
+}
+
+/// Point in time at which the TTL of the app runs out.
+///
+/// A fixed TTL counts from the start of the app's containers, an idle TTL from
+/// the last request to the app, see [`crate::services::ActivityTracker`].
+/// Extending the TTL restarts both. Returns `None` if the app lives forever or
+/// no container of the app is running.
+fn ttl_deadline(
+    app: &AppData,
+    recorded_activity: Option<DateTime<Local>>,
+) -> Option<DateTime<Local>> {
+    let ttl_started_at = app.settings.as_ref().and_then(|s| s.ttl_started_at);
+    match app.get_ttl() {
+        AppTtl::Forever => None,
+        AppTtl::Idle(hours) => {
+            let recorded = recorded_activity.max(ttl_started_at);
+            last_activity(app, recorded).map(|at| at + chrono::Duration::hours(hours.into()))
         }
-        if let Some(started_at) = service.started_at {
-            let service_start_time = started_at;
-            let service_ttl = now - service_start_time;
-            if service_ttl.num_seconds() > app_ttl_seconds.into() {
-                needs_termination = true;
-            } else {
-                remaining_ttl = std::cmp::max(
-                    app_ttl_seconds - service_ttl.num_seconds() as u32,
-                    remaining_ttl,
-                );
-            }
-            info!(
-                "{}.{} is running for {}; {} remaining before being stopped",
-                app.name,
-                service.service,
-                format_chrono_duration(&service_ttl),
-                format_chrono_duration(&chrono::Duration::seconds(remaining_ttl as i64))
-            );
+        app_ttl => {
+            let app_ttl_seconds: u32 = app_ttl.into();
+            app.services
+                .iter()
+                .filter(|service| service.is_running())
+                .filter_map(|service| service.started_at)
+                .map(|started_at| {
+                    started_at.max(ttl_started_at.unwrap_or(started_at))
+                        + chrono::Duration::seconds(app_ttl_seconds.into())
+                })
+                .min()
         }
     }
-    if needs_termination {
-        let destroy = app.status != AppStatus::Unsupported
-            && app.settings.as_ref().is_some_and(|s| s.destroy_on_ttl);
-        info!(
-            "{} app, because TTL {} reached for {}",
-            if destroy { "Destroying" } else { "Stopping" },
-            app_ttl_seconds,
-            app.name
-        );
-        let _ = match destroy {
-            false => force_stop_app(app_state, app).await?,
-            true => destroy_app(app_state, app).await?,
-        };
+}
+
+/// Latest of the recorded activity and the start of the app's running containers.
+///
+/// Returns `None` if no container of the app is running.
+fn last_activity(app: &AppData, recorded: Option<DateTime<Local>>) -> Option<DateTime<Local>> {
+    let started_at = app
+        .services
+        .iter()
+        .filter(|service| service.is_running())
+        .filter_map(|service| service.started_at)
+        .max()?;
+    Some(recorded.map_or(started_at, |recorded| recorded.max(started_at)))
+}
+
+/// Tell the notification receivers of the app that its TTL runs out soon.
+async fn warn_ttl_expiring(app_state: &SharedAppState, app: &AppData, remaining: chrono::Duration) {
+    info!(
+        "Warning receivers of {} that its TTL runs out in {}",
+        app.name,
+        format_chrono_duration(&remaining)
+    );
+    let Some(settings) = &app.settings else {
+        return;
+    };
+    let msg = Message::new(
+        MessageType::AppTtlExpiring(remaining.num_seconds().max(0) as u64),
+        app,
+    );
+    if let Err(e) = notify(app_state, settings.notify.iter(), &msg).await {
+        warn!("Failed to send TTL warning for {}: {}", app.name, e);
     }
+}
+
+async fn terminate_app(app_state: SharedAppState, app: &AppData) -> anyhow::Result<()> {
+    let destroy = app.status != AppStatus::Unsupported
+        && app.settings.as_ref().is_some_and(|s| s.destroy_on_ttl);
+    info!(
+        "{} app {}",
+        if destroy { "Destroying" } else { "Stopping" },
+        app.name
+    );
+    let _ = match destroy {
+        false => force_stop_app(app_state, app, None).await?,
+        true => destroy_app(app_state, app, None).await?,
+    };
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use scotty_core::apps::app_data::{AppSettings, ContainerState, ContainerStatus};
+
+    fn app_started_at(started_at: DateTime<Local>, status: ContainerStatus) -> AppData {
+        AppData {
+            services: vec![ContainerState {
+                status,
+                started_at: Some(started_at),
+                ..Default::default()
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_last_activity_prefers_latest() {
+        let now = Local::now();
+        let started = now - chrono::Duration::hours(10);
+        let app = app_started_at(started, ContainerStatus::Running);
+
+        assert_eq!(last_activity(&app, None), Some(started));
+        assert_eq!(last_activity(&app, Some(now)), Some(now));
+        assert_eq!(
+            last_activity(&app, Some(started - chrono::Duration::hours(1))),
+            Some(started)
+        );
+    }
+
+    #[test]
+    fn test_ttl_deadline() {
+        let now = Local::now();
+        let started = now - chrono::Duration::hours(10);
+        let mut app = app_started_at(started, ContainerStatus::Running);
+        app.settings = Some(AppSettings {
+            time_to_live: AppTtl::Hours(12),
+            ..Default::default()
+        });
+        assert_eq!(
+            ttl_deadline(&app, None),
+            Some(started + chrono::Duration::hours(12))
+        );
+
+        // Extending the TTL restarts the clock
+        app.settings.as_mut().unwrap().ttl_started_at = Some(now);
+        assert_eq!(
+            ttl_deadline(&app, None),
+            Some(now + chrono::Duration::hours(12))
+        );
+
+        // An idle TTL counts from the last request
+        let settings = app.settings.as_mut().unwrap();
+        settings.time_to_live = AppTtl::Idle(2);
+        settings.ttl_started_at = None;
+        let request = now - chrono::Duration::hours(1);
+        assert_eq!(
+            ttl_deadline(&app, Some(request)),
+            Some(request + chrono::Duration::hours(2))
+        );
+
+        app.settings.as_mut().unwrap().time_to_live = AppTtl::Forever;
+        assert_eq!(ttl_deadline(&app, Some(request)), None);
+    }
+
+    #[test]
+    fn test_last_activity_ignores_stopped_apps() {
+        let now = Local::now();
+        let app = app_started_at(now, ContainerStatus::Exited);
+        assert_eq!(last_activity(&app, Some(now)), None);
+    }
+}
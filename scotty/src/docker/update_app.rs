@@ -0,0 +1,551 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::Context as _;
+use tokio::sync::RwLock;
+use tracing::{info, instrument};
+
+use crate::api::error::AppError;
+use crate::app_state::SharedAppState;
+use crate::state_machine::{StateHandler, StateMachine};
+use scotty_core::apps::app_data::{AppData, AppSettings, AppStatus};
+use scotty_core::apps::file_list::{File, FileList};
+use scotty_core::notification_types::{Message, MessageType};
+use scotty_core::settings::app_blueprint::ActionName;
+use scotty_core::tasks::running_app_context::RunningAppContext;
+
+use super::create_app::validate_app;
+use super::helper::run_sm;
+use super::loadbalancer::factory::LoadBalancerFactory;
+use super::rebuild_app::rebuild_app_prepare;
+use super::state_machine_handlers::context::Context;
+use super::state_machine_handlers::create_load_balancer_config::CreateLoadBalancerConfig;
+use super::state_machine_handlers::release_tcp_ports_handler::ReleaseTcpPortsHandler;
+use super::state_machine_handlers::revision_handler::{
+    CreateRevisionHandler, RollbackRevisionHandler,
+};
+use super::state_machine_handlers::run_docker_compose_handler::RunDockerComposeHandler;
+use super::state_machine_handlers::run_post_actions_handler::RunPostActionsHandler;
+use super::state_machine_handlers::save_files_handler::SaveFilesHandler;
+use super::state_machine_handlers::save_settings_handler::SaveSettingsHandler;
+use super::state_machine_handlers::task_completion_handler::TaskCompletionHandler;
+use super::state_machine_handlers::update_app_data_handler::UpdateAppDataHandler;
+
+/// Adds a status line describing the detected changes to the task output.
+#[derive(Debug)]
+struct ReportChangesHandler {
+    next_state: UpdateAppStates,
+    message: String,
+}
+
+#[async_trait::async_trait]
+impl StateHandler<UpdateAppStates, Context> for ReportChangesHandler {
+    async fn transition(
+        &self,
+        _from: &UpdateAppStates,
+        context: Arc<RwLock<Context>>,
+    ) -> anyhow::Result<UpdateAppStates> {
+        let context = context.read().await;
+        let task_id = context.task.read().await.id;
+        context
+            .app_state
+            .task_manager
+            .add_task_status(&task_id, self.message.clone())
+            .await;
+
+        Ok(self.next_state)
+    }
+}
+
+struct RunDockerComposeBuildHandler {
+    next_state: UpdateAppStates,
+    app: AppData,
+}
+
+#[async_trait::async_trait]
+impl StateHandler<UpdateAppStates, Context> for RunDockerComposeBuildHandler {
+    async fn transition(
+        &self,
+        _from: &UpdateAppStates,
+        context: Arc<RwLock<Context>>,
+    ) -> anyhow::Result<UpdateAppStates> {
+        let app_state = &context.read().await.app_state;
+        let sm = rebuild_app_prepare(app_state, &self.app, false).await?;
+        let handle = sm.spawn(context.clone());
+
+        // Gracefully handle both errors and panics from nested state machine
+        handle
+            .await
+            .map_err(|e| anyhow::anyhow!("Docker compose rebuild task panicked: {}", e))?
+            .context("Docker compose rebuild failed")?;
+
+        Ok(self.next_state)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+enum UpdateAppStates {
+    ReportChanges,
+    CreateRevision,
+    SaveSettings,
+    SaveFiles,
+    CreateLoadBalancerConfig,
+    RecreateContainers,
+    RunDockerComposeBuildAndRun,
+    RunPostActions,
+    UpdateAppData,
+    SetFinished,
+    SetFailed,
+    Done,
+}
+
+/// Changes an update applies to an app.
+#[derive(Debug, Default)]
+struct UpdateChanges {
+    /// Files which are new or differ from the files on disk
+    files: Vec<File>,
+    /// New settings, if they differ from the current ones
+    settings: Option<AppSettings>,
+    /// Only the routing changed, the app is not rebuilt
+    routing_only: bool,
+    /// The load balancer reads the routing from the running containers, so
+    /// they are recreated to apply it
+    recreate_containers: bool,
+}
+
+impl UpdateChanges {
+    fn is_empty(&self) -> bool {
+        self.files.is_empty() && self.settings.is_none()
+    }
+
+    fn describe(&self) -> String {
+        if self.is_empty() {
+            return "No changes detected, skipping rebuild".to_string();
+        }
+        if self.routing_only {
+            return match self.recreate_containers {
+                true => "Updating routing settings, recreating the containers".to_string(),
+                false => "Updating routing settings, keeping the containers".to_string(),
+            };
+        }
+        let mut changes = Vec::new();
+        if self.settings.is_some() {
+            changes.push("settings".to_string());
+        }
+        if !self.files.is_empty() {
+            changes.push(format!(
+                "{} file(s): {}",
+                self.files.len(),
+                self.files
+                    .iter()
+                    .map(|file| file.name.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+        }
+        format!("Updating {}", changes.join(" and "))
+    }
+}
+
+/// Whether the settings only differ in fields which end up in the routing of
+/// the load balancer, like basic auth or middlewares.
+fn only_routing_changed(current: &AppSettings, new: &AppSettings) -> anyhow::Result<bool> {
+    let without_routing = |settings: &AppSettings| AppSettings {
+        basic_auth: vec![],
+        ip_allowlist: vec![],
+        forward_auth: false,
+        disallow_robots: false,
+        response_headers: Default::default(),
+        redirects: vec![],
+        maintenance: false,
+        middlewares: vec![],
+        ..settings.clone()
+    };
+    Ok(serde_norway::to_string(&without_routing(current))?
+        == serde_norway::to_string(&without_routing(new))?)
+}
+
+/// Whether the settings only differ in the maintenance mode, which Traefik
+/// applies without touching the containers, also when routing via labels.
+fn only_maintenance_changed(current: &AppSettings, new: &AppSettings) -> anyhow::Result<bool> {
+    let without_maintenance = |settings: &AppSettings| AppSettings {
+        maintenance: false,
+        ..settings.clone()
+    };
+    Ok(serde_norway::to_string(&without_maintenance(current))?
+        == serde_norway::to_string(&without_maintenance(new))?)
+}
+
+/// Return the files which are new or differ from the files in the app directory.
+async fn get_changed_files(root_directory: &Path, files: &FileList) -> anyhow::Result<Vec<File>> {
+    let mut changed = Vec::new();
+    for file in &files.files {
+        let file_path = path_clean::clean(root_directory.join(&file.name));
+        if !file_path.starts_with(root_directory) {
+            return Err(anyhow::anyhow!(
+                "Attempted directory traversal attack detected"
+            ));
+        }
+        match tokio::fs::read(&file_path).await {
+            Ok(content) if content == file.content => {}
+            Ok(_) => changed.push(file.clone()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => changed.push(file.clone()),
+            Err(e) => return Err(e.into()),
+        }
+    }
+    Ok(changed)
+}
+
+async fn update_app_prepare(
+    app_state: SharedAppState,
+    app: &AppData,
+    changes: &UpdateChanges,
+) -> anyhow::Result<StateMachine<UpdateAppStates, Context>> {
+    let mut sm = StateMachine::new(UpdateAppStates::ReportChanges, UpdateAppStates::Done);
+    sm.set_error_state(UpdateAppStates::SetFailed);
+
+    sm.add_handler(
+        UpdateAppStates::ReportChanges,
+        Arc::new(ReportChangesHandler {
+            next_state: match changes.is_empty() {
+                true => UpdateAppStates::SetFinished,
+                false => UpdateAppStates::CreateRevision,
+            },
+            message: changes.describe(),
+        }),
+    );
+    sm.add_handler(
+        UpdateAppStates::SetFinished,
+        Arc::new(TaskCompletionHandler::success(
+            UpdateAppStates::Done,
+            match changes.is_empty() {
+                true => None,
+                false => Some(Message::new(MessageType::AppUpdated, app)),
+            },
+        )),
+    );
+    sm.add_handler(
+        UpdateAppStates::SetFailed,
+        Arc::new(ReleaseTcpPortsHandler {
+            completion: RollbackRevisionHandler {
+                completion: TaskCompletionHandler::failure(UpdateAppStates::Done, None),
+            },
+        }),
+    );
+    if changes.is_empty() {
+        return Ok(sm);
+    }
+
+    let settings = app
+        .settings
+        .clone()
+        .ok_or_else(|| AppError::AppSettingsNotFound(app.name.clone()))?;
+    let mut applied_files: Vec<String> =
+        changes.files.iter().map(|file| file.name.clone()).collect();
+    if changes.settings.is_some() {
+        applied_files.push(".scotty.yml".to_string());
+    }
+
+    sm.add_handler(
+        UpdateAppStates::CreateRevision,
+        Arc::new(CreateRevisionHandler::<UpdateAppStates> {
+            next_state: UpdateAppStates::SaveSettings,
+            reason: "update".to_string(),
+            applied_files,
+        }),
+    );
+    sm.add_handler(
+        UpdateAppStates::SaveSettings,
+        Arc::new(SaveSettingsHandler::<UpdateAppStates> {
+            next_state: UpdateAppStates::SaveFiles,
+            settings: settings.clone(),
+        }),
+    );
+    sm.add_handler(
+        UpdateAppStates::SaveFiles,
+        Arc::new(SaveFilesHandler::<UpdateAppStates> {
+            next_state: UpdateAppStates::CreateLoadBalancerConfig,
+            files: FileList {
+                files: changes.files.clone(),
+            },
+        }),
+    );
+    sm.add_handler(
+        UpdateAppStates::CreateLoadBalancerConfig,
+        Arc::new(CreateLoadBalancerConfig::<UpdateAppStates> {
+            next_state: match (changes.routing_only, changes.recreate_containers) {
+                (true, true) => UpdateAppStates::RecreateContainers,
+                (true, false) => UpdateAppStates::UpdateAppData,
+                (false, _) => UpdateAppStates::RunDockerComposeBuildAndRun,
+            },
+            load_balancer_type: app_state.settings.load_balancer_type.clone(),
+            settings: settings.clone(),
+        }),
+    );
+    // Compose only recreates the containers whose labels changed
+    sm.add_handler(
+        UpdateAppStates::RecreateContainers,
+        Arc::new(RunDockerComposeHandler::<UpdateAppStates> {
+            next_state: UpdateAppStates::UpdateAppData,
+            command: ["up", "-d"].iter().map(|s| s.to_string()).collect(),
+            env: app.get_environment(),
+        }),
+    );
+    sm.add_handler(
+        UpdateAppStates::RunDockerComposeBuildAndRun,
+        Arc::new(RunDockerComposeBuildHandler {
+            next_state: UpdateAppStates::RunPostActions,
+            app: app.clone(),
+        }),
+    );
+    sm.add_handler(
+        UpdateAppStates::RunPostActions,
+        Arc::new(RunPostActionsHandler::<UpdateAppStates> {
+            next_state: UpdateAppStates::UpdateAppData,
+            action: ActionName::PostRebuild,
+            settings: Some(settings),
+        }),
+    );
+    sm.add_handler(
+        UpdateAppStates::UpdateAppData,
+        Arc::new(UpdateAppDataHandler::<UpdateAppStates> {
+            next_state: UpdateAppStates::SetFinished,
+        }),
+    );
+
+    Ok(sm)
+}
+
+/// Push new files and optionally new settings to an existing app.
+///
+/// Only files which differ from the files on disk are written. The app is
+/// rebuilt only if files or settings changed, otherwise the task finishes
+/// right away.
+#[instrument(skip(app_state, files))]
+pub async fn update_app(
+    app_state: SharedAppState,
+    app: &AppData,
+    settings: Option<AppSettings>,
+    files: &FileList,
+    requested_by: Option<&str>,
+) -> anyhow::Result<RunningAppContext> {
+    if app.status == AppStatus::Unsupported {
+        return Err(AppError::OperationNotSupportedForLegacyApp(app.name.clone()).into());
+    }
+    let current_settings = app
+        .settings
+        .clone()
+        .ok_or_else(|| AppError::AppSettingsNotFound(app.name.clone()))?;
+
+    // The scopes of an app can't be changed by an update, the maintenance
+    // mode is only toggled on its own
+    let settings = settings.map(|settings| AppSettings {
+        scopes: current_settings.scopes.clone(),
+        maintenance: current_settings.maintenance,
+        ..settings
+    });
+    let compose_file = validate_app(
+        app_state.clone(),
+        settings.as_ref().unwrap_or(&current_settings),
+        files,
+    )
+    .await?;
+    let current_compose_file = Path::new(&app.docker_compose_path)
+        .strip_prefix(&app.root_directory)
+        .map(|path| path.display().to_string())
+        .unwrap_or_default();
+    if compose_file.name != current_compose_file {
+        return Err(AppError::BadRequest(format!(
+            "The compose file of app {} is {}, it can't be replaced by {}",
+            app.name, current_compose_file, compose_file.name
+        ))
+        .into());
+    }
+
+    let changed_files = get_changed_files(Path::new(&app.root_directory), files).await?;
+    apply_changes(
+        app_state,
+        app,
+        current_settings,
+        settings,
+        changed_files,
+        requested_by,
+    )
+    .await
+}
+
+/// Apply new settings to an app without pushing files, e.g. after its basic
+/// auth users changed. Routing changes don't rebuild the app.
+#[instrument(skip(app_state, settings))]
+pub async fn update_app_settings(
+    app_state: SharedAppState,
+    app: &AppData,
+    settings: AppSettings,
+    requested_by: Option<&str>,
+) -> anyhow::Result<RunningAppContext> {
+    if app.status == AppStatus::Unsupported {
+        return Err(AppError::OperationNotSupportedForLegacyApp(app.name.clone()).into());
+    }
+    let current_settings = app
+        .settings
+        .clone()
+        .ok_or_else(|| AppError::AppSettingsNotFound(app.name.clone()))?;
+    apply_changes(
+        app_state,
+        app,
+        current_settings,
+        Some(settings),
+        vec![],
+        requested_by,
+    )
+    .await
+}
+
+async fn apply_changes(
+    app_state: SharedAppState,
+    app: &AppData,
+    current_settings: AppSettings,
+    settings: Option<AppSettings>,
+    changed_files: Vec<File>,
+    requested_by: Option<&str>,
+) -> anyhow::Result<RunningAppContext> {
+    let settings_changed = match &settings {
+        Some(settings) => {
+            serde_norway::to_string(settings)? != serde_norway::to_string(&current_settings)?
+        }
+        None => false,
+    };
+    let settings = settings.filter(|_| settings_changed);
+    let routing_only = match &settings {
+        Some(settings) if changed_files.is_empty() => {
+            only_routing_changed(&current_settings, settings)?
+        }
+        _ => false,
+    };
+    let maintenance_only = match &settings {
+        Some(settings) if routing_only => only_maintenance_changed(&current_settings, settings)?,
+        _ => false,
+    };
+    // Stopped apps pick up the new routing when they are started again
+    let recreate_containers = routing_only
+        && !maintenance_only
+        && app.status == AppStatus::Running
+        && !LoadBalancerFactory::create(&app_state.settings.load_balancer_type)
+            .applies_routing_without_recreate(&app_state.settings);
+    let changes = UpdateChanges {
+        files: changed_files,
+        settings,
+        routing_only,
+        recreate_containers,
+    };
+    info!("Updating app {}: {}", app.name, changes.describe());
+
+    let app_data = AppData {
+        settings: Some(changes.settings.clone().unwrap_or(current_settings)),
+        ..app.clone()
+    };
+    let sm = update_app_prepare(app_state.clone(), &app_data, &changes).await?;
+    run_sm(app_state, &app_data, sm, requested_by).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use scotty_core::apps::app_data::BasicAuthUser;
+
+    fn file(name: &str, content: &str) -> File {
+        File {
+            name: name.to_string(),
+            content: content.as_bytes().to_vec(),
+            compressed: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_changed_files() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("compose.yml"), "services: {}").unwrap();
+        std::fs::write(dir.path().join("nginx.conf"), "old").unwrap();
+
+        let files = FileList {
+            files: vec![
+                file("compose.yml", "services: {}"),
+                file("nginx.conf", "new"),
+                file("config/app.env", "FOO=bar"),
+            ],
+        };
+        let changed = get_changed_files(dir.path(), &files).await.unwrap();
+        let names: Vec<&str> = changed.iter().map(|file| file.name.as_str()).collect();
+        assert_eq!(names, vec!["nginx.conf", "config/app.env"]);
+    }
+
+    #[tokio::test]
+    async fn test_get_changed_files_rejects_traversal() {
+        let dir = tempfile::tempdir().unwrap();
+        let files = FileList {
+            files: vec![file("../outside.txt", "nope")],
+        };
+        assert!(get_changed_files(dir.path(), &files).await.is_err());
+    }
+
+    #[test]
+    fn test_describe_changes() {
+        assert_eq!(
+            UpdateChanges::default().describe(),
+            "No changes detected, skipping rebuild"
+        );
+        let changes = UpdateChanges {
+            files: vec![file("compose.yml", "")],
+            settings: Some(AppSettings::default()),
+            ..Default::default()
+        };
+        assert_eq!(
+            changes.describe(),
+            "Updating settings and 1 file(s): compose.yml"
+        );
+        let changes = UpdateChanges {
+            settings: Some(AppSettings::default()),
+            routing_only: true,
+            recreate_containers: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            changes.describe(),
+            "Updating routing settings, recreating the containers"
+        );
+    }
+
+    #[test]
+    fn test_only_routing_changed() {
+        let current = AppSettings::default();
+        let new = AppSettings {
+            basic_auth: vec![BasicAuthUser::new("user", "pass")],
+            disallow_robots: true,
+            middlewares: vec!["custom@file".to_string()],
+            ..current.clone()
+        };
+        assert!(only_routing_changed(&current, &new).unwrap());
+
+        let new = AppSettings {
+            domain: "other.example.com".to_string(),
+            ..new
+        };
+        assert!(!only_routing_changed(&current, &new).unwrap());
+    }
+
+    #[test]
+    fn test_only_maintenance_changed() {
+        let current = AppSettings::default();
+        let new = AppSettings {
+            maintenance: true,
+            ..current.clone()
+        };
+        assert!(only_maintenance_changed(&current, &new).unwrap());
+
+        let new = AppSettings {
+            forward_auth: true,
+            ..new
+        };
+        assert!(!only_maintenance_changed(&current, &new).unwrap());
+    }
+}
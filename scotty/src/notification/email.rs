@@ -0,0 +1,208 @@
+use async_trait::async_trait;
+use lettre::{
+    message::{Mailbox, MultiPart},
+    transport::smtp::authentication::Credentials,
+    AsyncSmtpTransport, AsyncTransport, Tokio1Executor,
+};
+use tracing::info;
+
+use scotty_core::notification_types::{EmailContext, Message, NotificationImpl};
+use scotty_core::settings::notification_services::EmailSettings;
+
+use super::escape_html;
+
+pub struct NotifyEmail {
+    settings: EmailSettings,
+    context: EmailContext,
+}
+
+impl NotifyEmail {
+    pub fn new(settings: &EmailSettings, context: &EmailContext) -> Self {
+        NotifyEmail {
+            settings: settings.to_owned(),
+            context: context.to_owned(),
+        }
+    }
+
+    fn build_email(&self, msg: &Message) -> anyhow::Result<lettre::Message> {
+        let mut builder = lettre::Message::builder()
+            .from(self.settings.from.parse::<Mailbox>()?)
+            .subject(format!(
+                "[scotty] {}: {}",
+                msg.app_name,
+                msg.message_type.event_name()
+            ));
+        for recipient in &self.context.recipients {
+            builder = builder.to(recipient
+                .parse::<Mailbox>()
+                .map_err(|e| anyhow::anyhow!("Invalid recipient {}: {}", recipient, e))?);
+        }
+
+        let mut text = msg.message.clone();
+        let mut html = format!("<p>{}</p>", escape_html(&msg.message));
+        if !msg.urls.is_empty() {
+            text = format!("{}\n\n* {}", text, msg.urls.join("\n* "));
+            let links: Vec<String> = msg
+                .urls
+                .iter()
+                .map(|url| {
+                    let url = escape_html(url);
+                    format!("<li><a href=\"{url}\">{url}</a></li>")
+                })
+                .collect();
+            html = format!("{}<ul>{}</ul>", html, links.join(""));
+        }
+
+        Ok(builder.multipart(MultiPart::alternative_plain_html(text, html))?)
+    }
+
+    fn transport(&self) -> anyhow::Result<AsyncSmtpTransport<Tokio1Executor>> {
+        let mut builder = match self.settings.starttls {
+            true => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&self.settings.host)?,
+            false => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&self.settings.host),
+        }
+        .port(self.settings.port);
+        if let (Some(username), Some(password)) = (&self.settings.username, &self.settings.password)
+        {
+            builder = builder.credentials(Credentials::new(
+                username.expose_secret().to_string(),
+                password.expose_secret().to_string(),
+            ));
+        }
+        Ok(builder.build())
+    }
+}
+
+#[async_trait]
+impl NotificationImpl for NotifyEmail {
+    async fn notify(&self, msg: &Message) -> anyhow::Result<()> {
+        info!(
+            "Sending email notification to {} via {}:{}",
+            self.context.recipients.join(", "),
+            &self.settings.host,
+            self.settings.port
+        );
+        if self.context.recipients.is_empty() {
+            return Err(anyhow::anyhow!(
+                "Email receiver for service {} has no recipients",
+                self.context.service_id
+            ));
+        }
+
+        let email = self.build_email(msg)?;
+        self.transport()?
+            .send(email)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to send email: {}", e))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use scotty_core::notification_types::MessageType;
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::TcpListener;
+
+    /// A minimal SMTP server accepting a single mail, returns the envelope
+    /// recipients and the mail data.
+    async fn smtp_stand_in() -> (u16, tokio::task::JoinHandle<(Vec<String>, String)>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let handle = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let (reader, mut writer) = stream.into_split();
+            let mut lines = BufReader::new(reader).lines();
+            let mut recipients = vec![];
+            let mut data = String::new();
+
+            writer.write_all(b"220 localhost ESMTP\r\n").await.unwrap();
+            while let Some(line) = lines.next_line().await.unwrap() {
+                let command = line.to_uppercase();
+                let reply: &[u8] = if command.starts_with("EHLO") {
+                    b"250 localhost\r\n"
+                } else if command.starts_with("RCPT TO:") {
+                    recipients.push(line[8..].trim_matches(|c| c == '<' || c == '>').to_string());
+                    b"250 OK\r\n"
+                } else if command == "DATA" {
+                    writer.write_all(b"354 Go ahead\r\n").await.unwrap();
+                    while let Some(line) = lines.next_line().await.unwrap() {
+                        if line == "." {
+                            break;
+                        }
+                        data.push_str(&line);
+                        data.push('\n');
+                    }
+                    b"250 OK queued\r\n"
+                } else if command == "QUIT" {
+                    writer.write_all(b"221 Bye\r\n").await.unwrap();
+                    break;
+                } else {
+                    b"250 OK\r\n"
+                };
+                writer.write_all(reply).await.unwrap();
+            }
+            (recipients, data)
+        });
+        (port, handle)
+    }
+
+    #[tokio::test]
+    async fn test_notify_email() {
+        let (port, server) = smtp_stand_in().await;
+        let settings: EmailSettings = serde_norway::from_str(&format!(
+            "host: 127.0.0.1\nport: {port}\nstarttls: false\nfrom: Scotty <scotty@example.com>"
+        ))
+        .unwrap();
+        let context = EmailContext {
+            service_id: "smtp".to_string(),
+            recipients: vec!["pm@example.com".to_string(), "qa@example.com".to_string()],
+        };
+        let msg = Message {
+            message_type: MessageType::AppCreated,
+            app_name: "my-app".to_string(),
+            message: "App my-app created".to_string(),
+            urls: vec!["https://my-app.example.com".to_string()],
+            task_id: None,
+        };
+
+        NotifyEmail::new(&settings, &context)
+            .notify(&msg)
+            .await
+            .unwrap();
+
+        let (recipients, data) = server.await.unwrap();
+        assert_eq!(recipients, vec!["pm@example.com", "qa@example.com"]);
+        assert!(data.contains("Subject: [scotty] my-app: Created"));
+        assert!(data.contains("multipart/alternative"));
+        assert!(data.contains("Content-Type: text/plain"));
+        assert!(data.contains("Content-Type: text/html"));
+        assert!(data.contains("* https://my-app.example.com"));
+        assert!(data.contains("<li><a href="));
+    }
+
+    #[tokio::test]
+    async fn test_notify_email_without_recipients_fails() {
+        let settings: EmailSettings =
+            serde_norway::from_str("host: 127.0.0.1\nfrom: scotty@example.com").unwrap();
+        assert_eq!(settings.port, 587);
+        assert!(settings.starttls);
+        let context = EmailContext {
+            service_id: "smtp".to_string(),
+            recipients: vec![],
+        };
+        let msg = Message {
+            message_type: MessageType::AppCreated,
+            app_name: "my-app".to_string(),
+            message: "App my-app created".to_string(),
+            urls: vec![],
+            task_id: None,
+        };
+
+        assert!(NotifyEmail::new(&settings, &context)
+            .notify(&msg)
+            .await
+            .is_err());
+    }
+}
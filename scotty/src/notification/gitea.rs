@@ -0,0 +1,154 @@
+use async_trait::async_trait;
+use reqwest::Client;
+use serde_json::json;
+use tracing::{debug, info};
+
+use scotty_core::notification_types::{GiteaContext, Message, MessageType, NotificationImpl};
+use scotty_core::settings::notification_services::{CommitReport, GiteaSettings};
+
+use super::{ensure_success, markdown_comment};
+
+pub struct NotifyGitea {
+    context: GiteaContext,
+    settings: GiteaSettings,
+}
+
+impl NotifyGitea {
+    pub fn new(settings: &GiteaSettings, context: &GiteaContext) -> Self {
+        NotifyGitea {
+            settings: settings.to_owned(),
+            context: context.to_owned(),
+        }
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!(
+            "{}/api/v1/repos/{}/{}",
+            self.settings.host.trim_end_matches('/'),
+            self.context.repo,
+            path
+        )
+    }
+
+    async fn post(
+        &self,
+        client: &Client,
+        path: &str,
+        body: serde_json::Value,
+    ) -> anyhow::Result<()> {
+        let response = client
+            .post(self.url(path))
+            .header(
+                "Authorization",
+                format!("token {}", self.settings.token.expose_secret()),
+            )
+            .json(&body)
+            .send()
+            .await?;
+        ensure_success(response, &format!("post to gitea {}", path)).await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl NotificationImpl for NotifyGitea {
+    async fn notify(&self, msg: &Message) -> anyhow::Result<()> {
+        info!(
+            "Sending gitea notification to PR {} of {} at {}",
+            &self.context.pr, &self.context.repo, &self.settings.host
+        );
+        let client = Client::new();
+        // Pull requests share their numbers with issues
+        self.post(
+            &client,
+            &format!("issues/{}/comments", self.context.pr),
+            json!({ "body": markdown_comment(msg) }),
+        )
+        .await?;
+
+        let destroyed = matches!(msg.message_type, MessageType::AppDestroyed);
+        if self.settings.commit_report != CommitReport::Status
+            || !(destroyed || msg.message_type.is_deployment())
+        {
+            return Ok(());
+        }
+        let Some(sha) = &self.context.sha else {
+            debug!(
+                "No commit given for PR {}, skipping status",
+                self.context.pr
+            );
+            return Ok(());
+        };
+        let mut status = json!({
+            "state": "success",
+            "description": msg.message,
+            "context": format!("scotty/{}", msg.app_name),
+        });
+        if !destroyed {
+            if let Some(url) = msg.urls.first() {
+                status["target_url"] = json!(url);
+            }
+        }
+        self.post(&client, &format!("statuses/{}", sha), status)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::{
+        matchers::{body_partial_json, header, method, path},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    #[tokio::test]
+    async fn test_comment_and_commit_status() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/api/v1/repos/ops/website/issues/3/comments"))
+            .and(header("Authorization", "token gitea-secret"))
+            .and(body_partial_json(json!({
+                "body": "**App website created**\n\nUrls:\n- [https://website.example.com](https://website.example.com)"
+            })))
+            .respond_with(ResponseTemplate::new(201))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/api/v1/repos/ops/website/statuses/a1b2c3d"))
+            .and(body_partial_json(json!({
+                "state": "success",
+                "context": "scotty/website",
+                "target_url": "https://website.example.com",
+            })))
+            .respond_with(ResponseTemplate::new(201))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let settings: GiteaSettings = serde_norway::from_str(&format!(
+            "host: {}\ntoken: gitea-secret\ncommit_report: status",
+            server.uri()
+        ))
+        .unwrap();
+        let context = GiteaContext {
+            service_id: "gitea".to_string(),
+            repo: "ops/website".to_string(),
+            pr: 3,
+            sha: Some("a1b2c3d".to_string()),
+        };
+        let msg = Message {
+            message_type: MessageType::AppCreated,
+            app_name: "website".to_string(),
+            message: "App website created".to_string(),
+            urls: vec!["https://website.example.com".to_string()],
+            task_id: None,
+        };
+
+        NotifyGitea::new(&settings, &context)
+            .notify(&msg)
+            .await
+            .unwrap();
+    }
+}
@@ -0,0 +1,318 @@
+use async_trait::async_trait;
+use reqwest::{Client, RequestBuilder};
+use serde::Deserialize;
+use serde_json::json;
+use tracing::{debug, info};
+
+use scotty_core::notification_types::{GithubContext, Message, MessageType, NotificationImpl};
+use scotty_core::settings::notification_services::{CommitReport, GithubSettings};
+
+use super::{ensure_success, markdown_comment};
+
+pub struct NotifyGithub {
+    context: GithubContext,
+    settings: GithubSettings,
+    client: Client,
+}
+
+#[derive(Deserialize)]
+struct Deployment {
+    id: u64,
+}
+
+impl NotifyGithub {
+    pub fn new(settings: &GithubSettings, context: &GithubContext) -> Self {
+        NotifyGithub {
+            settings: settings.to_owned(),
+            context: context.to_owned(),
+            client: Client::new(),
+        }
+    }
+
+    fn request(&self, method: reqwest::Method, path: &str) -> RequestBuilder {
+        self.client
+            .request(
+                method,
+                format!(
+                    "{}/repos/{}/{}",
+                    self.settings.api_url.trim_end_matches('/'),
+                    self.context.repo,
+                    path
+                ),
+            )
+            .bearer_auth(self.settings.token.expose_secret())
+            .header("Accept", "application/vnd.github+json")
+            .header("X-GitHub-Api-Version", "2022-11-28")
+            .header("User-Agent", "scotty")
+    }
+
+    async fn add_comment(&self, msg: &Message) -> anyhow::Result<()> {
+        let response = self
+            .request(
+                reqwest::Method::POST,
+                &format!("issues/{}/comments", self.context.pr),
+            )
+            .json(&json!({ "body": markdown_comment(msg) }))
+            .send()
+            .await?;
+        ensure_success(response, "add comment to pull request").await?;
+        Ok(())
+    }
+
+    async fn set_commit_status(&self, msg: &Message, sha: &str) -> anyhow::Result<()> {
+        let mut status = json!({
+            "state": "success",
+            "description": msg.message,
+            "context": format!("scotty/{}", msg.app_name),
+        });
+        if msg.message_type.is_deployment() {
+            if let Some(url) = msg.urls.first() {
+                status["target_url"] = json!(url);
+            }
+        }
+        let response = self
+            .request(reqwest::Method::POST, &format!("statuses/{}", sha))
+            .json(&status)
+            .send()
+            .await?;
+        ensure_success(response, "set commit status").await?;
+        Ok(())
+    }
+
+    async fn create_deployment(&self, msg: &Message, sha: &str) -> anyhow::Result<()> {
+        let response = self
+            .request(reqwest::Method::POST, "deployments")
+            .json(&json!({
+                "ref": sha,
+                "environment": msg.app_name,
+                "description": msg.message,
+                "auto_merge": false,
+                "required_contexts": [],
+                "transient_environment": true,
+                "production_environment": false,
+            }))
+            .send()
+            .await?;
+        let deployment: Deployment = ensure_success(response, "create deployment")
+            .await?
+            .json()
+            .await?;
+
+        let mut status = json!({
+            "state": "success",
+            "description": msg.message,
+            "auto_inactive": true,
+        });
+        if let Some(url) = msg.urls.first() {
+            status["environment_url"] = json!(url);
+        }
+        self.set_deployment_status(deployment.id, status).await
+    }
+
+    async fn deactivate_deployment(&self, msg: &Message) -> anyhow::Result<()> {
+        let response = self
+            .request(
+                reqwest::Method::GET,
+                &format!(
+                    "deployments?environment={}&per_page=1",
+                    urlencoding::encode(&msg.app_name)
+                ),
+            )
+            .send()
+            .await?;
+        let deployments: Vec<Deployment> = ensure_success(response, "list deployments")
+            .await?
+            .json()
+            .await?;
+        // Older deployments were deactivated by `auto_inactive`
+        match deployments.first() {
+            Some(deployment) => {
+                self.set_deployment_status(
+                    deployment.id,
+                    json!({ "state": "inactive", "description": msg.message }),
+                )
+                .await
+            }
+            None => Ok(()),
+        }
+    }
+
+    async fn set_deployment_status(
+        &self,
+        deployment_id: u64,
+        status: serde_json::Value,
+    ) -> anyhow::Result<()> {
+        let response = self
+            .request(
+                reqwest::Method::POST,
+                &format!("deployments/{}/statuses", deployment_id),
+            )
+            .json(&status)
+            .send()
+            .await?;
+        ensure_success(response, "set deployment status").await?;
+        Ok(())
+    }
+
+    async fn report_commit(&self, msg: &Message) -> anyhow::Result<()> {
+        let destroyed = matches!(msg.message_type, MessageType::AppDestroyed);
+        if !destroyed && !msg.message_type.is_deployment() {
+            return Ok(());
+        }
+        match (&self.settings.commit_report, &self.context.sha) {
+            (CommitReport::None, _) => Ok(()),
+            (CommitReport::Deployment, _) if destroyed => self.deactivate_deployment(msg).await,
+            (_, None) => {
+                debug!(
+                    "No commit given for PR {}, skipping report",
+                    self.context.pr
+                );
+                Ok(())
+            }
+            (CommitReport::Status, Some(sha)) => self.set_commit_status(msg, sha).await,
+            (CommitReport::Deployment, Some(sha)) => self.create_deployment(msg, sha).await,
+        }
+    }
+}
+
+#[async_trait]
+impl NotificationImpl for NotifyGithub {
+    async fn notify(&self, msg: &Message) -> anyhow::Result<()> {
+        info!(
+            "Sending github notification to PR {} of {}",
+            &self.context.pr, &self.context.repo
+        );
+        self.add_comment(msg).await?;
+        self.report_commit(msg).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::{
+        matchers::{body_partial_json, header, method, path, query_param},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    fn notifier(server: &MockServer, commit_report: &str, sha: Option<&str>) -> NotifyGithub {
+        let settings: GithubSettings = serde_norway::from_str(&format!(
+            "api_url: {}\ntoken: ghp_secret\ncommit_report: {}",
+            server.uri(),
+            commit_report
+        ))
+        .unwrap();
+        let context = GithubContext {
+            service_id: "github".to_string(),
+            repo: "factorial-io/scotty".to_string(),
+            pr: 12,
+            sha: sha.map(|sha| sha.to_string()),
+        };
+        NotifyGithub::new(&settings, &context)
+    }
+
+    fn message(message_type: MessageType) -> Message {
+        Message {
+            message_type,
+            app_name: "my-app".to_string(),
+            message: "App my-app rebuilt".to_string(),
+            urls: vec!["https://my-app.example.com".to_string()],
+            task_id: None,
+        }
+    }
+
+    async fn mock_comment(server: &MockServer) {
+        Mock::given(method("POST"))
+            .and(path("/repos/factorial-io/scotty/issues/12/comments"))
+            .and(header("Authorization", "Bearer ghp_secret"))
+            .and(body_partial_json(json!({
+                "body": "**App my-app rebuilt**\n\nUrls:\n- [https://my-app.example.com](https://my-app.example.com)"
+            })))
+            .respond_with(ResponseTemplate::new(201))
+            .expect(1)
+            .mount(server)
+            .await;
+    }
+
+    #[tokio::test]
+    async fn test_comment_and_commit_status() {
+        let server = MockServer::start().await;
+        mock_comment(&server).await;
+        Mock::given(method("POST"))
+            .and(path("/repos/factorial-io/scotty/statuses/a1b2c3d"))
+            .and(body_partial_json(json!({
+                "state": "success",
+                "context": "scotty/my-app",
+                "target_url": "https://my-app.example.com",
+            })))
+            .respond_with(ResponseTemplate::new(201))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        notifier(&server, "status", Some("a1b2c3d"))
+            .notify(&message(MessageType::AppRebuilt))
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_deployment_is_created() {
+        let server = MockServer::start().await;
+        mock_comment(&server).await;
+        Mock::given(method("POST"))
+            .and(path("/repos/factorial-io/scotty/deployments"))
+            .and(body_partial_json(
+                json!({ "ref": "a1b2c3d", "environment": "my-app" }),
+            ))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!({ "id": 42 })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/repos/factorial-io/scotty/deployments/42/statuses"))
+            .and(body_partial_json(json!({
+                "state": "success",
+                "environment_url": "https://my-app.example.com",
+            })))
+            .respond_with(ResponseTemplate::new(201))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        notifier(&server, "deployment", Some("a1b2c3d"))
+            .notify(&message(MessageType::AppRebuilt))
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_deployment_is_deactivated_on_destroy() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/repos/factorial-io/scotty/issues/12/comments"))
+            .respond_with(ResponseTemplate::new(201))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repos/factorial-io/scotty/deployments"))
+            .and(query_param("environment", "my-app"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([{ "id": 42 }])))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/repos/factorial-io/scotty/deployments/42/statuses"))
+            .and(body_partial_json(json!({ "state": "inactive" })))
+            .respond_with(ResponseTemplate::new(201))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        // The commit is not needed to find the environment
+        notifier(&server, "deployment", None)
+            .notify(&message(MessageType::AppDestroyed))
+            .await
+            .unwrap();
+    }
+}
@@ -1,25 +1,194 @@
 #![allow(dead_code)]
 
 use async_trait::async_trait;
-use reqwest::Client;
-use serde::Serialize;
-use tracing::info;
+use chrono::{DateTime, Utc};
+use reqwest::{Client, StatusCode};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tracing::{debug, info};
 
-use scotty_core::notification_types::{GitlabContext, Message, NotificationImpl};
-use scotty_core::settings::notification_services::GitlabSettings;
+use scotty_core::notification_types::{GitlabContext, Message, MessageType, NotificationImpl};
+use scotty_core::settings::notification_services::{CommitReport, GitlabNotes, GitlabSettings};
+
+use super::gitlab_notes::{GitlabNote, GitlabNoteStore};
+use super::{ensure_success, markdown_comment};
 
 pub struct NotifyGitlab {
     context: GitlabContext,
     settings: GitlabSettings,
+    notes: GitlabNoteStore,
 }
 
 impl NotifyGitlab {
-    pub fn new(settings: &GitlabSettings, context: &GitlabContext) -> Self {
+    pub fn new(settings: &GitlabSettings, context: &GitlabContext, notes: GitlabNoteStore) -> Self {
         NotifyGitlab {
             settings: settings.to_owned(),
             context: context.to_owned(),
+            notes,
+        }
+    }
+
+    /// Create the status note of the app, or edit the one created before.
+    async fn update_status_note(&self, client: &Client, msg: &Message) -> anyhow::Result<()> {
+        let _guard = GitlabNoteStore::lock().await;
+        let key = GitlabNoteStore::key(
+            &self.context.service_id,
+            &self.context.project_id,
+            self.context.mr_id,
+            &msg.app_name,
+        );
+        let previous = self.notes.get(&key).await?;
+        let state = app_state(&msg.message_type)
+            .map(|state| state.to_string())
+            .or_else(|| previous.as_ref().map(|note| note.state.clone()))
+            .unwrap_or_else(|| "Unknown".to_string());
+        let comment = MergeRequestComment {
+            body: status_table(msg, &state, Utc::now()),
+        };
+        let token = self.settings.token.expose_secret();
+
+        let edited = match &previous {
+            Some(note) => {
+                update_merge_request_note(
+                    client,
+                    &self.settings.host,
+                    &self.context.project_id,
+                    self.context.mr_id,
+                    note.note_id,
+                    &comment,
+                    token,
+                )
+                .await?
+            }
+            None => false,
+        };
+        let note_id = match (edited, previous) {
+            (true, Some(note)) => note.note_id,
+            // The note was never created or deleted on the merge request
+            _ => {
+                add_comment_to_merge_request(
+                    client,
+                    &self.settings.host,
+                    &self.context.project_id,
+                    self.context.mr_id,
+                    comment,
+                    token,
+                )
+                .await?
+                .id
+            }
+        };
+
+        let note = match msg.message_type {
+            MessageType::AppDestroyed => None,
+            _ => Some(GitlabNote { note_id, state }),
+        };
+        self.notes.set(&key, note).await
+    }
+
+    fn project_url(&self, path: &str) -> String {
+        format!(
+            "{}/api/v4/projects/{}/{}",
+            self.settings.host,
+            urlencoding::encode(&self.context.project_id),
+            path
+        )
+    }
+
+    async fn set_commit_status(
+        &self,
+        client: &Client,
+        msg: &Message,
+        sha: &str,
+    ) -> anyhow::Result<()> {
+        let mut status = json!({
+            "state": "success",
+            "name": format!("scotty/{}", msg.app_name),
+            "description": msg.message,
+        });
+        if msg.message_type.is_deployment() {
+            if let Some(url) = msg.urls.first() {
+                status["target_url"] = json!(url);
+            }
         }
+        let response = client
+            .post(self.project_url(&format!("statuses/{}", sha)))
+            .header("PRIVATE-TOKEN", self.settings.token.expose_secret())
+            .json(&status)
+            .send()
+            .await?;
+        ensure_success(response, "set commit status").await?;
+        Ok(())
+    }
+
+    /// Create or update the environment of the app, or stop it once the app
+    /// is destroyed.
+    async fn update_environment(&self, client: &Client, msg: &Message) -> anyhow::Result<()> {
+        let token = self.settings.token.expose_secret();
+        let response = client
+            .get(self.project_url(&format!(
+                "environments?name={}",
+                urlencoding::encode(&msg.app_name)
+            )))
+            .header("PRIVATE-TOKEN", token)
+            .send()
+            .await?;
+        let environments: Vec<Environment> = ensure_success(response, "list environments")
+            .await?
+            .json()
+            .await?;
+        let environment = environments.first();
+
+        let request = match (&msg.message_type, environment) {
+            (MessageType::AppDestroyed, Some(environment)) => {
+                client.post(self.project_url(&format!("environments/{}/stop", environment.id)))
+            }
+            (MessageType::AppDestroyed, None) => return Ok(()),
+            (_, Some(environment)) => client
+                .put(self.project_url(&format!("environments/{}", environment.id)))
+                .json(&json!({ "external_url": msg.urls.first() })),
+            (_, None) => client
+                .post(self.project_url("environments"))
+                .json(&json!({ "name": msg.app_name, "external_url": msg.urls.first() })),
+        };
+        let response = request.header("PRIVATE-TOKEN", token).send().await?;
+        ensure_success(response, "update environment").await?;
+        Ok(())
     }
+
+    async fn report_commit(&self, client: &Client, msg: &Message) -> anyhow::Result<()> {
+        let destroyed = matches!(msg.message_type, MessageType::AppDestroyed);
+        if !destroyed && !msg.message_type.is_deployment() {
+            return Ok(());
+        }
+        match (&self.settings.commit_report, &self.context.sha) {
+            (CommitReport::None, _) => Ok(()),
+            // Environments belong to the project, not to a commit
+            (CommitReport::Deployment, _) => self.update_environment(client, msg).await,
+            (CommitReport::Status, Some(sha)) => self.set_commit_status(client, msg, sha).await,
+            (CommitReport::Status, None) => {
+                debug!(
+                    "No commit given for MR {}, skipping status",
+                    self.context.mr_id
+                );
+                Ok(())
+            }
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct Environment {
+    id: u64,
+}
+
+fn notes_url(gitlab_url: &str, project_id: &str, merge_request_iid: u64) -> String {
+    format!(
+        "{gitlab_url}/api/v4/projects/{project_id}/merge_requests/{merge_request_iid}/notes",
+        gitlab_url = gitlab_url,
+        project_id = urlencoding::encode(project_id),
+        merge_request_iid = merge_request_iid
+    )
 }
 
 async fn add_comment_to_merge_request(
@@ -29,13 +198,8 @@ async fn add_comment_to_merge_request(
     merge_request_iid: u64,
     comment: MergeRequestComment,
     personal_access_token: &str,
-) -> anyhow::Result<()> {
-    let url = format!(
-        "{gitlab_url}/api/v4/projects/{project_id}/merge_requests/{merge_request_iid}/notes",
-        gitlab_url = gitlab_url,
-        project_id = urlencoding::encode(project_id),
-        merge_request_iid = merge_request_iid
-    );
+) -> anyhow::Result<MergeRequestNote> {
+    let url = notes_url(gitlab_url, project_id, merge_request_iid);
     info!("Adding comment to MR: {}", url);
 
     let response = client
@@ -45,19 +209,80 @@ async fn add_comment_to_merge_request(
         .send()
         .await?;
 
-    if response.status().is_success() {
-        Ok(())
-    } else {
-        let status = response.status();
-        let error_text = response.text().await?;
-        Err(anyhow::anyhow!(
-            "Failed to add comment. Status: {}. Error: {}",
-            status,
-            error_text
-        ))
+    Ok(ensure_success(response, "add comment")
+        .await?
+        .json()
+        .await?)
+}
+
+/// Replace the body of a note, returns false if the note does not exist
+/// anymore.
+async fn update_merge_request_note(
+    client: &Client,
+    gitlab_url: &str,
+    project_id: &str,
+    merge_request_iid: u64,
+    note_id: u64,
+    comment: &MergeRequestComment,
+    personal_access_token: &str,
+) -> anyhow::Result<bool> {
+    let url = format!(
+        "{}/{}",
+        notes_url(gitlab_url, project_id, merge_request_iid),
+        note_id
+    );
+    info!("Updating comment of MR: {}", url);
+
+    let response = client
+        .put(&url)
+        .header("PRIVATE-TOKEN", personal_access_token)
+        .json(comment)
+        .send()
+        .await?;
+    if response.status() == StatusCode::NOT_FOUND {
+        return Ok(false);
+    }
+    ensure_success(response, "update comment").await?;
+    Ok(true)
+}
+
+/// State of the app after the event, `None` if the event doesn't change it
+fn app_state(message_type: &MessageType) -> Option<&'static str> {
+    match message_type {
+        message_type if message_type.is_deployment() => Some("Running"),
+        MessageType::AppStopped => Some("Stopped"),
+        MessageType::AppPurged => Some("Purged"),
+        MessageType::AppDestroyed => Some("Destroyed"),
+        _ => None,
     }
 }
 
+fn status_table(msg: &Message, state: &str, updated: DateTime<Utc>) -> String {
+    let urls = match msg.message_type {
+        MessageType::AppDestroyed => "-".to_string(),
+        _ if msg.urls.is_empty() => "-".to_string(),
+        _ => msg
+            .urls
+            .iter()
+            .map(|u| format!("[{u}]({u})"))
+            .collect::<Vec<_>>()
+            .join("<br>"),
+    };
+    format!(
+        "**scotty: {}**\n\n| State | URLs | Last action | Updated |\n| --- | --- | --- | --- |\n| {} | {} | {} | {} |",
+        msg.app_name,
+        state,
+        urls,
+        msg.message.replace('|', "\\|"),
+        updated.format("%Y-%m-%d %H:%M:%S UTC")
+    )
+}
+
+#[derive(Deserialize, Debug)]
+struct MergeRequestNote {
+    id: u64,
+}
+
 #[derive(Serialize, Debug)]
 struct MergeRequestComment {
     body: String,
@@ -71,26 +296,287 @@ impl NotificationImpl for NotifyGitlab {
             &self.context.mr_id, &self.settings.host
         );
         let client = Client::new();
-        let comment = MergeRequestComment {
-            body: format!(
-                "**{}**\n\nUrls:\n{}",
-                msg.message,
-                msg.urls
-                    .iter()
-                    .map(|u| { format!("- [{u}]({u})") })
-                    .collect::<Vec<_>>()
-                    .join("\n")
-            ),
-        };
+        match self.settings.notes {
+            GitlabNotes::Update => self.update_status_note(&client, msg).await?,
+            GitlabNotes::PerEvent => {
+                let comment = MergeRequestComment {
+                    body: markdown_comment(msg),
+                };
+                add_comment_to_merge_request(
+                    &client,
+                    &self.settings.host,
+                    &self.context.project_id,
+                    self.context.mr_id,
+                    comment,
+                    self.settings.token.expose_secret(),
+                )
+                .await?;
+            }
+        }
+        self.report_commit(&client, msg).await
+    }
+}
 
-        add_comment_to_merge_request(
-            &client,
-            &self.settings.host,
-            &self.context.project_id,
-            self.context.mr_id,
-            comment,
-            self.settings.token.expose_secret(),
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notification::gitlab_notes::GITLAB_NOTES_FILE;
+    use wiremock::{
+        matchers::{body_partial_json, body_string_contains, header, method, path, query_param},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    const NOTES_PATH: &str = "/api/v4/projects/group%2Fproject/merge_requests/7/notes";
+
+    fn notifier(
+        server: &MockServer,
+        settings: &str,
+        sha: Option<&str>,
+        notes: &tempfile::TempDir,
+    ) -> NotifyGitlab {
+        let settings: GitlabSettings = serde_norway::from_str(&format!(
+            "host: {}\ntoken: glpat-secret\n{}",
+            server.uri(),
+            settings
+        ))
+        .unwrap();
+        let context = GitlabContext {
+            service_id: "gitlab".to_string(),
+            project_id: "group/project".to_string(),
+            mr_id: 7,
+            sha: sha.map(|sha| sha.to_string()),
+        };
+        NotifyGitlab::new(
+            &settings,
+            &context,
+            GitlabNoteStore::new(notes.path().join(GITLAB_NOTES_FILE)),
         )
-        .await
+    }
+
+    fn message(message_type: MessageType, text: &str) -> Message {
+        Message {
+            message_type,
+            app_name: "my-app".to_string(),
+            message: text.to_string(),
+            urls: vec!["https://my-app.example.com".to_string()],
+            task_id: None,
+        }
+    }
+
+    async fn mock_note(server: &MockServer) {
+        Mock::given(method("POST"))
+            .and(path(NOTES_PATH))
+            .and(header("PRIVATE-TOKEN", "glpat-secret"))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!({ "id": 99 })))
+            .expect(1)
+            .mount(server)
+            .await;
+    }
+
+    #[test]
+    fn test_status_table() {
+        let updated = DateTime::parse_from_rfc3339("2026-10-17T09:30:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let msg = message(MessageType::AppRebuilt, "App my-app | rebuilt");
+        assert_eq!(
+            status_table(&msg, "Running", updated),
+            "**scotty: my-app**\n\n\
+             | State | URLs | Last action | Updated |\n\
+             | --- | --- | --- | --- |\n\
+             | Running | [https://my-app.example.com](https://my-app.example.com) | App my-app \\| rebuilt | 2026-10-17 09:30:00 UTC |"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_status_note_is_updated_in_place() {
+        let server = MockServer::start().await;
+        let notes = tempfile::tempdir().unwrap();
+        mock_note(&server).await;
+        Mock::given(method("PUT"))
+            .and(path(format!("{}/99", NOTES_PATH)))
+            .and(body_string_contains("| Running |"))
+            .and(body_string_contains("App my-app will be stopped"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "id": 99 })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let notifier = notifier(&server, "", None, &notes);
+        notifier
+            .notify(&message(MessageType::AppCreated, "App my-app created"))
+            .await
+            .unwrap();
+        // The TTL warning keeps the state of the app
+        notifier
+            .notify(&message(
+                MessageType::AppTtlExpiring(3600),
+                "App my-app will be stopped in 1h",
+            ))
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_deleted_status_note_is_created_again() {
+        let server = MockServer::start().await;
+        let notes = tempfile::tempdir().unwrap();
+        let store = GitlabNoteStore::new(notes.path().join(GITLAB_NOTES_FILE));
+        let key = GitlabNoteStore::key("gitlab", "group/project", 7, "my-app");
+        store
+            .set(
+                &key,
+                Some(GitlabNote {
+                    note_id: 12,
+                    state: "Running".to_string(),
+                }),
+            )
+            .await
+            .unwrap();
+        Mock::given(method("PUT"))
+            .and(path(format!("{}/12", NOTES_PATH)))
+            .respond_with(ResponseTemplate::new(404))
+            .expect(1)
+            .mount(&server)
+            .await;
+        mock_note(&server).await;
+
+        notifier(&server, "", None, &notes)
+            .notify(&message(MessageType::AppStopped, "App my-app stopped"))
+            .await
+            .unwrap();
+        assert_eq!(
+            store.get(&key).await.unwrap(),
+            Some(GitlabNote {
+                note_id: 99,
+                state: "Stopped".to_string(),
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_status_note_is_forgotten_on_destroy() {
+        let server = MockServer::start().await;
+        let notes = tempfile::tempdir().unwrap();
+        mock_note(&server).await;
+        Mock::given(method("PUT"))
+            .and(path(format!("{}/99", NOTES_PATH)))
+            .and(body_string_contains("| Destroyed | - |"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let notifier = notifier(&server, "", None, &notes);
+        notifier
+            .notify(&message(MessageType::AppCreated, "App my-app created"))
+            .await
+            .unwrap();
+        notifier
+            .notify(&message(MessageType::AppDestroyed, "App my-app destroyed"))
+            .await
+            .unwrap();
+
+        let store = GitlabNoteStore::new(notes.path().join(GITLAB_NOTES_FILE));
+        let key = GitlabNoteStore::key("gitlab", "group/project", 7, "my-app");
+        assert_eq!(store.get(&key).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_note_per_event() {
+        let server = MockServer::start().await;
+        let notes = tempfile::tempdir().unwrap();
+        Mock::given(method("POST"))
+            .and(path(NOTES_PATH))
+            .and(body_partial_json(json!({
+                "body": "**App my-app rebuilt**\n\nUrls:\n- [https://my-app.example.com](https://my-app.example.com)"
+            })))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!({ "id": 1 })))
+            .expect(2)
+            .mount(&server)
+            .await;
+
+        let notifier = notifier(&server, "notes: per_event", None, &notes);
+        for _ in 0..2 {
+            notifier
+                .notify(&message(MessageType::AppRebuilt, "App my-app rebuilt"))
+                .await
+                .unwrap();
+        }
+        assert!(!notes.path().join(GITLAB_NOTES_FILE).exists());
+    }
+
+    #[tokio::test]
+    async fn test_note_and_commit_status() {
+        let server = MockServer::start().await;
+        let notes = tempfile::tempdir().unwrap();
+        mock_note(&server).await;
+        Mock::given(method("POST"))
+            .and(path("/api/v4/projects/group%2Fproject/statuses/a1b2c3d"))
+            .and(body_partial_json(json!({
+                "state": "success",
+                "name": "scotty/my-app",
+                "target_url": "https://my-app.example.com",
+            })))
+            .respond_with(ResponseTemplate::new(201))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        notifier(&server, "commit_report: status", Some("a1b2c3d"), &notes)
+            .notify(&message(MessageType::AppCreated, "App my-app created"))
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_environment_is_created() {
+        let server = MockServer::start().await;
+        let notes = tempfile::tempdir().unwrap();
+        mock_note(&server).await;
+        Mock::given(method("GET"))
+            .and(path("/api/v4/projects/group%2Fproject/environments"))
+            .and(query_param("name", "my-app"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([])))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/api/v4/projects/group%2Fproject/environments"))
+            .and(body_partial_json(json!({
+                "name": "my-app",
+                "external_url": "https://my-app.example.com",
+            })))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!({ "id": 5 })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        notifier(&server, "commit_report: deployment", None, &notes)
+            .notify(&message(MessageType::AppCreated, "App my-app created"))
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_environment_is_stopped_on_destroy() {
+        let server = MockServer::start().await;
+        let notes = tempfile::tempdir().unwrap();
+        mock_note(&server).await;
+        Mock::given(method("GET"))
+            .and(path("/api/v4/projects/group%2Fproject/environments"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([{ "id": 5 }])))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/api/v4/projects/group%2Fproject/environments/5/stop"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        notifier(&server, "commit_report: deployment", None, &notes)
+            .notify(&message(MessageType::AppDestroyed, "App my-app destroyed"))
+            .await
+            .unwrap();
     }
 }
@@ -0,0 +1,103 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::{Mutex, MutexGuard};
+
+/// Default file name of the gitlab note ids, relative to the apps root folder.
+pub const GITLAB_NOTES_FILE: &str = ".scotty-gitlab-notes.json";
+
+/// Notifications run concurrently, the lock keeps them from creating two notes
+/// for the same app or overwriting each other's entries.
+static LOCK: once_cell::sync::Lazy<Mutex<()>> = once_cell::sync::Lazy::new(|| Mutex::new(()));
+
+/// The merge request note scotty keeps up to date for an app.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GitlabNote {
+    pub note_id: u64,
+    /// Last known state of the app, kept for events which don't change it
+    pub state: String,
+}
+
+/// Stores the notes by merge request and app in a JSON file, so they survive
+/// server restarts.
+#[derive(Debug, Clone)]
+pub struct GitlabNoteStore {
+    path: PathBuf,
+}
+
+impl GitlabNoteStore {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    pub fn key(service_id: &str, project_id: &str, mr_id: u64, app_name: &str) -> String {
+        format!("{}/{}!{}/{}", service_id, project_id, mr_id, app_name)
+    }
+
+    /// Hold the returned guard while reading, updating and writing a note.
+    pub async fn lock() -> MutexGuard<'static, ()> {
+        LOCK.lock().await
+    }
+
+    pub async fn get(&self, key: &str) -> anyhow::Result<Option<GitlabNote>> {
+        Ok(self.load().await?.remove(key))
+    }
+
+    /// Store the note of `key`, or forget it if `note` is `None`.
+    pub async fn set(&self, key: &str, note: Option<GitlabNote>) -> anyhow::Result<()> {
+        let mut notes = self.load().await?;
+        match note {
+            Some(note) => notes.insert(key.to_string(), note),
+            None => notes.remove(key),
+        };
+
+        let tmp_path = self.path.with_extension("json.tmp");
+        tokio::fs::write(&tmp_path, serde_json::to_string_pretty(&notes)?).await?;
+        tokio::fs::rename(&tmp_path, &self.path).await?;
+        Ok(())
+    }
+
+    async fn load(&self) -> anyhow::Result<BTreeMap<String, GitlabNote>> {
+        match tokio::fs::read_to_string(&self.path).await {
+            Ok(content) => Ok(serde_json::from_str(&content)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(BTreeMap::new()),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_set_and_forget_notes() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = GitlabNoteStore::new(dir.path().join(GITLAB_NOTES_FILE));
+        let key = GitlabNoteStore::key("gitlab", "group/project", 7, "my-app");
+        assert_eq!(key, "gitlab/group/project!7/my-app");
+        assert_eq!(store.get(&key).await.unwrap(), None);
+
+        let note = GitlabNote {
+            note_id: 99,
+            state: "Running".to_string(),
+        };
+        store.set(&key, Some(note.clone())).await.unwrap();
+        store
+            .set(
+                "other",
+                Some(GitlabNote {
+                    note_id: 1,
+                    state: "Stopped".to_string(),
+                }),
+            )
+            .await
+            .unwrap();
+        assert_eq!(store.get(&key).await.unwrap(), Some(note));
+
+        store.set(&key, None).await.unwrap();
+        assert_eq!(store.get(&key).await.unwrap(), None);
+        assert!(store.get("other").await.unwrap().is_some());
+    }
+}
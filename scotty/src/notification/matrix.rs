@@ -0,0 +1,144 @@
+use async_trait::async_trait;
+use serde::Serialize;
+use tracing::info;
+
+use scotty_core::notification_types::{MatrixContext, Message, NotificationImpl};
+use scotty_core::settings::notification_services::MatrixSettings;
+use scotty_types::uuid::Uuid;
+
+use super::escape_html;
+
+pub struct NotifyMatrix {
+    settings: MatrixSettings,
+    context: MatrixContext,
+}
+
+impl NotifyMatrix {
+    pub fn new(settings: &MatrixSettings, context: &MatrixContext) -> Self {
+        NotifyMatrix {
+            settings: settings.to_owned(),
+            context: context.to_owned(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct MatrixMessage {
+    msgtype: String,
+    body: String,
+    format: String,
+    formatted_body: String,
+}
+
+impl MatrixMessage {
+    fn new(msg: &Message) -> Self {
+        let mut body = msg.message.clone();
+        let mut formatted_body = escape_html(&msg.message);
+        if !msg.urls.is_empty() {
+            body = format!("{}\n\n* {}", body, msg.urls.join("\n* "));
+            let links: Vec<String> = msg
+                .urls
+                .iter()
+                .map(|url| {
+                    let url = escape_html(url);
+                    format!("<li><a href=\"{url}\">{url}</a></li>")
+                })
+                .collect();
+            formatted_body = format!("{}<ul>{}</ul>", formatted_body, links.join(""));
+        }
+        MatrixMessage {
+            // Notices are meant for bots and don't trigger other bots
+            msgtype: "m.notice".to_string(),
+            body,
+            format: "org.matrix.custom.html".to_string(),
+            formatted_body,
+        }
+    }
+}
+
+#[async_trait]
+impl NotificationImpl for NotifyMatrix {
+    async fn notify(&self, msg: &Message) -> anyhow::Result<()> {
+        info!(
+            "Sending matrix notification to room {} at {}",
+            &self.context.room_id, &self.settings.homeserver
+        );
+
+        let client = reqwest::Client::new();
+        let url = format!(
+            "{}/_matrix/client/v3/rooms/{}/send/m.room.message/{}",
+            self.settings.homeserver.trim_end_matches('/'),
+            urlencoding::encode(&self.context.room_id),
+            Uuid::new_v4()
+        );
+
+        let response = client
+            .put(&url)
+            .bearer_auth(self.settings.access_token.expose_secret())
+            .header("Content-Type", "application/json")
+            .body(serde_json::to_string(&MatrixMessage::new(msg))?)
+            .send()
+            .await?;
+
+        if response.status().is_success() {
+            Ok(())
+        } else {
+            Err(anyhow::anyhow!(
+                "Failed to send message to Matrix: {:?}",
+                response.status()
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use scotty_core::notification_types::MessageType;
+    use serde_json::json;
+    use wiremock::{
+        matchers::{body_partial_json, header, method, path_regex},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    #[tokio::test]
+    async fn test_notify_matrix_room() {
+        let server = MockServer::start().await;
+        Mock::given(method("PUT"))
+            .and(path_regex(
+                r"^/_matrix/client/v3/rooms/%21abcdef%3Amatrix\.example\.com/send/m\.room\.message/[0-9a-f-]{36}$",
+            ))
+            .and(header("Authorization", "Bearer syt_secret"))
+            .and(body_partial_json(json!({
+                "msgtype": "m.notice",
+                "body": "App <my-app> started\n\n* https://my-app.example.com",
+                "formatted_body": "App &lt;my-app&gt; started<ul><li><a href=\"https://my-app.example.com\">https://my-app.example.com</a></li></ul>",
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "event_id": "$1" })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let settings: MatrixSettings = serde_norway::from_str(&format!(
+            "homeserver: {}/\naccess_token: syt_secret",
+            server.uri()
+        ))
+        .unwrap();
+        let context = MatrixContext {
+            service_id: "matrix".to_string(),
+            room_id: "!abcdef:matrix.example.com".to_string(),
+        };
+        let msg = Message {
+            message_type: MessageType::AppStarted,
+            app_name: "my-app".to_string(),
+            message: "App <my-app> started".to_string(),
+            urls: vec!["https://my-app.example.com".to_string()],
+            task_id: None,
+        };
+
+        NotifyMatrix::new(&settings, &context)
+            .notify(&msg)
+            .await
+            .unwrap();
+    }
+}
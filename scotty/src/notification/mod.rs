@@ -1,5 +1,53 @@
+pub mod email;
+pub mod gitea;
+pub mod github;
 pub mod gitlab;
+pub mod gitlab_notes;
 pub mod log;
+pub mod matrix;
 pub mod mattermost;
 pub mod notify;
+pub mod slack;
+pub mod teams;
 pub mod webhook;
+
+use scotty_core::notification_types::Message;
+
+/// Markdown body for comments on merge and pull requests
+pub(crate) fn markdown_comment(msg: &Message) -> String {
+    format!(
+        "**{}**\n\nUrls:\n{}",
+        msg.message,
+        msg.urls
+            .iter()
+            .map(|u| format!("- [{u}]({u})"))
+            .collect::<Vec<_>>()
+            .join("\n")
+    )
+}
+
+/// Turn an unsuccessful response into an error containing its body
+pub(crate) async fn ensure_success(
+    response: reqwest::Response,
+    action: &str,
+) -> anyhow::Result<reqwest::Response> {
+    if response.status().is_success() {
+        return Ok(response);
+    }
+    let status = response.status();
+    let error_text = response.text().await?;
+    Err(anyhow::anyhow!(
+        "Failed to {}. Status: {}. Error: {}",
+        action,
+        status,
+        error_text
+    ))
+}
+
+/// Escape text for notifications formatted as HTML
+pub(crate) fn escape_html(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -5,7 +5,17 @@ use tracing::{error, instrument};
 use crate::app_state::AppState;
 
 use super::{
-    gitlab::NotifyGitlab, log::NotifyLog, mattermost::NotifyMattermost, webhook::NotifyWebhook,
+    email::NotifyEmail,
+    gitea::NotifyGitea,
+    github::NotifyGithub,
+    gitlab::NotifyGitlab,
+    gitlab_notes::{GitlabNoteStore, GITLAB_NOTES_FILE},
+    log::NotifyLog,
+    matrix::NotifyMatrix,
+    mattermost::NotifyMattermost,
+    slack::NotifySlack,
+    teams::NotifyTeams,
+    webhook::NotifyWebhook,
 };
 use scotty_core::notification_types::{Message, NotificationImpl, NotificationReceiver};
 
@@ -23,6 +33,9 @@ async fn get_notification_receiver_impl(
                 context.service_id
             ))?,
             context,
+            GitlabNoteStore::new(
+                std::path::Path::new(&state.settings.apps.root_folder).join(GITLAB_NOTES_FILE),
+            ),
         ))),
         NotificationReceiver::Webhook(context) => Ok(Box::new(NotifyWebhook::new(
             ns.get_webhook(&context.service_id).ok_or(anyhow::anyhow!(
@@ -41,6 +54,49 @@ async fn get_notification_receiver_impl(
                 ))?,
             context,
         ))),
+        NotificationReceiver::Slack(context) => Ok(Box::new(NotifySlack::new(
+            ns.get_slack(&context.service_id).ok_or(anyhow::anyhow!(
+                "slack service {} not found in settings",
+                context.service_id
+            ))?,
+            context,
+            state.settings.api.configured_base_url(),
+        ))),
+        NotificationReceiver::Matrix(context) => Ok(Box::new(NotifyMatrix::new(
+            ns.get_matrix(&context.service_id).ok_or(anyhow::anyhow!(
+                "matrix service {} not found in settings",
+                context.service_id
+            ))?,
+            context,
+        ))),
+        NotificationReceiver::Teams(context) => Ok(Box::new(NotifyTeams::new(
+            ns.get_teams(&context.service_id).ok_or(anyhow::anyhow!(
+                "teams service {} not found in settings",
+                context.service_id
+            ))?,
+            context,
+        ))),
+        NotificationReceiver::Github(context) => Ok(Box::new(NotifyGithub::new(
+            ns.get_github(&context.service_id).ok_or(anyhow::anyhow!(
+                "github service {} not found in settings",
+                context.service_id
+            ))?,
+            context,
+        ))),
+        NotificationReceiver::Gitea(context) => Ok(Box::new(NotifyGitea::new(
+            ns.get_gitea(&context.service_id).ok_or(anyhow::anyhow!(
+                "gitea service {} not found in settings",
+                context.service_id
+            ))?,
+            context,
+        ))),
+        NotificationReceiver::Email(context) => Ok(Box::new(NotifyEmail::new(
+            ns.get_email(&context.service_id).ok_or(anyhow::anyhow!(
+                "email service {} not found in settings",
+                context.service_id
+            ))?,
+            context,
+        ))),
     }
 }
 
@@ -0,0 +1,229 @@
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use tracing::info;
+
+use scotty_core::notification_types::{Message, NotificationImpl, SlackContext};
+use scotty_core::settings::notification_services::SlackSettings;
+
+pub struct NotifySlack {
+    settings: SlackSettings,
+    context: SlackContext,
+    base_url: Option<String>,
+}
+
+impl NotifySlack {
+    pub fn new(settings: &SlackSettings, context: &SlackContext, base_url: Option<String>) -> Self {
+        NotifySlack {
+            settings: settings.to_owned(),
+            context: context.to_owned(),
+            base_url,
+        }
+    }
+
+    fn task_url(&self, msg: &Message) -> Option<String> {
+        match (&self.base_url, msg.task_id) {
+            (Some(base_url), Some(task_id)) => Some(format!(
+                "{}/tasks/{}",
+                base_url.trim_end_matches('/'),
+                task_id
+            )),
+            _ => None,
+        }
+    }
+
+    /// Build a Block Kit payload, `text` is used for notifications and
+    /// clients without block support.
+    fn payload(&self, msg: &Message) -> Value {
+        let mut blocks = vec![
+            json!({
+                "type": "header",
+                "text": {
+                    "type": "plain_text",
+                    "text": format!("{}: {}", msg.app_name, msg.message_type.event_name()),
+                },
+            }),
+            json!({
+                "type": "section",
+                "text": { "type": "mrkdwn", "text": msg.message },
+            }),
+        ];
+        if !msg.urls.is_empty() {
+            let urls: Vec<String> = msg.urls.iter().map(|url| format!("• <{}>", url)).collect();
+            blocks.push(json!({
+                "type": "section",
+                "text": { "type": "mrkdwn", "text": urls.join("\n") },
+            }));
+        }
+        if let Some(task_url) = self.task_url(msg) {
+            blocks.push(json!({
+                "type": "context",
+                "elements": [
+                    { "type": "mrkdwn", "text": format!("<{}|View task>", task_url) },
+                ],
+            }));
+        }
+
+        let mut payload = json!({
+            "text": msg.message,
+            "blocks": blocks,
+        });
+        // Incoming webhooks always post to the channel they were created for
+        if self.settings.token.is_some() {
+            payload["channel"] = json!(self.context.channel);
+        }
+        payload
+    }
+}
+
+#[async_trait]
+impl NotificationImpl for NotifySlack {
+    async fn notify(&self, msg: &Message) -> anyhow::Result<()> {
+        info!(
+            "Sending slack notification to channel {}",
+            &self.context.channel
+        );
+
+        let client = reqwest::Client::new();
+        let request = match (&self.settings.webhook_url, &self.settings.token) {
+            (Some(webhook_url), _) => client.post(webhook_url.expose_secret()),
+            (None, Some(token)) => client
+                .post(format!(
+                    "{}/chat.postMessage",
+                    self.settings.api_url.trim_end_matches('/')
+                ))
+                .bearer_auth(token.expose_secret()),
+            (None, None) => {
+                return Err(anyhow::anyhow!(
+                    "Slack service {} has neither a webhook_url nor a token",
+                    self.context.service_id
+                ))
+            }
+        };
+
+        let response = request
+            .header("Content-Type", "application/json; charset=utf-8")
+            .body(serde_json::to_string(&self.payload(msg))?)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(anyhow::anyhow!(
+                "Failed to send message to Slack: {:?}",
+                response.status()
+            ));
+        }
+
+        // The Web API reports errors with a 200 status and `ok: false`
+        if self.settings.webhook_url.is_none() {
+            let body: Value = response.json().await?;
+            if body["ok"] != json!(true) {
+                return Err(anyhow::anyhow!(
+                    "Failed to send message to Slack: {}",
+                    body["error"].as_str().unwrap_or("unknown error")
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use scotty_core::notification_types::MessageType;
+    use scotty_types::uuid::Uuid;
+    use wiremock::{
+        matchers::{body_partial_json, header, method, path},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    fn settings(yaml: &str) -> SlackSettings {
+        serde_norway::from_str(yaml).unwrap()
+    }
+
+    fn context() -> SlackContext {
+        SlackContext {
+            service_id: "slack".to_string(),
+            channel: "#deployments".to_string(),
+        }
+    }
+
+    fn message() -> Message {
+        Message {
+            message_type: MessageType::AppStarted,
+            app_name: "my-app".to_string(),
+            message: "App my-app started".to_string(),
+            urls: vec!["https://my-app.example.com".to_string()],
+            task_id: Some(Uuid::nil()),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_notify_via_webhook() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/services/T000/B000/XXX"))
+            .and(body_partial_json(json!({
+                "text": "App my-app started",
+                "blocks": [
+                    { "type": "header", "text": { "text": "my-app: Started" } },
+                    { "type": "section" },
+                    { "type": "section", "text": { "text": "• <https://my-app.example.com>" } },
+                    { "type": "context", "elements": [{
+                        "text": "<https://scotty.example.com/tasks/00000000-0000-0000-0000-000000000000|View task>"
+                    }] },
+                ],
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_string("ok"))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let settings = settings(&format!(
+            "webhook_url: {}/services/T000/B000/XXX",
+            server.uri()
+        ));
+        let notifier = NotifySlack::new(
+            &settings,
+            &context(),
+            Some("https://scotty.example.com/".to_string()),
+        );
+        notifier.notify(&message()).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_notify_via_bot_token() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat.postMessage"))
+            .and(header("Authorization", "Bearer xoxb-secret"))
+            .and(body_partial_json(json!({ "channel": "#deployments" })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "ok": true })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let settings = settings(&format!("token: xoxb-secret\napi_url: {}", server.uri()));
+        let notifier = NotifySlack::new(&settings, &context(), None);
+        notifier.notify(&message()).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_notify_via_bot_token_reports_api_errors() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat.postMessage"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(json!({ "ok": false, "error": "channel_not_found" })),
+            )
+            .mount(&server)
+            .await;
+
+        let settings = settings(&format!("token: xoxb-secret\napi_url: {}", server.uri()));
+        let notifier = NotifySlack::new(&settings, &context(), None);
+        let err = notifier.notify(&message()).await.unwrap_err();
+        assert!(err.to_string().contains("channel_not_found"));
+    }
+}
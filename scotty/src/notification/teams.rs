@@ -0,0 +1,137 @@
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use tracing::info;
+
+use scotty_core::notification_types::{Message, NotificationImpl, TeamsContext};
+use scotty_core::settings::notification_services::TeamsSettings;
+
+pub struct NotifyTeams {
+    settings: TeamsSettings,
+    context: TeamsContext,
+}
+
+impl NotifyTeams {
+    pub fn new(settings: &TeamsSettings, context: &TeamsContext) -> Self {
+        NotifyTeams {
+            settings: settings.to_owned(),
+            context: context.to_owned(),
+        }
+    }
+}
+
+/// Wrap an Adaptive Card with the app name, the message and a button per URL
+/// into the message envelope Teams webhooks expect.
+fn adaptive_card(msg: &Message) -> Value {
+    let actions: Vec<Value> = msg
+        .urls
+        .iter()
+        .map(|url| json!({ "type": "Action.OpenUrl", "title": url, "url": url }))
+        .collect();
+
+    json!({
+        "type": "message",
+        "attachments": [{
+            "contentType": "application/vnd.microsoft.card.adaptive",
+            "contentUrl": null,
+            "content": {
+                "$schema": "http://adaptivecards.io/schemas/adaptive-card.json",
+                "type": "AdaptiveCard",
+                "version": "1.4",
+                "body": [
+                    {
+                        "type": "TextBlock",
+                        "size": "Medium",
+                        "weight": "Bolder",
+                        "text": format!("{}: {}", msg.app_name, msg.message_type.event_name()),
+                    },
+                    { "type": "TextBlock", "text": msg.message, "wrap": true },
+                ],
+                "actions": actions,
+            },
+        }],
+    })
+}
+
+#[async_trait]
+impl NotificationImpl for NotifyTeams {
+    async fn notify(&self, msg: &Message) -> anyhow::Result<()> {
+        info!(
+            "Sending teams notification via service {}",
+            &self.context.service_id
+        );
+
+        let client = reqwest::Client::new();
+        let response = client
+            .post(self.settings.webhook_url.expose_secret())
+            .header("Content-Type", "application/json")
+            .body(serde_json::to_string(&adaptive_card(msg))?)
+            .send()
+            .await?;
+
+        // Workflows answer with 202 Accepted, incoming webhooks with 200
+        if response.status().is_success() {
+            Ok(())
+        } else {
+            Err(anyhow::anyhow!(
+                "Failed to send message to Teams: {:?}",
+                response.status()
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use scotty_core::notification_types::MessageType;
+    use wiremock::{
+        matchers::{body_partial_json, method, path},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    #[tokio::test]
+    async fn test_notify_teams_channel() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/workflows/trigger"))
+            .and(body_partial_json(json!({
+                "type": "message",
+                "attachments": [{
+                    "contentType": "application/vnd.microsoft.card.adaptive",
+                    "content": {
+                        "type": "AdaptiveCard",
+                        "body": [
+                            { "text": "my-app: Rebuilt" },
+                            { "text": "App my-app rebuilt" },
+                        ],
+                        "actions": [
+                            { "type": "Action.OpenUrl", "url": "https://my-app.example.com" },
+                        ],
+                    },
+                }],
+            })))
+            .respond_with(ResponseTemplate::new(202))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let settings: TeamsSettings =
+            serde_norway::from_str(&format!("webhook_url: {}/workflows/trigger", server.uri()))
+                .unwrap();
+        let context = TeamsContext {
+            service_id: "teams".to_string(),
+        };
+        let msg = Message {
+            message_type: MessageType::AppRebuilt,
+            app_name: "my-app".to_string(),
+            message: "App my-app rebuilt".to_string(),
+            urls: vec!["https://my-app.example.com".to_string()],
+            task_id: None,
+        };
+
+        NotifyTeams::new(&settings, &context)
+            .notify(&msg)
+            .await
+            .unwrap();
+    }
+}
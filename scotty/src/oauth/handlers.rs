@@ -177,50 +177,60 @@ pub async fn start_authorization_flow(
         None => return AppError::OAuthError(OAuthError::OauthNotConfigured).into_response(),
     };
 
-    // Generate session ID and CSRF token separately
-    let session_id = Uuid::new_v4().to_string();
-    let csrf_token_raw = CsrfToken::new_random();
-    let csrf_token = CsrfToken::new(format!("{}:{}", session_id, csrf_token_raw.secret()));
-
-    // Store frontend callback URL separately before consuming params.redirect_uri
-    let frontend_callback_url = params.redirect_uri.clone();
-
     // Determine redirect URL - use configured URL from settings
     let redirect_url = params
         .redirect_uri
+        .clone()
         .unwrap_or_else(|| app_state.settings.api.oauth.redirect_url.clone());
 
+    match begin_web_flow(oauth_state, redirect_url, params.redirect_uri) {
+        Ok(auth_url) => {
+            debug!("Redirecting to GitLab OAuth: {}", auth_url);
+            Redirect::temporary(auth_url.as_str()).into_response()
+        }
+        Err(e) => e.into_response(),
+    }
+}
+
+/// Store a new web flow session and return the authorization URL of the
+/// provider. After the callback, the browser is sent to
+/// `frontend_callback_url` with the id of a session to exchange for a token.
+pub(crate) fn begin_web_flow(
+    oauth_state: &OAuthState,
+    redirect_url: String,
+    frontend_callback_url: Option<String>,
+) -> Result<url::Url, AppError> {
+    // Generate session ID and CSRF token separately
+    let session_id = Uuid::new_v4().to_string();
+    let csrf_token_raw = CsrfToken::new_random();
+    let csrf_token = CsrfToken::new(format!("{}:{}", session_id, csrf_token_raw.secret()));
+
     debug!("Using redirect URL for authorization: {}", redirect_url);
 
     // Generate authorization URL with PKCE
-    match oauth_state
+    let (auth_url, pkce_verifier) = oauth_state
         .client
-        .get_authorization_url(redirect_url.clone(), csrf_token.clone())
-    {
-        Ok((auth_url, pkce_verifier)) => {
-            // Store session for later verification
-            let session = WebFlowSession {
-                csrf_token: MaskedSecret::new(csrf_token_raw.secret().clone()), // Store only the raw CSRF token part (protected)
-                pkce_verifier: MaskedSecret::new(pkce_verifier.secret().to_string()), // Store PKCE verifier (protected)
-                redirect_url: redirect_url.clone(), // OAuth redirect URL for token exchange
-                frontend_callback_url,              // Frontend callback URL
-                expires_at: SystemTime::now() + Duration::from_secs(600), // 10 minutes
-            };
-
-            {
-                let mut sessions = oauth_state.web_flow_store.lock().unwrap();
-                sessions.insert(session_id.clone(), session);
-            }
-
-            debug!("Redirecting to GitLab OAuth: {}", auth_url);
-            Redirect::temporary(auth_url.as_str()).into_response()
-        }
-        Err(e) => {
+        .get_authorization_url(redirect_url.clone(), csrf_token)
+        .map_err(|e| {
             error!("Failed to generate authorization URL: {}", e);
             AppError::InternalServerError(format!("Failed to start authorization: {}", e))
-                .into_response()
-        }
+        })?;
+
+    // Store session for later verification
+    let session = WebFlowSession {
+        csrf_token: MaskedSecret::new(csrf_token_raw.secret().clone()), // Store only the raw CSRF token part (protected)
+        pkce_verifier: MaskedSecret::new(pkce_verifier.secret().to_string()), // Store PKCE verifier (protected)
+        redirect_url,          // OAuth redirect URL for token exchange
+        frontend_callback_url, // Frontend callback URL
+        expires_at: SystemTime::now() + Duration::from_secs(600), // 10 minutes
+    };
+
+    {
+        let mut sessions = oauth_state.web_flow_store.lock().unwrap();
+        sessions.insert(session_id, session);
     }
+
+    Ok(auth_url)
 }
 
 #[derive(Deserialize, utoipa::IntoParams, utoipa::ToSchema)]
@@ -427,7 +437,15 @@ pub async fn handle_oauth_callback(
                     // Redirect to frontend with session ID
                     let frontend_url =
                         if let Some(frontend_callback) = &session.frontend_callback_url {
-                            format!("{}?session_id={}", frontend_callback, oauth_session_id)
+                            let separator = if frontend_callback.contains('?') {
+                                '&'
+                            } else {
+                                '?'
+                            };
+                            format!(
+                                "{}{}session_id={}",
+                                frontend_callback, separator, oauth_session_id
+                            )
                         } else {
                             // Fallback to the public base URL (api.base_url)
                             format!(
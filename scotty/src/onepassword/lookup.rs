@@ -1,5 +1,6 @@
 use std::collections::HashMap;
 
+use scotty_core::apps::app_data::BasicAuthUser;
 use scotty_core::utils::secret::{MaskedSecret, SecretHashMap};
 use tracing::{debug, error};
 
@@ -51,6 +52,34 @@ pub async fn resolve_environment_variables(
     resolved
 }
 
+/// Replace 1Password references in the passwords of basic auth users by the
+/// passwords they point to. Unlike environment variables, a reference which
+/// can't be resolved is an error, it would lock everybody out of the app.
+pub async fn resolve_basic_auth_users(
+    settings: &Settings,
+    users: &[BasicAuthUser],
+) -> anyhow::Result<Vec<BasicAuthUser>> {
+    let mut resolved = Vec::with_capacity(users.len());
+    for user in users {
+        let password = match user.is_reference() {
+            true => lookup_password(settings, &user.password)
+                .await
+                .map_err(|e| {
+                    anyhow::anyhow!(
+                        "Failed to resolve password of basic auth user {}: {}",
+                        user.username,
+                        e
+                    )
+                })?
+                .expose_secret()
+                .to_string(),
+            false => user.password.clone(),
+        };
+        resolved.push(BasicAuthUser::new(&user.username, &password));
+    }
+    Ok(resolved)
+}
+
 async fn lookup_password(settings: &Settings, op_uri: &str) -> anyhow::Result<MaskedSecret> {
     // Remove "op://" prefix
     let parts: Vec<&str> = op_uri
@@ -0,0 +1,120 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use chrono::{DateTime, Local};
+use tokio::sync::RwLock;
+use tracing::warn;
+
+/// File name of the saved activity, relative to the apps root folder.
+pub const ACTIVITY_FILE: &str = ".scotty-activity.json";
+
+/// Remembers when an app last received an HTTP request.
+///
+/// Requests are reported by the load balancer through the activity endpoint.
+/// With a file, the activity is loaded on startup and saved by [`Self::save`],
+/// so a restart of scotty does not reset the idle TTL of the apps.
+#[derive(Debug, Clone, Default)]
+pub struct ActivityTracker {
+    last_seen: Arc<RwLock<HashMap<String, DateTime<Local>>>>,
+    path: Option<PathBuf>,
+}
+
+impl ActivityTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a tracker saving its data to `path`, loading what is saved there.
+    pub fn with_file(path: PathBuf) -> Self {
+        let last_seen = match std::fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+                warn!("Ignoring invalid activity file {}: {}", path.display(), e);
+                HashMap::new()
+            }),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => HashMap::new(),
+            Err(e) => {
+                warn!("Failed to read activity file {}: {}", path.display(), e);
+                HashMap::new()
+            }
+        };
+        Self {
+            last_seen: Arc::new(RwLock::new(last_seen)),
+            path: Some(path),
+        }
+    }
+
+    /// Save the recorded activity to the file of the tracker, if any.
+    pub async fn save(&self) -> anyhow::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let content = serde_json::to_string(&*self.last_seen.read().await)?;
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        // Write to a temporary file first, so a crash never leaves half a file
+        let temp_path = path.with_extension("json.tmp");
+        tokio::fs::write(&temp_path, content).await?;
+        tokio::fs::rename(&temp_path, path).await?;
+        Ok(())
+    }
+
+    /// Record a request to the app
+    pub async fn record(&self, app_name: &str) {
+        self.record_at(app_name, Local::now()).await;
+    }
+
+    pub async fn record_at(&self, app_name: &str, at: DateTime<Local>) {
+        let mut last_seen = self.last_seen.write().await;
+        let entry = last_seen.entry(app_name.to_string()).or_insert(at);
+        if *entry < at {
+            *entry = at;
+        }
+    }
+
+    /// Time of the last recorded request to the app
+    pub async fn last_activity(&self, app_name: &str) -> Option<DateTime<Local>> {
+        self.last_seen.read().await.get(app_name).copied()
+    }
+
+    /// Forget the activity of an app, e.g. after it was destroyed
+    pub async fn remove(&self, app_name: &str) {
+        self.last_seen.write().await.remove(app_name);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_keeps_latest_activity() {
+        let tracker = ActivityTracker::new();
+        assert_eq!(tracker.last_activity("app").await, None);
+
+        let now = Local::now();
+        let earlier = now - chrono::Duration::hours(1);
+        tracker.record_at("app", now).await;
+        tracker.record_at("app", earlier).await;
+        assert_eq!(tracker.last_activity("app").await, Some(now));
+
+        tracker.remove("app").await;
+        assert_eq!(tracker.last_activity("app").await, None);
+    }
+
+    #[tokio::test]
+    async fn test_saved_activity_survives_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(ACTIVITY_FILE);
+        let now = Local::now();
+
+        let tracker = ActivityTracker::with_file(path.clone());
+        assert_eq!(tracker.last_activity("app").await, None);
+        tracker.record_at("app", now).await;
+        tracker.save().await.unwrap();
+
+        let restarted = ActivityTracker::with_file(path);
+        assert_eq!(restarted.last_activity("app").await, Some(now));
+    }
+}
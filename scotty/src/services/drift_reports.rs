@@ -0,0 +1,95 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use scotty_core::apps::app_data::{DriftIssue, LoadBalancerDrift};
+use tokio::sync::RwLock;
+
+/// Keeps the load balancer drift found by the last reconciliation of every
+/// app, so it can be shown in the app info and announced only once.
+#[derive(Debug, Clone, Default)]
+pub struct DriftReports {
+    reports: Arc<RwLock<HashMap<String, LoadBalancerDrift>>>,
+}
+
+impl DriftReports {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub async fn get(&self, app_name: &str) -> Option<LoadBalancerDrift> {
+        self.reports.read().await.get(app_name).cloned()
+    }
+
+    /// Store the issues found for an app. Returns true if they differ from the
+    /// ones of the previous check, i.e. the drift should be announced.
+    pub async fn update(&self, app_name: &str, issues: Vec<DriftIssue>, repairing: bool) -> bool {
+        let mut reports = self.reports.write().await;
+        if issues.is_empty() {
+            reports.remove(app_name);
+            return false;
+        }
+        if let Some(report) = reports.get_mut(app_name) {
+            if report.issues == issues {
+                report.repairing = repairing;
+                return false;
+            }
+        }
+        reports.insert(
+            app_name.to_string(),
+            LoadBalancerDrift {
+                issues,
+                detected_at: chrono::Local::now(),
+                repairing,
+            },
+        );
+        true
+    }
+
+    /// Forget the drift of apps which no longer exist
+    pub async fn retain(&self, app_names: &[String]) {
+        self.reports
+            .write()
+            .await
+            .retain(|app_name, _| app_names.contains(app_name));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_reports_changed_drift_once() {
+        let reports = DriftReports::new();
+
+        assert!(!reports.update("app", vec![], false).await);
+        assert!(reports.get("app").await.is_none());
+
+        assert!(
+            reports
+                .update("app", vec![DriftIssue::OverrideChanged], false)
+                .await
+        );
+        assert!(
+            !reports
+                .update("app", vec![DriftIssue::OverrideChanged], false)
+                .await
+        );
+        assert!(
+            reports
+                .update(
+                    "app",
+                    vec![
+                        DriftIssue::OverrideChanged,
+                        DriftIssue::NetworkDetached("proxy--app".to_string())
+                    ],
+                    false
+                )
+                .await
+        );
+        assert_eq!(reports.get("app").await.unwrap().issues.len(), 2);
+
+        assert!(!reports.update("app", vec![], false).await);
+        assert!(reports.get("app").await.is_none());
+    }
+}
@@ -0,0 +1,63 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use tokio::sync::RwLock;
+
+use crate::api::basic_auth::CurrentUser;
+
+/// How long a validated token is trusted without asking the OIDC provider again
+const VALIDATION_TTL: Duration = Duration::from_secs(60);
+
+/// Remembers recently validated tokens of the forward-auth endpoint.
+///
+/// Traefik asks scotty on every request to a protected app, including all of
+/// its assets, so validating each of them against the OIDC provider would be
+/// far too slow.
+#[derive(Debug, Clone, Default)]
+pub struct ForwardAuthCache {
+    users: Arc<RwLock<HashMap<String, (CurrentUser, Instant)>>>,
+}
+
+impl ForwardAuthCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// User of a token validated within the last minute
+    pub async fn get(&self, token: &str) -> Option<CurrentUser> {
+        self.users
+            .read()
+            .await
+            .get(token)
+            .filter(|(_, validated_at)| validated_at.elapsed() < VALIDATION_TTL)
+            .map(|(user, _)| user.clone())
+    }
+
+    pub async fn insert(&self, token: &str, user: CurrentUser) {
+        let mut users = self.users.write().await;
+        users.retain(|_, (_, validated_at)| validated_at.elapsed() < VALIDATION_TTL);
+        users.insert(token.to_string(), (user, Instant::now()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_remembers_validated_tokens() {
+        let cache = ForwardAuthCache::new();
+        assert!(cache.get("token").await.is_none());
+
+        let user = CurrentUser {
+            email: "alice@example.com".to_string(),
+            name: "Alice".to_string(),
+            picture: None,
+            access_token: None,
+        };
+        cache.insert("token", user).await;
+        assert_eq!(cache.get("token").await.unwrap().email, "alice@example.com");
+        assert!(cache.get("other").await.is_none());
+    }
+}
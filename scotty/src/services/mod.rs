@@ -1,3 +1,13 @@
+pub mod activity;
 pub mod authorization;
+pub mod drift_reports;
+pub mod forward_auth;
+pub mod tcp_ports;
+pub mod ttl_warnings;
 
+pub use activity::ActivityTracker;
 pub use authorization::AuthorizationService;
+pub use drift_reports::DriftReports;
+pub use forward_auth::ForwardAuthCache;
+pub use tcp_ports::TcpPortReservations;
+pub use ttl_warnings::TtlWarnings;
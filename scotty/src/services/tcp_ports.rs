@@ -0,0 +1,31 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use tokio::sync::{Mutex, MutexGuard};
+
+/// Public ports handed out to the TCP and UDP services of apps.
+///
+/// An app only shows up with its ports in the app list once its settings are
+/// saved, which happens later in its task. The reservations cover that gap,
+/// and holding the lock serializes the allocation, so two apps created at the
+/// same time never get the same port.
+#[derive(Debug, Clone, Default)]
+pub struct TcpPortReservations {
+    reserved: Arc<Mutex<HashMap<String, HashSet<u16>>>>,
+}
+
+impl TcpPortReservations {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Lock the reservations while ports get allocated
+    pub async fn lock(&self) -> MutexGuard<'_, HashMap<String, HashSet<u16>>> {
+        self.reserved.lock().await
+    }
+
+    /// Drop the reservations of an app, e.g. after it got destroyed.
+    pub async fn release(&self, app_name: &str) {
+        self.reserved.lock().await.remove(app_name);
+    }
+}
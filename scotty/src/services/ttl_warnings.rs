@@ -0,0 +1,55 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use chrono::{DateTime, Local};
+use tokio::sync::RwLock;
+
+/// Remembers for which TTL deadline an app was already warned about, so every
+/// deadline is announced only once.
+#[derive(Debug, Clone, Default)]
+pub struct TtlWarnings {
+    warned: Arc<RwLock<HashMap<String, DateTime<Local>>>>,
+}
+
+impl TtlWarnings {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns true if no warning was sent for this deadline of the app yet,
+    /// and marks it as warned.
+    pub async fn should_warn(&self, app_name: &str, deadline: DateTime<Local>) -> bool {
+        let mut warned = self.warned.write().await;
+        if warned.get(app_name) == Some(&deadline) {
+            return false;
+        }
+        warned.insert(app_name.to_string(), deadline);
+        true
+    }
+
+    /// Forget the warnings of an app, e.g. after it got destroyed.
+    pub async fn remove(&self, app_name: &str) {
+        self.warned.write().await.remove(app_name);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_warns_once_per_deadline() {
+        let warnings = TtlWarnings::new();
+        let deadline = Local::now();
+
+        assert!(warnings.should_warn("app", deadline).await);
+        assert!(!warnings.should_warn("app", deadline).await);
+        assert!(warnings.should_warn("other-app", deadline).await);
+
+        let extended = deadline + chrono::Duration::days(1);
+        assert!(warnings.should_warn("app", extended).await);
+
+        warnings.remove("app").await;
+        assert!(warnings.should_warn("app", extended).await);
+    }
+}
@@ -6,11 +6,15 @@ use scotty_core::settings::{
     apps::Apps,
     docker::{DockerConnectOptions, DockerSettings},
     files::FilesSettings,
-    loadbalancer::{HaproxyConfigSettings, LoadBalancerType, TraefikSettings},
+    loadbalancer::{
+        CaddySettings, HaproxyConfigSettings, LoadBalancerType, NginxProxySettings, TraefikSettings,
+    },
     notification_services::NotificationServiceSettings,
     output::OutputSettings,
+    revisions::RevisionSettings,
     scheduler_interval::SchedulerInterval,
     shell::ShellSettings,
+    task_history::TaskHistorySettings,
 };
 use serde::Deserialize;
 use std::{collections::HashMap, env};
@@ -22,6 +26,20 @@ pub struct Scheduler {
     pub running_app_check: SchedulerInterval,
     pub ttl_check: SchedulerInterval,
     pub task_cleanup: SchedulerInterval,
+    #[serde(default = "Scheduler::default_schedule_check")]
+    pub schedule_check: SchedulerInterval,
+    #[serde(default = "Scheduler::default_drift_check")]
+    pub drift_check: SchedulerInterval,
+}
+
+impl Scheduler {
+    fn default_schedule_check() -> SchedulerInterval {
+        SchedulerInterval::Minutes(1)
+    }
+
+    fn default_drift_check() -> SchedulerInterval {
+        SchedulerInterval::Minutes(10)
+    }
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -44,6 +62,10 @@ pub struct Settings {
     pub traefik: TraefikSettings,
     pub haproxy: HaproxyConfigSettings,
     #[serde(default)]
+    pub caddy: CaddySettings,
+    #[serde(default)]
+    pub nginx_proxy: NginxProxySettings,
+    #[serde(default)]
     pub onepassword: HashMap<String, OnePasswordSettings>,
     #[serde(default)]
     pub notification_services: NotificationServiceSettings,
@@ -53,6 +75,10 @@ pub struct Settings {
     pub shell: ShellSettings,
     #[serde(default)]
     pub files: FilesSettings,
+    #[serde(default)]
+    pub task_history: TaskHistorySettings,
+    #[serde(default)]
+    pub revisions: RevisionSettings,
 }
 impl Default for Settings {
     fn default() -> Self {
@@ -64,6 +90,8 @@ impl Default for Settings {
                 running_app_check: SchedulerInterval::Minutes(1),
                 ttl_check: SchedulerInterval::Hours(1),
                 task_cleanup: SchedulerInterval::Minutes(1),
+                schedule_check: Scheduler::default_schedule_check(),
+                drift_check: Scheduler::default_drift_check(),
             },
             apps: Apps::default(),
             docker: DockerSettings {
@@ -76,11 +104,15 @@ impl Default for Settings {
                 ..Default::default()
             },
             haproxy: HaproxyConfigSettings { use_tls: false },
+            caddy: CaddySettings::default(),
+            nginx_proxy: NginxProxySettings::default(),
             onepassword: HashMap::new(),
             notification_services: NotificationServiceSettings::default(),
             output: OutputSettings::default(),
             shell: ShellSettings::default(),
             files: FilesSettings::default(),
+            task_history: TaskHistorySettings::default(),
+            revisions: RevisionSettings::default(),
         }
     }
 }
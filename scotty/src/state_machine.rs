@@ -1,3 +1,4 @@
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::{collections::HashMap, sync::Arc};
 use tokio::sync::RwLock;
 use tracing::{error, info};
@@ -18,6 +19,7 @@ where
     state: S,
     end_state: S,
     error_state: Option<S>,
+    cancel_flag: Option<Arc<AtomicBool>>,
     handlers: HashMap<S, Arc<dyn StateHandler<S, C> + Send + Sync>>,
 }
 
@@ -38,6 +40,7 @@ where
             state: initial_state,
             end_state,
             error_state: None,
+            cancel_flag: None,
             handlers: HashMap::new(),
         }
     }
@@ -46,12 +49,30 @@ where
         self.error_state = Some(error_state);
     }
 
+    /// Stop the state machine before the next transition once the flag is set.
+    /// A cancelled state machine behaves like a failed one and runs the error handler.
+    pub fn set_cancel_flag(&mut self, cancel_flag: Arc<AtomicBool>) {
+        self.cancel_flag = Some(cancel_flag);
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancel_flag
+            .as_ref()
+            .is_some_and(|flag| flag.load(Ordering::SeqCst))
+    }
+
     pub fn add_handler(&mut self, state: S, handler: Arc<dyn StateHandler<S, C> + Send + Sync>) {
         self.handlers.insert(state, handler);
     }
 
     pub async fn run(&mut self, context: Arc<RwLock<C>>) -> anyhow::Result<()> {
         while self.state != self.end_state {
+            if self.is_cancelled() {
+                let cancelled_state = self.state;
+                error!("State machine cancelled in state {:?}", cancelled_state);
+                self.run_error_handler(context.clone()).await;
+                return Err(anyhow::anyhow!("Cancelled in state {:?}", cancelled_state));
+            }
             if let Some(handler) = self.handlers.get(&self.state) {
                 info!("Running handler for state {:?}", self.state);
                 let old_state = self.state;
@@ -61,21 +82,13 @@ where
                         info!("Transitioned from {:?} to {:?}", old_state, self.state);
                     }
                     Err(e) => {
-                        // If error_state is set, transition to it first
                         if let Some(error_state) = self.error_state {
                             error!(
                                 "Handler for {:?} failed, transitioning to error state {:?}",
                                 old_state, error_state
                             );
-                            self.state = error_state;
-
-                            // Run the error handler if it exists
-                            if let Some(error_handler) = self.handlers.get(&error_state) {
-                                let _ = error_handler
-                                    .transition(&error_state, context.clone())
-                                    .await;
-                            }
                         }
+                        self.run_error_handler(context.clone()).await;
                         return Err(e);
                     }
                 }
@@ -89,6 +102,17 @@ where
         Ok(())
     }
 
+    /// Transition to the error state, if set, and run its handler
+    async fn run_error_handler(&mut self, context: Arc<RwLock<C>>) {
+        if let Some(error_state) = self.error_state {
+            self.state = error_state;
+
+            if let Some(error_handler) = self.handlers.get(&error_state) {
+                let _ = error_handler.transition(&error_state, context).await;
+            }
+        }
+    }
+
     pub fn spawn(self, context: Arc<RwLock<C>>) -> tokio::task::JoinHandle<anyhow::Result<()>> {
         let cloned_self = Arc::new(RwLock::new(self));
 
@@ -308,6 +332,80 @@ mod tests {
         assert_eq!(context.read().await.output, "Error handler called\n");
     }
 
+    /// Test that a cancelled state machine stops and runs the error handler
+    #[tokio::test]
+    async fn test_cancelled_state_machine_runs_error_handler() {
+        #[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+        enum CancelTestState {
+            Start,
+            Middle,
+            Failed,
+            End,
+        }
+
+        struct CancellingHandler {
+            flag: Arc<AtomicBool>,
+        }
+
+        #[async_trait::async_trait]
+        impl StateHandler<CancelTestState, Context> for CancellingHandler {
+            async fn transition(
+                &self,
+                _from: &CancelTestState,
+                context: Arc<RwLock<Context>>,
+            ) -> anyhow::Result<CancelTestState> {
+                context.write().await.output.push_str("Start\n");
+                self.flag.store(true, Ordering::SeqCst);
+                Ok(CancelTestState::Middle)
+            }
+        }
+
+        struct RecordingHandler {
+            label: &'static str,
+        }
+
+        #[async_trait::async_trait]
+        impl StateHandler<CancelTestState, Context> for RecordingHandler {
+            async fn transition(
+                &self,
+                _from: &CancelTestState,
+                context: Arc<RwLock<Context>>,
+            ) -> anyhow::Result<CancelTestState> {
+                context
+                    .write()
+                    .await
+                    .output
+                    .push_str(&format!("{}\n", self.label));
+                Ok(CancelTestState::End)
+            }
+        }
+
+        let flag = Arc::new(AtomicBool::new(false));
+        let mut state_machine = StateMachine::new(CancelTestState::Start, CancelTestState::End);
+        state_machine.set_error_state(CancelTestState::Failed);
+        state_machine.set_cancel_flag(flag.clone());
+        state_machine.add_handler(
+            CancelTestState::Start,
+            Arc::new(CancellingHandler { flag: flag.clone() }),
+        );
+        state_machine.add_handler(
+            CancelTestState::Middle,
+            Arc::new(RecordingHandler { label: "Middle" }),
+        );
+        state_machine.add_handler(
+            CancelTestState::Failed,
+            Arc::new(RecordingHandler { label: "Failed" }),
+        );
+
+        let context = Arc::new(RwLock::new(Context::default()));
+        let result = state_machine.run(context.clone()).await;
+
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err().to_string(), "Cancelled in state Middle");
+        assert_eq!(context.read().await.output, "Start\nFailed\n");
+        assert_eq!(state_machine.state, CancelTestState::Failed);
+    }
+
     /// Test that panics are caught by spawn() and returned as JoinError
     #[tokio::test]
     async fn test_handler_panic_caught_by_spawn() {
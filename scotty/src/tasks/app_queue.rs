@@ -0,0 +1,198 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use tokio::sync::{Notify, RwLock};
+use uuid::Uuid;
+
+/// Serializes lifecycle operations per app.
+///
+/// Every app has a queue of task ids. The task at the front of the queue holds
+/// the lock for the app, all other tasks wait for their turn.
+#[derive(Debug, Clone, Default)]
+pub struct AppOperationQueue {
+    queues: Arc<RwLock<HashMap<String, VecDeque<Uuid>>>>,
+    notify: Arc<Notify>,
+}
+
+impl AppOperationQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Acquire the lock for the app only if it is free.
+    ///
+    /// Returns the id of the task holding the lock otherwise.
+    pub async fn try_acquire(&self, app_name: &str, task_id: &Uuid) -> Result<(), Uuid> {
+        let mut queues = self.queues.write().await;
+        let queue = queues.entry(app_name.to_string()).or_default();
+        if let Some(holder) = queue.front() {
+            return Err(*holder);
+        }
+        queue.push_back(*task_id);
+        Ok(())
+    }
+
+    /// Add a task to the queue of the app and return its position.
+    ///
+    /// Position 0 means the task holds the lock and can start right away.
+    pub async fn enqueue(&self, app_name: &str, task_id: &Uuid) -> usize {
+        let mut queues = self.queues.write().await;
+        let queue = queues.entry(app_name.to_string()).or_default();
+        queue.push_back(*task_id);
+        queue.len() - 1
+    }
+
+    /// Task currently holding the lock for the app
+    pub async fn holder(&self, app_name: &str) -> Option<Uuid> {
+        let queues = self.queues.read().await;
+        queues
+            .get(app_name)
+            .and_then(|queue| queue.front().copied())
+    }
+
+    pub async fn position(&self, app_name: &str, task_id: &Uuid) -> Option<usize> {
+        let queues = self.queues.read().await;
+        queues
+            .get(app_name)
+            .and_then(|queue| queue.iter().position(|id| id == task_id))
+    }
+
+    /// Positions of all tasks waiting for the app, the lock holder excluded
+    pub async fn waiting(&self, app_name: &str) -> Vec<(Uuid, usize)> {
+        let queues = self.queues.read().await;
+        queues
+            .get(app_name)
+            .map(|queue| {
+                queue
+                    .iter()
+                    .enumerate()
+                    .skip(1)
+                    .map(|(position, id)| (*id, position))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Wait until the task holds the lock for the app.
+    ///
+    /// Returns false if the task got cancelled while waiting, the task is
+    /// removed from the queue in that case.
+    pub async fn wait_for_turn(
+        &self,
+        app_name: &str,
+        task_id: &Uuid,
+        cancel_flag: &AtomicBool,
+    ) -> bool {
+        loop {
+            let notified = self.notify.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+
+            if cancel_flag.load(Ordering::SeqCst) {
+                self.release(app_name, task_id).await;
+                return false;
+            }
+            match self.position(app_name, task_id).await {
+                Some(0) | None => return true,
+                Some(_) => notified.await,
+            }
+        }
+    }
+
+    /// Remove the task from the queue of the app and wake up waiting tasks
+    pub async fn release(&self, app_name: &str, task_id: &Uuid) {
+        {
+            let mut queues = self.queues.write().await;
+            if let Some(queue) = queues.get_mut(app_name) {
+                queue.retain(|id| id != task_id);
+                if queue.is_empty() {
+                    queues.remove(app_name);
+                }
+            }
+        }
+        self.wake();
+    }
+
+    /// Wake up all waiting tasks, so they can re-check their position
+    pub fn wake(&self) {
+        self.notify.notify_waiters();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_try_acquire_reports_holder() {
+        let queue = AppOperationQueue::new();
+        let first = Uuid::new_v4();
+        let second = Uuid::new_v4();
+
+        assert!(queue.try_acquire("app", &first).await.is_ok());
+        assert_eq!(queue.try_acquire("app", &second).await, Err(first));
+        assert!(queue.try_acquire("other-app", &second).await.is_ok());
+
+        queue.release("app", &first).await;
+        assert!(queue.try_acquire("app", &second).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_waiting_task_starts_after_release() {
+        let queue = AppOperationQueue::new();
+        let first = Uuid::new_v4();
+        let second = Uuid::new_v4();
+
+        assert_eq!(queue.enqueue("app", &first).await, 0);
+        assert_eq!(queue.enqueue("app", &second).await, 1);
+        assert_eq!(queue.waiting("app").await, vec![(second, 1)]);
+
+        let waiter = {
+            let queue = queue.clone();
+            tokio::spawn(async move {
+                let cancelled = AtomicBool::new(false);
+                queue.wait_for_turn("app", &second, &cancelled).await
+            })
+        };
+
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        assert!(!waiter.is_finished());
+
+        queue.release("app", &first).await;
+        let started = tokio::time::timeout(std::time::Duration::from_secs(1), waiter)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(started);
+        assert_eq!(queue.holder("app").await, Some(second));
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_task_leaves_queue() {
+        let queue = AppOperationQueue::new();
+        let first = Uuid::new_v4();
+        let second = Uuid::new_v4();
+        queue.enqueue("app", &first).await;
+        queue.enqueue("app", &second).await;
+
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let waiter = {
+            let queue = queue.clone();
+            let cancelled = cancelled.clone();
+            tokio::spawn(async move { queue.wait_for_turn("app", &second, &cancelled).await })
+        };
+
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        cancelled.store(true, Ordering::SeqCst);
+        queue.wake();
+
+        let started = tokio::time::timeout(std::time::Duration::from_secs(1), waiter)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(!started);
+        assert_eq!(queue.position("app", &second).await, None);
+        assert_eq!(queue.holder("app").await, Some(first));
+    }
+}
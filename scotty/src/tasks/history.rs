@@ -0,0 +1,413 @@
+use std::collections::HashMap;
+use std::io::SeekFrom;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncBufReadExt, AsyncSeekExt, AsyncWriteExt};
+use tokio::sync::Mutex;
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+use scotty_core::output::TaskOutput;
+use scotty_core::settings::task_history::TaskHistorySettings;
+use scotty_core::tasks::task_details::{State, TaskDetails};
+
+/// A single finished task as stored in the history file.
+///
+/// `TaskDetails` skips its output when serialized, so the captured output is
+/// stored next to it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct TaskHistoryEntry {
+    #[serde(flatten)]
+    details: TaskDetails,
+    #[serde(default)]
+    output: TaskOutput,
+}
+
+impl From<&TaskDetails> for TaskHistoryEntry {
+    fn from(details: &TaskDetails) -> Self {
+        Self {
+            details: details.clone(),
+            output: details.output.clone(),
+        }
+    }
+}
+
+impl From<TaskHistoryEntry> for TaskDetails {
+    fn from(entry: TaskHistoryEntry) -> Self {
+        TaskDetails {
+            output: entry.output,
+            ..entry.details
+        }
+    }
+}
+
+/// A task in the index of the history file.
+#[derive(Debug, Clone)]
+struct IndexedTask {
+    /// Byte offset of the last line of the task in the history file
+    offset: u64,
+    /// The task without its output, enough to list and filter it
+    summary: TaskDetails,
+}
+
+impl IndexedTask {
+    fn new(offset: u64, task: &TaskDetails) -> Self {
+        Self {
+            offset,
+            summary: TaskDetails {
+                output: TaskOutput::default(),
+                ..task.clone()
+            },
+        }
+    }
+}
+
+type TaskIndex = HashMap<Uuid, IndexedTask>;
+
+/// Filter applied when listing tasks.
+#[derive(Debug, Clone, Default, Deserialize, utoipa::IntoParams, utoipa::ToSchema)]
+#[into_params(parameter_in = Query)]
+pub struct TaskFilter {
+    /// Only return tasks of this app
+    pub app: Option<String>,
+    /// Only return tasks in this state: `Queued`, `Running`, `Finished`,
+    /// `Failed` or `Cancelled`
+    #[param(value_type = Option<String>)]
+    pub state: Option<State>,
+    /// Only return tasks started at or after this time (RFC 3339)
+    #[param(value_type = Option<String>)]
+    pub since: Option<DateTime<Utc>>,
+    /// Only return tasks started before this time (RFC 3339)
+    #[param(value_type = Option<String>)]
+    pub until: Option<DateTime<Utc>>,
+}
+
+impl TaskFilter {
+    pub fn matches(&self, task: &TaskDetails) -> bool {
+        if let Some(app) = &self.app {
+            if task.app_name.as_deref() != Some(app.as_str()) {
+                return false;
+            }
+        }
+        if let Some(state) = &self.state {
+            if &task.state != state {
+                return false;
+            }
+        }
+        if let Some(since) = self.since {
+            if task.start_time < since {
+                return false;
+            }
+        }
+        if let Some(until) = self.until {
+            if task.start_time >= until {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// Append-only JSONL store for finished tasks.
+#[derive(Debug, Clone)]
+pub struct TaskHistory {
+    path: PathBuf,
+    retention: chrono::Duration,
+    /// Guards the file, the index is built on the first lookup
+    lock: Arc<Mutex<Option<TaskIndex>>>,
+}
+
+impl TaskHistory {
+    pub fn new(path: PathBuf, retention: chrono::Duration) -> Self {
+        Self {
+            path,
+            retention,
+            lock: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Create the history from settings, returns None if it is disabled.
+    pub fn from_settings(settings: &TaskHistorySettings, root_folder: &str) -> Option<Self> {
+        if !settings.enabled {
+            return None;
+        }
+        Some(Self::new(
+            settings.get_file_path(root_folder),
+            chrono::Duration::days(settings.retention_days as i64),
+        ))
+    }
+
+    /// Append a finished task to the history file.
+    pub async fn record(&self, task: &TaskDetails) -> anyhow::Result<()> {
+        let mut line = serde_json::to_string(&TaskHistoryEntry::from(task))?;
+        line.push('\n');
+
+        let mut index = self.lock.lock().await;
+        if let Some(parent) = self.path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .await?;
+        let offset = file.metadata().await?.len();
+        file.write_all(line.as_bytes()).await?;
+        file.flush().await?;
+        if let Some(index) = index.as_mut() {
+            index.insert(task.id, IndexedTask::new(offset, task));
+        }
+
+        debug!("Recorded task {} in task history", task.id);
+        Ok(())
+    }
+
+    /// Load all tasks from the history file, in the order they were recorded.
+    #[cfg(test)]
+    pub async fn load(&self) -> anyhow::Result<Vec<TaskDetails>> {
+        let _guard = self.lock.lock().await;
+        self.load_entries().await
+    }
+
+    /// Return all tasks matching the filter, without their output.
+    ///
+    /// The tasks come from the index of the file, which is kept up to date
+    /// while tasks get recorded, so the file is only read once.
+    pub async fn query(&self, filter: &TaskFilter) -> anyhow::Result<Vec<TaskDetails>> {
+        let mut index = self.lock.lock().await;
+        let index = self.ensure_index(&mut index).await?;
+        Ok(index
+            .values()
+            .map(|task| &task.summary)
+            .filter(|task| filter.matches(task))
+            .cloned()
+            .collect())
+    }
+
+    /// Find a single task by its id.
+    ///
+    /// Only the line of the task is parsed, using the index of the file.
+    pub async fn find(&self, uuid: &Uuid) -> anyhow::Result<Option<TaskDetails>> {
+        let mut index = self.lock.lock().await;
+        let index = self.ensure_index(&mut index).await?;
+        let Some(offset) = index.get(uuid).map(|task| task.offset) else {
+            return Ok(None);
+        };
+
+        let mut file = tokio::fs::File::open(&self.path).await?;
+        file.seek(SeekFrom::Start(offset)).await?;
+        let mut line = String::new();
+        tokio::io::BufReader::new(file).read_line(&mut line).await?;
+        let entry: TaskHistoryEntry = serde_json::from_str(&line)?;
+        Ok(Some(entry.into()))
+    }
+
+    /// Remove all tasks which finished before the retention period.
+    pub async fn prune(&self) -> anyhow::Result<usize> {
+        let mut index = self.lock.lock().await;
+        let tasks = self.load_entries().await?;
+        let cutoff = Utc::now() - self.retention;
+
+        let total = tasks.len();
+        let kept: Vec<TaskDetails> = tasks
+            .into_iter()
+            .filter(|task| task.finish_time.unwrap_or(task.start_time) >= cutoff)
+            .collect();
+        let removed = total - kept.len();
+        if removed == 0 {
+            return Ok(0);
+        }
+
+        let mut content = String::new();
+        for task in &kept {
+            content.push_str(&serde_json::to_string(&TaskHistoryEntry::from(task))?);
+            content.push('\n');
+        }
+        let tmp_path = self.path.with_extension("jsonl.tmp");
+        tokio::fs::write(&tmp_path, content).await?;
+        tokio::fs::rename(&tmp_path, &self.path).await?;
+        // The offsets changed, the next lookup rebuilds the index
+        *index = None;
+
+        info!("Removed {} expired tasks from task history", removed);
+        Ok(removed)
+    }
+
+    async fn ensure_index<'a>(
+        &self,
+        index: &'a mut Option<TaskIndex>,
+    ) -> anyhow::Result<&'a TaskIndex> {
+        if index.is_none() {
+            *index = Some(self.build_index().await?);
+        }
+        Ok(index.get_or_insert_with(TaskIndex::new))
+    }
+
+    async fn build_index(&self) -> anyhow::Result<TaskIndex> {
+        let content = match tokio::fs::read_to_string(&self.path).await {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(TaskIndex::new()),
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut index = TaskIndex::new();
+        let mut offset = 0;
+        for line in content.split_inclusive('\n') {
+            // Later lines win, like they do when loading the whole file. The
+            // output is skipped when deserializing the details.
+            if let Ok(task) = serde_json::from_str::<TaskDetails>(line) {
+                index.insert(task.id, IndexedTask::new(offset as u64, &task));
+            }
+            offset += line.len();
+        }
+        Ok(index)
+    }
+
+    async fn load_entries(&self) -> anyhow::Result<Vec<TaskDetails>> {
+        let content = match tokio::fs::read_to_string(&self.path).await {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut tasks = Vec::new();
+        for (idx, line) in content.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str::<TaskHistoryEntry>(line) {
+                Ok(entry) => tasks.push(entry.into()),
+                Err(e) => warn!(
+                    "Skipping invalid line {} in task history {}: {}",
+                    idx + 1,
+                    self.path.display(),
+                    e
+                ),
+            }
+        }
+        Ok(tasks)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use scotty_core::output::OutputStreamType;
+
+    fn finished_task(app: &str, state: State, age: chrono::Duration) -> TaskDetails {
+        let start_time = Utc::now() - age;
+        let mut task = TaskDetails::new("rebuild".to_string(), Some(app.to_string()));
+        task.state = state;
+        task.start_time = start_time;
+        task.finish_time = Some(start_time + chrono::Duration::seconds(5));
+        task.requested_by = Some("jane@example.com".to_string());
+        task.output
+            .add_line(OutputStreamType::Stdout, "hello".to_string());
+        task
+    }
+
+    #[tokio::test]
+    async fn test_record_and_load_keeps_output() {
+        let dir = tempfile::tempdir().unwrap();
+        let history = TaskHistory::new(dir.path().join("history.jsonl"), chrono::Duration::days(1));
+
+        let task = finished_task("app-a", State::Failed, chrono::Duration::minutes(1));
+        history.record(&task).await.unwrap();
+
+        let found = history.find(&task.id).await.unwrap().unwrap();
+        assert_eq!(found.state, State::Failed);
+        assert_eq!(found.app_name.as_deref(), Some("app-a"));
+        assert_eq!(found.requested_by.as_deref(), Some("jane@example.com"));
+        assert_eq!(found.output.lines.len(), 1);
+        assert_eq!(found.output.lines[0].content, "hello");
+    }
+
+    #[tokio::test]
+    async fn test_query_filters() {
+        let dir = tempfile::tempdir().unwrap();
+        let history = TaskHistory::new(dir.path().join("history.jsonl"), chrono::Duration::days(1));
+
+        let old = finished_task("app-a", State::Finished, chrono::Duration::hours(5));
+        let failed = finished_task("app-a", State::Failed, chrono::Duration::hours(1));
+        let other = finished_task("app-b", State::Failed, chrono::Duration::minutes(10));
+        for task in [&old, &failed, &other] {
+            history.record(task).await.unwrap();
+        }
+
+        let by_app = TaskFilter {
+            app: Some("app-a".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(history.query(&by_app).await.unwrap().len(), 2);
+
+        let by_state = TaskFilter {
+            state: Some(State::Failed),
+            ..Default::default()
+        };
+        assert_eq!(history.query(&by_state).await.unwrap().len(), 2);
+
+        let by_time = TaskFilter {
+            since: Some(Utc::now() - chrono::Duration::hours(2)),
+            until: Some(Utc::now() - chrono::Duration::minutes(30)),
+            ..Default::default()
+        };
+        let result = history.query(&by_time).await.unwrap();
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].id, failed.id);
+        // Listing does not read the output, a single task still has it
+        assert!(result[0].output.lines.is_empty());
+
+        // Tasks recorded after the index got built are listed as well
+        let recent = finished_task("app-a", State::Cancelled, chrono::Duration::minutes(1));
+        history.record(&recent).await.unwrap();
+        assert_eq!(history.query(&by_app).await.unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_prune_removes_expired_tasks() {
+        let dir = tempfile::tempdir().unwrap();
+        let history = TaskHistory::new(dir.path().join("history.jsonl"), chrono::Duration::days(1));
+
+        let expired = finished_task("app-a", State::Finished, chrono::Duration::days(3));
+        let recent = finished_task("app-a", State::Finished, chrono::Duration::hours(3));
+        history.record(&expired).await.unwrap();
+        history.record(&recent).await.unwrap();
+
+        assert_eq!(history.prune().await.unwrap(), 1);
+        let tasks = history.load().await.unwrap();
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].id, recent.id);
+    }
+
+    #[tokio::test]
+    async fn test_find_after_record_and_prune() {
+        let dir = tempfile::tempdir().unwrap();
+        let history = TaskHistory::new(dir.path().join("history.jsonl"), chrono::Duration::days(1));
+
+        let expired = finished_task("app-a", State::Finished, chrono::Duration::days(3));
+        let recent = finished_task("app-b", State::Failed, chrono::Duration::hours(3));
+        history.record(&expired).await.unwrap();
+        assert!(history.find(&recent.id).await.unwrap().is_none());
+
+        // Recorded after the index got built
+        history.record(&recent).await.unwrap();
+        let found = history.find(&recent.id).await.unwrap().unwrap();
+        assert_eq!(found.app_name.as_deref(), Some("app-b"));
+
+        history.prune().await.unwrap();
+        assert!(history.find(&expired.id).await.unwrap().is_none());
+        let found = history.find(&recent.id).await.unwrap().unwrap();
+        assert_eq!(found.output.lines[0].content, "hello");
+    }
+
+    #[tokio::test]
+    async fn test_missing_file_is_empty() {
+        let dir = tempfile::tempdir().unwrap();
+        let history = TaskHistory::new(dir.path().join("missing.jsonl"), chrono::Duration::days(1));
+        assert!(history.load().await.unwrap().is_empty());
+        assert_eq!(history.prune().await.unwrap(), 0);
+    }
+}
@@ -1,6 +1,7 @@
 #![allow(dead_code)]
 
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::{collections::HashMap, sync::Arc};
 
 use scotty_core::settings::{output::OutputSettings, scheduler_interval::SchedulerInterval};
@@ -8,7 +9,7 @@ use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
 
 use tokio::process::Command;
 use tokio::sync::RwLock;
-use tracing::{debug, info, instrument};
+use tracing::{debug, info, instrument, warn};
 
 use uuid::Uuid;
 
@@ -17,6 +18,8 @@ use scotty_core::tasks::task_details::{State, TaskDetails, TaskState};
 
 use crate::api::websocket::WebSocketMessenger;
 use crate::metrics;
+use crate::tasks::app_queue::AppOperationQueue;
+use crate::tasks::history::{TaskFilter, TaskHistory};
 use crate::tasks::timed_buffer::TimedBuffer;
 
 /// Helper function to add multiple lines to task output with a single write lock
@@ -54,12 +57,17 @@ async fn add_output_line(
 async fn handle_task_completion(
     details: &Arc<RwLock<TaskDetails>>,
     _messenger: &WebSocketMessenger,
-    _task_manager: &TaskManager,
+    task_manager: &TaskManager,
     task_id: uuid::Uuid,
     exit_code: Result<i32, anyhow::Error>,
 ) {
     debug!("Handling task completion for task {}", task_id);
+    task_manager.set_process_group(&task_id, None).await;
+
     let (state, status_code, _reason) = match exit_code {
+        _ if task_manager.is_task_cancelled(&task_id).await => {
+            (State::Cancelled, None, "cancelled")
+        }
         Ok(0) => (State::Finished, Some(0), "completed"),
         Ok(e) => (State::Failed, Some(e), "failed"),
         Err(_) => (State::Failed, None, "failed"),
@@ -72,10 +80,20 @@ async fn handle_task_completion(
     debug!("Task {} completed with status {:?}", task_id, state);
 }
 
+/// Cancellation bookkeeping for a single task
+#[derive(Clone, Debug, Default)]
+struct TaskControl {
+    cancelled: Arc<AtomicBool>,
+    process_group: Option<u32>,
+}
+
 #[derive(Clone, Debug)]
 pub struct TaskManager {
     pub processes: Arc<RwLock<HashMap<Uuid, TaskState>>>,
     messenger: WebSocketMessenger,
+    history: Option<TaskHistory>,
+    controls: Arc<RwLock<HashMap<Uuid, TaskControl>>>,
+    app_queue: AppOperationQueue,
 }
 
 impl TaskManager {
@@ -83,9 +101,36 @@ impl TaskManager {
         Self {
             processes: Arc::new(RwLock::new(HashMap::new())),
             messenger,
+            history: None,
+            controls: Arc::new(RwLock::new(HashMap::new())),
+            app_queue: AppOperationQueue::new(),
         }
     }
 
+    /// Persist finished tasks to the given history store
+    pub fn with_history(mut self, history: Option<TaskHistory>) -> Self {
+        self.history = history;
+        self
+    }
+
+    /// Queue serializing lifecycle operations per app
+    pub fn app_queue(&self) -> &AppOperationQueue {
+        &self.app_queue
+    }
+
+    /// Mark a task as waiting in the operation queue of its app
+    pub async fn set_task_queue_position(&self, uuid: &Uuid, position: Option<usize>) -> bool {
+        self.modify_task_details(uuid, |details| {
+            details.queue_position = position;
+            if position.is_some() {
+                details.state = State::Queued;
+            } else if details.state == State::Queued {
+                details.state = State::Running;
+            }
+        })
+        .await
+    }
+
     /// Helper: Get a task's details Arc with minimal lock time
     async fn get_details_arc(&self, uuid: &Uuid) -> Option<Arc<RwLock<TaskDetails>>> {
         let processes = self.processes.read().await;
@@ -127,12 +172,143 @@ impl TaskManager {
         task_list
     }
 
+    /// Get live tasks and tasks from the history matching the filter, newest first
+    pub async fn get_filtered_task_list(&self, filter: &TaskFilter) -> Vec<TaskDetails> {
+        let mut task_list: Vec<TaskDetails> = self
+            .get_task_list()
+            .await
+            .into_iter()
+            .filter(|task| filter.matches(task))
+            .collect();
+
+        if let Some(history) = &self.history {
+            match history.query(filter).await {
+                Ok(tasks) => {
+                    for task in tasks {
+                        if !task_list.iter().any(|t| t.id == task.id) {
+                            task_list.push(task);
+                        }
+                    }
+                }
+                Err(e) => warn!("Failed to read task history: {}", e),
+            }
+        }
+
+        task_list.sort_by_key(|task| std::cmp::Reverse(task.start_time));
+        task_list
+    }
+
     pub async fn get_task_details(&self, uuid: &Uuid) -> Option<TaskDetails> {
         debug!("TaskManager: Getting task details for {}", uuid);
 
+        if let Some(details_arc) = self.get_details_arc(uuid).await {
+            let details = details_arc.read().await;
+            return Some(details.clone());
+        }
+
+        // Fall back to the history for tasks which were already cleaned up
+        let history = self.history.as_ref()?;
+        match history.find(uuid).await {
+            Ok(task) => task,
+            Err(e) => {
+                warn!("Failed to read task history: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Get the cancellation flag of a task, the flag is shared with the state machine running it
+    pub async fn get_cancel_flag(&self, uuid: &Uuid) -> Arc<AtomicBool> {
+        let mut controls = self.controls.write().await;
+        controls.entry(*uuid).or_default().cancelled.clone()
+    }
+
+    pub async fn is_task_cancelled(&self, uuid: &Uuid) -> bool {
+        let controls = self.controls.read().await;
+        controls
+            .get(uuid)
+            .is_some_and(|control| control.cancelled.load(Ordering::SeqCst))
+    }
+
+    async fn set_process_group(&self, uuid: &Uuid, process_group: Option<u32>) {
+        let mut controls = self.controls.write().await;
+        controls.entry(*uuid).or_default().process_group = process_group;
+    }
+
+    /// Cancel a running task
+    ///
+    /// Marks the task as cancelled and terminates the process group of its
+    /// current child process, if any. The state machine owning the task
+    /// notices the cancellation and runs its error handler.
+    pub async fn cancel_task(&self, uuid: &Uuid) -> Option<TaskDetails> {
         let details_arc = self.get_details_arc(uuid).await?;
-        let details = details_arc.read().await;
-        Some(details.clone())
+
+        let process_group = {
+            let mut controls = self.controls.write().await;
+            let control = controls.entry(*uuid).or_default();
+            control.cancelled.store(true, Ordering::SeqCst);
+            control.process_group
+        };
+
+        info!("Cancelling task {}", uuid);
+        self.add_task_status_error(uuid, "Task cancelled".to_string())
+            .await;
+
+        if let Some(process_group) = process_group {
+            if let Err(e) = signal_process_group(process_group, libc::SIGTERM) {
+                warn!(
+                    "Failed to terminate process group {} of task {}: {}",
+                    process_group, uuid, e
+                );
+            }
+
+            // Kill the process group if it does not react to SIGTERM
+            let task_manager = self.clone();
+            let uuid = *uuid;
+            tokio::spawn(async move {
+                tokio::time::sleep(std::time::Duration::from_secs(PROCESS_KILL_TIMEOUT_SECS)).await;
+                let still_running = {
+                    let controls = task_manager.controls.read().await;
+                    controls
+                        .get(&uuid)
+                        .and_then(|control| control.process_group)
+                        == Some(process_group)
+                };
+                if still_running {
+                    warn!(
+                        "Process group {} of task {} still running, killing it",
+                        process_group, uuid
+                    );
+                    let _ = signal_process_group(process_group, libc::SIGKILL);
+                }
+            });
+        }
+
+        let details = {
+            let mut details = details_arc.write().await;
+            details.state = State::Cancelled;
+            details.queue_position = None;
+            details.clone()
+        };
+
+        // Let a queued task leave the operation queue of its app
+        self.app_queue.wake();
+
+        Some(details)
+    }
+
+    /// Write a finished task to the task history, if enabled
+    pub async fn record_task_history(&self, uuid: &Uuid) {
+        let Some(history) = &self.history else {
+            return;
+        };
+        let Some(details_arc) = self.get_details_arc(uuid).await else {
+            return;
+        };
+        let details = details_arc.read().await.clone();
+        if let Err(e) = history.record(&details).await {
+            warn!("Failed to record task {} in task history: {}", uuid, e);
+        }
     }
 
     pub async fn get_task_output(&self, uuid: &Uuid) -> Option<TaskOutput> {
@@ -231,7 +407,7 @@ impl TaskManager {
             .signed_duration_since(start_time)
             .num_milliseconds() as f64
             / 1000.0;
-        let failed = matches!(state, State::Failed);
+        let failed = matches!(state, State::Failed | State::Cancelled);
         metrics::metrics().record_task_finished(duration_secs, failed);
     }
 
@@ -247,6 +423,54 @@ impl TaskManager {
             .await
     }
 
+    /// Run a command for a task and wait for it to finish, also if the task
+    /// got cancelled, e.g. to roll back the changes of the task.
+    ///
+    /// The output ends up in the task. The process group gets killed if the
+    /// command does not finish within `timeout`.
+    pub async fn run_process_and_wait(
+        &self,
+        cwd: &Path,
+        cmd: &str,
+        args: &[&str],
+        env: &HashMap<String, String>,
+        details: Arc<RwLock<TaskDetails>>,
+        timeout: std::time::Duration,
+    ) -> anyhow::Result<i32> {
+        let cwd = cwd.to_path_buf();
+        let args = args.iter().map(|s| s.to_string()).collect::<Vec<String>>();
+        let task_id = details.read().await.id;
+
+        let result =
+            tokio::time::timeout(timeout, run_process(self, &cwd, cmd, &args, env, &details)).await;
+
+        let process_group = {
+            let controls = self.controls.read().await;
+            controls
+                .get(&task_id)
+                .and_then(|control| control.process_group)
+        };
+        self.set_process_group(&task_id, None).await;
+
+        match result {
+            Ok(exit_code) => exit_code,
+            Err(_) => {
+                if let Some(process_group) = process_group {
+                    warn!(
+                        "Process group {} of task {} timed out, killing it",
+                        process_group, task_id
+                    );
+                    let _ = signal_process_group(process_group, libc::SIGKILL);
+                }
+                Err(anyhow::anyhow!(
+                    "{} did not finish within {} seconds",
+                    cmd,
+                    timeout.as_secs()
+                ))
+            }
+        }
+    }
+
     pub async fn start_process_with_settings(
         &self,
         cwd: &Path,
@@ -262,7 +486,7 @@ impl TaskManager {
         let env = env.clone();
         let id = details.read().await.id;
 
-        {
+        if !self.is_task_cancelled(&id).await {
             debug!("details write lock, setting state to running");
             let mut details = details.write().await;
             details.state = State::Running;
@@ -284,6 +508,7 @@ impl TaskManager {
                 let details = details.clone();
                 let messenger_for_spawn = messenger.clone();
                 let exit_result = spawn_process(
+                    &task_manager,
                     &cwd,
                     &cmd,
                     &args,
@@ -368,19 +593,45 @@ impl TaskManager {
         // Step 3: Remove tasks with a write lock (minimal time)
         if !to_remove.is_empty() {
             let mut processes = self.processes.write().await;
-            for uuid in to_remove {
-                processes.remove(&uuid);
+            for uuid in &to_remove {
+                processes.remove(uuid);
             }
             let active_count = processes.len();
             drop(processes); // Release lock before recording metrics
 
+            let mut controls = self.controls.write().await;
+            for uuid in &to_remove {
+                controls.remove(uuid);
+            }
+            drop(controls);
+
             metrics::metrics().record_task_cleanup(active_count);
         }
         // Write lock released immediately
+
+        if let Some(history) = &self.history {
+            if let Err(e) = history.prune().await {
+                warn!("Failed to prune task history: {}", e);
+            }
+        }
+    }
+}
+
+/// Seconds to wait after SIGTERM before a cancelled process group gets killed
+const PROCESS_KILL_TIMEOUT_SECS: u64 = 10;
+
+fn signal_process_group(process_group: u32, signal: libc::c_int) -> std::io::Result<()> {
+    // SAFETY: killpg has no memory safety requirements, it only sends a signal
+    let result = unsafe { libc::killpg(process_group as libc::pid_t, signal) };
+    if result == 0 {
+        Ok(())
+    } else {
+        Err(std::io::Error::last_os_error())
     }
 }
 
 async fn spawn_process(
+    task_manager: &TaskManager,
     cwd: &PathBuf,
     cmd: &str,
     args: &Vec<String>,
@@ -388,14 +639,36 @@ async fn spawn_process(
     details: &Arc<RwLock<TaskDetails>>,
     _messenger: WebSocketMessenger,
 ) -> anyhow::Result<i32> {
+    let task_id = details.read().await.id;
+    if task_manager.is_task_cancelled(&task_id).await {
+        return Err(anyhow::anyhow!("Task {} was cancelled", task_id));
+    }
+
+    run_process(task_manager, cwd, cmd, args, env, details).await
+}
+
+async fn run_process(
+    task_manager: &TaskManager,
+    cwd: &PathBuf,
+    cmd: &str,
+    args: &Vec<String>,
+    env: &HashMap<String, String>,
+    details: &Arc<RwLock<TaskDetails>>,
+) -> anyhow::Result<i32> {
+    let task_id = details.read().await.id;
+
+    // Run the command in its own process group, so cancelling the task
+    // also terminates all processes started by it.
     let mut child = Command::new(cmd)
         .args(args)
         .envs(env)
         .current_dir(cwd)
+        .process_group(0)
         .stdout(std::process::Stdio::piped())
         .stderr(std::process::Stdio::piped())
         .spawn()
         .expect("Failed to start command");
+    task_manager.set_process_group(&task_id, child.id()).await;
 
     let stdout = child.stdout.take().expect("Failed to get stdout");
     let stderr = child.stderr.take().expect("Failed to get stderr");
@@ -470,3 +743,133 @@ async fn read_unified_output(
         add_output_lines(&details, buffer.flush(), task_id).await;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::test_utils::create_test_websocket_messenger;
+
+    #[tokio::test]
+    async fn test_cancel_task_kills_process_group() {
+        let task_manager = TaskManager::new(create_test_websocket_messenger());
+        let details = Arc::new(RwLock::new(TaskDetails::new(
+            "sleep".to_string(),
+            Some("test-app".to_string()),
+        )));
+        let dir = tempfile::tempdir().unwrap();
+
+        // The shell spawns a child, so killing only the direct child would not be enough
+        let id = task_manager
+            .start_process(
+                dir.path(),
+                "sh",
+                &["-c", "sleep 30; echo done"],
+                &HashMap::new(),
+                details.clone(),
+            )
+            .await;
+
+        // Wait until the process group is known
+        for _ in 0..50 {
+            let has_process_group = {
+                let controls = task_manager.controls.read().await;
+                controls
+                    .get(&id)
+                    .is_some_and(|control| control.process_group.is_some())
+            };
+            if has_process_group {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+
+        let cancelled = task_manager.cancel_task(&id).await.unwrap();
+        assert_eq!(cancelled.state, State::Cancelled);
+        assert!(task_manager.is_task_cancelled(&id).await);
+
+        let handle = task_manager.get_task_handle(&id).await.unwrap();
+        tokio::time::timeout(std::time::Duration::from_secs(5), async {
+            while !handle.read().await.is_finished() {
+                tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+            }
+        })
+        .await
+        .expect("cancelled process did not terminate");
+
+        let details = task_manager.get_task_details(&id).await.unwrap();
+        assert_eq!(details.state, State::Cancelled);
+        assert!(details.finish_time.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_run_process_and_wait_ignores_cancel_flag() {
+        let task_manager = TaskManager::new(create_test_websocket_messenger());
+        let details = Arc::new(RwLock::new(TaskDetails::new(
+            "echo".to_string(),
+            Some("test-app".to_string()),
+        )));
+        let id = details.read().await.id;
+        task_manager.add_task(&id, details.clone(), None).await;
+        task_manager.cancel_task(&id).await.unwrap();
+        let dir = tempfile::tempdir().unwrap();
+
+        let exit_code = task_manager
+            .run_process_and_wait(
+                dir.path(),
+                "sh",
+                &["-c", "echo rolled back"],
+                &HashMap::new(),
+                details.clone(),
+                std::time::Duration::from_secs(5),
+            )
+            .await
+            .unwrap();
+        assert_eq!(exit_code, 0);
+
+        // The output gets collected in the background
+        for _ in 0..50 {
+            if details
+                .read()
+                .await
+                .output
+                .lines
+                .iter()
+                .any(|line| line.content == "rolled back")
+            {
+                return;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+        panic!("output of the process is missing");
+    }
+
+    #[tokio::test]
+    async fn test_run_process_and_wait_times_out() {
+        let task_manager = TaskManager::new(create_test_websocket_messenger());
+        let details = Arc::new(RwLock::new(TaskDetails::new(
+            "sleep".to_string(),
+            Some("test-app".to_string()),
+        )));
+        let dir = tempfile::tempdir().unwrap();
+
+        let started = std::time::Instant::now();
+        let result = task_manager
+            .run_process_and_wait(
+                dir.path(),
+                "sh",
+                &["-c", "sleep 30"],
+                &HashMap::new(),
+                details,
+                std::time::Duration::from_millis(200),
+            )
+            .await;
+        assert!(result.is_err());
+        assert!(started.elapsed() < std::time::Duration::from_secs(5));
+    }
+
+    #[tokio::test]
+    async fn test_cancel_unknown_task() {
+        let task_manager = TaskManager::new(create_test_websocket_messenger());
+        assert!(task_manager.cancel_task(&Uuid::new_v4()).await.is_none());
+    }
+}
@@ -1,3 +1,5 @@
+pub mod app_queue;
+pub mod history;
 pub mod manager;
 pub mod output_streaming;
 pub mod timed_buffer;
@@ -104,6 +104,10 @@ pub async fn get_or_post(
             let body = body.unwrap_or_else(|| serde_json::json!({}));
             client.post_json::<Value, Value>(&url, &body).await
         }
+        "put" => {
+            let body = body.unwrap_or_else(|| serde_json::json!({}));
+            client.put_json::<Value, Value>(&url, &body).await
+        }
         "delete" => {
             // DELETE requests with JSON bodies need manual handling since there's no delete_json method
             if let Some(body) = body {
@@ -253,13 +257,15 @@ pub async fn wait_for_task(
 
         // Poll for task completion status
         let mut done = false;
+        let mut queue_position = None;
         while !done {
             let result = get(server, &format!("task/{}", context.task.id)).await?;
             let task: TaskDetails =
                 serde_json::from_value(result).context("Failed to parse task")?;
 
             // Check if task is done
-            done = task.state != State::Running;
+            done = !matches!(task.state, State::Running | State::Queued);
+            report_queue_position(ui, &task, &mut queue_position);
 
             if done {
                 // Wait a bit for any remaining WebSocket messages to be processed
@@ -291,7 +297,11 @@ pub async fn wait_for_task(
                             ui.eprintln(format!("Exit code: {}", exit_code).red().to_string());
                         }
                     }
-                    State::Running => {} // Should not happen since we check above
+                    State::Cancelled => {
+                        ui.set_status("Task cancelled", Status::Failed);
+                        return Err(anyhow::anyhow!("Task {} was cancelled", task.id));
+                    }
+                    State::Running | State::Queued => {} // Should not happen since we check above
                 }
 
                 // Return error if task failed
@@ -318,12 +328,14 @@ pub async fn wait_for_task(
         }
 
         let mut done = false;
+        let mut queue_position = None;
         while !done {
             let result = get(server, &format!("task/{}", context.task.id)).await?;
             let task: TaskDetails =
                 serde_json::from_value(result).context("Failed to parse task")?;
 
-            done = task.state != State::Running;
+            done = !matches!(task.state, State::Running | State::Queued);
+            report_queue_position(ui, &task, &mut queue_position);
 
             if done {
                 match task.state {
@@ -343,7 +355,11 @@ pub async fn wait_for_task(
                             ui.eprintln(format!("Exit code: {}", exit_code).red().to_string());
                         }
                     }
-                    State::Running => {} // Should not happen
+                    State::Cancelled => {
+                        ui.set_status("Task cancelled", Status::Failed);
+                        return Err(anyhow::anyhow!("Task {} was cancelled", task.id));
+                    }
+                    State::Running | State::Queued => {} // Should not happen
                 }
 
                 if let Some(exit_code) = task.last_exit_code {
@@ -360,6 +376,32 @@ pub async fn wait_for_task(
     Ok(())
 }
 
+/// Show the position of the task in the operation queue of its app while it waits
+fn report_queue_position(
+    ui: &Arc<Ui>,
+    task: &scotty_core::tasks::task_details::TaskDetails,
+    last_position: &mut Option<usize>,
+) {
+    let position = match task.state {
+        State::Queued => task.queue_position,
+        _ => None,
+    };
+    if position == *last_position {
+        return;
+    }
+    match position {
+        Some(position) => {
+            let message = format!("Waiting in queue (position {})...", position);
+            if !ui.is_terminal() {
+                ui.eprintln(&message);
+            }
+            ui.new_status_line(message);
+        }
+        None => ui.new_status_line("Running task..."),
+    }
+    *last_position = position;
+}
+
 fn display_task_output_line(line: &scotty_types::OutputLine, ui: &Arc<Ui>) {
     use scotty_types::OutputStreamType;
 
@@ -1,6 +1,8 @@
 use crate::utils::parsers::{
-    parse_app_ttl, parse_basic_auth, parse_custom_domain_mapping, parse_env_vars,
-    parse_folder_containing_docker_compose, parse_service_ids, parse_service_ports,
+    parse_app_ttl, parse_basic_auth, parse_cron_expression, parse_custom_domain_mapping,
+    parse_domain_setting, parse_env_vars, parse_folder_containing_docker_compose, parse_ip_range,
+    parse_on_off, parse_redirect, parse_response_header, parse_service_ids, parse_service_ports,
+    parse_tcp_service, parse_timezone, parse_tls_domain, parse_udp_service,
 };
 use clap::{Parser, Subcommand};
 use clap_complete::Shell;
@@ -9,7 +11,9 @@ use scotty_core::{
         CreateAssignmentRequest, CreateRoleRequest, CreateScopeRequest, GetUserPermissionsRequest,
         RemoveAssignmentRequest, TestPermissionRequest,
     },
-    apps::app_data::{AppTtl, ServicePortMapping},
+    apps::app_data::{
+        AppTtl, BasicAuthUser, RedirectRule, ServicePortMapping, TcpServiceMapping, TlsDomain,
+    },
     apps::create_app_request::CustomDomainMapping,
     notification_types::NotificationReceiver,
 };
@@ -68,9 +72,36 @@ pub enum Commands {
     /// Add a new app
     #[command(name = "app:create")]
     Create(Box<CreateCommand>),
+    /// Push new files and optionally new settings to an existing app
+    #[command(name = "app:update")]
+    Update(Box<UpdateCommand>),
     /// Destroy an app
     #[command(name = "app:destroy")]
     Destroy(DestroyCommand),
+    /// List the stored revisions of an app
+    #[command(name = "app:revisions")]
+    Revisions(RevisionsCommand),
+    /// Roll back an app to a previous revision
+    #[command(name = "app:rollback")]
+    Rollback(RollbackCommand),
+    /// Show the certificates and their expiry for the domains of an app
+    #[command(name = "app:certificates")]
+    Certificates(CertificatesCommand),
+    /// List the basic auth users of an app
+    #[command(name = "app:auth:list")]
+    AuthList(AuthListCommand),
+    /// Add a basic auth user to an app or change its password
+    #[command(name = "app:auth:add")]
+    AuthAdd(AuthAddCommand),
+    /// Remove a basic auth user from an app
+    #[command(name = "app:auth:remove")]
+    AuthRemove(AuthRemoveCommand),
+    /// Set a new TTL for an app, counting from now
+    #[command(name = "app:extend")]
+    Extend(ExtendCommand),
+    /// Turn the maintenance mode of an app on or off
+    #[command(name = "app:maintenance")]
+    Maintenance(MaintenanceCommand),
     /// Run a custom action on an app
     #[command(name = "app:action")]
     Action(ActionCommand),
@@ -104,6 +135,10 @@ pub enum Commands {
     #[command(name = "notify:remove")]
     NotifyRemove(NotifyRemoveCommand),
 
+    /// Cancel a running task
+    #[command(name = "task:cancel")]
+    TaskCancel(TaskCancelCommand),
+
     /// List all available blueprints
     #[command(name = "blueprint:list")]
     BlueprintList,
@@ -188,6 +223,16 @@ pub enum Commands {
     #[command(name = "admin:actions:revoke")]
     AdminActionsRevoke(AdminActionReviewCommand),
 
+    /// List uploaded certificates
+    #[command(name = "admin:certificates:list")]
+    AdminCertificatesList,
+    /// Upload a certificate which apps can use for their domains
+    #[command(name = "admin:certificates:upload")]
+    AdminCertificatesUpload(UploadCertificateCommand),
+    /// Delete an uploaded certificate
+    #[command(name = "admin:certificates:delete")]
+    AdminCertificatesDelete(DeleteCertificateCommand),
+
     #[command(name = "test")]
     Test,
 }
@@ -198,6 +243,12 @@ pub struct CompletionCommand {
     pub shell: Shell,
 }
 
+#[derive(Debug, Parser)]
+pub struct TaskCancelCommand {
+    /// Id of the task to cancel
+    pub task_id: uuid::Uuid,
+}
+
 #[derive(Debug, Parser)]
 pub struct BlueprintListCommand {}
 
@@ -219,6 +270,78 @@ pub type AdoptCommand = RunCommand;
 pub type InfoCommand = RunCommand;
 pub type RebuildCommand = RunCommand;
 pub type DestroyCommand = RunCommand;
+pub type RevisionsCommand = RunCommand;
+pub type CertificatesCommand = RunCommand;
+pub type AuthListCommand = RunCommand;
+
+#[derive(Debug, Parser)]
+pub struct AuthAddCommand {
+    /// Name of the app
+    pub app_name: String,
+
+    /// Credentials of the user (user:password), the password may be a op:// reference
+    #[arg(value_parser=parse_basic_auth, value_name="USER:PASSWORD")]
+    pub user: BasicAuthUser,
+}
+
+#[derive(Debug, Parser)]
+pub struct AuthRemoveCommand {
+    /// Name of the app
+    pub app_name: String,
+
+    /// Name of the user
+    pub username: String,
+}
+
+#[derive(Debug, Parser)]
+pub struct UploadCertificateCommand {
+    /// Name under which apps refer to the certificate
+    pub name: String,
+
+    /// Path to the PEM encoded certificate, optionally followed by its chain
+    #[arg(long, value_name = "PATH")]
+    pub cert: String,
+
+    /// Path to the PEM encoded private key
+    #[arg(long, value_name = "PATH")]
+    pub key: String,
+}
+
+#[derive(Debug, Parser)]
+pub struct DeleteCertificateCommand {
+    /// Name of the certificate
+    pub name: String,
+}
+
+#[derive(Debug, Parser)]
+pub struct RollbackCommand {
+    /// Name of the app
+    pub app_name: String,
+
+    /// Revision to restore, defaults to the latest revision
+    #[arg(long, value_name = "REVISION")]
+    pub to: Option<u32>,
+}
+
+#[derive(Debug, Parser)]
+pub struct ExtendCommand {
+    /// Name of the app
+    pub app_name: String,
+
+    /// New time to live (ttl) for the app, can be in days, hours or forever, prefix with `idle:` for apps with an idle TTL
+    #[arg(value_parser=parse_app_ttl, value_name="<DAYS>d|<HOURS>h|idle:<HOURS>h|FOREVER")]
+    pub ttl: AppTtl,
+}
+
+#[derive(Debug, Parser)]
+pub struct MaintenanceCommand {
+    /// Show a maintenance page instead of the app (on) or route requests to the app again (off)
+    #[arg(value_parser=parse_on_off, value_name="on|off")]
+    pub enabled: bool,
+
+    /// Name of the app
+    pub app_name: String,
+}
 
 #[derive(Debug, Parser)]
 pub struct NotifyAddCommand {
@@ -229,7 +352,10 @@ pub struct NotifyAddCommand {
     /// Some service-ids support additional parameters e.g.
     /// the mattermost-channel or
     /// the gitlab project-id and mergerequest-id.
-    #[arg(long,value_parser=parse_service_ids, value_name="SERVICE_TYPE://SERVICE_ID/(CHANNEL|PROJECT_ID/MR_ID)")]
+    /// Merge and pull requests take an optional commit, e.g.
+    /// github://github/owner/repo/12@SHA. Adding a receiver
+    /// again updates its commit.
+    #[arg(long,value_parser=parse_service_ids, value_name="SERVICE_TYPE://SERVICE_ID/(CHANNEL|PROJECT_ID/MR_ID[@SHA])")]
     pub service_id: Vec<NotificationReceiver>,
 }
 
@@ -320,17 +446,45 @@ pub struct CreateCommand {
     #[arg(name="folder", long, value_parser=parse_folder_containing_docker_compose)]
     pub docker_compose_path: String,
 
-    /// Public service ports to expose, can be specified multiple times (e.g. web:80, api:8080)
-    #[arg(long, value_parser=parse_service_ports, value_name="SERVICE:PORT", required_unless_present="app_blueprint")]
+    /// Public service ports to expose, can be specified multiple times (e.g. web:80, api:8080/api)
+    #[arg(long, value_parser=parse_service_ports, value_name="SERVICE:PORT[/PATH]", required_unless_present="app_blueprint")]
     pub service: Vec<ServicePortMapping>,
 
+    /// Remove the path prefix of services before passing requests to them
+    #[arg(long, default_value = "false")]
+    pub strip_path_prefix: bool,
+
+    /// TCP services to expose, routed via TLS with SNI or with `/port` on a dedicated port (e.g. postgres:5432, redis:6379/port)
+    #[arg(long, value_parser=parse_tcp_service, value_name="SERVICE:PORT[/sni|/port]")]
+    pub tcp_service: Vec<TcpServiceMapping>,
+
+    /// UDP services to expose on a dedicated port (e.g. mqtt:1883)
+    #[arg(long, value_parser=parse_udp_service, value_name="SERVICE:PORT")]
+    pub udp_service: Vec<TcpServiceMapping>,
+
     /// Custom domain(s) to use for the app (e.g. example.com:my-service), add an option for every domain or service
     #[arg(long, value_name="DOMAIN:SERVICE", value_parser=parse_custom_domain_mapping)]
     pub custom_domain: Vec<CustomDomainMapping>,
 
-    /// Basic auth credentials for the app (user:password)
+    /// Basic auth credentials for the app (user:password), the password may be a op:// reference, use multiple times for multiple users
     #[arg(long, value_parser=parse_basic_auth, value_name="USER:PASSWORD")]
-    pub basic_auth: Option<(String, String)>,
+    pub basic_auth: Vec<BasicAuthUser>,
+
+    /// Only allow requests from this address or CIDR range, use multiple times for multiple ranges
+    #[arg(long, value_parser=parse_ip_range, value_name = "CIDR")]
+    pub ip_allow: Vec<String>,
+
+    /// Only let users through who are logged in to scotty and may view the app
+    #[arg(long, default_value = "false")]
+    pub forward_auth: bool,
+
+    /// Add a header to all responses of the app (e.g. Strict-Transport-Security:max-age=31536000), use multiple times for multiple headers
+    #[arg(long, value_parser=parse_response_header, value_name = "NAME:VALUE")]
+    pub header: Vec<(String, String)>,
+
+    /// Redirect all requests of a domain of the app to another domain (e.g. www.example.com:example.com), use multiple times for multiple redirects
+    #[arg(long, value_parser=parse_redirect, value_name = "FROM:TO")]
+    pub redirect: Vec<RedirectRule>,
 
     /// Path to a file containing environment variables (one KEY=VALUE per line)
     #[arg(long, value_name = "PATH")]
@@ -348,8 +502,8 @@ pub struct CreateCommand {
     #[arg(long, required_unless_present = "service")]
     pub app_blueprint: Option<String>,
 
-    /// Time to live (ttl) for the app, can be in days, hours or forever
-    #[arg(long, value_parser=parse_app_ttl, default_value="7d", value_name="<DAYS>d|<HOURS>h|FOREVER")]
+    /// Time to live (ttl) for the app, can be in days, hours or forever, prefix with `idle:` to stop the app only after that time without requests
+    #[arg(long, value_parser=parse_app_ttl, default_value="7d", value_name="<DAYS>d|<HOURS>h|idle:<HOURS>h|FOREVER")]
     pub ttl: AppTtl,
 
     /// Destroy the app after TTL is reached
@@ -372,11 +526,157 @@ pub struct CreateCommand {
     #[arg(long, value_name = "MIDDLEWARE")]
     pub middleware: Vec<String>,
 
+    /// Certificate resolver for all domains of the app, overriding the one of the server
+    #[arg(long, value_name = "RESOLVER")]
+    pub certresolver: Option<String>,
+
+    /// Certificate to request for a main domain and additional SANs (e.g. example.com,*.example.com), can be specified multiple times
+    #[arg(long, value_parser=parse_tls_domain, value_name = "MAIN[,SAN...]")]
+    pub tls_domain: Vec<TlsDomain>,
+
+    /// Certificate resolver for a single domain (e.g. shop.example.com:dns), can be specified multiple times
+    #[arg(long, value_parser=parse_domain_setting, value_name = "DOMAIN:RESOLVER")]
+    pub domain_certresolver: Vec<(String, String)>,
+
+    /// Uploaded certificate for a single domain (e.g. shop.example.com:shop), can be specified multiple times
+    #[arg(long, value_parser=parse_domain_setting, value_name = "DOMAIN:CERTIFICATE")]
+    pub domain_certificate: Vec<(String, String)>,
+
+    /// Rebuild the app without downtime by starting new containers before replacing the old ones
+    #[arg(long, default_value = "false")]
+    pub blue_green: bool,
+
+    /// Start the app on a schedule, cron expression like "0 8 * * Mon-Fri"
+    #[arg(long, value_name = "CRON", value_parser = parse_cron_expression)]
+    pub start_at: Option<String>,
+
+    /// Stop the app on a schedule, cron expression like "0 19 * * Mon-Fri"
+    #[arg(long, value_name = "CRON", value_parser = parse_cron_expression)]
+    pub stop_at: Option<String>,
+
+    /// Timezone of --start-at and --stop-at, e.g. Europe/Berlin (defaults to UTC)
+    #[arg(long, value_name = "TIMEZONE", value_parser = parse_timezone)]
+    pub schedule_timezone: Option<String>,
+
     /// Scope(s) to create the app in, can be specified multiple times (defaults to 'default')
     #[arg(long, value_name = "SCOPE")]
     pub scope: Vec<String>,
 }
 
+#[derive(Debug, Parser)]
+pub struct UpdateCommand {
+    /// Name of the app
+    pub app_name: String,
+
+    /// Path to the folder containing a docker-compose file and other needed files
+    #[arg(name="folder", long, value_parser=parse_folder_containing_docker_compose)]
+    pub docker_compose_path: String,
+
+    /// Public service ports to expose, can be specified multiple times (e.g. web:80, api:8080/api)
+    #[arg(long, value_parser=parse_service_ports, value_name="SERVICE:PORT[/PATH]")]
+    pub service: Vec<ServicePortMapping>,
+
+    /// Remove the path prefix of services before passing requests to them
+    #[arg(long, default_value = "false", requires = "service")]
+    pub strip_path_prefix: bool,
+
+    /// TCP services to expose, routed via TLS with SNI or with `/port` on a dedicated port (e.g. postgres:5432, redis:6379/port)
+    #[arg(long, value_parser=parse_tcp_service, value_name="SERVICE:PORT[/sni|/port]")]
+    pub tcp_service: Vec<TcpServiceMapping>,
+
+    /// UDP services to expose on a dedicated port (e.g. mqtt:1883)
+    #[arg(long, value_parser=parse_udp_service, value_name="SERVICE:PORT")]
+    pub udp_service: Vec<TcpServiceMapping>,
+
+    /// Name of the app blueprint to use
+    #[arg(long)]
+    pub app_blueprint: Option<String>,
+
+    /// Custom domain(s) to use for the app (e.g. example.com:my-service), add an option for every domain or service
+    #[arg(long, value_name="DOMAIN:SERVICE", value_parser=parse_custom_domain_mapping)]
+    pub custom_domain: Vec<CustomDomainMapping>,
+
+    /// Basic auth credentials for the app (user:password), the password may be a op:// reference, use multiple times for multiple users
+    #[arg(long, value_parser=parse_basic_auth, value_name="USER:PASSWORD")]
+    pub basic_auth: Vec<BasicAuthUser>,
+
+    /// Only allow requests from this address or CIDR range, use multiple times for multiple ranges
+    #[arg(long, value_parser=parse_ip_range, value_name = "CIDR")]
+    pub ip_allow: Vec<String>,
+
+    /// Only let users through who are logged in to scotty and may view the app
+    #[arg(long, default_value = "false")]
+    pub forward_auth: bool,
+
+    /// Add a header to all responses of the app (e.g. Strict-Transport-Security:max-age=31536000), use multiple times for multiple headers
+    #[arg(long, value_parser=parse_response_header, value_name = "NAME:VALUE")]
+    pub header: Vec<(String, String)>,
+
+    /// Redirect all requests of a domain of the app to another domain (e.g. www.example.com:example.com), use multiple times for multiple redirects
+    #[arg(long, value_parser=parse_redirect, value_name = "FROM:TO")]
+    pub redirect: Vec<RedirectRule>,
+
+    /// Path to a file containing environment variables (one KEY=VALUE per line)
+    #[arg(long, value_name = "PATH")]
+    pub env_file: Option<String>,
+
+    /// Pass environment variables to the app (e.g. KEY=VALUE), use multiple times for multiple variables
+    #[arg(long, value_name = "KEY=VALUE", value_parser(parse_env_vars))]
+    pub env: Vec<(String, String)>,
+
+    /// Name of private docker registry to use (Needs to be configured on server-side)
+    #[arg(long)]
+    pub registry: Option<String>,
+
+    /// Time to live (ttl) for the app, can be in days, hours or forever, prefix with `idle:` to stop the app only after that time without requests (keeps the current TTL if not passed)
+    #[arg(long, value_parser=parse_app_ttl, value_name="<DAYS>d|<HOURS>h|idle:<HOURS>h|FOREVER")]
+    pub ttl: Option<AppTtl>,
+
+    /// Destroy the app after TTL is reached
+    #[arg(long, default_value = "false")]
+    pub destroy_on_ttl: bool,
+
+    /// Allow search engines to index the app
+    #[arg(long, default_value = "false")]
+    pub allow_robots: bool,
+
+    /// Custom Traefik middlewares to apply to the app, can be specified multiple times
+    #[arg(long, value_name = "MIDDLEWARE")]
+    pub middleware: Vec<String>,
+
+    /// Certificate resolver for all domains of the app, overriding the one of the server
+    #[arg(long, value_name = "RESOLVER")]
+    pub certresolver: Option<String>,
+
+    /// Certificate to request for a main domain and additional SANs (e.g. example.com,*.example.com), can be specified multiple times
+    #[arg(long, value_parser=parse_tls_domain, value_name = "MAIN[,SAN...]")]
+    pub tls_domain: Vec<TlsDomain>,
+
+    /// Certificate resolver for a single domain (e.g. shop.example.com:dns), can be specified multiple times
+    #[arg(long, value_parser=parse_domain_setting, value_name = "DOMAIN:RESOLVER")]
+    pub domain_certresolver: Vec<(String, String)>,
+
+    /// Uploaded certificate for a single domain (e.g. shop.example.com:shop), can be specified multiple times
+    #[arg(long, value_parser=parse_domain_setting, value_name = "DOMAIN:CERTIFICATE")]
+    pub domain_certificate: Vec<(String, String)>,
+
+    /// Rebuild the app without downtime by starting new containers before replacing the old ones
+    #[arg(long, default_value = "false")]
+    pub blue_green: bool,
+
+    /// Start the app on a schedule, cron expression like "0 8 * * Mon-Fri"
+    #[arg(long, value_name = "CRON", value_parser = parse_cron_expression)]
+    pub start_at: Option<String>,
+
+    /// Stop the app on a schedule, cron expression like "0 19 * * Mon-Fri"
+    #[arg(long, value_name = "CRON", value_parser = parse_cron_expression)]
+    pub stop_at: Option<String>,
+
+    /// Timezone of --start-at and --stop-at, e.g. Europe/Berlin (defaults to UTC)
+    #[arg(long, value_name = "TIMEZONE", value_parser = parse_timezone)]
+    pub schedule_timezone: Option<String>,
+}
+
 #[derive(Debug, Parser)]
 pub struct AuthLoginCommand {
     /// Use a specific OAuth provider URL
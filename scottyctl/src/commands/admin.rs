@@ -5,13 +5,17 @@ use tabled::{builder::Builder, settings::Style};
 
 use crate::{
     api::{delete, get, post},
-    cli::{AdminActionGetCommand, AdminActionReviewCommand},
+    cli::{
+        AdminActionGetCommand, AdminActionReviewCommand, DeleteCertificateCommand,
+        UploadCertificateCommand,
+    },
     context::AppContext,
     utils::ui::Ui,
 };
 use scotty_core::admin::{
-    CreateAssignmentRequest, CreateRoleRequest, CreateScopeRequest, GetUserPermissionsRequest,
-    RemoveAssignmentRequest, SuccessResponse, TestPermissionRequest,
+    CertificateInfo, CertificatesListResponse, CreateAssignmentRequest, CreateRoleRequest,
+    CreateScopeRequest, GetUserPermissionsRequest, RemoveAssignmentRequest, SuccessResponse,
+    TestPermissionRequest, UploadCertificateRequest,
 };
 
 /// Helper function to handle success responses from admin API calls
@@ -578,3 +582,106 @@ pub async fn revoke_action(
         "Failed to revoke action",
     )
 }
+
+// Certificates Management
+pub async fn list_certificates(context: &AppContext) -> anyhow::Result<()> {
+    let ui = context.ui();
+    ui.new_status_line(format!(
+        "Getting list of certificates from {} ...",
+        context.server().server
+    ));
+    ui.run(async || {
+        let result = get(context.server(), "admin/certificates").await?;
+        let response: CertificatesListResponse =
+            serde_json::from_value(result).context("Failed to parse certificates list")?;
+
+        if response.certificates.is_empty() {
+            return Ok("No certificates found.".to_string());
+        }
+
+        let mut builder = Builder::default();
+        builder.push_record(vec!["Name", "Domains", "Expires"]);
+        for certificate in &response.certificates {
+            builder.push_record(vec![
+                certificate.name.clone(),
+                certificate.domains.join(", "),
+                certificate
+                    .expires_at
+                    .with_timezone(&chrono::Local)
+                    .format("%Y-%m-%d %H:%M:%S")
+                    .to_string(),
+            ]);
+        }
+
+        let mut table = builder.build();
+        table.with(Style::rounded());
+        ui.success("Certificates retrieved successfully!");
+        Ok(table.to_string())
+    })
+    .await
+}
+
+pub async fn upload_certificate(
+    context: &AppContext,
+    cmd: &UploadCertificateCommand,
+) -> anyhow::Result<()> {
+    let ui = context.ui();
+    ui.new_status_line(format!(
+        "Uploading certificate '{}' to {} ...",
+        cmd.name.bright_blue(),
+        context.server().server
+    ));
+
+    let payload = UploadCertificateRequest {
+        name: cmd.name.clone(),
+        certificate: std::fs::read_to_string(&cmd.cert)
+            .with_context(|| format!("Failed to read certificate {}", cmd.cert))?,
+        key: std::fs::read_to_string(&cmd.key)
+            .with_context(|| format!("Failed to read key {}", cmd.key))?,
+    };
+    let result = post(
+        context.server(),
+        "admin/certificates",
+        serde_json::to_value(&payload)?,
+    )
+    .await?;
+    let certificate: CertificateInfo =
+        serde_json::from_value(result).context("Failed to parse certificate")?;
+
+    ui.success(format!(
+        "Certificate '{}' for {} uploaded, expires {}.",
+        certificate.name.bright_green(),
+        certificate.domains.join(", "),
+        certificate
+            .expires_at
+            .with_timezone(&chrono::Local)
+            .format("%Y-%m-%d")
+    ));
+    Ok(())
+}
+
+pub async fn delete_certificate(
+    context: &AppContext,
+    cmd: &DeleteCertificateCommand,
+) -> anyhow::Result<()> {
+    let ui = context.ui();
+    ui.new_status_line(format!(
+        "Deleting certificate '{}' on {} ...",
+        cmd.name.bright_blue(),
+        context.server().server
+    ));
+
+    let result = delete(
+        context.server(),
+        &format!("admin/certificates/{}", cmd.name),
+        None,
+    )
+    .await?;
+
+    handle_success_response(
+        ui,
+        result,
+        format!("Certificate '{}' deleted.", cmd.name.bright_green()),
+        "Failed to delete certificate",
+    )
+}
@@ -0,0 +1,124 @@
+use anyhow::Context;
+use owo_colors::OwoColorize;
+use tabled::{builder::Builder, settings::Style};
+
+use crate::{
+    api::{delete, get, post, wait_for_task},
+    cli::{AuthAddCommand, AuthListCommand, AuthRemoveCommand},
+    context::AppContext,
+};
+use scotty_core::{
+    apps::app_data::{AddBasicAuthUserRequest, BasicAuthUsersResponse},
+    tasks::running_app_context::RunningAppContext,
+};
+
+/// List the basic auth users of an app
+pub async fn list_basic_auth_users(
+    context: &AppContext,
+    cmd: &AuthListCommand,
+) -> anyhow::Result<()> {
+    let ui = context.ui();
+    ui.new_status_line(format!(
+        "Getting basic auth users of app {}...",
+        cmd.app_name.yellow()
+    ));
+    ui.run(async || {
+        let result = get(context.server(), &format!("apps/{}/auth", cmd.app_name)).await?;
+        let response: BasicAuthUsersResponse =
+            serde_json::from_value(result).context("Failed to parse basic auth users")?;
+
+        if response.users.is_empty() {
+            return Ok(format!("App '{}' has no basic auth users.", cmd.app_name));
+        }
+
+        let mut builder = Builder::default();
+        builder.push_record(vec!["User", "Password"]);
+        for user in &response.users {
+            builder.push_record(vec![
+                user.username.clone(),
+                user.password_reference
+                    .clone()
+                    .unwrap_or_else(|| "hashed".to_string()),
+            ]);
+        }
+
+        let mut table = builder.build();
+        table.with(Style::rounded());
+        ui.success(format!("Basic auth users of app '{}':", cmd.app_name));
+        Ok(table.to_string())
+    })
+    .await
+}
+
+/// Add a basic auth user to an app or change its password
+pub async fn add_basic_auth_user(context: &AppContext, cmd: &AuthAddCommand) -> anyhow::Result<()> {
+    let ui = context.ui();
+    ui.new_status_line(format!(
+        "Adding basic auth user {} to app {}...",
+        cmd.user.username.yellow(),
+        cmd.app_name.yellow()
+    ));
+    ui.run(async || {
+        let ws_connection =
+            crate::websocket::AuthenticatedWebSocket::connect(context.server()).await;
+
+        let payload = AddBasicAuthUserRequest {
+            username: cmd.user.username.clone(),
+            password: cmd.user.password.clone(),
+        };
+        let result = post(
+            context.server(),
+            &format!("apps/{}/auth", cmd.app_name),
+            serde_json::to_value(payload)?,
+        )
+        .await?;
+        let app_context: RunningAppContext =
+            serde_json::from_value(result).context("Failed to parse context from API")?;
+
+        wait_for_task(context.server(), &app_context, ui, ws_connection).await?;
+        Ok(format!(
+            "Basic auth user {} of app {} has been saved.",
+            cmd.user.username.yellow(),
+            cmd.app_name.yellow()
+        ))
+    })
+    .await
+}
+
+/// Remove a basic auth user from an app
+pub async fn remove_basic_auth_user(
+    context: &AppContext,
+    cmd: &AuthRemoveCommand,
+) -> anyhow::Result<()> {
+    let ui = context.ui();
+    ui.new_status_line(format!(
+        "Removing basic auth user {} from app {}...",
+        cmd.username.yellow(),
+        cmd.app_name.yellow()
+    ));
+    ui.run(async || {
+        let ws_connection =
+            crate::websocket::AuthenticatedWebSocket::connect(context.server()).await;
+
+        let result = delete(
+            context.server(),
+            &format!(
+                "apps/{}/auth/{}",
+                cmd.app_name,
+                urlencoding::encode(&cmd.username)
+            ),
+            None,
+        )
+        .await?;
+        let app_context: RunningAppContext =
+            serde_json::from_value(result).context("Failed to parse context from API")?;
+
+        wait_for_task(context.server(), &app_context, ui, ws_connection).await?;
+        Ok(format!(
+            "Basic auth user {} has been removed from app {}.",
+            cmd.username.yellow(),
+            cmd.app_name.yellow()
+        ))
+    })
+    .await
+}
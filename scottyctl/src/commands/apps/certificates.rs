@@ -0,0 +1,56 @@
+use anyhow::Context;
+use owo_colors::OwoColorize;
+use tabled::{builder::Builder, settings::Style};
+
+use crate::{api::get, cli::CertificatesCommand, context::AppContext};
+use scotty_core::apps::app_data::AppCertificatesResponse;
+
+/// Show the certificates and their expiry for the domains of an app
+pub async fn list_app_certificates(
+    context: &AppContext,
+    cmd: &CertificatesCommand,
+) -> anyhow::Result<()> {
+    let ui = context.ui();
+    ui.new_status_line(format!(
+        "Checking certificates of app {}...",
+        cmd.app_name.yellow()
+    ));
+    ui.run(async || {
+        let result = get(
+            context.server(),
+            &format!("apps/{}/certificates", cmd.app_name),
+        )
+        .await?;
+        let response: AppCertificatesResponse =
+            serde_json::from_value(result).context("Failed to parse certificates")?;
+
+        if response.certificates.is_empty() {
+            return Ok(format!("App '{}' has no public domains.", cmd.app_name));
+        }
+
+        let mut builder = Builder::default();
+        builder.push_record(vec!["Domain", "Source", "Expires"]);
+        for certificate in &response.certificates {
+            let source = match (&certificate.certificate, &certificate.certresolver) {
+                (Some(certificate), _) => format!("uploaded: {certificate}"),
+                (None, Some(certresolver)) => format!("resolver: {certresolver}"),
+                (None, None) => "default".to_string(),
+            };
+            let expires = match (&certificate.expires_at, &certificate.error) {
+                (Some(expires_at), _) => expires_at
+                    .with_timezone(&chrono::Local)
+                    .format("%Y-%m-%d %H:%M:%S")
+                    .to_string(),
+                (None, Some(error)) => error.red().to_string(),
+                (None, None) => String::new(),
+            };
+            builder.push_record(vec![certificate.domain.clone(), source, expires]);
+        }
+
+        let mut table = builder.build();
+        table.with(Style::rounded());
+        ui.success(format!("Certificates of app '{}':", cmd.app_name));
+        Ok(table.to_string())
+    })
+    .await
+}
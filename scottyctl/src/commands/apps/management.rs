@@ -3,22 +3,27 @@ use owo_colors::OwoColorize;
 
 use crate::{
     api::{get_or_post, wait_for_task},
-    cli::CreateCommand,
+    cli::{CreateCommand, ExtendCommand, MaintenanceCommand, UpdateCommand},
     context::AppContext,
-    utils::{files::collect_files, parsers::parse_env_file},
+    utils::{files::collect_files, parsers::parse_env_file, ui::Ui},
 };
 use base64::prelude::*;
 use flate2::write::GzEncoder;
 use flate2::Compression;
 use scotty_core::{
     apps::{
-        app_data::AppSettings,
+        app_data::{
+            AppData, AppSchedule, AppSettings, AppTlsSettings, ExtendTtlRequest, RebuildStrategy,
+            ServicePortMapping, SetMaintenanceRequest, TlsDomain,
+        },
         create_app_request::CreateAppRequest,
         file_list::{File, FileList},
+        update_app_request::UpdateAppRequest,
     },
     tasks::running_app_context::RunningAppContext,
     utils::secret::SecretHashMap,
 };
+use std::collections::{BTreeMap, HashMap};
 use std::io::Write;
 
 use super::{format_app_info, get_app_info};
@@ -28,80 +33,8 @@ pub async fn create_app(context: &AppContext, cmd: &CreateCommand) -> anyhow::Re
     let ui = context.ui();
     ui.new_status_line(format!("Creating app {}...", cmd.app_name.yellow()));
     ui.run(async || {
-        ui.new_status_line("Collecting files...");
-        let file_list = collect_files(&cmd.docker_compose_path)?;
-
-        // Compress and encode files
-        let mut total_original_size = 0;
-        let mut total_compressed_size = 0;
-
-        let file_list = FileList {
-            files: file_list
-                .files
-                .iter()
-                .map(|f| -> anyhow::Result<File> {
-                    total_original_size += f.content.len();
-
-                    // Compress with gzip
-                    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
-                    encoder
-                        .write_all(&f.content)
-                        .context(format!("Failed to compress file: {}", f.name))?;
-                    let compressed = encoder
-                        .finish()
-                        .context(format!("Failed to finish compression for: {}", f.name))?;
-
-                    total_compressed_size += compressed.len();
-
-                    Ok(File {
-                        name: f.name.clone(),
-                        content: BASE64_STANDARD.encode(&compressed).into(),
-                        compressed: true,
-                    })
-                })
-                .collect::<Result<Vec<_>, _>>()?,
-        };
-
-        let compression_ratio = if total_original_size > 0 {
-            (100.0 - (total_compressed_size as f64 / total_original_size as f64 * 100.0)) as u32
-        } else {
-            0
-        };
-
-        ui.success(format!(
-            "{} files ready to beam (compressed {}%, {} → {}).",
-            file_list.files.len(),
-            compression_ratio,
-            scotty_core::utils::format::format_bytes(total_original_size),
-            scotty_core::utils::format::format_bytes(total_compressed_size)
-        ));
-
-        // Combine environment variables from env-file and command line
-        let mut environment = cmd.env.clone();
-
-        // Add environment variables from env-file if specified
-        if let Some(env_file_path) = &cmd.env_file {
-            ui.new_status_line("Collecting env-file...");
-            match parse_env_file(env_file_path) {
-                Ok(env_file_vars) => {
-                    ui.success(format!(
-                        "Loaded {} environment variables from {}",
-                        env_file_vars.len().to_string().green(),
-                        env_file_path.yellow()
-                    ));
-                    let mut combined_env = env_file_vars;
-                    combined_env.extend(environment.iter().cloned());
-                    environment = combined_env;
-                }
-                Err(e) => {
-                    return Err(anyhow::anyhow!(
-                        "Failed to parse env file {}: {}",
-                        env_file_path,
-                        e
-                    ));
-                }
-            }
-        }
+        let file_list = collect_and_compress_files(ui, &cmd.docker_compose_path)?;
+        let environment = collect_environment(ui, &cmd.env, &cmd.env_file)?;
 
         // Use default scope if none specified
         let requested_scopes = if cmd.scope.is_empty() {
@@ -114,8 +47,13 @@ pub async fn create_app(context: &AppContext, cmd: &CreateCommand) -> anyhow::Re
             app_name: cmd.app_name.clone(),
             custom_domains: cmd.custom_domain.clone(),
             settings: AppSettings {
-                public_services: cmd.service.clone(),
+                public_services: with_strip_path(&cmd.service, cmd.strip_path_prefix),
+                tcp_services: [cmd.tcp_service.clone(), cmd.udp_service.clone()].concat(),
                 basic_auth: cmd.basic_auth.clone(),
+                ip_allowlist: cmd.ip_allow.clone(),
+                forward_auth: cmd.forward_auth,
+                response_headers: cmd.header.iter().cloned().collect(),
+                redirects: cmd.redirect.clone(),
                 environment: SecretHashMap::from_hashmap(environment.iter().cloned().collect()),
                 registry: cmd.registry.clone(),
                 app_blueprint: cmd.app_blueprint.clone(),
@@ -123,6 +61,17 @@ pub async fn create_app(context: &AppContext, cmd: &CreateCommand) -> anyhow::Re
                 disallow_robots: !cmd.allow_robots,
                 destroy_on_ttl: cmd.destroy_on_ttl,
                 middlewares: cmd.middleware.clone(),
+                rebuild_strategy: match cmd.blue_green {
+                    true => RebuildStrategy::BlueGreen,
+                    false => RebuildStrategy::Recreate,
+                },
+                schedule: collect_schedule(&cmd.start_at, &cmd.stop_at, &cmd.schedule_timezone),
+                tls: collect_tls_settings(
+                    &cmd.certresolver,
+                    &cmd.tls_domain,
+                    &cmd.domain_certresolver,
+                    &cmd.domain_certificate,
+                ),
                 ..Default::default()
             },
             files: file_list,
@@ -168,3 +117,379 @@ pub async fn create_app(context: &AppContext, cmd: &CreateCommand) -> anyhow::Re
     })
     .await
 }
+
+/// Push new files and optionally new settings to an existing app
+pub async fn update_app(context: &AppContext, cmd: &UpdateCommand) -> anyhow::Result<()> {
+    let ui = context.ui();
+    ui.new_status_line(format!("Updating app {}...", cmd.app_name.yellow()));
+    ui.run(async || {
+        let file_list = collect_and_compress_files(ui, &cmd.docker_compose_path)?;
+
+        let changes = collect_settings_changes(ui, cmd)?;
+        let settings = (!changes.is_empty()).then_some(changes);
+
+        let payload = UpdateAppRequest {
+            settings,
+            files: file_list,
+            custom_domains: cmd.custom_domain.clone(),
+        };
+        let payload = serde_json::to_value(&payload).context("Failed to serialize payload")?;
+        let size = scotty_core::utils::format::format_bytes(payload.to_string().len());
+
+        // Connect WebSocket before starting the task
+        let ws_connection =
+            crate::websocket::AuthenticatedWebSocket::connect(context.server()).await;
+
+        ui.new_status_line(format!(
+            "Beaming the update of {} up to {} ({})...",
+            cmd.app_name.yellow(),
+            context.server().server.yellow(),
+            size.blue()
+        ));
+        let result = get_or_post(
+            context.server(),
+            &format!("apps/{}", cmd.app_name),
+            "PUT",
+            Some(payload),
+        )
+        .await?;
+        let app_context: RunningAppContext =
+            serde_json::from_value(result).context("Failed to parse context from API")?;
+
+        wait_for_task(context.server(), &app_context, ui, ws_connection).await?;
+        let app_data = get_app_info(context.server(), &app_context.app_data.name).await?;
+        ui.success(format!(
+            "App {} updated successfully!",
+            cmd.app_name.yellow(),
+        ));
+
+        format_app_info(&app_data)
+    })
+    .await
+}
+
+/// Set a new TTL for an app, counting from now
+pub async fn extend_ttl(context: &AppContext, cmd: &ExtendCommand) -> anyhow::Result<()> {
+    let ui = context.ui();
+    ui.new_status_line(format!("Extending TTL of app {}...", cmd.app_name.yellow()));
+    ui.run(async || {
+        let payload = ExtendTtlRequest {
+            time_to_live: cmd.ttl.clone(),
+        };
+        let result = get_or_post(
+            context.server(),
+            &format!("apps/{}/ttl", cmd.app_name),
+            "POST",
+            Some(serde_json::to_value(payload)?),
+        )
+        .await?;
+        let app_data: AppData =
+            serde_json::from_value(result).context("Failed to parse app data from API")?;
+
+        ui.success(format!(
+            "TTL of app {} set to {:?}",
+            app_data.name.yellow(),
+            cmd.ttl
+        ));
+        format_app_info(&app_data)
+    })
+    .await
+}
+
+/// Turn the maintenance mode of an app on or off
+pub async fn set_maintenance(context: &AppContext, cmd: &MaintenanceCommand) -> anyhow::Result<()> {
+    let ui = context.ui();
+    let state = if cmd.enabled { "on" } else { "off" };
+    ui.new_status_line(format!(
+        "Turning maintenance mode of app {} {}...",
+        cmd.app_name.yellow(),
+        state
+    ));
+    ui.run(async || {
+        let ws_connection =
+            crate::websocket::AuthenticatedWebSocket::connect(context.server()).await;
+
+        let payload = SetMaintenanceRequest {
+            enabled: cmd.enabled,
+        };
+        let result = get_or_post(
+            context.server(),
+            &format!("apps/{}/maintenance", cmd.app_name),
+            "POST",
+            Some(serde_json::to_value(payload)?),
+        )
+        .await?;
+        let app_context: RunningAppContext =
+            serde_json::from_value(result).context("Failed to parse context from API")?;
+
+        wait_for_task(context.server(), &app_context, ui, ws_connection).await?;
+        Ok(format!(
+            "Maintenance mode of app {} is {}.",
+            cmd.app_name.yellow(),
+            state.yellow()
+        ))
+    })
+    .await
+}
+
+/// Build the schedule of an app from the command line options
+fn collect_schedule(
+    start_at: &Option<String>,
+    stop_at: &Option<String>,
+    timezone: &Option<String>,
+) -> Option<AppSchedule> {
+    let schedule = AppSchedule {
+        start_at: start_at.clone(),
+        stop_at: stop_at.clone(),
+        timezone: timezone.clone(),
+    };
+    (!schedule.is_empty()).then_some(schedule)
+}
+
+/// Collect the settings passed to `app:update`, options which were not passed
+/// are left out so the app keeps its current value.
+fn collect_settings_changes(
+    ui: &Ui,
+    cmd: &UpdateCommand,
+) -> anyhow::Result<serde_json::Map<String, serde_json::Value>> {
+    let mut changes = serde_json::Map::new();
+    let mut change = |field: &str, value: serde_json::Value| {
+        changes.insert(field.to_string(), value);
+    };
+
+    if !cmd.service.is_empty() {
+        change(
+            "public_services",
+            serde_json::to_value(with_strip_path(&cmd.service, cmd.strip_path_prefix))?,
+        );
+    }
+    if !cmd.tcp_service.is_empty() || !cmd.udp_service.is_empty() {
+        change(
+            "tcp_services",
+            serde_json::to_value([cmd.tcp_service.clone(), cmd.udp_service.clone()].concat())?,
+        );
+    }
+    if !cmd.basic_auth.is_empty() {
+        change("basic_auth", serde_json::to_value(&cmd.basic_auth)?);
+    }
+    if !cmd.ip_allow.is_empty() {
+        change("ip_allowlist", serde_json::to_value(&cmd.ip_allow)?);
+    }
+    if cmd.forward_auth {
+        change("forward_auth", true.into());
+    }
+    if !cmd.header.is_empty() {
+        let headers: BTreeMap<_, _> = cmd.header.iter().cloned().collect();
+        change("response_headers", serde_json::to_value(headers)?);
+    }
+    if !cmd.redirect.is_empty() {
+        change("redirects", serde_json::to_value(&cmd.redirect)?);
+    }
+    if !cmd.env.is_empty() || cmd.env_file.is_some() {
+        let environment = collect_environment(ui, &cmd.env, &cmd.env_file)?;
+        let environment: HashMap<_, _> = environment.into_iter().collect();
+        change("environment", serde_json::to_value(environment)?);
+    }
+    if let Some(registry) = &cmd.registry {
+        change("registry", registry.clone().into());
+    }
+    if let Some(app_blueprint) = &cmd.app_blueprint {
+        change("app_blueprint", app_blueprint.clone().into());
+    }
+    if let Some(ttl) = &cmd.ttl {
+        change("time_to_live", serde_json::to_value(ttl)?);
+    }
+    if cmd.allow_robots {
+        change("disallow_robots", false.into());
+    }
+    if cmd.destroy_on_ttl {
+        change("destroy_on_ttl", true.into());
+    }
+    if !cmd.middleware.is_empty() {
+        change("middlewares", serde_json::to_value(&cmd.middleware)?);
+    }
+    if cmd.blue_green {
+        change(
+            "rebuild_strategy",
+            serde_json::to_value(RebuildStrategy::BlueGreen)?,
+        );
+    }
+    if let Some(schedule) = collect_schedule(&cmd.start_at, &cmd.stop_at, &cmd.schedule_timezone)
+        .filter(|schedule| schedule.start_at.is_some() || schedule.stop_at.is_some())
+    {
+        change("schedule", serde_json::to_value(schedule)?);
+    }
+    let tls = collect_tls_settings(
+        &cmd.certresolver,
+        &cmd.tls_domain,
+        &cmd.domain_certresolver,
+        &cmd.domain_certificate,
+    );
+    if !tls.is_empty() {
+        change("tls", serde_json::to_value(tls)?);
+    }
+    Ok(changes)
+}
+
+/// Build the TLS settings of an app from the command line options
+fn collect_tls_settings(
+    certresolver: &Option<String>,
+    tls_domains: &[TlsDomain],
+    domain_certresolvers: &[(String, String)],
+    domain_certificates: &[(String, String)],
+) -> AppTlsSettings {
+    let mut settings = AppTlsSettings {
+        certresolver: certresolver.clone(),
+        domains: tls_domains.to_vec(),
+        ..Default::default()
+    };
+    for (domain, certresolver) in domain_certresolvers {
+        settings
+            .per_domain
+            .entry(domain.clone())
+            .or_default()
+            .certresolver = Some(certresolver.clone());
+    }
+    for (domain, certificate) in domain_certificates {
+        settings
+            .per_domain
+            .entry(domain.clone())
+            .or_default()
+            .certificate = Some(certificate.clone());
+    }
+    settings
+}
+
+/// Apply `--strip-path-prefix` to all services routed by a path prefix
+fn with_strip_path(services: &[ServicePortMapping], strip_path: bool) -> Vec<ServicePortMapping> {
+    services
+        .iter()
+        .cloned()
+        .map(|service| ServicePortMapping {
+            strip_path: strip_path && service.path.is_some(),
+            ..service
+        })
+        .collect()
+}
+
+/// Collect the files of the app folder, gzip and base64 encode them
+fn collect_and_compress_files(ui: &Ui, folder: &str) -> anyhow::Result<FileList> {
+    ui.new_status_line("Collecting files...");
+    let file_list = collect_files(folder)?;
+
+    // Compress and encode files
+    let mut total_original_size = 0;
+    let mut total_compressed_size = 0;
+
+    let file_list = FileList {
+        files: file_list
+            .files
+            .iter()
+            .map(|f| -> anyhow::Result<File> {
+                total_original_size += f.content.len();
+
+                // Compress with gzip
+                let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+                encoder
+                    .write_all(&f.content)
+                    .context(format!("Failed to compress file: {}", f.name))?;
+                let compressed = encoder
+                    .finish()
+                    .context(format!("Failed to finish compression for: {}", f.name))?;
+
+                total_compressed_size += compressed.len();
+
+                Ok(File {
+                    name: f.name.clone(),
+                    content: BASE64_STANDARD.encode(&compressed).into(),
+                    compressed: true,
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?,
+    };
+
+    let compression_ratio = if total_original_size > 0 {
+        (100.0 - (total_compressed_size as f64 / total_original_size as f64 * 100.0)) as u32
+    } else {
+        0
+    };
+
+    ui.success(format!(
+        "{} files ready to beam (compressed {}%, {} → {}).",
+        file_list.files.len(),
+        compression_ratio,
+        scotty_core::utils::format::format_bytes(total_original_size),
+        scotty_core::utils::format::format_bytes(total_compressed_size)
+    ));
+
+    Ok(file_list)
+}
+
+/// Combine environment variables from env-file and command line
+fn collect_environment(
+    ui: &Ui,
+    env: &[(String, String)],
+    env_file: &Option<String>,
+) -> anyhow::Result<Vec<(String, String)>> {
+    let mut environment = env.to_vec();
+
+    // Add environment variables from env-file if specified
+    if let Some(env_file_path) = env_file {
+        ui.new_status_line("Collecting env-file...");
+        match parse_env_file(env_file_path) {
+            Ok(env_file_vars) => {
+                ui.success(format!(
+                    "Loaded {} environment variables from {}",
+                    env_file_vars.len().to_string().green(),
+                    env_file_path.yellow()
+                ));
+                let mut combined_env = env_file_vars;
+                combined_env.extend(environment.iter().cloned());
+                environment = combined_env;
+            }
+            Err(e) => {
+                return Err(anyhow::anyhow!(
+                    "Failed to parse env file {}: {}",
+                    env_file_path,
+                    e
+                ));
+            }
+        }
+    }
+
+    Ok(environment)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    fn update_command(args: &[&str]) -> UpdateCommand {
+        let folder = concat!(env!("CARGO_MANIFEST_DIR"), "/../examples/create/nginx");
+        UpdateCommand::try_parse_from(
+            ["app:update", "my-app", "--folder", folder]
+                .iter()
+                .chain(args),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_only_passed_settings_are_changed() {
+        let ui = Ui::new();
+        assert!(collect_settings_changes(&ui, &update_command(&[]))
+            .unwrap()
+            .is_empty());
+
+        let changes = collect_settings_changes(
+            &ui,
+            &update_command(&["--ttl", "forever", "--middleware", "compress"]),
+        )
+        .unwrap();
+        assert_eq!(
+            serde_json::Value::Object(changes),
+            serde_json::json!({ "time_to_live": "Forever", "middlewares": ["compress"] })
+        );
+    }
+}
@@ -7,23 +7,32 @@ use crate::{
     context::{AppContext, ServerSettings},
     utils::formatting::format_since,
 };
-use scotty_core::{apps::app_data::AppData, tasks::running_app_context::RunningAppContext};
+use scotty_core::{
+    apps::app_data::{AppData, TcpRouting},
+    tasks::running_app_context::RunningAppContext,
+};
 
 // Re-export submodules
 pub mod actions;
+pub mod basic_auth;
+pub mod certificates;
 pub mod cp;
 pub mod lifecycle;
 pub mod list;
 pub mod logs;
 pub mod management;
+pub mod revisions;
 pub mod shell;
 
 // Re-export public functions to maintain backward compatibility
 pub use actions::*;
+pub use basic_auth::*;
+pub use certificates::*;
 pub use lifecycle::*;
 pub use list::*;
 pub use logs::*;
 pub use management::*;
+pub use revisions::*;
 pub use shell::*;
 
 /// Shared utility for calling apps API endpoints that return a RunningAppContext
@@ -149,14 +158,25 @@ pub async fn validate_app_and_service(
 }
 
 /// Shared utility for formatting app information into a table
+fn format_pull_request(repo: &str, number: u64, sha: &Option<String>) -> String {
+    match sha {
+        Some(sha) => format!("Repo: {}  PR: {}  Commit: {}", repo, number, sha),
+        None => format!("Repo: {}  PR: {}", repo, number),
+    }
+}
+
 pub fn format_app_info(app_data: &AppData) -> anyhow::Result<String> {
     let mut builder = Builder::default();
     builder.push_record(vec!["Service", "Status", "Running since", "URL"]);
     for service in &app_data.services {
         let urls = service.get_urls();
+        let status = match service.health {
+            Some(health) => format!("{} ({})", service.status, health),
+            None => service.status.to_string(),
+        };
         builder.push_record(vec![
             &service.service,
-            &service.status.to_string(),
+            &status,
             &format_since(&service.running_since()),
             &urls.join("\n"),
         ]);
@@ -185,10 +205,34 @@ pub fn format_app_info(app_data: &AppData) -> anyhow::Result<String> {
                     scotty_core::notification_types::NotificationReceiver::Mattermost(ctx) => {
                         ["Mattermost", &ctx.service_id, &ctx.channel]
                     }
+                    scotty_core::notification_types::NotificationReceiver::Slack(ctx) => {
+                        ["Slack", &ctx.service_id, &ctx.channel]
+                    }
+                    scotty_core::notification_types::NotificationReceiver::Matrix(ctx) => {
+                        ["Matrix", &ctx.service_id, &ctx.room_id]
+                    }
+                    scotty_core::notification_types::NotificationReceiver::Teams(ctx) => {
+                        ["Teams", &ctx.service_id, ""]
+                    }
+                    scotty_core::notification_types::NotificationReceiver::Email(ctx) => {
+                        context = ctx.recipients.join(", ");
+                        ["Email", &ctx.service_id, &context]
+                    }
                     scotty_core::notification_types::NotificationReceiver::Gitlab(ctx) => {
                         context = format!("Project-Id: {}  MR-Id: {}", ctx.project_id, ctx.mr_id);
+                        if let Some(sha) = &ctx.sha {
+                            context = format!("{}  Commit: {}", context, sha);
+                        }
                         ["Gitlab", &ctx.service_id, &context]
                     }
+                    scotty_core::notification_types::NotificationReceiver::Github(ctx) => {
+                        context = format_pull_request(&ctx.repo, ctx.pr, &ctx.sha);
+                        ["Github", &ctx.service_id, &context]
+                    }
+                    scotty_core::notification_types::NotificationReceiver::Gitea(ctx) => {
+                        context = format_pull_request(&ctx.repo, ctx.pr, &ctx.sha);
+                        ["Gitea", &ctx.service_id, &context]
+                    }
                 });
             }
             let table = builder.build().with(Style::rounded()).to_string();
@@ -196,6 +240,28 @@ pub fn format_app_info(app_data: &AppData) -> anyhow::Result<String> {
         }
     }
 
+    if let Some(settings) = &app_data.settings {
+        if !settings.tcp_services.is_empty() {
+            result += "\nTCP/UDP services";
+            let mut builder = Builder::default();
+            builder.push_record(["Service", "Port", "Routing", "Endpoint"]);
+            for service in &settings.tcp_services {
+                let routing = match service.routing {
+                    TcpRouting::Sni => "TLS with SNI",
+                    TcpRouting::Port => "Dedicated port",
+                };
+                builder.push_record([
+                    service.service.clone(),
+                    format!("{}/{}", service.port, service.protocol),
+                    routing.to_string(),
+                    service.get_endpoint(&settings.domain).unwrap_or_default(),
+                ]);
+            }
+            let table = builder.build().with(Style::rounded()).to_string();
+            result += format!("\n{table}").as_str();
+        }
+    }
+
     // Add scope information if available
     if let Some(settings) = &app_data.settings {
         if !settings.scopes.is_empty() {
@@ -203,5 +269,37 @@ pub fn format_app_info(app_data: &AppData) -> anyhow::Result<String> {
         }
     }
 
+    if let Some(schedule) = app_data.settings.as_ref().and_then(|s| s.schedule.as_ref()) {
+        match schedule.next_transition(chrono::Utc::now()) {
+            Ok(Some(transition)) => {
+                result += &format!(
+                    "\nNext scheduled {}: {}",
+                    transition.action,
+                    transition
+                        .at
+                        .with_timezone(&chrono::Local)
+                        .format("%Y-%m-%d %H:%M %Z")
+                );
+            }
+            Ok(None) => {}
+            Err(e) => result += &format!("\nInvalid schedule: {e}"),
+        }
+    }
+
+    if let Some(drift) = &app_data.drift {
+        result += &format!(
+            "\n{} since {}{}",
+            "Load balancer config drifted".red(),
+            drift.detected_at.format("%Y-%m-%d %H:%M"),
+            match drift.repairing {
+                true => ", repairing",
+                false => ", run app:rebuild to repair",
+            }
+        );
+        for issue in &drift.issues {
+            result += &format!("\n  - {issue}");
+        }
+    }
+
     Ok(result)
 }
@@ -0,0 +1,95 @@
+use anyhow::Context;
+use owo_colors::OwoColorize;
+use tabled::{builder::Builder, settings::Style};
+
+use crate::{
+    api::{get, post, wait_for_task},
+    cli::{RevisionsCommand, RollbackCommand},
+    context::AppContext,
+};
+use scotty_core::{
+    apps::revision::{RevisionInfo, RollbackRequest},
+    tasks::running_app_context::RunningAppContext,
+};
+
+use super::{format_app_info, get_app_info};
+
+/// List the stored revisions of an app
+pub async fn list_revisions(context: &AppContext, cmd: &RevisionsCommand) -> anyhow::Result<()> {
+    let ui = context.ui();
+    ui.new_status_line(format!(
+        "Getting revisions for app {}...",
+        cmd.app_name.yellow()
+    ));
+    ui.run(async || {
+        let result = get(
+            context.server(),
+            &format!("apps/{}/revisions", cmd.app_name),
+        )
+        .await?;
+        let revisions: Vec<RevisionInfo> =
+            serde_json::from_value(result).context("Failed to parse revisions")?;
+
+        if revisions.is_empty() {
+            return Ok(format!("No revisions found for app '{}'.", cmd.app_name));
+        }
+
+        let mut builder = Builder::default();
+        builder.push_record(vec!["Revision", "Created", "Reason", "Files"]);
+        for revision in revisions.iter().rev() {
+            builder.push_record(vec![
+                revision.revision.to_string(),
+                revision
+                    .created_at
+                    .with_timezone(&chrono::Local)
+                    .format("%Y-%m-%d %H:%M:%S")
+                    .to_string(),
+                revision.reason.clone(),
+                revision.files.join(", "),
+            ]);
+        }
+
+        let mut table = builder.build();
+        table.with(Style::rounded());
+        ui.success(format!("Revisions of app '{}':", cmd.app_name));
+        Ok(table.to_string())
+    })
+    .await
+}
+
+/// Roll back an app to a previous revision
+pub async fn rollback_app(context: &AppContext, cmd: &RollbackCommand) -> anyhow::Result<()> {
+    let ui = context.ui();
+    ui.new_status_line(match cmd.to {
+        Some(revision) => format!(
+            "Rolling back app {} to revision {}...",
+            cmd.app_name.yellow(),
+            revision
+        ),
+        None => format!("Rolling back app {}...", cmd.app_name.yellow()),
+    });
+    ui.run(async || {
+        // Connect WebSocket before starting the task
+        let ws_connection =
+            crate::websocket::AuthenticatedWebSocket::connect(context.server()).await;
+
+        let payload = RollbackRequest { revision: cmd.to };
+        let result = post(
+            context.server(),
+            &format!("apps/{}/rollback", cmd.app_name),
+            serde_json::to_value(payload)?,
+        )
+        .await?;
+        let app_context: RunningAppContext =
+            serde_json::from_value(result).context("Failed to parse context from API")?;
+
+        wait_for_task(context.server(), &app_context, ui, ws_connection).await?;
+        let app_data = get_app_info(context.server(), &app_context.app_data.name).await?;
+        ui.success(format!(
+            "App {} has been rolled back successfully!",
+            cmd.app_name.yellow()
+        ));
+        format_app_info(&app_data)
+    })
+    .await
+}
@@ -3,4 +3,5 @@ pub mod apps;
 pub mod auth;
 pub mod blueprints;
 pub mod notify;
+pub mod tasks;
 pub mod test;
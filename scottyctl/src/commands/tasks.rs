@@ -0,0 +1,28 @@
+use anyhow::Context;
+use owo_colors::OwoColorize;
+
+use crate::{api::delete, cli::TaskCancelCommand, context::AppContext};
+use scotty_core::tasks::task_details::TaskDetails;
+
+/// Cancel a running task
+pub async fn cancel_task(context: &AppContext, cmd: &TaskCancelCommand) -> anyhow::Result<()> {
+    let ui = context.ui();
+    ui.new_status_line(format!(
+        "Cancelling task {}...",
+        cmd.task_id.to_string().yellow()
+    ));
+
+    let result = delete(context.server(), &format!("tasks/{}", cmd.task_id), None).await?;
+    let task: TaskDetails = serde_json::from_value(result).context("Failed to parse task")?;
+
+    let app_name = task
+        .app_name
+        .map(|name| format!(" of app '{}'", name.yellow()))
+        .unwrap_or_default();
+    ui.success(format!(
+        "Task {}{} cancelled.",
+        task.id.to_string().yellow(),
+        app_name
+    ));
+    Ok(())
+}
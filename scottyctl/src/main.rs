@@ -69,7 +69,20 @@ async fn main() -> anyhow::Result<()> {
             commands::apps::run_app(&app_context, cmd).await
         }
         Commands::Stop(cmd) => commands::apps::stop_app(&app_context, cmd).await,
+        Commands::Update(cmd) => commands::apps::update_app(&app_context, cmd).await,
         Commands::Destroy(cmd) => commands::apps::destroy_app(&app_context, cmd).await,
+        Commands::Revisions(cmd) => commands::apps::list_revisions(&app_context, cmd).await,
+        Commands::Rollback(cmd) => commands::apps::rollback_app(&app_context, cmd).await,
+        Commands::Certificates(cmd) => {
+            commands::apps::list_app_certificates(&app_context, cmd).await
+        }
+        Commands::AuthList(cmd) => commands::apps::list_basic_auth_users(&app_context, cmd).await,
+        Commands::AuthAdd(cmd) => commands::apps::add_basic_auth_user(&app_context, cmd).await,
+        Commands::AuthRemove(cmd) => {
+            commands::apps::remove_basic_auth_user(&app_context, cmd).await
+        }
+        Commands::Extend(cmd) => commands::apps::extend_ttl(&app_context, cmd).await,
+        Commands::Maintenance(cmd) => commands::apps::set_maintenance(&app_context, cmd).await,
         Commands::Purge(cmd) => commands::apps::purge_app(&app_context, cmd).await,
         Commands::Adopt(cmd) => commands::apps::adopt_app(&app_context, cmd).await,
         Commands::Info(cmd) => commands::apps::info_app(&app_context, cmd).await,
@@ -95,6 +108,7 @@ async fn main() -> anyhow::Result<()> {
             print_completions(cmd.shell, &mut cli_cmd);
             Ok(())
         }
+        Commands::TaskCancel(cmd) => commands::tasks::cancel_task(&app_context, cmd).await,
         Commands::BlueprintList => commands::blueprints::list_blueprints(&app_context).await,
         Commands::BlueprintInfo(cmd) => {
             commands::blueprints::blueprint_info(&app_context, cmd).await
@@ -134,6 +148,13 @@ async fn main() -> anyhow::Result<()> {
         Commands::AdminActionsRevoke(cmd) => {
             commands::admin::revoke_action(&app_context, cmd).await
         }
+        Commands::AdminCertificatesList => commands::admin::list_certificates(&app_context).await,
+        Commands::AdminCertificatesUpload(cmd) => {
+            commands::admin::upload_certificate(&app_context, cmd).await
+        }
+        Commands::AdminCertificatesDelete(cmd) => {
+            commands::admin::delete_certificate(&app_context, cmd).await
+        }
         Commands::Test => commands::test::run_tests(&app_context).await,
     };
 
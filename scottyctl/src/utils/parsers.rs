@@ -1,9 +1,15 @@
 use anyhow;
 use dotenvy;
 use scotty_core::{
-    apps::app_data::{AppTtl, ServicePortMapping},
+    apps::app_data::{
+        validate_response_header, AppSchedule, AppTtl, BasicAuthUser, RedirectRule,
+        ServicePortMapping, TcpRouting, TcpServiceMapping, TlsDomain, TransportProtocol,
+    },
     apps::create_app_request::CustomDomainMapping,
-    notification_types::{GitlabContext, MattermostContext, NotificationReceiver, WebhookContext},
+    notification_types::{
+        EmailContext, GiteaContext, GithubContext, GitlabContext, MatrixContext, MattermostContext,
+        NotificationReceiver, SlackContext, TeamsContext, WebhookContext,
+    },
 };
 
 pub fn parse_service_ids(s: &str) -> Result<NotificationReceiver, String> {
@@ -40,28 +46,117 @@ pub fn parse_service_ids(s: &str) -> Result<NotificationReceiver, String> {
                 channel: channel.to_string(),
             }))
         }
+        "slack" => {
+            if parts.len() != 2 {
+                return Err("Invalid service ID format for slack".to_string());
+            }
+            Ok(NotificationReceiver::Slack(SlackContext {
+                service_id: service_id.to_string(),
+                channel: parts[1].to_string(),
+            }))
+        }
+        "matrix" => {
+            if parts.len() != 2 {
+                return Err("Invalid service ID format for matrix".to_string());
+            }
+            Ok(NotificationReceiver::Matrix(MatrixContext {
+                service_id: service_id.to_string(),
+                room_id: parts[1].to_string(),
+            }))
+        }
+        "teams" => {
+            if parts.len() != 1 {
+                return Err("Invalid service ID format for teams".to_string());
+            }
+            Ok(NotificationReceiver::Teams(TeamsContext {
+                service_id: service_id.to_string(),
+            }))
+        }
+        "email" => {
+            if parts.len() != 2 {
+                return Err("Invalid service ID format for email".to_string());
+            }
+            let recipients: Vec<String> = parts[1]
+                .split(',')
+                .map(|recipient| recipient.trim().to_string())
+                .collect();
+            if recipients
+                .iter()
+                .any(|recipient| !recipient.contains('@'))
+            {
+                return Err(format!("Invalid recipients {} for email", parts[1]));
+            }
+            Ok(NotificationReceiver::Email(EmailContext {
+                service_id: service_id.to_string(),
+                recipients,
+            }))
+        }
         "gitlab" => {
             if parts.len() < 3 {
                 return Err("Invalid service ID format for gitlab".to_string());
             }
-            let project_id = parts[1..parts.len() - 1].join("/").to_string();
-            let mr_id = parts.last().unwrap().parse::<u64>().unwrap();
+            let (project_id, mr_id, sha) = parse_pull_request(&parts[1..], service_type)?;
             Ok(NotificationReceiver::Gitlab(GitlabContext {
                 service_id: service_id.to_string(),
                 project_id,
                 mr_id,
+                sha,
             }))
         }
+        "github" | "gitea" => {
+            if parts.len() != 4 {
+                return Err(format!("Invalid service ID format for {service_type}"));
+            }
+            let (repo, pr, sha) = parse_pull_request(&parts[1..], service_type)?;
+            Ok(match service_type {
+                "github" => NotificationReceiver::Github(GithubContext {
+                    service_id: service_id.to_string(),
+                    repo,
+                    pr,
+                    sha,
+                }),
+                _ => NotificationReceiver::Gitea(GiteaContext {
+                    service_id: service_id.to_string(),
+                    repo,
+                    pr,
+                    sha,
+                }),
+            })
+        }
         _ => Err(format!(
-            "Unknown service type {service_type}, allowed values are log, mattermost, webhook and gitlab"
+            "Unknown service type {service_type}, allowed values are log, mattermost, slack, matrix, teams, email, webhook, gitlab, github and gitea"
         )),
     }
 }
 
+/// Parse `REPO/NUMBER[@SHA]`, where the repository may contain slashes
+fn parse_pull_request(
+    parts: &[&str],
+    service_type: &str,
+) -> Result<(String, u64, Option<String>), String> {
+    let (repo, number) = parts.split_at(parts.len() - 1);
+    let (number, sha) = match number[0].split_once('@') {
+        Some((number, sha)) if !sha.is_empty() => (number, Some(sha.to_string())),
+        Some(_) => return Err(format!("Missing commit sha for {service_type}")),
+        None => (number[0], None),
+    };
+    let number = number
+        .parse::<u64>()
+        .map_err(|_| format!("Invalid merge or pull request number {number} for {service_type}"))?;
+    Ok((repo.join("/"), number, sha))
+}
+
 pub fn parse_app_ttl(s: &str) -> Result<AppTtl, String> {
     if s.eq_ignore_ascii_case("forever") {
         return Ok(AppTtl::Forever);
     }
+    if let Some(idle) = s.strip_prefix("idle:") {
+        return match parse_app_ttl(idle)? {
+            AppTtl::Hours(hours) => Ok(AppTtl::Idle(hours)),
+            AppTtl::Days(days) => Ok(AppTtl::Idle(days * 24)),
+            _ => Err(format!("Invalid idle TTL format: {s}")),
+        };
+    }
     if let Some(days) = s.strip_suffix("d") {
         if let Ok(num_days) = days.parse::<u32>() {
             return Ok(AppTtl::Days(num_days));
@@ -75,6 +170,18 @@ pub fn parse_app_ttl(s: &str) -> Result<AppTtl, String> {
     Err(format!("Invalid TTL format: {s}"))
 }
 
+pub fn parse_cron_expression(s: &str) -> Result<String, String> {
+    AppSchedule::parse_expression(s)
+        .map(|_| s.to_string())
+        .map_err(|e| e.to_string())
+}
+
+pub fn parse_timezone(s: &str) -> Result<String, String> {
+    AppSchedule::parse_timezone(s)
+        .map(|_| s.to_string())
+        .map_err(|e| e.to_string())
+}
+
 pub fn parse_folder_containing_docker_compose(s: &str) -> Result<String, String> {
     let path = std::path::Path::new(s);
     if path.is_dir() {
@@ -86,12 +193,19 @@ pub fn parse_folder_containing_docker_compose(s: &str) -> Result<String, String>
     }
 }
 
-pub fn parse_basic_auth(s: &str) -> Result<(String, String), String> {
-    let parts: Vec<&str> = s.split(':').collect();
-    if parts.len() != 2 {
-        return Err("Invalid basic auth format, should be user:password".to_string());
-    }
-    Ok((parts[0].to_string(), parts[1].to_string()))
+pub fn parse_basic_auth(s: &str) -> Result<BasicAuthUser, String> {
+    // The password may contain colons, e.g. in a op:// reference
+    let (username, password) = s
+        .split_once(':')
+        .ok_or_else(|| "Invalid basic auth format, should be user:password".to_string())?;
+    let user = BasicAuthUser::new(username, password);
+    user.validate()?;
+    Ok(user)
+}
+
+pub fn parse_ip_range(s: &str) -> Result<String, String> {
+    scotty_core::apps::app_data::validate_source_range(s)?;
+    Ok(s.to_string())
 }
 
 pub fn parse_custom_domain_mapping(s: &str) -> Result<CustomDomainMapping, String> {
@@ -108,18 +222,129 @@ pub fn parse_custom_domain_mapping(s: &str) -> Result<CustomDomainMapping, Strin
 pub fn parse_service_ports(s: &str) -> Result<ServicePortMapping, String> {
     let parts: Vec<&str> = s.split(':').collect();
     if parts.len() != 2 {
-        return Err("Invalid service port format, should be service:port".to_string());
+        return Err(
+            "Invalid service port format, should be service:port or service:port/path".to_string(),
+        );
     }
-    let port = parts[1]
+    let (port, path) = match parts[1].find('/') {
+        Some(idx) => (&parts[1][..idx], Some(&parts[1][idx..])),
+        None => (parts[1], None),
+    };
+    let port = port
         .parse::<u32>()
         .map_err(|_| "Invalid port number".to_string())?;
+    if let Some(path) = path {
+        ServicePortMapping::validate_path(path)?;
+    }
     Ok(ServicePortMapping {
         service: parts[0].to_string(),
         port,
         domains: vec![],
+        path: path
+            .map(|path| path.trim_end_matches('/').to_string())
+            .filter(|path| !path.is_empty()),
+        strip_path: false,
     })
 }
 
+/// Parse a TCP service, routed by SNI or with `/port` on a dedicated port
+pub fn parse_tcp_service(s: &str) -> Result<TcpServiceMapping, String> {
+    let (mapping, routing) = match s.split_once('/') {
+        Some((mapping, "port")) => (mapping, TcpRouting::Port),
+        Some((mapping, "sni")) => (mapping, TcpRouting::Sni),
+        Some(_) => {
+            return Err(
+                "Invalid TCP service format, should be service:port[/sni|/port]".to_string(),
+            )
+        }
+        None => (s, TcpRouting::Sni),
+    };
+    let (service, port) = parse_service_and_port(mapping)?;
+    Ok(TcpServiceMapping::new(
+        service,
+        port,
+        TransportProtocol::Tcp,
+        routing,
+    ))
+}
+
+/// Parse a certificate to request, the main domain followed by its SANs
+pub fn parse_tls_domain(s: &str) -> Result<TlsDomain, String> {
+    let mut names = s.split(',').map(str::trim);
+    let main = names
+        .next()
+        .filter(|main| !main.is_empty())
+        .ok_or_else(|| "Invalid TLS domain format, should be main[,san...]".to_string())?;
+    let sans: Vec<String> = names.map(str::to_string).collect();
+    if sans.iter().any(|san| san.is_empty()) {
+        return Err("Invalid TLS domain format, should be main[,san...]".to_string());
+    }
+    Ok(TlsDomain {
+        main: main.to_string(),
+        sans,
+    })
+}
+
+/// Parse a setting of a single domain, e.g. `shop.example.com:dns`
+pub fn parse_domain_setting(s: &str) -> Result<(String, String), String> {
+    match s.split_once(':') {
+        Some((domain, value)) if !domain.is_empty() && !value.is_empty() => {
+            Ok((domain.to_string(), value.to_string()))
+        }
+        _ => Err("Invalid format, should be domain:name".to_string()),
+    }
+}
+
+/// Parse a custom response header, e.g. `X-Frame-Options:DENY`
+pub fn parse_response_header(s: &str) -> Result<(String, String), String> {
+    let (name, value) = s
+        .split_once(':')
+        .ok_or_else(|| "Invalid header format, should be name:value".to_string())?;
+    let (name, value) = (name.trim(), value.trim());
+    validate_response_header(name, value)?;
+    Ok((name.to_string(), value.to_string()))
+}
+
+/// Parse a redirect between two domains, e.g. `www.example.com:example.com`
+pub fn parse_redirect(s: &str) -> Result<RedirectRule, String> {
+    let (from, to) = s
+        .split_once(':')
+        .ok_or_else(|| "Invalid redirect format, should be from-domain:to-domain".to_string())?;
+    let rule = RedirectRule::new(from, to);
+    rule.validate()?;
+    Ok(rule)
+}
+
+/// Parse the state of a switch, `on` or `off`
+pub fn parse_on_off(s: &str) -> Result<bool, String> {
+    match s.to_lowercase().as_str() {
+        "on" => Ok(true),
+        "off" => Ok(false),
+        _ => Err("Invalid state, should be on or off".to_string()),
+    }
+}
+
+/// Parse a UDP service, which always gets a dedicated port
+pub fn parse_udp_service(s: &str) -> Result<TcpServiceMapping, String> {
+    let (service, port) = parse_service_and_port(s)?;
+    Ok(TcpServiceMapping::new(
+        service,
+        port,
+        TransportProtocol::Udp,
+        TcpRouting::Port,
+    ))
+}
+
+fn parse_service_and_port(s: &str) -> Result<(&str, u32), String> {
+    let (service, port) = s
+        .split_once(':')
+        .ok_or_else(|| "Invalid service port format, should be service:port".to_string())?;
+    let port = port
+        .parse::<u32>()
+        .map_err(|_| "Invalid port number".to_string())?;
+    Ok((service, port))
+}
+
 pub fn parse_env_vars(s: &str) -> Result<(String, String), String> {
     match s.find('=') {
         Some(idx) => {
@@ -182,4 +407,185 @@ mod tests {
         let result = parse_env_vars("INVALID_FORMAT");
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_parse_slack_service_id() {
+        assert_eq!(
+            parse_service_ids("slack://slack/#deployments").unwrap(),
+            NotificationReceiver::Slack(SlackContext {
+                service_id: "slack".to_string(),
+                channel: "#deployments".to_string(),
+            })
+        );
+        assert!(parse_service_ids("slack://slack").is_err());
+    }
+
+    #[test]
+    fn test_parse_matrix_and_teams_service_ids() {
+        assert_eq!(
+            parse_service_ids("matrix://matrix/!abcdef:matrix.example.com").unwrap(),
+            NotificationReceiver::Matrix(MatrixContext {
+                service_id: "matrix".to_string(),
+                room_id: "!abcdef:matrix.example.com".to_string(),
+            })
+        );
+        assert_eq!(
+            parse_service_ids("teams://teams").unwrap(),
+            NotificationReceiver::Teams(TeamsContext {
+                service_id: "teams".to_string(),
+            })
+        );
+        assert!(parse_service_ids("teams://teams/channel").is_err());
+    }
+
+    #[test]
+    fn test_parse_forge_service_ids() {
+        assert_eq!(
+            parse_service_ids("gitlab://gitlab/group/sub/project/7").unwrap(),
+            NotificationReceiver::Gitlab(GitlabContext {
+                service_id: "gitlab".to_string(),
+                project_id: "group/sub/project".to_string(),
+                mr_id: 7,
+                sha: None,
+            })
+        );
+        assert_eq!(
+            parse_service_ids("github://github/factorial-io/scotty/12@a1b2c3d").unwrap(),
+            NotificationReceiver::Github(GithubContext {
+                service_id: "github".to_string(),
+                repo: "factorial-io/scotty".to_string(),
+                pr: 12,
+                sha: Some("a1b2c3d".to_string()),
+            })
+        );
+        assert_eq!(
+            parse_service_ids("gitea://gitea/ops/website/3").unwrap(),
+            NotificationReceiver::Gitea(GiteaContext {
+                service_id: "gitea".to_string(),
+                repo: "ops/website".to_string(),
+                pr: 3,
+                sha: None,
+            })
+        );
+        assert!(parse_service_ids("github://github/scotty/12").is_err());
+        assert!(parse_service_ids("github://github/factorial-io/scotty/12@").is_err());
+        assert!(parse_service_ids("gitlab://gitlab/group/project/latest").is_err());
+    }
+
+    #[test]
+    fn test_parse_email_service_id() {
+        assert_eq!(
+            parse_service_ids("email://smtp/pm@example.com, qa@example.com").unwrap(),
+            NotificationReceiver::Email(EmailContext {
+                service_id: "smtp".to_string(),
+                recipients: vec!["pm@example.com".to_string(), "qa@example.com".to_string()],
+            })
+        );
+        assert!(parse_service_ids("email://smtp").is_err());
+        assert!(parse_service_ids("email://smtp/pm@example.com,").is_err());
+    }
+
+    #[test]
+    fn test_parse_service_ports() {
+        let mapping = parse_service_ports("web:80").unwrap();
+        assert_eq!(mapping.service, "web");
+        assert_eq!(mapping.port, 80);
+        assert_eq!(mapping.path, None);
+
+        let mapping = parse_service_ports("api:8080/api").unwrap();
+        assert_eq!(mapping.service, "api");
+        assert_eq!(mapping.port, 8080);
+        assert_eq!(mapping.path.as_deref(), Some("/api"));
+
+        let mapping = parse_service_ports("api:8080/api/v1/").unwrap();
+        assert_eq!(mapping.path.as_deref(), Some("/api/v1"));
+
+        // A single slash routes the whole host
+        assert_eq!(parse_service_ports("web:80/").unwrap().path, None);
+
+        assert!(parse_service_ports("web").is_err());
+        assert!(parse_service_ports("web:http").is_err());
+        assert!(parse_service_ports("web:80/a`b").is_err());
+    }
+
+    #[test]
+    fn test_parse_tcp_and_udp_services() {
+        let mapping = parse_tcp_service("db:5432").unwrap();
+        assert_eq!(mapping.service, "db");
+        assert_eq!(mapping.port, 5432);
+        assert_eq!(mapping.protocol, TransportProtocol::Tcp);
+        assert_eq!(mapping.routing, TcpRouting::Sni);
+
+        let mapping = parse_tcp_service("redis:6379/port").unwrap();
+        assert_eq!(mapping.routing, TcpRouting::Port);
+        assert!(parse_tcp_service("redis:6379/http").is_err());
+        assert!(parse_tcp_service("redis").is_err());
+
+        let mapping = parse_udp_service("dns:53").unwrap();
+        assert_eq!(mapping.protocol, TransportProtocol::Udp);
+        assert_eq!(mapping.routing, TcpRouting::Port);
+    }
+
+    #[test]
+    fn test_parse_tls_settings() {
+        let domain = parse_tls_domain("example.com,*.example.com").unwrap();
+        assert_eq!(domain.main, "example.com");
+        assert_eq!(domain.sans, vec!["*.example.com"]);
+        assert!(parse_tls_domain("example.com").unwrap().sans.is_empty());
+        assert!(parse_tls_domain("").is_err());
+        assert!(parse_tls_domain("example.com,").is_err());
+
+        assert_eq!(
+            parse_domain_setting("shop.example.com:dns").unwrap(),
+            ("shop.example.com".to_string(), "dns".to_string())
+        );
+        assert!(parse_domain_setting("shop.example.com").is_err());
+        assert!(parse_domain_setting("shop.example.com:").is_err());
+    }
+
+    #[test]
+    fn test_parse_basic_auth() {
+        assert_eq!(
+            parse_basic_auth("user:pass").unwrap(),
+            BasicAuthUser::new("user", "pass")
+        );
+        assert_eq!(
+            parse_basic_auth("user:op://token/vault/item/password").unwrap(),
+            BasicAuthUser::new("user", "op://token/vault/item/password")
+        );
+        assert!(parse_basic_auth("user").is_err());
+        assert!(parse_basic_auth(":pass").is_err());
+    }
+
+    #[test]
+    fn test_parse_headers_and_redirects() {
+        assert_eq!(
+            parse_response_header("Content-Security-Policy: default-src 'self'").unwrap(),
+            (
+                "Content-Security-Policy".to_string(),
+                "default-src 'self'".to_string()
+            )
+        );
+        assert!(parse_response_header("X-Frame-Options").is_err());
+
+        let rule = parse_redirect("www.example.com:example.com").unwrap();
+        assert_eq!(rule, RedirectRule::new("www.example.com", "example.com"));
+        assert!(parse_redirect("www.example.com").is_err());
+
+        assert!(parse_on_off("on").unwrap());
+        assert!(!parse_on_off("OFF").unwrap());
+        assert!(parse_on_off("maybe").is_err());
+    }
+
+    #[test]
+    fn test_parse_app_ttl() {
+        assert_eq!(parse_app_ttl("7d"), Ok(AppTtl::Days(7)));
+        assert_eq!(parse_app_ttl("12h"), Ok(AppTtl::Hours(12)));
+        assert_eq!(parse_app_ttl("forever"), Ok(AppTtl::Forever));
+        assert_eq!(parse_app_ttl("idle:8h"), Ok(AppTtl::Idle(8)));
+        assert_eq!(parse_app_ttl("idle:2d"), Ok(AppTtl::Idle(48)));
+        assert!(parse_app_ttl("idle:forever").is_err());
+        assert!(parse_app_ttl("idle:idle:8h").is_err());
+        assert!(parse_app_ttl("7w").is_err());
+    }
 }